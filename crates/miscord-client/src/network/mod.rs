@@ -3,12 +3,33 @@ mod websocket;
 
 use crate::state::{AppState, LoginRequest, LoginResponse, RegisterRequest, RegisterResponse};
 use anyhow::Result;
-use miscord_protocol::{ChannelData, ChannelType, CommunityData, MessageData, ThreadData, UserData};
+use miscord_protocol::{
+    ChannelData, ChannelFollowData, ChannelPositionData, ChannelType, CommunityData, CustomEmojiData, ExportData,
+    ExportFormat, ForumPostData, ForumTagData, InteractionData, InteractionOptionData, MessageData,
+    PrivacySettingsData, ReadStateData, SlashCommandData, StorageUsageData, ThreadData, ThreadSummaryData,
+    UploadSessionData, UserData,
+};
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+/// How long a single upload chunk may take before it's retried
+const UPLOAD_CHUNK_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Consecutive failed chunks before an upload gives up
+const UPLOAD_MAX_RETRIES: u32 = 10;
+
+/// Wait before the first retry of a chunk; doubles with each further failure
+const UPLOAD_RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// How much of a file is read at a time while hashing it
+const HASH_READ_SIZE: u64 = 8 * 1024 * 1024;
+
 /// Response from get_voice_participants API
 #[derive(Debug, Clone, Deserialize)]
 pub struct VoiceParticipantResponse {
@@ -28,6 +49,70 @@ pub struct MessageSearchResult {
     pub community_name: String,
 }
 
+/// A file to upload as an attachment
+#[derive(Debug, Clone)]
+pub struct UploadFile {
+    pub filename: String,
+    pub content_type: String,
+    pub size: u64,
+    pub source: UploadSource,
+}
+
+/// Where an upload's contents come from. Files on disk are read a chunk at a time
+/// so large ones are never held in memory.
+#[derive(Debug, Clone)]
+pub enum UploadSource {
+    Path(PathBuf),
+    Bytes(Arc<Vec<u8>>),
+}
+
+impl UploadSource {
+    async fn open(&self) -> Result<UploadReader> {
+        Ok(match self {
+            UploadSource::Path(path) => UploadReader::File(tokio::fs::File::open(path).await?),
+            UploadSource::Bytes(data) => UploadReader::Bytes(data.clone()),
+        })
+    }
+}
+
+enum UploadReader {
+    File(tokio::fs::File),
+    Bytes(Arc<Vec<u8>>),
+}
+
+impl UploadReader {
+    async fn read_at(&mut self, offset: u64, len: usize) -> Result<Vec<u8>> {
+        match self {
+            UploadReader::File(file) => {
+                file.seek(std::io::SeekFrom::Start(offset)).await?;
+                let mut chunk = vec![0; len];
+                file.read_exact(&mut chunk).await?;
+                Ok(chunk)
+            }
+            UploadReader::Bytes(data) => {
+                let start = offset as usize;
+                Ok(data[start..start + len].to_vec())
+            }
+        }
+    }
+}
+
+impl UploadFile {
+    /// Hex SHA-256 of the file's contents, read a piece at a time
+    async fn sha256(&self) -> Result<String> {
+        let mut reader = self.source.open().await?;
+        let mut hasher = Sha256::new();
+        let mut offset = 0;
+        while offset < self.size {
+            let len = HASH_READ_SIZE.min(self.size - offset);
+            hasher.update(reader.read_at(offset, len as usize).await?);
+            offset += len;
+        }
+
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+}
+
 #[derive(Clone)]
 pub struct NetworkClient {
     state: AppState,
@@ -87,6 +172,55 @@ impl NetworkClient {
         Ok(user)
     }
 
+    /// Get the current user's privacy settings
+    pub async fn get_privacy_settings(&self) -> Result<PrivacySettingsData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::get(
+            &format!("{}/api/users/me/privacy", server_url),
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// Update the current user's privacy settings
+    pub async fn update_privacy_settings(&self, settings: &PrivacySettingsData) -> Result<PrivacySettingsData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::patch(
+            &format!("{}/api/users/me/privacy", server_url),
+            settings,
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// How much storage the current user's files take up
+    pub async fn get_storage_usage(&self) -> Result<StorageUsageData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::get(
+            &format!("{}/api/users/me/storage", server_url),
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// How much storage a community's files take up
+    pub async fn get_community_storage_usage(&self, community_id: Uuid) -> Result<StorageUsageData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::get(
+            &format!("{}/api/communities/{}/storage", server_url, community_id),
+            token.as_deref(),
+        )
+        .await
+    }
+
     pub async fn register(&self, server_url: &str, request: RegisterRequest) -> Result<RegisterResponse> {
         self.set_server_url(server_url).await;
         api::post(&format!("{}/api/auth/register", server_url), &request, None).await
@@ -178,6 +312,18 @@ impl NetworkClient {
         .await
     }
 
+    /// Leave a community
+    pub async fn leave_community(&self, community_id: Uuid, user_id: Uuid) -> Result<()> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::delete(
+            &format!("{}/api/communities/{}/members/{}", server_url, community_id, user_id),
+            token.as_deref(),
+        )
+        .await
+    }
+
     pub async fn create_invite(&self, community_id: Uuid) -> Result<String> {
         let server_url = self.get_server_url().await;
         let token = self.get_token().await;
@@ -221,11 +367,42 @@ impl NetworkClient {
         .await
     }
 
+    /// Mark a channel as read up to a specific message
+    pub async fn mark_message_read(&self, channel_id: Uuid, message_id: Uuid) -> Result<ReadStateData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        #[derive(serde::Serialize)]
+        struct MarkChannelRead {
+            message_id: Uuid,
+        }
+
+        api::post(
+            &format!("{}/api/channels/{}/read", server_url, channel_id),
+            &MarkChannelRead { message_id },
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// Get "seen by" read receipts for a DM or group DM
+    pub async fn get_read_receipts(&self, channel_id: Uuid) -> Result<Vec<ReadStateData>> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::get(
+            &format!("{}/api/channels/{}/read-receipts", server_url, channel_id),
+            token.as_deref(),
+        )
+        .await
+    }
+
     pub async fn create_channel(
         &self,
         community_id: Uuid,
         name: &str,
         channel_type: ChannelType,
+        parent_id: Option<Uuid>,
     ) -> Result<ChannelData> {
         let server_url = self.get_server_url().await;
         let token = self.get_token().await;
@@ -234,6 +411,8 @@ impl NetworkClient {
         struct CreateChannel {
             name: String,
             channel_type: ChannelType,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            parent_id: Option<Uuid>,
         }
 
         api::post(
@@ -241,12 +420,52 @@ impl NetworkClient {
             &CreateChannel {
                 name: name.to_string(),
                 channel_type,
+                parent_id,
             },
             token.as_deref(),
         )
         .await
     }
 
+    /// Update channel positions and categories in one request
+    pub async fn reorder_channels(
+        &self,
+        community_id: Uuid,
+        positions: &[ChannelPositionData],
+    ) -> Result<Vec<ChannelPositionData>> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::patch(
+            &format!("{}/api/communities/{}/channels", server_url, community_id),
+            &positions,
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// Set a channel's retention period or DM disappearing timer (None keeps messages forever)
+    pub async fn set_channel_retention(
+        &self,
+        channel_id: Uuid,
+        retention_seconds: Option<i32>,
+    ) -> Result<ChannelData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        #[derive(serde::Serialize)]
+        struct SetChannelRetention {
+            retention_seconds: Option<i32>,
+        }
+
+        api::put(
+            &format!("{}/api/channels/{}/retention", server_url, channel_id),
+            &SetChannelRetention { retention_seconds },
+            token.as_deref(),
+        )
+        .await
+    }
+
     // Members
 
     pub async fn get_members(&self, community_id: Uuid) -> Result<Vec<UserData>> {
@@ -259,6 +478,136 @@ impl NetworkClient {
         .await
     }
 
+    // Custom emoji
+
+    pub async fn get_community_emojis(&self, community_id: Uuid) -> Result<Vec<CustomEmojiData>> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+        api::get(
+            &format!("{}/api/communities/{}/emojis", server_url, community_id),
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// Upload a custom emoji image to a community
+    pub async fn upload_community_emoji(
+        &self,
+        community_id: Uuid,
+        name: &str,
+        filename: String,
+        data: Vec<u8>,
+    ) -> Result<CustomEmojiData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        let client = reqwest::Client::new();
+        let form = reqwest::multipart::Form::new()
+            .text("name", name.to_string())
+            .part("file", reqwest::multipart::Part::bytes(data).file_name(filename));
+
+        let mut request = client
+            .post(format!("{}/api/communities/{}/emojis", server_url, community_id))
+            .multipart(form);
+
+        if let Some(token) = token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow::anyhow!("Emoji upload failed: {}", error_text));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn rename_community_emoji(&self, emoji_id: Uuid, name: &str) -> Result<CustomEmojiData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        #[derive(serde::Serialize)]
+        struct RenameEmoji {
+            name: String,
+        }
+
+        api::patch(
+            &format!("{}/api/emojis/{}", server_url, emoji_id),
+            &RenameEmoji {
+                name: name.to_string(),
+            },
+            token.as_deref(),
+        )
+        .await
+    }
+
+    pub async fn delete_community_emoji(&self, emoji_id: Uuid) -> Result<()> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::delete(
+            &format!("{}/api/emojis/{}", server_url, emoji_id),
+            token.as_deref(),
+        )
+        .await
+    }
+
+    // Slash commands
+
+    pub async fn get_commands(&self, community_id: Uuid) -> Result<Vec<SlashCommandData>> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+        api::get(
+            &format!("{}/api/communities/{}/commands", server_url, community_id),
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// Invoke a slash command; the bot's reply arrives over the WebSocket
+    pub async fn invoke_command(
+        &self,
+        channel_id: Uuid,
+        command_id: Uuid,
+        options: Vec<InteractionOptionData>,
+    ) -> Result<InteractionData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        #[derive(serde::Serialize)]
+        struct InvokeCommand {
+            command_id: Uuid,
+            options: Vec<InteractionOptionData>,
+        }
+
+        api::post(
+            &format!("{}/api/channels/{}/interactions", server_url, channel_id),
+            &InvokeCommand { command_id, options },
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// Click a button a bot attached to a message
+    pub async fn click_button(&self, message_id: Uuid, custom_id: &str) -> Result<InteractionData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        #[derive(serde::Serialize)]
+        struct ClickButton<'a> {
+            custom_id: &'a str,
+        }
+
+        api::post(
+            &format!("{}/api/messages/{}/interactions", server_url, message_id),
+            &ClickButton { custom_id },
+            token.as_deref(),
+        )
+        .await
+    }
+
     // Messages
 
     pub async fn get_messages(&self, channel_id: Uuid, before: Option<Uuid>) -> Result<Vec<MessageData>> {
@@ -277,6 +626,30 @@ impl NetworkClient {
         api::get(&url, token.as_deref()).await
     }
 
+    /// Get messages newer than `after` (newest first)
+    pub async fn get_messages_after(&self, channel_id: Uuid, after: Uuid) -> Result<Vec<MessageData>> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::get(
+            &format!("{}/api/channels/{}/messages?after={}", server_url, channel_id, after),
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// Get a window of messages centered on `around` (newest first)
+    pub async fn get_messages_around(&self, channel_id: Uuid, around: Uuid) -> Result<Vec<MessageData>> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::get(
+            &format!("{}/api/channels/{}/messages?around={}", server_url, channel_id, around),
+            token.as_deref(),
+        )
+        .await
+    }
+
     /// Search messages by content
     pub async fn search_messages(&self, query: &str, community_id: Option<Uuid>) -> Result<Vec<MessageSearchResult>> {
         let server_url = self.get_server_url().await;
@@ -351,44 +724,139 @@ impl NetworkClient {
         .await
     }
 
-    /// Upload files to a channel
-    pub async fn upload_files(&self, channel_id: Uuid, files: Vec<(String, String, Vec<u8>)>) -> Result<Vec<miscord_protocol::AttachmentData>> {
+    /// Upload a file in chunks, resuming where the server left off if the connection drops.
+    /// `on_progress` is called with the bytes the server has and whether we're waiting to reconnect.
+    pub async fn upload_file(
+        &self,
+        channel_id: Uuid,
+        file: &UploadFile,
+        on_progress: impl Fn(u64, bool),
+    ) -> Result<miscord_protocol::AttachmentData> {
         let server_url = self.get_server_url().await;
         let token = self.get_token().await;
 
-        let client = reqwest::Client::new();
-        let mut form = reqwest::multipart::Form::new();
-
-        for (filename, content_type, data) in files {
-            let part = reqwest::multipart::Part::bytes(data)
-                .file_name(filename)
-                .mime_str(&content_type)
-                .map_err(|e| anyhow::anyhow!("Invalid content type: {}", e))?;
-            form = form.part("file", part);
+        // The server may already have the file, in which case the bytes needn't be sent
+        #[derive(serde::Serialize)]
+        struct ReuseUpload<'a> {
+            channel_id: Uuid,
+            filename: &'a str,
+            sha256: String,
         }
 
-        let mut request = client
-            .post(format!("{}/api/channels/{}/upload", server_url, channel_id))
-            .multipart(form);
-
-        if let Some(token) = token {
-            request = request.header("Authorization", format!("Bearer {}", token));
+        on_progress(0, false);
+        match file.sha256().await {
+            Ok(sha256) => {
+                let existing: Result<Option<miscord_protocol::AttachmentData>> = api::post(
+                    &format!("{}/api/uploads/existing", server_url),
+                    &ReuseUpload {
+                        channel_id,
+                        filename: &file.filename,
+                        sha256,
+                    },
+                    token.as_deref(),
+                )
+                .await;
+                match existing {
+                    Ok(Some(attachment)) => {
+                        on_progress(file.size, false);
+                        return Ok(attachment);
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Couldn't check whether the server has {}: {}", file.filename, e),
+                }
+            }
+            Err(e) => tracing::warn!("Couldn't hash {}: {}", file.filename, e),
         }
 
-        let response = request.send().await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(anyhow::anyhow!("Upload failed: {}", error_text));
+        #[derive(serde::Serialize)]
+        struct CreateUpload<'a> {
+            channel_id: Uuid,
+            filename: &'a str,
+            content_type: &'a str,
+            size_bytes: u64,
         }
 
-        #[derive(serde::Deserialize)]
-        struct UploadResponse {
-            attachments: Vec<miscord_protocol::AttachmentData>,
+        let mut session: UploadSessionData = api::post(
+            &format!("{}/api/uploads", server_url),
+            &CreateUpload {
+                channel_id,
+                filename: &file.filename,
+                content_type: &file.content_type,
+                size_bytes: file.size,
+            },
+            token.as_deref(),
+        )
+        .await?;
+
+        let client = reqwest::Client::builder()
+            .connect_timeout(std::time::Duration::from_secs(10))
+            .timeout(UPLOAD_CHUNK_TIMEOUT)
+            .build()?;
+        let mut reader = file.source.open().await?;
+        let mut failures = 0;
+
+        while (session.received_bytes as u64) < file.size {
+            let offset = session.received_bytes as u64;
+            on_progress(offset, failures > 0);
+
+            let len = (session.chunk_size as u64).min(file.size - offset);
+            let chunk = reader.read_at(offset, len as usize).await?;
+
+            let mut request = client
+                .put(format!("{}/api/uploads/{}/chunks?offset={}", server_url, session.id, offset))
+                .body(chunk);
+            if let Some(token) = &token {
+                request = request.header("Authorization", format!("Bearer {}", token));
+            }
+
+            let error = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    session = response.json().await?;
+                    failures = 0;
+                    continue;
+                }
+                // A conflict means we're out of step with the server about the offset, which the
+                // resync below fixes; server errors may clear up on their own
+                Ok(response)
+                    if response.status() == reqwest::StatusCode::CONFLICT || response.status().is_server_error() =>
+                {
+                    anyhow::anyhow!("Chunk upload failed with status {}", response.status())
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let text = response.text().await.unwrap_or_default();
+                    anyhow::bail!("Upload failed with status {}: {}", status, text);
+                }
+                Err(e) => e.into(),
+            };
+
+            failures += 1;
+            if failures > UPLOAD_MAX_RETRIES {
+                return Err(error.context("Upload failed after repeated retries"));
+            }
+            tracing::warn!("Upload of {} interrupted, retrying: {}", file.filename, error);
+            on_progress(offset, true);
+
+            // Back off, then ask the server how much it has before carrying on
+            let delay = UPLOAD_RETRY_BASE_DELAY * 2u32.pow(failures.min(5) - 1);
+            tokio::time::sleep(delay).await;
+            if let Ok(current) = api::get::<UploadSessionData>(
+                &format!("{}/api/uploads/{}", server_url, session.id),
+                token.as_deref(),
+            )
+            .await
+            {
+                session = current;
+            }
         }
 
-        let upload_response: UploadResponse = response.json().await?;
-        Ok(upload_response.attachments)
+        on_progress(file.size, false);
+        api::post(
+            &format!("{}/api/uploads/{}/complete", server_url, session.id),
+            &serde_json::json!({}),
+            token.as_deref(),
+        )
+        .await
     }
 
     pub async fn update_message(&self, message_id: Uuid, content: &str) -> Result<MessageData> {
@@ -471,6 +939,79 @@ impl NetworkClient {
         .await
     }
 
+    /// Forward a message into another channel or DM
+    pub async fn forward_message(&self, message_id: Uuid, channel_id: Uuid) -> Result<MessageData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        #[derive(serde::Serialize)]
+        struct ForwardMessage {
+            channel_id: Uuid,
+        }
+
+        api::post(
+            &format!("{}/api/messages/{}/forward", server_url, message_id),
+            &ForwardMessage { channel_id },
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// Publish an announcement to the channels following its channel
+    pub async fn publish_message(&self, message_id: Uuid) -> Result<()> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::post_empty_void(
+            &format!("{}/api/messages/{}/publish", server_url, message_id),
+            token.as_deref(),
+        )
+        .await
+    }
+
+    // Announcement follows
+
+    /// Announcement channels that a channel follows
+    pub async fn get_channel_follows(&self, channel_id: Uuid) -> Result<Vec<ChannelFollowData>> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::get(
+            &format!("{}/api/channels/{}/follows", server_url, channel_id),
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// Copy an announcement channel's published messages into `target_channel_id`
+    pub async fn follow_channel(&self, source_channel_id: Uuid, target_channel_id: Uuid) -> Result<ChannelFollowData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        #[derive(serde::Serialize)]
+        struct CreateChannelFollow {
+            target_channel_id: Uuid,
+        }
+
+        api::post(
+            &format!("{}/api/channels/{}/followers", server_url, source_channel_id),
+            &CreateChannelFollow { target_channel_id },
+            token.as_deref(),
+        )
+        .await
+    }
+
+    pub async fn unfollow_channel(&self, source_channel_id: Uuid, target_channel_id: Uuid) -> Result<()> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::delete(
+            &format!("{}/api/channels/{}/followers/{}", server_url, source_channel_id, target_channel_id),
+            token.as_deref(),
+        )
+        .await
+    }
+
     /// Get pinned messages in a channel
     pub async fn get_pinned_messages(&self, channel_id: Uuid) -> Result<Vec<MessageData>> {
         let server_url = self.get_server_url().await;
@@ -525,6 +1066,66 @@ impl NetworkClient {
         .await
     }
 
+    /// Get the unarchived threads the current user follows
+    pub async fn get_followed_threads(&self) -> Result<Vec<ThreadSummaryData>> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::get(
+            &format!("{}/api/users/me/threads", server_url),
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// List active (or archived) threads in a channel
+    pub async fn get_channel_threads(&self, channel_id: Uuid, archived: bool) -> Result<Vec<ThreadSummaryData>> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::get(
+            &format!("{}/api/channels/{}/threads?archived={}", server_url, channel_id, archived),
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// Follow a thread to get notified about new replies
+    pub async fn follow_thread(&self, parent_message_id: Uuid) -> Result<()> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::put_empty(
+            &format!("{}/api/messages/{}/thread/follow", server_url, parent_message_id),
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// Stop following a thread
+    pub async fn unfollow_thread(&self, parent_message_id: Uuid) -> Result<()> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::delete(
+            &format!("{}/api/messages/{}/thread/follow", server_url, parent_message_id),
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// Mark all replies in a thread as read
+    pub async fn mark_thread_read(&self, parent_message_id: Uuid) -> Result<()> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::post_empty_void(
+            &format!("{}/api/messages/{}/thread/read", server_url, parent_message_id),
+            token.as_deref(),
+        )
+        .await
+    }
+
     /// Subscribe to thread updates via WebSocket
     pub async fn subscribe_thread(&self, parent_message_id: Uuid) {
         if let Some(client) = self.ws_client.read().await.as_ref() {
@@ -539,6 +1140,107 @@ impl NetworkClient {
         }
     }
 
+    // Forums
+
+    pub async fn get_forum_tags(&self, channel_id: Uuid) -> Result<Vec<ForumTagData>> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::get(
+            &format!("{}/api/channels/{}/forum/tags", server_url, channel_id),
+            token.as_deref(),
+        )
+        .await
+    }
+
+    pub async fn create_forum_tag(&self, channel_id: Uuid, name: &str) -> Result<ForumTagData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        #[derive(serde::Serialize)]
+        struct CreateForumTag {
+            name: String,
+        }
+
+        api::post(
+            &format!("{}/api/channels/{}/forum/tags", server_url, channel_id),
+            &CreateForumTag { name: name.to_string() },
+            token.as_deref(),
+        )
+        .await
+    }
+
+    pub async fn delete_forum_tag(&self, tag_id: Uuid) -> Result<()> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        api::delete(
+            &format!("{}/api/forum/tags/{}", server_url, tag_id),
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// List forum posts by recent activity, optionally only those with any of `tag_ids`
+    pub async fn get_forum_posts(&self, channel_id: Uuid, tag_ids: &[Uuid]) -> Result<Vec<ForumPostData>> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        let mut url = format!("{}/api/channels/{}/posts", server_url, channel_id);
+        if !tag_ids.is_empty() {
+            let tags: Vec<String> = tag_ids.iter().map(|id| id.to_string()).collect();
+            url.push_str(&format!("?tags={}", tags.join(",")));
+        }
+
+        api::get(&url, token.as_deref()).await
+    }
+
+    pub async fn create_forum_post(
+        &self,
+        channel_id: Uuid,
+        title: &str,
+        content: &str,
+        tag_ids: &[Uuid],
+    ) -> Result<ForumPostData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        #[derive(serde::Serialize)]
+        struct CreateForumPost {
+            title: String,
+            content: String,
+            tag_ids: Vec<Uuid>,
+        }
+
+        api::post(
+            &format!("{}/api/channels/{}/posts", server_url, channel_id),
+            &CreateForumPost {
+                title: title.to_string(),
+                content: content.to_string(),
+                tag_ids: tag_ids.to_vec(),
+            },
+            token.as_deref(),
+        )
+        .await
+    }
+
+    pub async fn set_forum_post_tags(&self, message_id: Uuid, tag_ids: &[Uuid]) -> Result<ForumPostData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        #[derive(serde::Serialize)]
+        struct UpdateForumPostTags {
+            tag_ids: Vec<Uuid>,
+        }
+
+        api::put(
+            &format!("{}/api/posts/{}/tags", server_url, message_id),
+            &UpdateForumPostTags { tag_ids: tag_ids.to_vec() },
+            token.as_deref(),
+        )
+        .await
+    }
+
     // Voice
 
     pub async fn join_voice(&self, channel_id: Uuid) -> Result<()> {
@@ -687,36 +1389,42 @@ impl NetworkClient {
         .await
     }
 
-    /// Fetch and decode an image from a URL
-    /// Returns (RGBA bytes, width, height)
+    /// Fetch and decode an external image through the server's media proxy, so the
+    /// site hosting it never sees the user's IP. Returns (RGBA bytes, width, height)
     pub async fn fetch_image(&self, url: &str) -> Result<(Vec<u8>, u32, u32)> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
         let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(10))
+            .timeout(std::time::Duration::from_secs(15))
             .build()?;
 
-        let response = client.get(url).send().await?;
+        // The proxy scales the image down to fit 400px (max for previews)
+        let mut request = client.get(format!(
+            "{}/api/media-proxy?url={}&size=400",
+            server_url,
+            urlencoding::encode(url)
+        ));
+        if let Some(token) = token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+
+        let response = request.send().await?.error_for_status()?;
         let bytes = response.bytes().await?;
 
         // Decode the image
         let img = image::load_from_memory(&bytes)
             .map_err(|e| anyhow::anyhow!("Failed to decode image: {}", e))?;
 
-        // Resize if too large (max 400px width for previews)
-        let img = if img.width() > 400 {
-            img.resize(400, 400, image::imageops::FilterType::Triangle)
-        } else {
-            img
-        };
-
         let rgba = img.to_rgba8();
         let (width, height) = rgba.dimensions();
 
         Ok((rgba.into_raw(), width, height))
     }
 
-    /// Fetch an attachment image from the server
+    /// Fetch an attachment image from the server, shrunk to fit within `max_size` pixels
     /// attachment_url should be a relative path like "/api/files/{id}"
-    pub async fn fetch_attachment_image(&self, attachment_url: &str) -> Result<(Vec<u8>, u32, u32)> {
+    pub async fn fetch_attachment_image(&self, attachment_url: &str, max_size: u32) -> Result<(Vec<u8>, u32, u32)> {
         let server_url = self.get_server_url().await;
         let full_url = format!("{}{}", server_url, attachment_url);
         let token = self.get_token().await;
@@ -737,9 +1445,8 @@ impl NetworkClient {
         let img = image::load_from_memory(&bytes)
             .map_err(|e| anyhow::anyhow!("Failed to decode image: {}", e))?;
 
-        // Resize if too large (max 500px width for attachment previews)
-        let img = if img.width() > 500 {
-            img.resize(500, 500, image::imageops::FilterType::Triangle)
+        let img = if img.width() > max_size || img.height() > max_size {
+            img.resize(max_size, max_size, image::imageops::FilterType::Triangle)
         } else {
             img
         };
@@ -772,6 +1479,46 @@ impl NetworkClient {
         Ok(bytes.to_vec())
     }
 
+    // Exports
+
+    /// Queue an export of a channel's history; progress arrives as ExportUpdated events
+    pub async fn create_export(&self, channel_id: Uuid, format: ExportFormat) -> Result<ExportData> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        #[derive(serde::Serialize)]
+        struct CreateExport {
+            format: ExportFormat,
+        }
+
+        api::post(
+            &format!("{}/api/channels/{}/exports", server_url, channel_id),
+            &CreateExport { format },
+            token.as_deref(),
+        )
+        .await
+    }
+
+    /// Download a finished export's zip file
+    pub async fn download_export(&self, export_id: Uuid) -> Result<Vec<u8>> {
+        let server_url = self.get_server_url().await;
+        let token = self.get_token().await;
+
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(300))
+            .build()?;
+
+        let mut request = client.get(format!("{}/api/exports/{}/download", server_url, export_id));
+        if let Some(token) = token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+
+        let response = request.send().await?.error_for_status()?;
+        let bytes = response.bytes().await?;
+
+        Ok(bytes.to_vec())
+    }
+
     /// Get the base server URL (e.g., "http://localhost:3000")
     pub async fn get_base_url(&self) -> String {
         self.get_server_url().await
@@ -282,6 +282,22 @@ impl WebSocketClient {
             } => {
                 state.update_thread_metadata(message_id, reply_count, last_reply_at).await;
             }
+            ServerMessage::FollowedThreadUpdated {
+                parent_message_id,
+                channel_id: _,
+                reply_count,
+                last_reply_at,
+            } => {
+                state
+                    .on_followed_thread_updated(parent_message_id, reply_count, last_reply_at)
+                    .await;
+            }
+            ServerMessage::ThreadArchived {
+                parent_message_id,
+                channel_id: _,
+            } => {
+                state.remove_followed_thread(parent_message_id).await;
+            }
             // Pinned messages
             ServerMessage::MessagePinned {
                 message_id,
@@ -298,6 +314,71 @@ impl WebSocketClient {
             } => {
                 state.mark_message_unpinned(message_id, channel_id).await;
             }
+            // Channel and community lifecycle
+            ServerMessage::ChannelCreated { channel } | ServerMessage::ChannelUpdated { channel } => {
+                state.upsert_channel(channel).await;
+            }
+            ServerMessage::ChannelDeleted {
+                channel_id,
+                community_id: _,
+            } => {
+                state.remove_channel(channel_id).await;
+            }
+            ServerMessage::CommunityUpdated { community } => {
+                state.upsert_community(community).await;
+            }
+            ServerMessage::CommunityDeleted { community_id } => {
+                state.remove_community(community_id).await;
+            }
+            ServerMessage::MemberJoined { community_id, user } => {
+                state.add_member(community_id, user).await;
+            }
+            ServerMessage::MemberLeft { community_id, user_id } => {
+                let current_user_id = state.read().await.current_user.as_ref().map(|u| u.id);
+                if current_user_id == Some(user_id) {
+                    // We left (or were removed) from another session
+                    state.remove_community(community_id).await;
+                } else {
+                    state.remove_member(community_id, user_id).await;
+                }
+            }
+            ServerMessage::ChannelsReordered {
+                community_id: _,
+                channels,
+            } => {
+                state.apply_channel_positions(&channels).await;
+            }
+            ServerMessage::MessagePublished {
+                message_id,
+                channel_id,
+                published_at,
+            } => {
+                state.set_message_published(channel_id, message_id, published_at).await;
+            }
+            ServerMessage::ForumPostCreated { post } | ServerMessage::ForumPostUpdated { post } => {
+                state.upsert_forum_post(post).await;
+            }
+            ServerMessage::ForumTagsUpdated { channel_id, tags } => {
+                state.set_forum_tags(channel_id, tags).await;
+            }
+            ServerMessage::ExportUpdated { export } => {
+                state.upsert_export(export).await;
+            }
+            ServerMessage::CommandsUpdated {
+                community_id,
+                commands,
+            } => {
+                state.set_commands(community_id, commands).await;
+            }
+            ServerMessage::ReadStateUpdated {
+                channel_id,
+                user_id,
+                last_read_message_id,
+            } => {
+                state
+                    .update_read_state(channel_id, user_id, last_read_message_id)
+                    .await;
+            }
             _ => {}
         }
     }
@@ -8,7 +8,11 @@ use uuid::Uuid;
 /// Cached image data (RGBA bytes, width, height) wrapped in Arc to avoid cloning
 pub type CachedImageData = Arc<(Vec<u8>, u32, u32)>;
 
-use miscord_protocol::{ChannelData, CommunityData, MessageData, UserData};
+use miscord_protocol::{
+    ChannelData, ChannelFollowData, ChannelPositionData, CommunityData, CustomEmojiData, ExportData, ForumPostData,
+    ForumTagData, MessageData, PrivacySettingsData, ReadStateData, SlashCommandData, StorageUsageData, ThreadSummaryData,
+    UserData,
+};
 
 use crate::network::OpenGraphData;
 
@@ -54,6 +58,25 @@ pub struct AppStateInner {
     // Messages (channel_id -> messages)
     pub messages: HashMap<Uuid, Vec<MessageData>>,
 
+    // Channels whose loaded messages are a window in history that doesn't reach the newest message
+    pub detached_channels: HashSet<Uuid>,
+
+    // DM read receipts (channel_id -> user_id -> last read message_id)
+    pub read_receipts: HashMap<Uuid, HashMap<Uuid, Uuid>>,
+
+    // Announcement channels each channel follows (target channel_id -> follows)
+    pub channel_follows: HashMap<Uuid, Vec<ChannelFollowData>>,
+
+    // Exports requested this session, with their latest progress (export_id -> export)
+    pub exports: HashMap<Uuid, ExportData>,
+
+    // Privacy settings of the current user (None until loaded)
+    pub privacy_settings: Option<PrivacySettingsData>,
+
+    // Storage used by the current user and by communities (None/missing until loaded)
+    pub storage_usage: Option<StorageUsageData>,
+    pub community_storage_usage: HashMap<Uuid, StorageUsageData>,
+
     // Message reactions (message_id -> emoji -> reaction state)
     pub message_reactions: HashMap<Uuid, HashMap<String, ReactionState>>,
 
@@ -63,6 +86,12 @@ pub struct AppStateInner {
     // Community members (community_id -> list of members)
     pub members: HashMap<Uuid, Vec<UserData>>,
 
+    // Custom emoji (community_id -> emoji list)
+    pub custom_emojis: HashMap<Uuid, Vec<CustomEmojiData>>,
+
+    // Slash commands registered by bots (community_id -> commands)
+    pub commands: HashMap<Uuid, Vec<SlashCommandData>>,
+
     // Typing indicators (channel_id -> (user_id -> started_at))
     pub typing_users: HashMap<Uuid, HashMap<Uuid, Instant>>,
 
@@ -111,6 +140,13 @@ pub struct AppStateInner {
     // Thread state
     pub open_thread: Option<Uuid>, // Parent message ID of currently open thread
     pub thread_messages: HashMap<Uuid, Vec<MessageData>>, // parent_message_id -> thread replies
+    pub followed_threads: Vec<ThreadSummaryData>, // most recently active first
+    pub followed_threads_stale: bool, // set when a followed thread we don't know about changes
+    pub thread_parents: HashMap<Uuid, MessageData>, // parents loaded with a thread, for parents outside `messages`
+
+    // Forum state (channel_id -> posts most recently active first / tags)
+    pub forum_posts: HashMap<Uuid, Vec<ForumPostData>>,
+    pub forum_tags: HashMap<Uuid, Vec<ForumTagData>>,
 
     // Link preview cache (url -> OpenGraph data)
     pub opengraph_cache: HashMap<String, OpenGraphData>,
@@ -148,9 +184,18 @@ impl Default for AppStateInner {
             channels: HashMap::new(),
             current_channel_id: None,
             messages: HashMap::new(),
+            detached_channels: HashSet::new(),
+            read_receipts: HashMap::new(),
+            channel_follows: HashMap::new(),
+            exports: HashMap::new(),
+            privacy_settings: None,
+            storage_usage: None,
+            community_storage_usage: HashMap::new(),
             message_reactions: HashMap::new(),
             users: HashMap::new(),
             members: HashMap::new(),
+            custom_emojis: HashMap::new(),
+            commands: HashMap::new(),
             typing_users: HashMap::new(),
             voice_channel_id: None,
             voice_participants: HashMap::new(),
@@ -182,6 +227,11 @@ impl Default for AppStateInner {
             pending_keyframe_requests: Vec::new(),
             open_thread: None,
             thread_messages: HashMap::new(),
+            followed_threads: Vec::new(),
+            followed_threads_stale: true,
+            thread_parents: HashMap::new(),
+            forum_posts: HashMap::new(),
+            forum_tags: HashMap::new(),
             opengraph_cache: HashMap::new(),
             opengraph_pending: HashSet::new(),
             image_cache: HashMap::new(),
@@ -262,6 +312,20 @@ impl AppState {
         state.communities.clear();
         state.channels.clear();
         state.messages.clear();
+        state.detached_channels.clear();
+        state.read_receipts.clear();
+        state.channel_follows.clear();
+        state.exports.clear();
+        state.privacy_settings = None;
+        state.storage_usage = None;
+        state.community_storage_usage.clear();
+        state.followed_threads.clear();
+        state.followed_threads_stale = true;
+        state.thread_parents.clear();
+        state.forum_posts.clear();
+        state.forum_tags.clear();
+        state.custom_emojis.clear();
+        state.commands.clear();
     }
 
     pub async fn is_authenticated(&self) -> bool {
@@ -270,6 +334,10 @@ impl AppState {
 
     pub async fn add_message(&self, message: MessageData) {
         let mut state = self.inner.write().await;
+        // A detached window picks new messages up once it is scrolled back to the present
+        if state.detached_channels.contains(&message.channel_id) {
+            return;
+        }
         // Messages are stored in DESC order (newest first)
         // Insert at front so newest message is at index 0
         state
@@ -291,6 +359,103 @@ impl AppState {
         }
     }
 
+    /// Apply new channel positions and categories (from a reorder)
+    pub async fn apply_channel_positions(&self, positions: &[ChannelPositionData]) {
+        let mut state = self.inner.write().await;
+        for entry in positions {
+            if let Some(channel) = state.channels.get_mut(&entry.id) {
+                channel.position = entry.position;
+                channel.parent_id = entry.parent_id;
+            }
+        }
+    }
+
+    /// Add or replace a channel (from a ChannelCreated/ChannelUpdated event).
+    /// Keeps the locally tracked unread count.
+    pub async fn upsert_channel(&self, mut channel: ChannelData) {
+        let mut state = self.inner.write().await;
+        if let Some(existing) = state.channels.get(&channel.id) {
+            channel.unread_count = existing.unread_count;
+        }
+        state.channels.insert(channel.id, channel);
+    }
+
+    /// Remove a deleted channel and everything loaded for it
+    pub async fn remove_channel(&self, channel_id: Uuid) {
+        let mut state = self.inner.write().await;
+        state.channels.remove(&channel_id);
+        state.messages.remove(&channel_id);
+        state.detached_channels.remove(&channel_id);
+        state.read_receipts.remove(&channel_id);
+        state.channel_follows.remove(&channel_id);
+        state.forum_posts.remove(&channel_id);
+        state.forum_tags.remove(&channel_id);
+        state.recent_channel_ids.retain(|&id| id != channel_id);
+        if state.current_channel_id == Some(channel_id) {
+            state.current_channel_id = None;
+        }
+
+        // The server moves a deleted category's channels to the top level
+        for channel in state.channels.values_mut() {
+            if channel.parent_id == Some(channel_id) {
+                channel.parent_id = None;
+            }
+        }
+    }
+
+    /// Add or replace a community (from a CommunityUpdated event)
+    pub async fn upsert_community(&self, community: CommunityData) {
+        let mut state = self.inner.write().await;
+        state.communities.insert(community.id, community);
+    }
+
+    /// Remove a community we left or that was deleted, with its channels
+    pub async fn remove_community(&self, community_id: Uuid) {
+        let mut state = self.inner.write().await;
+        state.communities.remove(&community_id);
+        state.members.remove(&community_id);
+        state.custom_emojis.remove(&community_id);
+        state.commands.remove(&community_id);
+
+        let channel_ids: Vec<Uuid> = state
+            .channels
+            .values()
+            .filter(|c| c.community_id == Some(community_id))
+            .map(|c| c.id)
+            .collect();
+        for channel_id in &channel_ids {
+            state.channels.remove(channel_id);
+            state.messages.remove(channel_id);
+            state.detached_channels.remove(channel_id);
+        }
+        state.recent_channel_ids.retain(|id| !channel_ids.contains(id));
+        state
+            .followed_threads
+            .retain(|t| !channel_ids.contains(&t.channel_id));
+
+        if state.current_community_id == Some(community_id) {
+            state.current_community_id = None;
+            state.current_channel_id = None;
+        }
+    }
+
+    /// Add a member who joined a community
+    pub async fn add_member(&self, community_id: Uuid, user: UserData) {
+        let mut state = self.inner.write().await;
+        let members = state.members.entry(community_id).or_default();
+        if !members.iter().any(|m| m.id == user.id) {
+            members.push(user);
+        }
+    }
+
+    /// Remove a member who left a community
+    pub async fn remove_member(&self, community_id: Uuid, user_id: Uuid) {
+        let mut state = self.inner.write().await;
+        if let Some(members) = state.members.get_mut(&community_id) {
+            members.retain(|m| m.id != user_id);
+        }
+    }
+
     pub async fn select_community(&self, community_id: Uuid) {
         let mut state = self.inner.write().await;
         state.current_community_id = Some(community_id);
@@ -305,6 +470,8 @@ impl AppState {
     pub async fn select_channel(&self, channel_id: Uuid) {
         let mut state = self.inner.write().await;
         state.current_channel_id = Some(channel_id);
+        // Selecting a channel always loads its latest messages
+        state.detached_channels.remove(&channel_id);
 
         // Add to recent channels (move to front if already present)
         state.recent_channel_ids.retain(|&id| id != channel_id);
@@ -326,6 +493,79 @@ impl AppState {
         }
     }
 
+    /// Replace the read receipts of a DM channel
+    pub async fn set_read_receipts(&self, channel_id: Uuid, receipts: Vec<ReadStateData>) {
+        let mut state = self.inner.write().await;
+        let receipts = receipts
+            .into_iter()
+            .filter_map(|r| Some((r.user_id, r.last_read_message_id?)))
+            .collect();
+        state.read_receipts.insert(channel_id, receipts);
+    }
+
+    /// Replace the announcement channels a channel follows
+    pub async fn set_channel_follows(&self, channel_id: Uuid, follows: Vec<ChannelFollowData>) {
+        let mut state = self.inner.write().await;
+        state.channel_follows.insert(channel_id, follows);
+    }
+
+    /// Add a follow created by the current user
+    pub async fn add_channel_follow(&self, follow: ChannelFollowData) {
+        let mut state = self.inner.write().await;
+        let follows = state.channel_follows.entry(follow.target_channel_id).or_default();
+        follows.retain(|f| f.source_channel_id != follow.source_channel_id);
+        follows.push(follow);
+    }
+
+    pub async fn remove_channel_follow(&self, source_channel_id: Uuid, target_channel_id: Uuid) {
+        let mut state = self.inner.write().await;
+        if let Some(follows) = state.channel_follows.get_mut(&target_channel_id) {
+            follows.retain(|f| f.source_channel_id != source_channel_id);
+        }
+    }
+
+    /// Add or update an export (from the create response or an ExportUpdated event)
+    pub async fn upsert_export(&self, export: ExportData) {
+        let mut state = self.inner.write().await;
+        state.exports.insert(export.id, export);
+    }
+
+    /// Mark an announcement as published (from a MessagePublished event)
+    pub async fn set_message_published(&self, channel_id: Uuid, message_id: Uuid, published_at: DateTime<Utc>) {
+        let mut state = self.inner.write().await;
+        if let Some(message) = state
+            .messages
+            .get_mut(&channel_id)
+            .and_then(|messages| messages.iter_mut().find(|m| m.id == message_id))
+        {
+            message.published_at = Some(published_at);
+        }
+    }
+
+    /// Apply a ReadStateUpdated event.
+    /// The current user's own updates come from other sessions and clear the unread badge.
+    pub async fn update_read_state(&self, channel_id: Uuid, user_id: Uuid, last_read_message_id: Option<Uuid>) {
+        let mut state = self.inner.write().await;
+        let is_self = state.current_user.as_ref().map(|u| u.id) == Some(user_id);
+
+        if is_self {
+            if let Some(channel) = state.channels.get_mut(&channel_id) {
+                channel.unread_count = 0;
+            }
+            return;
+        }
+
+        let receipts = state.read_receipts.entry(channel_id).or_default();
+        match last_read_message_id {
+            Some(message_id) => {
+                receipts.insert(user_id, message_id);
+            }
+            None => {
+                receipts.remove(&user_id);
+            }
+        }
+    }
+
     pub async fn join_voice(&self, channel_id: Uuid) {
         let mut state = self.inner.write().await;
         state.voice_channel_id = Some(channel_id);
@@ -650,6 +890,17 @@ impl AppState {
             state.message_reactions.insert(message.id, emoji_reactions);
         }
 
+        // Replying follows the thread on the server unless it was unfollowed before
+        let is_own_reply = state.current_user.as_ref().map(|u| u.id) == Some(message.author_id);
+        if is_own_reply
+            && !state
+                .followed_threads
+                .iter()
+                .any(|t| t.parent_message_id == parent_message_id)
+        {
+            state.followed_threads_stale = true;
+        }
+
         state
             .thread_messages
             .entry(parent_message_id)
@@ -673,6 +924,20 @@ impl AppState {
                 break;
             }
         }
+        if let Some(parent) = state.thread_parents.get_mut(&message_id) {
+            parent.reply_count = reply_count;
+            parent.last_reply_at = last_reply_at;
+        }
+
+        // A reply bumps a forum post to the top of its forum
+        for posts in state.forum_posts.values_mut() {
+            if let Some(post) = posts.iter_mut().find(|p| p.message_id == message_id) {
+                post.reply_count = reply_count;
+                post.last_reply_at = last_reply_at;
+                posts.sort_by_key(|p| std::cmp::Reverse(p.last_activity_at()));
+                break;
+            }
+        }
     }
 
     /// Get the currently open thread's parent message ID
@@ -697,6 +962,162 @@ impl AppState {
         state.thread_messages.remove(&parent_message_id);
     }
 
+    /// Replace the list of followed threads
+    pub async fn set_followed_threads(&self, threads: Vec<ThreadSummaryData>) {
+        let mut state = self.inner.write().await;
+        state.followed_threads = threads;
+        state.followed_threads_stale = false;
+    }
+
+    /// A followed thread got a new reply from someone else
+    pub async fn on_followed_thread_updated(
+        &self,
+        parent_message_id: Uuid,
+        reply_count: i32,
+        last_reply_at: Option<DateTime<Utc>>,
+    ) {
+        let mut state = self.inner.write().await;
+        let is_open = state.open_thread == Some(parent_message_id);
+
+        let Some(index) = state
+            .followed_threads
+            .iter()
+            .position(|t| t.parent_message_id == parent_message_id)
+        else {
+            // Newly followed (e.g. someone replied to our message); refetch the list
+            state.followed_threads_stale = true;
+            return;
+        };
+
+        let mut thread = state.followed_threads.remove(index);
+        thread.reply_count = reply_count;
+        thread.last_reply_at = last_reply_at;
+        thread.archived = false;
+        if !is_open {
+            thread.unread_count += 1;
+        }
+        state.followed_threads.insert(0, thread);
+    }
+
+    /// Clear the unread count of a followed thread
+    pub async fn mark_thread_read_local(&self, parent_message_id: Uuid) {
+        let mut state = self.inner.write().await;
+        if let Some(thread) = state
+            .followed_threads
+            .iter_mut()
+            .find(|t| t.parent_message_id == parent_message_id)
+        {
+            thread.unread_count = 0;
+        }
+    }
+
+    /// Update whether we follow a thread
+    pub async fn set_thread_following(&self, parent_message_id: Uuid, following: bool) {
+        let mut state = self.inner.write().await;
+        if following {
+            // The summary (channel name, author) comes from the server
+            state.followed_threads_stale = true;
+        } else {
+            state
+                .followed_threads
+                .retain(|t| t.parent_message_id != parent_message_id);
+        }
+    }
+
+    /// Remove an archived thread from the followed list
+    pub async fn remove_followed_thread(&self, parent_message_id: Uuid) {
+        let mut state = self.inner.write().await;
+        state
+            .followed_threads
+            .retain(|t| t.parent_message_id != parent_message_id);
+    }
+
+    /// Whether the current user follows a thread
+    pub fn is_following_thread_sync(&self, parent_message_id: Uuid) -> bool {
+        self.inner
+            .try_read()
+            .map(|s| {
+                s.followed_threads
+                    .iter()
+                    .any(|t| t.parent_message_id == parent_message_id)
+            })
+            .unwrap_or(false)
+    }
+
+    /// Remember the parent message of a loaded thread
+    pub async fn set_thread_parent(&self, parent: MessageData) {
+        let mut state = self.inner.write().await;
+        state.thread_parents.insert(parent.id, parent);
+    }
+
+    /// Replace the loaded posts of a forum
+    pub async fn set_forum_posts(&self, channel_id: Uuid, mut posts: Vec<ForumPostData>) {
+        let mut state = self.inner.write().await;
+        posts.sort_by_key(|p| std::cmp::Reverse(p.last_activity_at()));
+        state.forum_posts.insert(channel_id, posts);
+    }
+
+    /// Add a new forum post or replace an updated one
+    pub async fn upsert_forum_post(&self, post: ForumPostData) {
+        let mut state = self.inner.write().await;
+        let posts = state.forum_posts.entry(post.channel_id).or_default();
+        match posts.iter_mut().find(|p| p.message_id == post.message_id) {
+            Some(existing) => *existing = post,
+            None => posts.push(post),
+        }
+        posts.sort_by_key(|p| std::cmp::Reverse(p.last_activity_at()));
+    }
+
+    /// Replace the tags of a forum
+    pub async fn set_forum_tags(&self, channel_id: Uuid, tags: Vec<ForumTagData>) {
+        let mut state = self.inner.write().await;
+        state.forum_tags.insert(channel_id, tags);
+    }
+
+    /// Replace a channel's messages with a page of history (newest first).
+    /// `detached` marks a window that doesn't reach the newest message.
+    pub async fn set_message_window(&self, channel_id: Uuid, messages: Vec<MessageData>, detached: bool) {
+        let mut state = self.inner.write().await;
+        store_reactions(&mut state, &messages);
+        state.messages.insert(channel_id, messages);
+        if detached {
+            state.detached_channels.insert(channel_id);
+        } else {
+            state.detached_channels.remove(&channel_id);
+        }
+    }
+
+    /// Append a page of older messages (newest first) after the loaded ones.
+    /// Returns how many messages were new.
+    pub async fn append_older_messages(&self, channel_id: Uuid, messages: Vec<MessageData>) -> usize {
+        let mut state = self.inner.write().await;
+        store_reactions(&mut state, &messages);
+        let existing = state.messages.entry(channel_id).or_default();
+        let before = existing.len();
+        for message in messages {
+            if !existing.iter().any(|m| m.id == message.id) {
+                existing.push(message);
+            }
+        }
+        existing.len() - before
+    }
+
+    /// Prepend a page of newer messages (newest first) before the loaded ones.
+    /// `caught_up` means the page reached the newest message, so live messages resume.
+    pub async fn prepend_newer_messages(&self, channel_id: Uuid, messages: Vec<MessageData>, caught_up: bool) {
+        let mut state = self.inner.write().await;
+        store_reactions(&mut state, &messages);
+        let existing = state.messages.entry(channel_id).or_default();
+        let newer: Vec<MessageData> = messages
+            .into_iter()
+            .filter(|message| !existing.iter().any(|m| m.id == message.id))
+            .collect();
+        existing.splice(0..0, newer);
+        if caught_up {
+            state.detached_channels.remove(&channel_id);
+        }
+    }
+
     /// Mark a message as pinned (called when receiving MessagePinned WebSocket event)
     pub async fn mark_message_pinned(
         &self,
@@ -830,6 +1251,52 @@ impl AppState {
         state.image_failed.insert(url.to_string());
     }
 
+    // Custom emoji methods
+
+    /// Replace the cached custom emoji for a community
+    pub async fn set_custom_emojis(&self, community_id: Uuid, emojis: Vec<CustomEmojiData>) {
+        let mut state = self.inner.write().await;
+        state.custom_emojis.insert(community_id, emojis);
+    }
+
+    /// Get custom emoji usable in the current view synchronously (non-blocking).
+    /// The current community's emoji come first, so they win when a typed `:name:`
+    /// is resolved; emoji from other communities are included so they still render in DMs.
+    pub fn get_custom_emojis_sync(&self) -> Vec<CustomEmojiData> {
+        let Ok(state) = self.inner.try_read() else {
+            return Vec::new();
+        };
+
+        let mut result: Vec<CustomEmojiData> = state
+            .current_community_id
+            .and_then(|id| state.custom_emojis.get(&id))
+            .cloned()
+            .unwrap_or_default();
+
+        for (community_id, emojis) in &state.custom_emojis {
+            if Some(*community_id) == state.current_community_id {
+                continue;
+            }
+            result.extend(emojis.iter().cloned());
+        }
+
+        result
+    }
+
+    /// Replace the cached slash commands of a community
+    pub async fn set_commands(&self, community_id: Uuid, commands: Vec<SlashCommandData>) {
+        let mut state = self.inner.write().await;
+        state.commands.insert(community_id, commands);
+    }
+
+    /// Drop an ephemeral interaction response the user dismissed
+    pub async fn dismiss_ephemeral_message(&self, channel_id: Uuid, message_id: Uuid) {
+        let mut state = self.inner.write().await;
+        if let Some(messages) = state.messages.get_mut(&channel_id) {
+            messages.retain(|m| !(m.ephemeral && m.id == message_id));
+        }
+    }
+
     // Draft message methods
 
     /// Save a draft message for a channel
@@ -897,3 +1364,23 @@ impl Default for AppState {
         Self::new()
     }
 }
+
+/// Populate reaction state from freshly loaded messages
+fn store_reactions(state: &mut AppStateInner, messages: &[MessageData]) {
+    for msg in messages {
+        if msg.reactions.is_empty() {
+            continue;
+        }
+        let emoji_reactions: HashMap<String, ReactionState> = msg
+            .reactions
+            .iter()
+            .map(|reaction| {
+                let reaction_state = ReactionState {
+                    user_ids: reaction.user_ids.iter().copied().collect(),
+                };
+                (reaction.emoji.clone(), reaction_state)
+            })
+            .collect();
+        state.message_reactions.insert(msg.id, emoji_reactions);
+    }
+}
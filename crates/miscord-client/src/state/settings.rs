@@ -4,6 +4,7 @@
 //! Also handles session persistence for automatic login.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::PathBuf;
 use uuid::Uuid;
 
@@ -213,6 +214,9 @@ pub struct UiState {
     /// Whether the voice channels section is expanded
     #[serde(default = "default_true")]
     pub voice_channels_expanded: bool,
+    /// Channel categories the user has collapsed
+    #[serde(default)]
+    pub collapsed_categories: HashSet<Uuid>,
 }
 
 fn default_true() -> bool {
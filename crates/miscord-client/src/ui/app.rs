@@ -386,7 +386,7 @@ impl eframe::App for MiscordApp {
                 }
             }
             View::Settings => {
-                let close = self.settings_view.show(ctx, &self.state, &self.runtime);
+                let close = self.settings_view.show(ctx, &self.state, &self.network, &self.runtime);
                 if close {
                     self.view = View::Main;
                 }
@@ -0,0 +1,85 @@
+//! Decoder for BlurHash (https://blurha.sh) placeholders the server sends with image attachments
+
+use std::f32::consts::PI;
+
+const BASE83: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+fn decode83(s: &[u8]) -> Option<u32> {
+    s.iter().try_fold(0u32, |value, c| {
+        let digit = BASE83.iter().position(|b| b == c)? as u32;
+        Some(value * 83 + digit)
+    })
+}
+
+fn srgb_to_linear(value: u32) -> f32 {
+    let v = value as f32 / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    if v <= 0.0031308 {
+        (v * 12.92 * 255.0 + 0.5) as u8
+    } else {
+        ((1.055 * v.powf(1.0 / 2.4) - 0.055) * 255.0 + 0.5) as u8
+    }
+}
+
+fn sign_pow(value: f32, exp: f32) -> f32 {
+    value.abs().powf(exp).copysign(value)
+}
+
+/// Decode a blurhash into `width` x `height` RGBA pixels, or None if it's malformed.
+/// A small size such as 32x32 is plenty; it's scaled up when drawn.
+pub fn decode(hash: &str, width: u32, height: u32) -> Option<Vec<u8>> {
+    let bytes = hash.as_bytes();
+    if bytes.len() < 6 {
+        return None;
+    }
+
+    let size_flag = decode83(&bytes[0..1])?;
+    let x_components = size_flag % 9 + 1;
+    let y_components = size_flag / 9 + 1;
+    if bytes.len() != 4 + 2 * (x_components * y_components) as usize {
+        return None;
+    }
+
+    let maximum_value = (decode83(&bytes[1..2])? + 1) as f32 / 166.0;
+
+    let dc = decode83(&bytes[2..6])?;
+    let mut colors = vec![[
+        srgb_to_linear(dc >> 16),
+        srgb_to_linear((dc >> 8) & 255),
+        srgb_to_linear(dc & 255),
+    ]];
+    for i in 1..(x_components * y_components) as usize {
+        let value = decode83(&bytes[4 + i * 2..6 + i * 2])?;
+        let quantised = [value / (19 * 19), (value / 19) % 19, value % 19];
+        colors.push(quantised.map(|q| sign_pow((q as f32 - 9.0) / 9.0, 2.0) * maximum_value));
+    }
+
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let mut pixel = [0.0f32; 3];
+            for j in 0..y_components {
+                for i in 0..x_components {
+                    let basis = (PI * x as f32 * i as f32 / width as f32).cos()
+                        * (PI * y as f32 * j as f32 / height as f32).cos();
+                    let color = colors[(i + j * x_components) as usize];
+                    for (channel, value) in pixel.iter_mut().enumerate() {
+                        *value += color[channel] * basis;
+                    }
+                }
+            }
+            pixels.extend(pixel.map(linear_to_srgb));
+            pixels.push(255);
+        }
+    }
+
+    Some(pixels)
+}
@@ -1,28 +1,69 @@
 use eframe::egui;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::Instant;
 use uuid::Uuid;
 
 use crate::network::NetworkClient;
-use crate::state::{AppState, VoiceParticipant};
-use miscord_protocol::ChannelType;
+use crate::state::{AppState, UiState, VoiceParticipant};
+use miscord_protocol::{ChannelData, ChannelPositionData, ChannelType};
 
 use super::theme;
+use super::thread_panel::{followed_thread_button, open_followed_thread};
 
 /// How often to refresh voice participants for channels we're not in (in seconds)
 const VOICE_PARTICIPANTS_REFRESH_INTERVAL: f32 = 1.0;
 
+/// Minimum time between followed thread list refreshes (in seconds)
+const FOLLOWED_THREADS_REFRESH_INTERVAL: f32 = 5.0;
+
+/// Drag-and-drop payload for reordering channels
+#[derive(Debug, Clone, Copy)]
+struct DraggedChannel {
+    id: Uuid,
+    is_category: bool,
+}
+
+impl DraggedChannel {
+    fn of(channel: &ChannelData) -> Self {
+        Self {
+            id: channel.id,
+            is_category: channel.channel_type == ChannelType::Category,
+        }
+    }
+}
+
+/// Where a dragged channel was dropped
+#[derive(Debug, Clone, Copy)]
+enum DropTarget {
+    /// On a channel row: move in front of it, in the same category
+    Channel(Uuid),
+    /// On a category header: move into the category (or in front of it, for categories)
+    Category(Uuid),
+    /// On the text or voice section header: move out of any category
+    Uncategorized,
+}
+
 pub struct ChannelList {
     show_create_dialog: bool,
     new_channel_name: String,
     new_channel_type: ChannelType,
+    /// Category to create the new channel in
+    new_channel_parent: Option<Uuid>,
     show_invite_dialog: bool,
     invite_code: Option<String>,
     invite_loading: bool,
+    show_emoji_dialog: bool,
+    show_leave_dialog: bool,
+    /// Name for the next custom emoji upload
+    new_emoji_name: String,
+    /// Custom emoji being renamed (emoji_id, new name)
+    renaming_emoji: Option<(Uuid, String)>,
     /// Cache of voice participants for channels we're not in
     voice_participants_cache: HashMap<Uuid, Vec<VoiceParticipant>>,
     /// Last time we fetched voice participants
     voice_participants_last_fetch: Option<Instant>,
+    /// Last time we fetched the followed thread list
+    followed_threads_last_fetch: Option<Instant>,
 }
 
 impl ChannelList {
@@ -31,27 +72,32 @@ impl ChannelList {
             show_create_dialog: false,
             new_channel_name: String::new(),
             new_channel_type: ChannelType::Text,
+            new_channel_parent: None,
             show_invite_dialog: false,
             invite_code: None,
             invite_loading: false,
+            show_emoji_dialog: false,
+            show_leave_dialog: false,
+            new_emoji_name: String::new(),
+            renaming_emoji: None,
             voice_participants_cache: HashMap::new(),
             voice_participants_last_fetch: None,
+            followed_threads_last_fetch: None,
         }
     }
 
-    /// Show the channel list. Returns (text_channels_expanded, voice_channels_expanded).
+    /// Show the channel list. Returns true if the persisted UI state
+    /// (expanded sections, collapsed categories) changed.
     pub fn show(
         &mut self,
         ui: &mut egui::Ui,
         state: &AppState,
         network: &NetworkClient,
         runtime: &tokio::runtime::Runtime,
-        text_channels_expanded: bool,
-        voice_channels_expanded: bool,
-    ) -> (bool, bool) {
-        let mut text_expanded = text_channels_expanded;
-        let mut voice_expanded = voice_channels_expanded;
-        let (current_community, channels, current_channel) = runtime.block_on(async {
+        ui_state: &mut UiState,
+    ) -> bool {
+        let mut ui_state_changed = false;
+        let (current_community, mut channels, current_channel, is_owner) = runtime.block_on(async {
             let s = state.read().await;
             let current_community = s.current_community_id;
             let channels: Vec<_> = s
@@ -61,18 +107,27 @@ impl ChannelList {
                 .cloned()
                 .collect();
             let current_channel = s.current_channel_id;
-            (current_community, channels, current_channel)
+            // Only the owner can manage channels for now
+            let is_owner = current_community
+                .and_then(|id| s.communities.get(&id))
+                .zip(s.current_user.as_ref())
+                .is_some_and(|(community, user)| community.owner_id == user.id);
+            (current_community, channels, current_channel, is_owner)
         });
+        channels.sort_by(|a, b| a.position.cmp(&b.position).then_with(|| a.name.cmp(&b.name)));
 
         if current_community.is_none() {
             ui.centered_and_justified(|ui| {
                 ui.label("Select a community");
             });
-            return (text_expanded, voice_expanded);
+            return false;
         }
 
         let community_id = current_community.unwrap();
 
+        // A channel dropped this frame and where it was dropped
+        let mut channel_drop: Option<(DraggedChannel, DropTarget)> = None;
+
         ui.vertical(|ui| {
             // Community name header
             let community_name = runtime.block_on(async {
@@ -91,6 +146,12 @@ impl ChannelList {
                     if ui.button("+").on_hover_text("Create Channel").clicked() {
                         self.show_create_dialog = true;
                     }
+                    if ui.button("Emoji").on_hover_text("Manage Custom Emoji").clicked() {
+                        self.show_emoji_dialog = true;
+                    }
+                    if !is_owner && ui.button("Leave").on_hover_text("Leave Community").clicked() {
+                        self.show_leave_dialog = true;
+                    }
                     if ui.button("Invite").on_hover_text("Create Invite Link").clicked() {
                         self.show_invite_dialog = true;
                         self.invite_code = None;
@@ -116,10 +177,26 @@ impl ChannelList {
 
             ui.separator();
 
-            // Text channels
+            let category_ids: HashSet<Uuid> = channels
+                .iter()
+                .filter(|c| c.channel_type == ChannelType::Category)
+                .map(|c| c.id)
+                .collect();
+            let in_category = |c: &ChannelData| c.parent_id.is_some_and(|p| category_ids.contains(&p));
+
+            // Refresh voice participants for all voice channels we're not in
+            let voice_channel_ids: Vec<_> = channels
+                .iter()
+                .filter(|c| c.channel_type == ChannelType::Voice)
+                .map(|c| c.id)
+                .collect();
+            self.refresh_voice_participants(&voice_channel_ids, state, network, runtime);
+
+            // Text, forum and announcement channels outside of any category
             let text_channels: Vec<_> = channels
                 .iter()
-                .filter(|c| matches!(c.channel_type, ChannelType::Text))
+                .filter(|c| matches!(c.channel_type, ChannelType::Text | ChannelType::Forum | ChannelType::Announcement)
+                    && !in_category(c))
                 .collect();
 
             if !text_channels.is_empty() {
@@ -128,362 +205,183 @@ impl ChannelList {
                         .size(11.0)
                         .color(theme::TEXT_MUTED)
                 )
-                    .default_open(text_expanded)
+                    .default_open(ui_state.text_channels_expanded)
                     .show(ui, |ui| {
                     for channel in text_channels {
-                        let is_selected = current_channel == Some(channel.id);
-                        let has_unread = channel.unread_count > 0;
-                        let has_draft = state.has_draft_sync(channel.id);
-
-                        // Bright text for unread channels, muted for read, normal for selected
-                        let text_color = if is_selected {
-                            theme::TEXT_NORMAL
-                        } else if has_unread {
-                            theme::TEXT_BRIGHT
-                        } else {
-                            theme::TEXT_MUTED
-                        };
-
-                        let response = ui.horizontal(|ui| {
-                            ui.add(
-                                egui::Button::new(
-                                    egui::RichText::new(format!("# {}", channel.name))
-                                        .size(15.0)
-                                        .color(text_color)
-                                )
-                                .fill(if is_selected {
-                                    theme::BG_ACCENT
-                                } else {
-                                    egui::Color32::TRANSPARENT
-                                })
-                                .rounding(egui::Rounding::same(4.0))
-                                .min_size(egui::vec2(ui.available_width() - 32.0, 32.0))
-                            )
-                        });
-
-                        let badge_rect = response.response.rect;
-                        let painter = ui.painter();
-
-                        // Show draft indicator (pencil icon)
-                        if has_draft && !is_selected {
-                            let draft_pos = egui::pos2(
-                                badge_rect.right() - if has_unread { 32.0 } else { 16.0 },
-                                badge_rect.center().y,
-                            );
-                            painter.text(
-                                draft_pos,
-                                egui::Align2::CENTER_CENTER,
-                                "✏",
-                                egui::FontId::proportional(12.0),
-                                theme::TEXT_MUTED,
-                            );
+                        let response = self.show_text_channel(ui, channel, current_channel, state, network, runtime);
+                        if is_owner {
+                            channel_drag_and_drop(ui, &response, DraggedChannel::of(channel), DropTarget::Channel(channel.id), &mut channel_drop);
                         }
+                    }
+                });
+                if is_owner {
+                    channel_drop_target(ui, &text_response.header_response, DropTarget::Uncategorized, &mut channel_drop);
+                }
+                let expanded = text_response.fully_open();
+                if expanded != ui_state.text_channels_expanded {
+                    ui_state.text_channels_expanded = expanded;
+                    ui_state_changed = true;
+                }
+            }
 
-                        // Show unread badge inline
-                        if has_unread && !is_selected {
-                            let badge_center = egui::pos2(
-                                badge_rect.right() - 16.0,
-                                badge_rect.center().y,
-                            );
-                            let badge_text = if channel.unread_count > 99 {
-                                "99+".to_string()
-                            } else {
-                                channel.unread_count.to_string()
-                            };
-                            let badge_radius = 10.0;
-                            painter.circle_filled(badge_center, badge_radius, theme::RED);
-                            painter.text(
-                                badge_center,
-                                egui::Align2::CENTER_CENTER,
-                                &badge_text,
-                                egui::FontId::proportional(10.0),
-                                egui::Color32::WHITE,
-                            );
-                        }
-
-                        let response = response.inner;
-
-                        if response.clicked() {
-                            let state = state.clone();
-                            let network = network.clone();
-                            let channel_id = channel.id;
-
-                            runtime.spawn(async move {
-                                state.select_channel(channel_id).await;
-
-                                // Mark channel as read locally and on server
-                                state.mark_channel_read(channel_id).await;
-                                let _ = network.mark_channel_read(channel_id).await;
-
-                                // Load messages
-                                if let Ok(messages) = network.get_messages(channel_id, None).await {
-                                    let mut s = state.write().await;
+            ui.add_space(8.0);
 
-                                    // Populate message_reactions from loaded messages
-                                    for msg in &messages {
-                                        if !msg.reactions.is_empty() {
-                                            let mut emoji_reactions: std::collections::HashMap<String, crate::state::ReactionState> = std::collections::HashMap::new();
-                                            for reaction in &msg.reactions {
-                                                let reaction_state = crate::state::ReactionState {
-                                                    user_ids: reaction.user_ids.iter().copied().collect(),
-                                                };
-                                                emoji_reactions.insert(reaction.emoji.clone(), reaction_state);
-                                            }
-                                            s.message_reactions.insert(msg.id, emoji_reactions);
-                                        }
-                                    }
+            // Followed threads in this community
+            let (followed_threads, followed_threads_stale) = runtime.block_on(async {
+                let s = state.read().await;
+                let threads: Vec<_> = s
+                    .followed_threads
+                    .iter()
+                    .filter(|t| channels.iter().any(|c| c.id == t.channel_id))
+                    .cloned()
+                    .collect();
+                (threads, s.followed_threads_stale)
+            });
 
-                                    s.messages.insert(channel_id, messages);
-                                }
+            let should_refresh = followed_threads_stale
+                && self.followed_threads_last_fetch
+                    .map(|t| t.elapsed().as_secs_f32() > FOLLOWED_THREADS_REFRESH_INTERVAL)
+                    .unwrap_or(true);
+            if should_refresh {
+                self.followed_threads_last_fetch = Some(Instant::now());
+                let state = state.clone();
+                let network = network.clone();
+                runtime.spawn(async move {
+                    match network.get_followed_threads().await {
+                        Ok(threads) => state.set_followed_threads(threads).await,
+                        Err(e) => tracing::warn!("Failed to load followed threads: {}", e),
+                    }
+                });
+            }
 
-                                // Subscribe to channel
-                                network.subscribe_channel(channel_id).await;
-                            });
+            if !followed_threads.is_empty() {
+                egui::CollapsingHeader::new(
+                    egui::RichText::new("FOLLOWED THREADS")
+                        .size(11.0)
+                        .color(theme::TEXT_MUTED)
+                )
+                    .default_open(true)
+                    .show(ui, |ui| {
+                    for thread in &followed_threads {
+                        if followed_thread_button(ui, thread).clicked() {
+                            open_followed_thread(state, network, runtime, thread.channel_id, thread.parent_message_id);
                         }
                     }
                 });
-                text_expanded = text_response.fully_open();
-            }
 
-            ui.add_space(8.0);
+                ui.add_space(8.0);
+            }
 
-            // Voice channels
+            // Voice channels outside of any category
             let voice_channels: Vec<_> = channels
                 .iter()
-                .filter(|c| matches!(c.channel_type, ChannelType::Voice))
+                .filter(|c| c.channel_type == ChannelType::Voice && !in_category(c))
                 .collect();
 
             if !voice_channels.is_empty() {
-                // Check if we need to refresh the voice participants cache
-                let should_refresh = self.voice_participants_last_fetch
-                    .map(|t| t.elapsed().as_secs_f32() > VOICE_PARTICIPANTS_REFRESH_INTERVAL)
-                    .unwrap_or(true);
-
-                if should_refresh {
-                    // Fetch participants for all voice channels we're not in
-                    let voice_channel_ids: Vec<_> = voice_channels.iter().map(|c| c.id).collect();
-                    let current_voice_channel = runtime.block_on(async {
-                        state.read().await.voice_channel_id
-                    });
-
-                    for channel_id in voice_channel_ids {
-                        if current_voice_channel != Some(channel_id) {
-                            let participants = runtime.block_on(async {
-                                match network.get_voice_participants(channel_id).await {
-                                    Ok(server_participants) => {
-                                        server_participants.into_iter().map(|p| {
-                                            VoiceParticipant {
-                                                user_id: p.user_id,
-                                                username: p.username,
-                                                is_muted: p.self_muted,
-                                                is_deafened: p.self_deafened,
-                                                is_video_enabled: p.video_enabled,
-                                                is_screen_sharing: p.screen_sharing,
-                                                is_speaking: false,
-                                                speaking_since: None,
-                                            }
-                                        }).collect()
-                                    }
-                                    Err(_) => Vec::new()
-                                }
-                            });
-                            self.voice_participants_cache.insert(channel_id, participants);
-                        }
-                    }
-                    self.voice_participants_last_fetch = Some(Instant::now());
-                }
-
                 let voice_response = egui::CollapsingHeader::new(
                     egui::RichText::new("VOICE CHANNELS")
                         .size(11.0)
                         .color(theme::TEXT_MUTED)
                 )
-                    .default_open(voice_expanded)
+                    .default_open(ui_state.voice_channels_expanded)
                     .show(ui, |ui| {
                     for channel in voice_channels {
-                        // Get participants - from local state if we're in the channel, from cache otherwise
-                        let (voice_channel_id, participants, local_speaking) = runtime.block_on(async {
-                            let s = state.read().await;
-                            let voice_channel_id = s.voice_channel_id;
-                            let local_speaking = s.is_speaking;
-
-                            let participants = if voice_channel_id == Some(channel.id) {
-                                // We're in this channel - use local state
-                                s.voice_participants.values().cloned().collect()
-                            } else {
-                                // Use cached data
-                                Vec::new() // Will be filled from cache below
-                            };
+                        let response = self.show_voice_channel(ui, channel, state, network, runtime);
+                        if is_owner {
+                            channel_drag_and_drop(ui, &response, DraggedChannel::of(channel), DropTarget::Channel(channel.id), &mut channel_drop);
+                        }
+                    }
+                });
+                if is_owner {
+                    channel_drop_target(ui, &voice_response.header_response, DropTarget::Uncategorized, &mut channel_drop);
+                }
+                let expanded = voice_response.fully_open();
+                if expanded != ui_state.voice_channels_expanded {
+                    ui_state.voice_channels_expanded = expanded;
+                    ui_state_changed = true;
+                }
 
-                            (voice_channel_id, participants, local_speaking)
-                        });
+                ui.add_space(8.0);
+            }
 
-                        // If not in this channel, use cached participants
-                        let participants = if voice_channel_id != Some(channel.id) {
-                            self.voice_participants_cache.get(&channel.id).cloned().unwrap_or_default()
-                        } else {
-                            participants
-                        };
-
-                        let is_connected = voice_channel_id == Some(channel.id);
-
-                        let text_color = if is_connected {
-                            theme::GREEN
-                        } else {
-                            theme::TEXT_MUTED
-                        };
-
-                        let response = ui.add(
-                            egui::Button::new(
-                                egui::RichText::new(format!("🔊 {}", channel.name))
-                                    .size(15.0)
-                                    .color(text_color)
+            // Categories with their text and voice channels
+            for category in channels.iter().filter(|c| c.channel_type == ChannelType::Category) {
+                let id = ui.make_persistent_id(("channel_category", category.id));
+                let collapsing = egui::collapsing_header::CollapsingState::load_with_default_open(
+                    ui.ctx(),
+                    id,
+                    !ui_state.collapsed_categories.contains(&category.id),
+                );
+
+                let (_, header, _) = collapsing
+                    .show_header(ui, |ui| {
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(category.name.to_uppercase())
+                                    .size(11.0)
+                                    .color(theme::TEXT_MUTED)
                             )
-                            .fill(if is_connected {
-                                theme::BG_ACCENT
-                            } else {
-                                egui::Color32::TRANSPARENT
-                            })
-                            .rounding(egui::Rounding::same(4.0))
-                            .min_size(egui::vec2(ui.available_width(), 32.0))
-                        );
-
-                        if response.clicked() {
-                            let state = state.clone();
-                            let network = network.clone();
-                            let channel_id = channel.id;
-
-                            if is_connected {
-                                // Leave voice
-                                runtime.spawn(async move {
-                                    network.leave_voice().await;
-                                    state.leave_voice().await;
-                                });
-                            } else {
-                                // Join voice
-                                runtime.spawn(async move {
-                                    // Subscribe FIRST so we receive broadcasts about other users joining
-                                    network.subscribe_channel(channel_id).await;
-
-                                    // Set local voice channel BEFORE API call so we're ready
-                                    // to receive VoiceUserJoined broadcasts (including our own)
-                                    state.join_voice(channel_id).await;
-
-                                    if network.join_voice(channel_id).await.is_ok() {
-                                        // Fetch existing participants and add them
-                                        if let Ok(existing) = network.get_voice_participants(channel_id).await {
-                                            let mut s = state.write().await;
-                                            for p in existing {
-                                                // Don't overwrite ourselves
-                                                if s.current_user.as_ref().map(|u| u.id) != Some(p.user_id) {
-                                                    s.voice_participants.insert(p.user_id, crate::state::VoiceParticipant {
-                                                        user_id: p.user_id,
-                                                        username: p.username,
-                                                        is_muted: p.self_muted,
-                                                        is_deafened: p.self_deafened,
-                                                        is_video_enabled: p.video_enabled,
-                                                        is_screen_sharing: p.screen_sharing,
-                                                        is_speaking: false,
-                                                        speaking_since: None,
-                                                    });
-                                                }
-                                            }
-                                        }
-                                    } else {
-                                        // API call failed, revert local state
-                                        state.leave_voice().await;
-                                    }
-                                });
+                            .selectable(false)
+                            .sense(egui::Sense::click_and_drag())
+                        )
+                    })
+                    .body(|ui| {
+                        for channel in channels.iter().filter(|c| c.parent_id == Some(category.id)) {
+                            let response = match channel.channel_type {
+                                ChannelType::Voice => self.show_voice_channel(ui, channel, state, network, runtime),
+                                _ => self.show_text_channel(ui, channel, current_channel, state, network, runtime),
+                            };
+                            if is_owner {
+                                channel_drag_and_drop(ui, &response, DraggedChannel::of(channel), DropTarget::Channel(channel.id), &mut channel_drop);
                             }
                         }
+                    });
 
-                        // Show participants under the voice channel
-                        if !participants.is_empty() {
-                            let current_user_id = runtime.block_on(async {
-                                state.read().await.current_user.as_ref().map(|u| u.id)
-                            });
+                // Clicking the name toggles the category like the arrow does
+                if header.inner.clicked() {
+                    if let Some(mut collapsing) = egui::collapsing_header::CollapsingState::load(ui.ctx(), id) {
+                        collapsing.toggle(ui);
+                        collapsing.store(ui.ctx());
+                    }
+                }
+                if is_owner {
+                    channel_drag_and_drop(ui, &header.inner, DraggedChannel::of(category), DropTarget::Category(category.id), &mut channel_drop);
+                }
 
-                            for participant in &participants {
-                                ui.horizontal(|ui| {
-                                    ui.add_space(24.0); // Indent
-
-                                    // Avatar circle with initial
-                                    let initial = participant.username
-                                        .chars()
-                                        .next()
-                                        .unwrap_or('?')
-                                        .to_uppercase()
-                                        .to_string();
-
-                                    let (rect, _) = ui.allocate_exact_size(
-                                        egui::vec2(24.0, 24.0),
-                                        egui::Sense::hover(),
-                                    );
-                                    let painter = ui.painter_at(rect);
-                                    painter.circle_filled(rect.center(), 12.0, theme::BG_ACCENT);
-                                    painter.text(
-                                        rect.center(),
-                                        egui::Align2::CENTER_CENTER,
-                                        &initial,
-                                        egui::FontId::proportional(12.0),
-                                        theme::TEXT_NORMAL,
-                                    );
-
-                                    ui.add_space(8.0);
-
-                                    // Check if this participant is speaking
-                                    let is_self = current_user_id == Some(participant.user_id);
-                                    let is_speaking = if is_self {
-                                        local_speaking
-                                    } else {
-                                        participant.is_speaking
-                                    };
-
-                                    // Username - bright white when speaking, muted when silent
-                                    let name_color = if is_speaking {
-                                        theme::GREEN
-                                    } else {
-                                        theme::TEXT_MUTED
-                                    };
-
-                                    ui.label(
-                                        egui::RichText::new(&participant.username)
-                                            .color(name_color)
-                                            .size(14.0),
-                                    );
-
-                                    // Status icons
-                                    ui.add_space(4.0);
-                                    if participant.is_muted {
-                                        ui.label(
-                                            egui::RichText::new("🔇")
-                                                .size(12.0)
-                                                .color(theme::TEXT_MUTED),
-                                        );
-                                    }
-                                    if participant.is_video_enabled {
-                                        ui.label(
-                                            egui::RichText::new("📹")
-                                                .size(12.0)
-                                                .color(theme::TEXT_MUTED),
-                                        );
-                                    }
-                                    if participant.is_screen_sharing {
-                                        ui.label(
-                                            egui::RichText::new("🖥")
-                                                .size(12.0)
-                                                .color(theme::TEXT_MUTED),
-                                        );
-                                    }
-                                });
-                                ui.add_space(2.0);
-                            }
+                let is_open = egui::collapsing_header::CollapsingState::load(ui.ctx(), id)
+                    .map_or(true, |s| s.is_open());
+                let was_open = !ui_state.collapsed_categories.contains(&category.id);
+                if is_open != was_open {
+                    if is_open {
+                        ui_state.collapsed_categories.remove(&category.id);
+                    } else {
+                        ui_state.collapsed_categories.insert(category.id);
+                    }
+                    ui_state_changed = true;
+                }
+
+                ui.add_space(8.0);
+            }
+        });
+
+        // Apply a drag-and-drop reorder locally, then on the server
+        if let Some((dragged, target)) = channel_drop {
+            if let Some(positions) = reordered_positions(&channels, dragged, target) {
+                let state = state.clone();
+                let network = network.clone();
+                runtime.spawn(async move {
+                    state.apply_channel_positions(&positions).await;
+                    if let Err(e) = network.reorder_channels(community_id, &positions).await {
+                        tracing::warn!("Failed to reorder channels: {}", e);
+                        // Restore the server's order
+                        if let Ok(channels) = network.get_channels(community_id).await {
+                            state.set_channels(channels).await;
                         }
                     }
                 });
-                voice_expanded = voice_response.fully_open();
             }
-        });
+        }
 
         // Create channel dialog
         if self.show_create_dialog {
@@ -500,18 +398,55 @@ impl ChannelList {
                         ui.label("Type:");
                         ui.selectable_value(&mut self.new_channel_type, ChannelType::Text, "Text");
                         ui.selectable_value(&mut self.new_channel_type, ChannelType::Voice, "Voice");
+                        ui.selectable_value(&mut self.new_channel_type, ChannelType::Forum, "Forum");
+                        ui.selectable_value(&mut self.new_channel_type, ChannelType::Announcement, "Announcement");
+                        ui.selectable_value(&mut self.new_channel_type, ChannelType::Category, "Category");
                     });
 
+                    // Categories can't be nested
+                    let categories: Vec<_> = channels
+                        .iter()
+                        .filter(|c| c.channel_type == ChannelType::Category)
+                        .collect();
+                    if self.new_channel_type != ChannelType::Category && !categories.is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.label("Category:");
+                            let selected_name = self
+                                .new_channel_parent
+                                .and_then(|id| categories.iter().find(|c| c.id == id))
+                                .map(|c| c.name.clone())
+                                .unwrap_or_else(|| "None".to_string());
+                            egui::ComboBox::from_id_salt("new_channel_category")
+                                .selected_text(selected_name)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut self.new_channel_parent, None, "None");
+                                    for category in &categories {
+                                        ui.selectable_value(
+                                            &mut self.new_channel_parent,
+                                            Some(category.id),
+                                            &category.name,
+                                        );
+                                    }
+                                });
+                        });
+                    }
+
                     ui.horizontal(|ui| {
                         if ui.button("Create").clicked() {
                             let name = self.new_channel_name.clone();
                             let channel_type = self.new_channel_type.clone();
+                            let parent_id = if channel_type == ChannelType::Category {
+                                None
+                            } else {
+                                self.new_channel_parent
+                            };
                             let network = network.clone();
                             let state = state.clone();
 
                             runtime.spawn(async move {
-                                if let Ok(channel) =
-                                    network.create_channel(community_id, &name, channel_type).await
+                                if let Ok(channel) = network
+                                    .create_channel(community_id, &name, channel_type, parent_id)
+                                    .await
                                 {
                                     let mut s = state.write().await;
                                     s.channels.insert(channel.id, channel);
@@ -574,8 +509,725 @@ impl ChannelList {
                 });
         }
 
-        (text_expanded, voice_expanded)
+        // Custom emoji dialog
+        if self.show_emoji_dialog {
+            self.show_emoji_dialog(ui.ctx(), community_id, state, network, runtime);
+        }
+
+        // Leave community confirmation
+        if self.show_leave_dialog {
+            egui::Window::new("Leave Community")
+                .collapsible(false)
+                .resizable(false)
+                .show(ui.ctx(), |ui| {
+                    ui.label("Leave this community? You'll need a new invite to rejoin.");
+                    ui.add_space(8.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Leave").clicked() {
+                            let network = network.clone();
+                            let state = state.clone();
+                            runtime.spawn(async move {
+                                let Some(user_id) = state.read().await.current_user.as_ref().map(|u| u.id) else {
+                                    return;
+                                };
+                                match network.leave_community(community_id, user_id).await {
+                                    Ok(()) => state.remove_community(community_id).await,
+                                    Err(e) => tracing::error!("Failed to leave community: {}", e),
+                                }
+                            });
+                            self.show_leave_dialog = false;
+                        }
+
+                        if ui.button("Cancel").clicked() {
+                            self.show_leave_dialog = false;
+                        }
+                    });
+                });
+        }
+
+        ui_state_changed
+    }
+
+    /// Show a text channel row: unread badge, draft marker, and selecting it on click
+    fn show_text_channel(
+        &mut self,
+        ui: &mut egui::Ui,
+        channel: &ChannelData,
+        current_channel: Option<Uuid>,
+        state: &AppState,
+        network: &NetworkClient,
+        runtime: &tokio::runtime::Runtime,
+    ) -> egui::Response {
+        let is_selected = current_channel == Some(channel.id);
+        let has_unread = channel.unread_count > 0;
+        let has_draft = state.has_draft_sync(channel.id);
+
+        // Bright text for unread channels, muted for read, normal for selected
+        let text_color = if is_selected {
+            theme::TEXT_NORMAL
+        } else if has_unread {
+            theme::TEXT_BRIGHT
+        } else {
+            theme::TEXT_MUTED
+        };
+
+        // Forums and announcement channels get their own icon instead of the hash
+        let icon = match channel.channel_type {
+            ChannelType::Forum => "💬",
+            ChannelType::Announcement => "📢",
+            _ => "#",
+        };
+
+        let response = ui.horizontal(|ui| {
+            ui.add(
+                egui::Button::new(
+                    egui::RichText::new(format!("{} {}", icon, channel.name))
+                        .size(15.0)
+                        .color(text_color)
+                )
+                .fill(if is_selected {
+                    theme::BG_ACCENT
+                } else {
+                    egui::Color32::TRANSPARENT
+                })
+                .rounding(egui::Rounding::same(4.0))
+                .min_size(egui::vec2(ui.available_width() - 32.0, 32.0))
+                .sense(egui::Sense::click_and_drag())
+            )
+        });
+
+        let badge_rect = response.response.rect;
+        let painter = ui.painter();
+
+        // Show draft indicator (pencil icon)
+        if has_draft && !is_selected {
+            let draft_pos = egui::pos2(
+                badge_rect.right() - if has_unread { 32.0 } else { 16.0 },
+                badge_rect.center().y,
+            );
+            painter.text(
+                draft_pos,
+                egui::Align2::CENTER_CENTER,
+                "✏",
+                egui::FontId::proportional(12.0),
+                theme::TEXT_MUTED,
+            );
+        }
+
+        // Show unread badge inline
+        if has_unread && !is_selected {
+            let badge_center = egui::pos2(
+                badge_rect.right() - 16.0,
+                badge_rect.center().y,
+            );
+            let badge_text = if channel.unread_count > 99 {
+                "99+".to_string()
+            } else {
+                channel.unread_count.to_string()
+            };
+            let badge_radius = 10.0;
+            painter.circle_filled(badge_center, badge_radius, theme::RED);
+            painter.text(
+                badge_center,
+                egui::Align2::CENTER_CENTER,
+                &badge_text,
+                egui::FontId::proportional(10.0),
+                egui::Color32::WHITE,
+            );
+        }
+
+        let response = response.inner;
+
+        if response.clicked() {
+            let state = state.clone();
+            let network = network.clone();
+            let channel_id = channel.id;
+
+            runtime.spawn(async move {
+                state.select_channel(channel_id).await;
+
+                // Mark channel as read locally and on server
+                state.mark_channel_read(channel_id).await;
+                let _ = network.mark_channel_read(channel_id).await;
+
+                // Load messages
+                if let Ok(messages) = network.get_messages(channel_id, None).await {
+                    let mut s = state.write().await;
+
+                    // Populate message_reactions from loaded messages
+                    for msg in &messages {
+                        if !msg.reactions.is_empty() {
+                            let mut emoji_reactions: std::collections::HashMap<String, crate::state::ReactionState> = std::collections::HashMap::new();
+                            for reaction in &msg.reactions {
+                                let reaction_state = crate::state::ReactionState {
+                                    user_ids: reaction.user_ids.iter().copied().collect(),
+                                };
+                                emoji_reactions.insert(reaction.emoji.clone(), reaction_state);
+                            }
+                            s.message_reactions.insert(msg.id, emoji_reactions);
+                        }
+                    }
+
+                    s.messages.insert(channel_id, messages);
+                }
+
+                // Subscribe to channel
+                network.subscribe_channel(channel_id).await;
+            });
+        }
+
+        response
+    }
+
+    /// Show a voice channel row with its participants; clicking joins or leaves
+    fn show_voice_channel(
+        &mut self,
+        ui: &mut egui::Ui,
+        channel: &ChannelData,
+        state: &AppState,
+        network: &NetworkClient,
+        runtime: &tokio::runtime::Runtime,
+    ) -> egui::Response {
+        // Get participants - from local state if we're in the channel, from cache otherwise
+        let (voice_channel_id, participants, local_speaking) = runtime.block_on(async {
+            let s = state.read().await;
+            let voice_channel_id = s.voice_channel_id;
+            let local_speaking = s.is_speaking;
+
+            let participants = if voice_channel_id == Some(channel.id) {
+                // We're in this channel - use local state
+                s.voice_participants.values().cloned().collect()
+            } else {
+                // Use cached data
+                Vec::new() // Will be filled from cache below
+            };
+
+            (voice_channel_id, participants, local_speaking)
+        });
+
+        // If not in this channel, use cached participants
+        let participants = if voice_channel_id != Some(channel.id) {
+            self.voice_participants_cache.get(&channel.id).cloned().unwrap_or_default()
+        } else {
+            participants
+        };
+
+        let is_connected = voice_channel_id == Some(channel.id);
+
+        let text_color = if is_connected {
+            theme::GREEN
+        } else {
+            theme::TEXT_MUTED
+        };
+
+        let response = ui.add(
+            egui::Button::new(
+                egui::RichText::new(format!("🔊 {}", channel.name))
+                    .size(15.0)
+                    .color(text_color)
+            )
+            .fill(if is_connected {
+                theme::BG_ACCENT
+            } else {
+                egui::Color32::TRANSPARENT
+            })
+            .rounding(egui::Rounding::same(4.0))
+            .min_size(egui::vec2(ui.available_width(), 32.0))
+            .sense(egui::Sense::click_and_drag())
+        );
+
+        if response.clicked() {
+            let state = state.clone();
+            let network = network.clone();
+            let channel_id = channel.id;
+
+            if is_connected {
+                // Leave voice
+                runtime.spawn(async move {
+                    network.leave_voice().await;
+                    state.leave_voice().await;
+                });
+            } else {
+                // Join voice
+                runtime.spawn(async move {
+                    // Subscribe FIRST so we receive broadcasts about other users joining
+                    network.subscribe_channel(channel_id).await;
+
+                    // Set local voice channel BEFORE API call so we're ready
+                    // to receive VoiceUserJoined broadcasts (including our own)
+                    state.join_voice(channel_id).await;
+
+                    if network.join_voice(channel_id).await.is_ok() {
+                        // Fetch existing participants and add them
+                        if let Ok(existing) = network.get_voice_participants(channel_id).await {
+                            let mut s = state.write().await;
+                            for p in existing {
+                                // Don't overwrite ourselves
+                                if s.current_user.as_ref().map(|u| u.id) != Some(p.user_id) {
+                                    s.voice_participants.insert(p.user_id, crate::state::VoiceParticipant {
+                                        user_id: p.user_id,
+                                        username: p.username,
+                                        is_muted: p.self_muted,
+                                        is_deafened: p.self_deafened,
+                                        is_video_enabled: p.video_enabled,
+                                        is_screen_sharing: p.screen_sharing,
+                                        is_speaking: false,
+                                        speaking_since: None,
+                                    });
+                                }
+                            }
+                        }
+                    } else {
+                        // API call failed, revert local state
+                        state.leave_voice().await;
+                    }
+                });
+            }
+        }
+
+        // Show participants under the voice channel
+        if !participants.is_empty() {
+            let current_user_id = runtime.block_on(async {
+                state.read().await.current_user.as_ref().map(|u| u.id)
+            });
+
+            for participant in &participants {
+                ui.horizontal(|ui| {
+                    ui.add_space(24.0); // Indent
+
+                    // Avatar circle with initial
+                    let initial = participant.username
+                        .chars()
+                        .next()
+                        .unwrap_or('?')
+                        .to_uppercase()
+                        .to_string();
+
+                    let (rect, _) = ui.allocate_exact_size(
+                        egui::vec2(24.0, 24.0),
+                        egui::Sense::hover(),
+                    );
+                    let painter = ui.painter_at(rect);
+                    painter.circle_filled(rect.center(), 12.0, theme::BG_ACCENT);
+                    painter.text(
+                        rect.center(),
+                        egui::Align2::CENTER_CENTER,
+                        &initial,
+                        egui::FontId::proportional(12.0),
+                        theme::TEXT_NORMAL,
+                    );
+
+                    ui.add_space(8.0);
+
+                    // Check if this participant is speaking
+                    let is_self = current_user_id == Some(participant.user_id);
+                    let is_speaking = if is_self {
+                        local_speaking
+                    } else {
+                        participant.is_speaking
+                    };
+
+                    // Username - bright white when speaking, muted when silent
+                    let name_color = if is_speaking {
+                        theme::GREEN
+                    } else {
+                        theme::TEXT_MUTED
+                    };
+
+                    ui.label(
+                        egui::RichText::new(&participant.username)
+                            .color(name_color)
+                            .size(14.0),
+                    );
+
+                    // Status icons
+                    ui.add_space(4.0);
+                    if participant.is_muted {
+                        ui.label(
+                            egui::RichText::new("🔇")
+                                .size(12.0)
+                                .color(theme::TEXT_MUTED),
+                        );
+                    }
+                    if participant.is_video_enabled {
+                        ui.label(
+                            egui::RichText::new("📹")
+                                .size(12.0)
+                                .color(theme::TEXT_MUTED),
+                        );
+                    }
+                    if participant.is_screen_sharing {
+                        ui.label(
+                            egui::RichText::new("🖥")
+                                .size(12.0)
+                                .color(theme::TEXT_MUTED),
+                        );
+                    }
+                });
+                ui.add_space(2.0);
+            }
+        }
+
+        response
     }
+
+    /// Refresh the participant cache for voice channels we're not in
+    fn refresh_voice_participants(
+        &mut self,
+        voice_channel_ids: &[Uuid],
+        state: &AppState,
+        network: &NetworkClient,
+        runtime: &tokio::runtime::Runtime,
+    ) {
+        if voice_channel_ids.is_empty() {
+            return;
+        }
+
+        let should_refresh = self.voice_participants_last_fetch
+            .map(|t| t.elapsed().as_secs_f32() > VOICE_PARTICIPANTS_REFRESH_INTERVAL)
+            .unwrap_or(true);
+        if !should_refresh {
+            return;
+        }
+
+        let current_voice_channel = runtime.block_on(async {
+            state.read().await.voice_channel_id
+        });
+
+        for &channel_id in voice_channel_ids {
+            if current_voice_channel != Some(channel_id) {
+                let participants = runtime.block_on(async {
+                    match network.get_voice_participants(channel_id).await {
+                        Ok(server_participants) => {
+                            server_participants.into_iter().map(|p| {
+                                VoiceParticipant {
+                                    user_id: p.user_id,
+                                    username: p.username,
+                                    is_muted: p.self_muted,
+                                    is_deafened: p.self_deafened,
+                                    is_video_enabled: p.video_enabled,
+                                    is_screen_sharing: p.screen_sharing,
+                                    is_speaking: false,
+                                    speaking_since: None,
+                                }
+                            }).collect()
+                        }
+                        Err(_) => Vec::new()
+                    }
+                });
+                self.voice_participants_cache.insert(channel_id, participants);
+            }
+        }
+        self.voice_participants_last_fetch = Some(Instant::now());
+    }
+
+    /// Window for uploading, renaming and deleting the community's custom emoji
+    fn show_emoji_dialog(
+        &mut self,
+        ctx: &egui::Context,
+        community_id: Uuid,
+        state: &AppState,
+        network: &NetworkClient,
+        runtime: &tokio::runtime::Runtime,
+    ) {
+        let emojis = runtime.block_on(async {
+            state
+                .read()
+                .await
+                .custom_emojis
+                .get(&community_id)
+                .cloned()
+                .unwrap_or_default()
+        });
+
+        egui::Window::new("Custom Emoji")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut self.new_emoji_name);
+
+                    let name = self.new_emoji_name.trim().trim_matches(':').to_string();
+                    if ui
+                        .add_enabled(!name.is_empty(), egui::Button::new("Upload..."))
+                        .on_hover_text("PNG, JPEG, GIF or WebP, scaled down to 128px")
+                        .clicked()
+                    {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .set_title("Select emoji image")
+                            .add_filter("Images", &["png", "jpg", "jpeg", "gif", "webp"])
+                            .pick_file()
+                        {
+                            match std::fs::read(&path) {
+                                Ok(data) => {
+                                    let filename = path
+                                        .file_name()
+                                        .map(|f| f.to_string_lossy().to_string())
+                                        .unwrap_or_else(|| "emoji.png".to_string());
+                                    let network = network.clone();
+                                    let state = state.clone();
+
+                                    runtime.spawn(async move {
+                                        match network
+                                            .upload_community_emoji(community_id, &name, filename, data)
+                                            .await
+                                        {
+                                            Ok(emoji) => {
+                                                let mut s = state.write().await;
+                                                s.custom_emojis.entry(community_id).or_default().push(emoji);
+                                            }
+                                            Err(e) => {
+                                                tracing::warn!("Failed to upload emoji: {}", e);
+                                            }
+                                        }
+                                    });
+
+                                    self.new_emoji_name.clear();
+                                }
+                                Err(e) => {
+                                    tracing::warn!("Failed to read emoji image {:?}: {}", path, e);
+                                }
+                            }
+                        }
+                    }
+                });
+
+                ui.separator();
+
+                if emojis.is_empty() {
+                    ui.label(
+                        egui::RichText::new("No custom emoji yet")
+                            .italics()
+                            .color(egui::Color32::GRAY),
+                    );
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for emoji in &emojis {
+                            ui.horizontal(|ui| {
+                                let is_renaming = self
+                                    .renaming_emoji
+                                    .as_ref()
+                                    .map_or(false, |(id, _)| *id == emoji.id);
+
+                                if is_renaming {
+                                    let mut save = false;
+                                    if let Some((_, new_name)) = &mut self.renaming_emoji {
+                                        ui.text_edit_singleline(new_name);
+                                    }
+                                    if ui.button("Save").clicked() {
+                                        save = true;
+                                    }
+                                    if ui.button("Cancel").clicked() {
+                                        self.renaming_emoji = None;
+                                    }
+
+                                    if save {
+                                        if let Some((emoji_id, new_name)) = self.renaming_emoji.take() {
+                                            let network = network.clone();
+                                            let state = state.clone();
+                                            runtime.spawn(async move {
+                                                match network.rename_community_emoji(emoji_id, &new_name).await {
+                                                    Ok(renamed) => {
+                                                        let mut s = state.write().await;
+                                                        if let Some(list) = s.custom_emojis.get_mut(&community_id) {
+                                                            if let Some(e) = list.iter_mut().find(|e| e.id == emoji_id) {
+                                                                *e = renamed;
+                                                            }
+                                                        }
+                                                    }
+                                                    Err(e) => {
+                                                        tracing::warn!("Failed to rename emoji: {}", e);
+                                                    }
+                                                }
+                                            });
+                                        }
+                                    }
+                                } else {
+                                    ui.monospace(format!(":{}:", emoji.name));
+                                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                        if ui.small_button("Delete").clicked() {
+                                            let network = network.clone();
+                                            let state = state.clone();
+                                            let emoji_id = emoji.id;
+                                            runtime.spawn(async move {
+                                                match network.delete_community_emoji(emoji_id).await {
+                                                    Ok(()) => {
+                                                        let mut s = state.write().await;
+                                                        if let Some(list) = s.custom_emojis.get_mut(&community_id) {
+                                                            list.retain(|e| e.id != emoji_id);
+                                                        }
+                                                    }
+                                                    Err(e) => {
+                                                        tracing::warn!("Failed to delete emoji: {}", e);
+                                                    }
+                                                }
+                                            });
+                                        }
+                                        if ui.small_button("Rename").clicked() {
+                                            self.renaming_emoji = Some((emoji.id, emoji.name.clone()));
+                                        }
+                                    });
+                                }
+                            });
+                        }
+                    });
+
+                ui.add_space(8.0);
+                if ui.button("Close").clicked() {
+                    self.show_emoji_dialog = false;
+                    self.renaming_emoji = None;
+                }
+            });
+    }
+}
+
+/// Make a channel row draggable and accept other channels dropped on it
+fn channel_drag_and_drop(
+    ui: &egui::Ui,
+    response: &egui::Response,
+    own: DraggedChannel,
+    target: DropTarget,
+    channel_drop: &mut Option<(DraggedChannel, DropTarget)>,
+) {
+    response.dnd_set_drag_payload(own);
+
+    if response.dnd_hover_payload::<DraggedChannel>().is_some_and(|d| d.id == own.id) {
+        return;
+    }
+    channel_drop_target(ui, response, target, channel_drop);
+}
+
+/// Highlight a drop target while a channel is dragged over it and record the drop
+fn channel_drop_target(
+    ui: &egui::Ui,
+    response: &egui::Response,
+    target: DropTarget,
+    channel_drop: &mut Option<(DraggedChannel, DropTarget)>,
+) {
+    if let Some(hovered) = response.dnd_hover_payload::<DraggedChannel>() {
+        let stroke = egui::Stroke::new(2.0, theme::BLURPLE);
+        let moves_inside = match target {
+            DropTarget::Category(_) => !hovered.is_category,
+            DropTarget::Uncategorized => true,
+            DropTarget::Channel(_) => false,
+        };
+        if moves_inside {
+            ui.painter().rect_stroke(response.rect, 4.0, stroke);
+        } else {
+            // Insertion line above the row
+            ui.painter().hline(response.rect.x_range(), response.rect.top(), stroke);
+        }
+    }
+
+    if let Some(dropped) = response.dnd_release_payload::<DraggedChannel>() {
+        *channel_drop = Some((*dropped, target));
+    }
+}
+
+/// Compute new positions for every channel in a community after a drop.
+/// Categories and uncategorized channels share the top level, and each category's
+/// channels follow it. Returns None if the drop is invalid or changes nothing.
+fn reordered_positions(
+    channels: &[ChannelData],
+    dragged: DraggedChannel,
+    target: DropTarget,
+) -> Option<Vec<ChannelPositionData>> {
+    let category_ids: HashSet<Uuid> = channels
+        .iter()
+        .filter(|c| c.channel_type == ChannelType::Category)
+        .map(|c| c.id)
+        .collect();
+    let parent_of = |id: Uuid| {
+        channels
+            .iter()
+            .find(|c| c.id == id)
+            .and_then(|c| c.parent_id)
+            .filter(|p| category_ids.contains(p))
+    };
+
+    // Current order (channels are sorted by position)
+    let mut top: Vec<Uuid> = Vec::new();
+    let mut children: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for channel in channels {
+        match parent_of(channel.id) {
+            Some(parent_id) => children.entry(parent_id).or_default().push(channel.id),
+            None => top.push(channel.id),
+        }
+    }
+
+    // Take the dragged channel out of its list
+    match parent_of(dragged.id) {
+        Some(parent_id) => children.get_mut(&parent_id)?.retain(|&id| id != dragged.id),
+        None => top.retain(|&id| id != dragged.id),
+    }
+
+    // Put it back in its new place
+    match target {
+        DropTarget::Channel(target_id) => {
+            let target_parent = parent_of(target_id);
+            if dragged.is_category {
+                // Categories can't be nested: go in front of the target's category
+                let index = top.iter().position(|&id| Some(id) == target_parent)?;
+                top.insert(index, dragged.id);
+            } else {
+                let list = match target_parent {
+                    Some(parent_id) => children.entry(parent_id).or_default(),
+                    None => &mut top,
+                };
+                let index = list.iter().position(|&id| id == target_id)?;
+                list.insert(index, dragged.id);
+            }
+        }
+        DropTarget::Category(category_id) => {
+            if dragged.is_category {
+                let index = top.iter().position(|&id| id == category_id)?;
+                top.insert(index, dragged.id);
+            } else {
+                children.entry(category_id).or_default().push(dragged.id);
+            }
+        }
+        DropTarget::Uncategorized => {
+            if dragged.is_category {
+                return None;
+            }
+            // Uncategorized channels are listed before the categories
+            let index = top
+                .iter()
+                .position(|id| category_ids.contains(id))
+                .unwrap_or(top.len());
+            top.insert(index, dragged.id);
+        }
+    }
+
+    let mut positions = Vec::with_capacity(channels.len());
+    for &id in &top {
+        positions.push(ChannelPositionData {
+            id,
+            position: positions.len() as i32,
+            parent_id: None,
+        });
+        for &child_id in children.get(&id).into_iter().flatten() {
+            positions.push(ChannelPositionData {
+                id: child_id,
+                position: positions.len() as i32,
+                parent_id: Some(id),
+            });
+        }
+    }
+
+    let changed = positions.iter().any(|p| {
+        channels
+            .iter()
+            .find(|c| c.id == p.id)
+            .is_none_or(|c| c.position != p.position || c.parent_id != p.parent_id)
+    });
+
+    changed.then_some(positions)
 }
 
 impl Default for ChannelList {
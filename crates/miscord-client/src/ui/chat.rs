@@ -4,10 +4,11 @@ use std::collections::HashMap;
 use std::time::Instant;
 use uuid::Uuid;
 
-use crate::network::NetworkClient;
+use crate::network::{NetworkClient, UploadFile, UploadSource};
 use crate::state::AppState;
-use miscord_protocol::MessageData;
+use miscord_protocol::{ChannelData, ChannelType, ExportFormat, ExportStatus, MessageData, SlashCommandData};
 
+use super::commands;
 use super::gif_picker::GifPicker;
 use super::message::{
     format_file_size, format_relative_time, render_lightbox, render_message, MessageAction,
@@ -17,11 +18,20 @@ use super::message::{
 /// How often to send typing indicators (in seconds)
 const TYPING_THROTTLE_SECS: u64 = 3;
 
-/// Pending file attachment (filename, content_type, data)
-pub struct PendingAttachment {
-    pub filename: String,
-    pub content_type: String,
-    pub data: Vec<u8>,
+/// Messages per history page (the server's default page size)
+const HISTORY_PAGE_SIZE: usize = 50;
+
+/// An attachment being uploaded with a sent message
+#[derive(Clone)]
+struct UploadProgress {
+    id: Uuid,
+    filename: String,
+    sent_bytes: u64,
+    total_bytes: u64,
+    /// Waiting to retry after the connection dropped
+    reconnecting: bool,
+    /// Why the upload gave up; the entry stays until dismissed
+    error: Option<String>,
 }
 
 pub struct ChatView {
@@ -46,8 +56,16 @@ pub struct ChatView {
     pending_cursor_pos: Option<usize>,
     /// Whether mention was dismissed with Escape (prevents immediate re-open)
     mention_dismissed: bool,
+    /// Selected command index in the slash command dropdown
+    command_selected: usize,
+    /// Whether the command dropdown was dismissed with Escape
+    command_dismissed: bool,
+    /// Why the typed command couldn't be invoked
+    command_error: Option<String>,
     /// Pending file attachments to upload with the next message
-    pending_attachments: Vec<PendingAttachment>,
+    pending_attachments: Vec<UploadFile>,
+    /// Attachments of sent messages that are still uploading
+    uploads: std::sync::Arc<std::sync::RwLock<Vec<UploadProgress>>>,
     /// Currently viewed channel (for draft save/restore on channel switch)
     current_channel_id: Option<Uuid>,
     /// Whether we're currently loading older messages
@@ -62,6 +80,12 @@ pub struct ChatView {
     last_message_count: usize,
     /// Time when we started loading history (for timeout)
     loading_started_at: Option<Instant>,
+    /// Whether we're loading newer messages into a detached history window
+    loading_newer: bool,
+    /// Time when we started loading newer messages (for timeout)
+    loading_newer_started_at: Option<Instant>,
+    /// Scroll target we've already requested a surrounding window for
+    loading_around_id: Option<Uuid>,
     /// Currently selected message for keyboard navigation
     selected_message_id: Option<Uuid>,
     /// Whether the pinned messages panel is open
@@ -72,6 +96,16 @@ pub struct ChatView {
     pinned_messages_loading: bool,
     /// GIF picker state
     gif_picker: GifPicker,
+    /// Message chosen to be forwarded (shows the channel picker while set)
+    forwarding_message: Option<MessageData>,
+    /// Announcement channel to follow (shows the destination picker while set)
+    following_channel: Option<Uuid>,
+    /// Whether the export dialog is open for the current channel
+    export_dialog_open: bool,
+    /// Format picked in the export dialog
+    export_format: ExportFormat,
+    /// Newest message we've reported as read in the current channel
+    last_read_sent: Option<Uuid>,
 }
 
 /// Get date separator text for a message
@@ -90,6 +124,45 @@ fn get_date_separator(timestamp: DateTime<Utc>) -> String {
     }
 }
 
+/// Retention choices offered for community channels, in seconds
+const RETENTION_PRESETS: &[i32] = &[86400, 7 * 86400, 30 * 86400, 90 * 86400, 365 * 86400];
+
+/// Disappearing message timers offered in DMs, in seconds
+const DISAPPEARING_PRESETS: &[i32] = &[300, 3600, 86400, 7 * 86400];
+
+/// Format a retention period as its largest whole unit, e.g. "30 days"
+fn format_retention(seconds: i32) -> String {
+    let (value, unit) = if seconds % 86400 == 0 {
+        (seconds / 86400, "day")
+    } else if seconds % 3600 == 0 {
+        (seconds / 3600, "hour")
+    } else if seconds % 60 == 0 {
+        (seconds / 60, "minute")
+    } else {
+        (seconds, "second")
+    };
+    format!("{} {}{}", value, unit, if value == 1 { "" } else { "s" })
+}
+
+/// Download a finished export into the downloads folder and open it
+async fn save_export(network: &NetworkClient, export_id: Uuid, filename: &str) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    let bytes = network.download_export(export_id).await?;
+
+    let downloads_dir = dirs::download_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
+    let file_path = downloads_dir.join(filename);
+    std::fs::write(&file_path, &bytes).with_context(|| format!("Failed to write file to {:?}", file_path))?;
+
+    tracing::info!("Saved export to {:?}", file_path);
+
+    if let Err(e) = open::that(&downloads_dir) {
+        tracing::warn!("Failed to open downloads folder: {}", e);
+    }
+
+    Ok(())
+}
+
 /// Check if two messages are on different dates
 fn is_different_date(msg1: &MessageData, msg2: &MessageData) -> bool {
     let local1 = msg1.created_at.with_timezone(&Local);
@@ -111,7 +184,11 @@ impl ChatView {
             mention_selected: 0,
             pending_cursor_pos: None,
             mention_dismissed: false,
+            command_selected: 0,
+            command_dismissed: false,
+            command_error: None,
             pending_attachments: Vec::new(),
+            uploads: std::sync::Arc::new(std::sync::RwLock::new(Vec::new())),
             current_channel_id: None,
             loading_history: false,
             reached_history_end: false,
@@ -119,11 +196,19 @@ impl ChatView {
             jump_to_bottom_requested: false,
             last_message_count: 0,
             loading_started_at: None,
+            loading_newer: false,
+            loading_newer_started_at: None,
+            loading_around_id: None,
             selected_message_id: None,
             show_pinned_panel: false,
             pinned_messages: Vec::new(),
             pinned_messages_loading: false,
             gif_picker: GifPicker::new(),
+            forwarding_message: None,
+            following_channel: None,
+            export_dialog_open: false,
+            export_format: ExportFormat::Html,
+            last_read_sent: None,
         }
     }
 
@@ -142,7 +227,8 @@ impl ChatView {
             state.read().await.current_channel_id
         });
 
-        if new_channel_id != self.current_channel_id {
+        let channel_changed = new_channel_id != self.current_channel_id;
+        if channel_changed {
             // Channel changed - save draft for old channel, load draft for new
             if let Some(old_channel_id) = self.current_channel_id {
                 // Save current input as draft for the old channel
@@ -168,6 +254,7 @@ impl ChatView {
             self.replying_to = None;
             self.editing_message = None;
             self.mention_active = false;
+            self.command_error = None;
             self.current_channel_id = new_channel_id;
             // Reset history state for new channel
             self.loading_history = false;
@@ -176,14 +263,20 @@ impl ChatView {
             self.jump_to_bottom_requested = false;
             self.last_message_count = 0;
             self.loading_started_at = None;
+            self.loading_newer = false;
+            self.loading_newer_started_at = None;
+            self.loading_around_id = None;
+            self.last_read_sent = None;
             self.selected_message_id = None;
             // Reset pinned messages panel for new channel
             self.show_pinned_panel = false;
             self.pinned_messages.clear();
             self.pinned_messages_loading = false;
+            self.following_channel = None;
+            self.export_dialog_open = false;
         }
 
-        let (current_channel, messages, channel_name, typing_usernames, current_user_id, message_reactions, members, scroll_to_message_id) = runtime.block_on(async {
+        let (current_channel, messages, channel_name, channel_type, typing_usernames, current_user_id, message_reactions, members, scroll_to_message_id, detached, is_dm, seen_by) = runtime.block_on(async {
             let s = state.read().await;
             let channel_id = s.current_channel_id;
             let messages = channel_id
@@ -194,6 +287,9 @@ impl ChatView {
                 .and_then(|id| s.channels.get(&id))
                 .map(|c| c.name.clone())
                 .unwrap_or_default();
+            let channel_type = channel_id
+                .and_then(|id| s.channels.get(&id))
+                .map(|c| c.channel_type.clone());
 
             // Get members for mention autocomplete
             let members: Vec<(Uuid, String, String)> = s.current_community_id
@@ -255,9 +351,98 @@ impl ChatView {
             // Get scroll target
             let scroll_to_message_id = s.scroll_to_message_id;
 
-            (channel_id, messages, channel_name, typing_usernames, current_user_id, message_reactions, members, scroll_to_message_id)
+            // Whether we're looking at a window in history rather than the latest messages
+            let detached = channel_id.map_or(false, |id| s.detached_channels.contains(&id));
+
+            // Read receipts are only shown in DMs and group DMs
+            let is_dm = channel_id
+                .and_then(|id| s.channels.get(&id))
+                .map_or(false, |c| matches!(c.channel_type, ChannelType::DirectMessage | ChannelType::GroupDm));
+
+            // "Seen by" names grouped under each user's last read message
+            let mut seen_by: HashMap<Uuid, Vec<String>> = HashMap::new();
+            if is_dm {
+                if let Some(receipts) = channel_id.and_then(|id| s.read_receipts.get(&id)) {
+                    for (user_id, message_id) in receipts {
+                        if Some(*user_id) == current_user_id {
+                            continue;
+                        }
+                        let name = s.users
+                            .get(user_id)
+                            .map(|u| u.display_name.clone())
+                            .or_else(|| {
+                                messages
+                                    .iter()
+                                    .find(|m| m.author_id == *user_id)
+                                    .map(|m| m.author_name.clone())
+                            })
+                            .unwrap_or_else(|| "Someone".to_string());
+                        seen_by.entry(*message_id).or_default().push(name);
+                    }
+                }
+                for names in seen_by.values_mut() {
+                    names.sort();
+                }
+            }
+
+            (channel_id, messages, channel_name, channel_type, typing_usernames, current_user_id, message_reactions, members, scroll_to_message_id, detached, is_dm, seen_by)
         });
 
+        // Load read receipts when opening a DM
+        if let (true, true, Some(channel_id)) = (channel_changed, is_dm, current_channel) {
+            let state = state.clone();
+            let network = network.clone();
+            runtime.spawn(async move {
+                match network.get_read_receipts(channel_id).await {
+                    Ok(receipts) => state.set_read_receipts(channel_id, receipts).await,
+                    Err(e) => tracing::warn!("Failed to load read receipts: {}", e),
+                }
+            });
+        }
+
+        // Load the announcement channels a community channel follows
+        let can_follow = matches!(channel_type, Some(ChannelType::Text | ChannelType::Announcement));
+        if let (true, true, Some(channel_id)) = (channel_changed, can_follow, current_channel) {
+            let state = state.clone();
+            let network = network.clone();
+            runtime.spawn(async move {
+                match network.get_channel_follows(channel_id).await {
+                    Ok(follows) => state.set_channel_follows(channel_id, follows).await,
+                    Err(e) => tracing::warn!("Failed to load channel follows: {}", e),
+                }
+            });
+        }
+
+        // Jumping to a message that isn't loaded: load a window of history around it
+        if scroll_to_message_id.is_none() {
+            self.loading_around_id = None;
+        }
+        if let (Some(target_id), Some(channel_id)) = (scroll_to_message_id, current_channel) {
+            if self.loading_around_id != Some(target_id) && !messages.iter().any(|m| m.id == target_id) {
+                self.loading_around_id = Some(target_id);
+                self.loading_history = false;
+                self.reached_history_end = false;
+                let state = state.clone();
+                let network = network.clone();
+                runtime.spawn(async move {
+                    match network.get_messages_around(channel_id, target_id).await {
+                        Ok(window) => {
+                            let found = window.iter().any(|m| m.id == target_id);
+                            state.set_message_window(channel_id, window, true).await;
+                            if !found {
+                                // Thread replies aren't in channel history; the window shows their parent
+                                state.write().await.scroll_to_message_id = None;
+                            }
+                        }
+                        Err(e) => {
+                            tracing::warn!("Failed to load messages around {}: {}", target_id, e);
+                            state.write().await.scroll_to_message_id = None;
+                        }
+                    }
+                });
+            }
+        }
+
         // Handle keyboard navigation for messages
         self.handle_keyboard_navigation(ui, &messages, current_user_id, state, network, runtime);
 
@@ -286,12 +471,128 @@ impl ChatView {
             }
         }
 
+        // Slash commands, and channel names for #channel options
+        let (slash_commands, community_channels) = runtime.block_on(async {
+            let s = state.read().await;
+            let community_id = s.channels.get(&channel_id).and_then(|c| c.community_id);
+            let slash_commands: Vec<SlashCommandData> = community_id
+                .and_then(|id| s.commands.get(&id))
+                .cloned()
+                .unwrap_or_default();
+            let community_channels: Vec<(Uuid, String)> = s.channels
+                .values()
+                .filter(|c| community_id.is_some() && c.community_id == community_id)
+                .map(|c| (c.id, c.name.clone()))
+                .collect();
+            (slash_commands, community_channels)
+        });
+
+        let is_announcement = channel_type == Some(ChannelType::Announcement);
+        let (follows, is_owner, retention_seconds) = runtime.block_on(async {
+            let s = state.read().await;
+            let follows = s.channel_follows.get(&channel_id).cloned().unwrap_or_default();
+            // Only the owner manages what the community's channels follow
+            let is_owner = s.current_community_id
+                .and_then(|id| s.communities.get(&id))
+                .zip(s.current_user.as_ref())
+                .is_some_and(|(community, user)| community.owner_id == user.id);
+            let retention_seconds = s.channels.get(&channel_id).and_then(|c| c.retention_seconds);
+            (follows, is_owner, retention_seconds)
+        });
+        // Any DM participant can set the disappearing timer; channel retention is owner-only
+        let can_set_retention = is_dm || is_owner;
+
         // Channel header at top
         egui::TopBottomPanel::top("chat_header")
             .show_inside(ui, |ui| {
                 ui.horizontal(|ui| {
-                    ui.heading(format!("# {}", channel_name));
+                    let icon = if is_announcement { "📢" } else { "#" };
+                    ui.heading(format!("{} {}", icon, channel_name));
+
+                    // Active retention policy
+                    let (policy_label, policy_hover) = match (retention_seconds, is_dm) {
+                        (Some(seconds), true) => (
+                            format!("⏱ {}", format_retention(seconds)),
+                            format!("Messages disappear {} after they are sent", format_retention(seconds)),
+                        ),
+                        (Some(seconds), false) => (
+                            format!("⏳ {}", format_retention(seconds)),
+                            format!("Messages are deleted after {}", format_retention(seconds)),
+                        ),
+                        (None, true) => ("⏱ Off".to_string(), "Disappearing messages are off".to_string()),
+                        (None, false) => ("⏳ Forever".to_string(), "Message history is kept forever".to_string()),
+                    };
+                    let policy_text = egui::RichText::new(policy_label).size(13.0).color(egui::Color32::GRAY);
+                    if can_set_retention {
+                        ui.menu_button(policy_text, |ui| {
+                            let presets = if is_dm { DISAPPEARING_PRESETS } else { RETENTION_PRESETS };
+                            let mut choice = None;
+                            if ui.radio(retention_seconds.is_none(), "Off").clicked() {
+                                choice = Some(None);
+                            }
+                            for &seconds in presets {
+                                if ui.radio(retention_seconds == Some(seconds), format_retention(seconds)).clicked() {
+                                    choice = Some(Some(seconds));
+                                }
+                            }
+                            if let Some(retention_seconds) = choice {
+                                let state = state.clone();
+                                let network = network.clone();
+                                runtime.spawn(async move {
+                                    match network.set_channel_retention(channel_id, retention_seconds).await {
+                                        Ok(channel) => state.upsert_channel(channel).await,
+                                        Err(e) => tracing::warn!("Failed to set retention: {}", e),
+                                    }
+                                });
+                                ui.close_menu();
+                            }
+                        })
+                        .response
+                        .on_hover_text(policy_hover);
+                    } else if retention_seconds.is_some() {
+                        ui.label(policy_text).on_hover_text(policy_hover);
+                    }
+
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        // Announcement channels can be followed from other channels
+                        if is_announcement
+                            && ui
+                                .button(egui::RichText::new("📢 Follow").size(13.0))
+                                .on_hover_text("Copy published messages into a channel you manage")
+                                .clicked()
+                        {
+                            self.following_channel = Some(channel_id);
+                        }
+
+                        // Announcement channels this channel follows
+                        if !follows.is_empty() {
+                            ui.menu_button(
+                                egui::RichText::new(format!("Following ({})", follows.len())).size(13.0),
+                                |ui| {
+                                    for follow in &follows {
+                                        ui.horizontal(|ui| {
+                                            ui.label(format!(
+                                                "📢 {} in {}",
+                                                follow.source_channel_name, follow.source_community_name
+                                            ));
+                                            if is_owner && ui.small_button("Unfollow").clicked() {
+                                                let state = state.clone();
+                                                let network = network.clone();
+                                                let source_id = follow.source_channel_id;
+                                                runtime.spawn(async move {
+                                                    match network.unfollow_channel(source_id, channel_id).await {
+                                                        Ok(()) => state.remove_channel_follow(source_id, channel_id).await,
+                                                        Err(e) => tracing::warn!("Failed to unfollow channel: {}", e),
+                                                    }
+                                                });
+                                                ui.close_menu();
+                                            }
+                                        });
+                                    }
+                                },
+                            );
+                        }
+
                         // Pinned messages button
                         let pinned_count = self.pinned_messages.len();
                         let pin_btn_text = if self.show_pinned_panel {
@@ -311,6 +612,14 @@ impl ChatView {
                         if pin_btn.clicked() {
                             self.show_pinned_panel = !self.show_pinned_panel;
                         }
+
+                        if ui
+                            .button(egui::RichText::new("⬇ Export").size(13.0))
+                            .on_hover_text("Export channel")
+                            .clicked()
+                        {
+                            self.export_dialog_open = true;
+                        }
                     });
                 });
             });
@@ -518,6 +827,116 @@ impl ChatView {
                     }
                 }
 
+                // Slash command autocomplete while the command name is being typed
+                if !self.message_input.starts_with('/') {
+                    self.command_dismissed = false;
+                }
+                let matching_commands: Vec<&SlashCommandData> = if self.editing_message.is_none()
+                    && !self.command_dismissed
+                    && commands::is_typing_name(&self.message_input)
+                {
+                    let query = commands::command_name(&self.message_input).unwrap_or("");
+                    slash_commands.iter().filter(|c| c.name.starts_with(query)).take(8).collect()
+                } else {
+                    vec![]
+                };
+                if self.command_selected >= matching_commands.len() {
+                    self.command_selected = 0;
+                }
+
+                if !matching_commands.is_empty() && !mention_handled {
+                    let up = ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp));
+                    let down = ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown));
+                    let tab = ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab));
+                    let enter = ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Enter));
+                    let escape = ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape));
+
+                    if up && self.command_selected > 0 {
+                        self.command_selected -= 1;
+                        mention_handled = true;
+                    }
+                    if down && self.command_selected < matching_commands.len() - 1 {
+                        self.command_selected += 1;
+                        mention_handled = true;
+                    }
+                    if tab || enter {
+                        let name = matching_commands[self.command_selected].name.clone();
+                        self.insert_command(&name);
+                        mention_handled = true;
+                    }
+                    if escape {
+                        self.command_dismissed = true;
+                        self.command_selected = 0;
+                        mention_handled = true;
+                        refocus_input = true;
+                    }
+                }
+
+                // The command the input invokes, if any
+                let invoked_command = if self.editing_message.is_none() {
+                    commands::find_command(&self.message_input, &slash_commands)
+                } else {
+                    None
+                };
+                if invoked_command.is_none() {
+                    self.command_error = None;
+                }
+
+                // Uploads still in progress from sent messages
+                let uploads = self.uploads.read().map(|u| u.clone()).unwrap_or_default();
+                if !uploads.is_empty() {
+                    let mut dismissed = Vec::new();
+                    for upload in &uploads {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("⬆").size(14.0).color(super::theme::TEXT_MUTED));
+                            ui.label(
+                                egui::RichText::new(&upload.filename)
+                                    .size(12.0)
+                                    .color(super::theme::TEXT_NORMAL)
+                            );
+
+                            if let Some(error) = &upload.error {
+                                ui.label(
+                                    egui::RichText::new(format!("Upload failed: {}", error))
+                                        .size(12.0)
+                                        .color(super::theme::RED)
+                                );
+                                if ui.small_button("✕").on_hover_text("Dismiss").clicked() {
+                                    dismissed.push(upload.id);
+                                }
+                            } else {
+                                let fraction = if upload.total_bytes > 0 {
+                                    upload.sent_bytes as f32 / upload.total_bytes as f32
+                                } else {
+                                    0.0
+                                };
+                                let text = if upload.reconnecting {
+                                    "Reconnecting...".to_string()
+                                } else {
+                                    format!(
+                                        "{} / {}",
+                                        format_file_size(upload.sent_bytes as i64),
+                                        format_file_size(upload.total_bytes as i64)
+                                    )
+                                };
+                                ui.add(
+                                    egui::ProgressBar::new(fraction)
+                                        .desired_width(220.0)
+                                        .text(egui::RichText::new(text).size(11.0))
+                                );
+                            }
+                        });
+                    }
+                    if !dismissed.is_empty() {
+                        if let Ok(mut uploads) = self.uploads.write() {
+                            uploads.retain(|u| !dismissed.contains(&u.id));
+                        }
+                    }
+                    // Progress comes from a background task, so keep redrawing while it runs
+                    ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+                    ui.add_space(4.0);
+                }
+
                 // Show pending attachments above input
                 if !self.pending_attachments.is_empty() {
                     ui.horizontal_wrapped(|ui| {
@@ -551,7 +970,7 @@ impl ChatView {
 
                                         // Size
                                         ui.label(
-                                            egui::RichText::new(format_file_size(attachment.data.len() as i64))
+                                            egui::RichText::new(format_file_size(attachment.size as i64))
                                                 .size(11.0)
                                                 .color(egui::Color32::from_rgb(140, 140, 140))
                                         );
@@ -580,6 +999,27 @@ impl ChatView {
                     ui.add_space(4.0);
                 }
 
+                // Usage of the command being typed, and why it couldn't be sent
+                if let Some(command) = invoked_command {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(
+                            egui::RichText::new(commands::usage(command))
+                                .monospace()
+                                .size(12.0)
+                                .color(super::theme::TEXT_NORMAL)
+                        );
+                        ui.label(
+                            egui::RichText::new(&command.description)
+                                .size(12.0)
+                                .color(super::theme::TEXT_MUTED)
+                        );
+                    });
+                    if let Some(error) = &self.command_error {
+                        ui.label(egui::RichText::new(error).size(12.0).color(super::theme::RED));
+                    }
+                    ui.add_space(4.0);
+                }
+
                 // Message input
                 let text_edit_id = ui.make_persistent_id("chat_message_input");
                 let input_row_response = ui.horizontal(|ui| {
@@ -634,13 +1074,19 @@ impl ChatView {
                             if self.message_input.ends_with('\n') {
                                 self.message_input.pop();
                             }
-                            self.send_message(channel_id, state, network, runtime);
+                            match invoked_command {
+                                Some(command) => self.invoke_command(channel_id, command, &members, &community_channels, network, runtime),
+                                None => self.send_message(channel_id, state, network, runtime),
+                            }
                         }
                     }
 
                     let btn_text = if self.editing_message.is_some() { "Save" } else { "Send" };
                     if ui.button(btn_text).clicked() {
-                        self.send_message(channel_id, state, network, runtime);
+                        match invoked_command {
+                            Some(command) => self.invoke_command(channel_id, command, &members, &community_channels, network, runtime),
+                            None => self.send_message(channel_id, state, network, runtime),
+                        }
                     }
                 });
 
@@ -707,6 +1153,75 @@ impl ChatView {
                         });
                 }
 
+                // Show slash command dropdown above the input
+                if !matching_commands.is_empty() {
+                    let input_rect = input_row_response.response.rect;
+                    let dropdown_pos = egui::pos2(input_rect.left(), input_rect.top());
+
+                    egui::Area::new(egui::Id::new("command_dropdown"))
+                        .order(egui::Order::Foreground)
+                        .pivot(egui::Align2::LEFT_BOTTOM)
+                        .fixed_pos(dropdown_pos)
+                        .show(ui.ctx(), |ui| {
+                            egui::Frame::none()
+                                .fill(super::theme::BG_ELEVATED)
+                                .rounding(4.0)
+                                .inner_margin(4.0)
+                                .stroke(egui::Stroke::new(1.0, super::theme::BG_ACCENT))
+                                .shadow(egui::epaint::Shadow {
+                                    offset: egui::vec2(0.0, 2.0),
+                                    blur: 8.0,
+                                    spread: 0.0,
+                                    color: egui::Color32::from_black_alpha(60),
+                                })
+                                .show(ui, |ui| {
+                                    ui.set_min_width(320.0);
+                                    let mut chosen = None;
+                                    for (i, command) in matching_commands.iter().enumerate() {
+                                        let is_selected = i == self.command_selected;
+                                        let mut text = egui::text::LayoutJob::default();
+                                        text.append(
+                                            &format!("/{}", command.name),
+                                            0.0,
+                                            egui::TextFormat {
+                                                color: if is_selected {
+                                                    super::theme::TEXT_BRIGHT
+                                                } else {
+                                                    super::theme::TEXT_NORMAL
+                                                },
+                                                ..Default::default()
+                                            },
+                                        );
+                                        text.append(
+                                            &format!("{} · {}", command.description, command.application_name),
+                                            8.0,
+                                            egui::TextFormat {
+                                                color: super::theme::TEXT_MUTED,
+                                                ..Default::default()
+                                            },
+                                        );
+
+                                        let response = ui.add(
+                                            egui::Button::new(text)
+                                                .fill(if is_selected {
+                                                    super::theme::BG_ACCENT
+                                                } else {
+                                                    egui::Color32::TRANSPARENT
+                                                })
+                                                .min_size(egui::vec2(312.0, 28.0))
+                                        );
+
+                                        if response.clicked() {
+                                            chosen = Some(command.name.clone());
+                                        }
+                                    }
+                                    if let Some(name) = chosen {
+                                        self.insert_command(&name);
+                                    }
+                                });
+                        });
+                }
+
                 // GIF picker popup (above input)
                 if self.gif_picker.is_open() {
                     let input_rect = input_row_response.response.rect;
@@ -847,11 +1362,13 @@ impl ChatView {
                     show_thread_button: true,
                     show_thread_indicator: true,
                     show_reply_button: true,
+                    show_publish_button: is_announcement,
                     id_prefix: "chat",
                 };
 
                 // Only stick to bottom if we're not scrolling to a specific message and not loading history
                 let should_stick_to_bottom = scroll_to_message_id.is_none()
+                    && !detached
                     && !self.loading_history
                     && self.scrolled_to_bottom
                     && !self.jump_to_bottom_requested;
@@ -861,6 +1378,18 @@ impl ChatView {
                 if force_scroll_to_bottom {
                     self.jump_to_bottom_requested = false;
                     self.scrolled_to_bottom = true;
+
+                    // Leave a history window by reloading the latest messages
+                    if let (true, Some(channel_id)) = (detached, current_channel) {
+                        let state = state.clone();
+                        let network = network.clone();
+                        runtime.spawn(async move {
+                            match network.get_messages(channel_id, None).await {
+                                Ok(latest) => state.set_message_window(channel_id, latest, false).await,
+                                Err(e) => tracing::warn!("Failed to load latest messages: {}", e),
+                            }
+                        });
+                    }
                 }
 
                 let scroll_output = egui::ScrollArea::vertical()
@@ -920,9 +1449,9 @@ impl ChatView {
                                 ui.add_space(8.0);
                             }
 
-                            // Show reply preview if this is a reply
+                            // Show reply preview if this is a reply (click to jump to the original)
                             if let Some(reply_to_id) = message.reply_to_id {
-                                if let Some(original_msg) = message_lookup.get(&reply_to_id) {
+                                let preview_response = if let Some(original_msg) = message_lookup.get(&reply_to_id) {
                                     let preview_content: String = original_msg.content.chars().take(100).collect();
                                     let preview_content = if original_msg.content.len() > 100 {
                                         format!("{}...", preview_content)
@@ -947,16 +1476,26 @@ impl ChatView {
                                                 .small()
                                                 .color(egui::Color32::from_rgb(160, 160, 160)),
                                         );
-                                    });
+                                    }).response
                                 } else {
                                     ui.horizontal(|ui| {
                                         ui.add_space(16.0);
                                         ui.label(
-                                            egui::RichText::new("┌─ Original message not available")
+                                            egui::RichText::new("┌─ Original message not loaded")
                                                 .small()
                                                 .italics()
                                                 .color(egui::Color32::from_rgb(120, 120, 120)),
                                         );
+                                    }).response
+                                };
+
+                                let preview_response = preview_response
+                                    .interact(egui::Sense::click())
+                                    .on_hover_cursor(egui::CursorIcon::PointingHand);
+                                if preview_response.clicked() {
+                                    let state = state.clone();
+                                    runtime.spawn(async move {
+                                        state.write().await.scroll_to_message_id = Some(reply_to_id);
                                     });
                                 }
                             }
@@ -1054,6 +1593,17 @@ impl ChatView {
                                             }
                                         });
                                     }
+                                    MessageAction::Forward(msg) => {
+                                        self.forwarding_message = Some(msg);
+                                    }
+                                    MessageAction::Publish(msg_id) => {
+                                        let network = network.clone();
+                                        runtime.spawn(async move {
+                                            if let Err(e) = network.publish_message(msg_id).await {
+                                                tracing::warn!("Failed to publish message: {}", e);
+                                            }
+                                        });
+                                    }
                                 }
                             }
 
@@ -1089,6 +1639,18 @@ impl ChatView {
                                 ui.scroll_to_rect(message_rect, Some(egui::Align::Min));
                             }
 
+                            // "Seen by" receipt under the last message other DM participants have read
+                            if let Some(names) = seen_by.get(&message.id) {
+                                ui.horizontal(|ui| {
+                                    ui.add_space(16.0);
+                                    ui.label(
+                                        egui::RichText::new(format!("✓ Seen by {}", names.join(", ")))
+                                            .small()
+                                            .color(egui::Color32::from_rgb(140, 140, 140)),
+                                    );
+                                });
+                            }
+
                             ui.add_space(8.0);
                             prev_message = Some(message);
                         }
@@ -1104,8 +1666,26 @@ impl ChatView {
                     scroll_offset >= content_height - viewport_height - 50.0;
                 self.scrolled_to_bottom = at_bottom;
 
+                // Report the newest message as read once it's visible in a focused window
+                let window_focused = ui.ctx().input(|i| i.focused);
+                if let (true, false, true, Some(channel_id), Some(newest)) =
+                    (at_bottom, detached, window_focused, current_channel, messages.first())
+                {
+                    if self.last_read_sent != Some(newest.id) {
+                        self.last_read_sent = Some(newest.id);
+                        let network = network.clone();
+                        let message_id = newest.id;
+                        runtime.spawn(async move {
+                            if let Err(e) = network.mark_message_read(channel_id, message_id).await {
+                                tracing::warn!("Failed to mark message as read: {}", e);
+                            }
+                        });
+                    }
+                }
+
                 // Detect when loading completes by checking message count changes
                 let current_message_count = messages.len();
+                let message_count_changed = current_message_count != self.last_message_count;
                 if self.loading_history {
                     if current_message_count > self.last_message_count {
                         // New messages were added - loading is complete
@@ -1132,8 +1712,8 @@ impl ChatView {
                 {
                     self.loading_history = true;
                     self.loading_started_at = Some(Instant::now());
-                    // Get the oldest message ID (first in the list since we store DESC)
-                    if let Some(oldest_msg) = messages.first() {
+                    // Get the oldest message ID (last in the list since we store DESC)
+                    if let Some(oldest_msg) = messages.last() {
                         let oldest_id = oldest_msg.id;
                         let channel_id = current_channel.unwrap();
                         let state = state.clone();
@@ -1142,12 +1722,8 @@ impl ChatView {
                         runtime.spawn(async move {
                             match network.get_messages(channel_id, Some(oldest_id)).await {
                                 Ok(older_messages) => {
-                                    // Append older messages to the existing list
-                                    let mut s = state.write().await;
-                                    if let Some(existing) = s.messages.get_mut(&channel_id) {
-                                        // Messages are in DESC order, older messages go after existing
-                                        existing.extend(older_messages);
-                                    }
+                                    // Messages are in DESC order, older messages go after existing
+                                    state.append_older_messages(channel_id, older_messages).await;
                                 }
                                 Err(e) => {
                                     tracing::warn!("Failed to load older messages: {}", e);
@@ -1157,8 +1733,51 @@ impl ChatView {
                     }
                 }
 
-                // Show "Jump to present" button when not at bottom
-                if !at_bottom {
+                // Detect when loading newer messages completes
+                if self.loading_newer {
+                    let timed_out = self
+                        .loading_newer_started_at
+                        .map_or(true, |started| started.elapsed().as_secs() >= 2);
+                    if !detached || message_count_changed || timed_out {
+                        self.loading_newer = false;
+                        self.loading_newer_started_at = None;
+                    }
+                }
+
+                // In a history window, load newer messages when scrolled near the bottom
+                if at_bottom
+                    && detached
+                    && !self.loading_newer
+                    && scroll_to_message_id.is_none()
+                    && current_channel.is_some()
+                {
+                    if let Some(newest_msg) = messages.first() {
+                        self.loading_newer = true;
+                        self.loading_newer_started_at = Some(Instant::now());
+                        let newest_id = newest_msg.id;
+                        let channel_id = current_channel.unwrap();
+                        let state = state.clone();
+                        let network = network.clone();
+
+                        runtime.spawn(async move {
+                            match network.get_messages_after(channel_id, newest_id).await {
+                                Ok(newer_messages) => {
+                                    // A short page means we've caught up with the present
+                                    let caught_up = newer_messages.len() < HISTORY_PAGE_SIZE;
+                                    state
+                                        .prepend_newer_messages(channel_id, newer_messages, caught_up)
+                                        .await;
+                                }
+                                Err(e) => {
+                                    tracing::warn!("Failed to load newer messages: {}", e);
+                                }
+                            }
+                        });
+                    }
+                }
+
+                // Show "Jump to present" button when not at bottom or viewing older history
+                if !at_bottom || detached {
                     let button_rect = egui::Rect::from_center_size(
                         egui::pos2(
                             scroll_output.inner_rect.center().x,
@@ -1191,8 +1810,336 @@ impl ChatView {
                 }
             });
 
+        // Channel picker for forwarding a message
+        self.show_forward_dialog(ui.ctx(), state, network, runtime);
+        self.show_follow_dialog(ui.ctx(), state, network, runtime);
+        self.show_export_dialog(ui.ctx(), channel_id, &channel_name, state, network, runtime);
+
         // Render lightbox overlay on top if an image is being viewed
-        render_lightbox(ui.ctx(), &mut self.renderer_state);
+        render_lightbox(ui.ctx(), state, &mut self.renderer_state);
+    }
+
+    /// Show the channel picker window while a message is being forwarded
+    fn show_forward_dialog(
+        &mut self,
+        ctx: &egui::Context,
+        state: &AppState,
+        network: &NetworkClient,
+        runtime: &tokio::runtime::Runtime,
+    ) {
+        let Some(message) = self.forwarding_message.clone() else {
+            return;
+        };
+
+        // Any channel messages can be posted in, except the one it came from
+        let mut channels: Vec<ChannelData> = runtime.block_on(async {
+            state
+                .read()
+                .await
+                .channels
+                .values()
+                .filter(|c| !matches!(c.channel_type, ChannelType::Voice | ChannelType::Category | ChannelType::Forum) && c.id != message.channel_id)
+                .cloned()
+                .collect()
+        });
+        channels.sort_by_key(|c| c.position);
+
+        let mut open = true;
+        let mut selected: Option<Uuid> = None;
+
+        egui::Window::new("Forward Message")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                // Preview of what is being forwarded
+                ui.label(
+                    egui::RichText::new(&message.author_name)
+                        .strong()
+                        .color(egui::Color32::from_rgb(96, 165, 250)),
+                );
+                let preview: String = message.content.chars().take(120).collect();
+                ui.label(
+                    egui::RichText::new(preview)
+                        .color(egui::Color32::from_rgb(180, 180, 180)),
+                );
+                ui.separator();
+
+                if channels.is_empty() {
+                    ui.label(
+                        egui::RichText::new("No other channels to forward to")
+                            .color(egui::Color32::GRAY)
+                            .italics(),
+                    );
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for channel in &channels {
+                            let label = match channel.channel_type {
+                                ChannelType::Text => format!("# {}", channel.name),
+                                ChannelType::Announcement => format!("📢 {}", channel.name),
+                                _ => format!("@ {}", channel.name),
+                            };
+                            if ui
+                                .add(egui::Button::new(label).frame(false))
+                                .clicked()
+                            {
+                                selected = Some(channel.id);
+                            }
+                        }
+                    });
+            });
+
+        if let Some(channel_id) = selected {
+            let network = network.clone();
+            let message_id = message.id;
+            runtime.spawn(async move {
+                if let Err(e) = network.forward_message(message_id, channel_id).await {
+                    tracing::warn!("Failed to forward message: {}", e);
+                }
+            });
+            self.forwarding_message = None;
+        } else if !open {
+            self.forwarding_message = None;
+        }
+    }
+
+    /// Pick a channel in a community the user owns to receive an announcement channel's posts
+    fn show_follow_dialog(
+        &mut self,
+        ctx: &egui::Context,
+        state: &AppState,
+        network: &NetworkClient,
+        runtime: &tokio::runtime::Runtime,
+    ) {
+        let Some(source_id) = self.following_channel else {
+            return;
+        };
+
+        let mut channels: Vec<(String, ChannelData)> = runtime.block_on(async {
+            let s = state.read().await;
+            let user_id = s.current_user.as_ref().map(|u| u.id);
+            s.channels
+                .values()
+                .filter(|c| matches!(c.channel_type, ChannelType::Text | ChannelType::Announcement) && c.id != source_id)
+                .filter_map(|c| {
+                    let community = s.communities.get(&c.community_id?)?;
+                    (Some(community.owner_id) == user_id).then(|| (community.name.clone(), c.clone()))
+                })
+                .collect()
+        });
+        channels.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.position.cmp(&b.1.position)));
+
+        let mut open = true;
+        let mut selected: Option<Uuid> = None;
+
+        egui::Window::new("Follow Announcements")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.label("Published messages will be copied into:");
+                ui.separator();
+
+                if channels.is_empty() {
+                    ui.label(
+                        egui::RichText::new("You don't manage any text channels")
+                            .color(egui::Color32::GRAY)
+                            .italics(),
+                    );
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for (community_name, channel) in &channels {
+                            let label = format!("# {} ({})", channel.name, community_name);
+                            if ui
+                                .add(egui::Button::new(label).frame(false))
+                                .clicked()
+                            {
+                                selected = Some(channel.id);
+                            }
+                        }
+                    });
+            });
+
+        if let Some(target_id) = selected {
+            let network = network.clone();
+            let state = state.clone();
+            runtime.spawn(async move {
+                match network.follow_channel(source_id, target_id).await {
+                    Ok(follow) => state.add_channel_follow(follow).await,
+                    Err(e) => tracing::warn!("Failed to follow channel: {}", e),
+                }
+            });
+            self.following_channel = None;
+        } else if !open {
+            self.following_channel = None;
+        }
+    }
+
+    /// Start exports of the current channel and download the finished ones
+    fn show_export_dialog(
+        &mut self,
+        ctx: &egui::Context,
+        channel_id: Uuid,
+        channel_name: &str,
+        state: &AppState,
+        network: &NetworkClient,
+        runtime: &tokio::runtime::Runtime,
+    ) {
+        if !self.export_dialog_open {
+            return;
+        }
+
+        let mut exports: Vec<_> = runtime.block_on(async {
+            let s = state.read().await;
+            s.exports
+                .values()
+                .filter(|e| e.channel_id == channel_id && e.thread_parent_id.is_none())
+                .cloned()
+                .collect()
+        });
+        exports.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+        let mut open = true;
+
+        egui::Window::new("Export Channel")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(340.0)
+            .show(ctx, |ui| {
+                ui.label("Full history with threads, reactions, pins and attachments, as a zip file.");
+                ui.add_space(4.0);
+
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.export_format, ExportFormat::Html, "HTML");
+                    ui.selectable_value(&mut self.export_format, ExportFormat::Markdown, "Markdown");
+                    ui.selectable_value(&mut self.export_format, ExportFormat::Json, "JSON");
+                });
+
+                if ui.button("Start Export").clicked() {
+                    let state = state.clone();
+                    let network = network.clone();
+                    let format = self.export_format;
+                    runtime.spawn(async move {
+                        match network.create_export(channel_id, format).await {
+                            Ok(export) => state.upsert_export(export).await,
+                            Err(e) => tracing::warn!("Failed to start export: {}", e),
+                        }
+                    });
+                }
+
+                if exports.is_empty() {
+                    return;
+                }
+
+                ui.separator();
+
+                for export in &exports {
+                    let format = match export.format {
+                        ExportFormat::Json => "JSON",
+                        ExportFormat::Html => "HTML",
+                        ExportFormat::Markdown => "Markdown",
+                    };
+                    let started = export.created_at.with_timezone(&Local).format("%H:%M");
+
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} · {}", format, started));
+
+                        match export.status {
+                            ExportStatus::Pending => {
+                                ui.label(egui::RichText::new("Queued").color(egui::Color32::GRAY));
+                            }
+                            ExportStatus::Running => {
+                                let progress = if export.total_messages > 0 {
+                                    export.exported_messages as f32 / export.total_messages as f32
+                                } else {
+                                    0.0
+                                };
+                                ui.add(
+                                    egui::ProgressBar::new(progress)
+                                        .desired_width(140.0)
+                                        .text(format!("{} / {}", export.exported_messages, export.total_messages)),
+                                );
+                            }
+                            ExportStatus::Completed => {
+                                if ui.button("💾 Save").clicked() {
+                                    let network = network.clone();
+                                    let export_id = export.id;
+                                    let filename = format!(
+                                        "{}-{}-{}.zip",
+                                        channel_name.replace(['/', '\\'], "_"),
+                                        format.to_lowercase(),
+                                        export.created_at.format("%Y%m%d")
+                                    );
+                                    runtime.spawn(async move {
+                                        if let Err(e) = save_export(&network, export_id, &filename).await {
+                                            tracing::warn!("Failed to save export: {}", e);
+                                        }
+                                    });
+                                }
+                            }
+                            ExportStatus::Failed => {
+                                let error = export.error.as_deref().unwrap_or("Unknown error");
+                                ui.label(egui::RichText::new("Failed").color(egui::Color32::from_rgb(237, 66, 69)))
+                                    .on_hover_text(error);
+                            }
+                        }
+                    });
+                }
+            });
+
+        if !open {
+            self.export_dialog_open = false;
+        }
+    }
+
+    /// Complete the command name in the input, ready for its options
+    fn insert_command(&mut self, name: &str) {
+        self.message_input = format!("/{} ", name);
+        self.pending_cursor_pos = Some(self.message_input.chars().count());
+        self.command_selected = 0;
+    }
+
+    /// Send the typed slash command to its application, keeping the input if it's invalid
+    fn invoke_command(
+        &mut self,
+        channel_id: Uuid,
+        command: &SlashCommandData,
+        members: &[(Uuid, String, String)],
+        channels: &[(Uuid, String)],
+        network: &NetworkClient,
+        runtime: &tokio::runtime::Runtime,
+    ) {
+        let options = match commands::parse_options(&self.message_input, command, members, channels) {
+            Ok(options) => options,
+            Err(e) => {
+                self.command_error = Some(e);
+                return;
+            }
+        };
+
+        match runtime.block_on(network.invoke_command(channel_id, command.id, options)) {
+            Ok(_) => {
+                self.message_input.clear();
+                self.command_error = None;
+                self.last_typing_sent = None;
+                self.prev_input_len = 0;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to invoke /{}: {}", command.name, e);
+                self.command_error = Some(e.to_string());
+            }
+        }
     }
 
     fn send_message(
@@ -1207,7 +2154,8 @@ impl ChatView {
             return;
         }
 
-        let content = self.message_input.clone();
+        let content =
+            super::markdown::expand_custom_emoji(&self.message_input, &state.get_custom_emojis_sync());
         self.message_input.clear();
 
         // Clear the draft since we're sending
@@ -1217,10 +2165,7 @@ impl ChatView {
         });
 
         // Take pending attachments
-        let attachments: Vec<(String, String, Vec<u8>)> = self.pending_attachments
-            .drain(..)
-            .map(|a| (a.filename, a.content_type, a.data))
-            .collect();
+        let attachments: Vec<UploadFile> = self.pending_attachments.drain(..).collect();
 
         // Reset typing state
         self.last_typing_sent = None;
@@ -1239,18 +2184,51 @@ impl ChatView {
             });
         } else {
             let reply_to_id = self.replying_to.take().map(|m| m.id);
+            let uploads = self.uploads.clone();
             runtime.spawn(async move {
                 network.stop_typing(channel_id).await;
 
                 // Upload attachments first if any, collect their IDs
                 let mut attachment_ids = Vec::new();
-                if !attachments.is_empty() {
-                    match network.upload_files(channel_id, attachments).await {
-                        Ok(uploaded) => {
-                            attachment_ids = uploaded.iter().map(|a| a.id).collect();
+                for file in attachments {
+                    let upload_id = Uuid::new_v4();
+                    if let Ok(mut list) = uploads.write() {
+                        list.push(UploadProgress {
+                            id: upload_id,
+                            filename: file.filename.clone(),
+                            sent_bytes: 0,
+                            total_bytes: file.size,
+                            reconnecting: false,
+                            error: None,
+                        });
+                    }
+
+                    let result = network
+                        .upload_file(channel_id, &file, |sent_bytes, reconnecting| {
+                            if let Ok(mut list) = uploads.write() {
+                                if let Some(upload) = list.iter_mut().find(|u| u.id == upload_id) {
+                                    upload.sent_bytes = sent_bytes;
+                                    upload.reconnecting = reconnecting;
+                                }
+                            }
+                        })
+                        .await;
+
+                    match result {
+                        Ok(attachment) => {
+                            attachment_ids.push(attachment.id);
+                            if let Ok(mut list) = uploads.write() {
+                                list.retain(|u| u.id != upload_id);
+                            }
                         }
                         Err(e) => {
-                            tracing::warn!("Failed to upload attachments: {}", e);
+                            tracing::warn!("Failed to upload {}: {}", file.filename, e);
+                            if let Ok(mut list) = uploads.write() {
+                                if let Some(upload) = list.iter_mut().find(|u| u.id == upload_id) {
+                                    upload.reconnecting = false;
+                                    upload.error = Some(e.to_string());
+                                }
+                            }
                         }
                     }
                 }
@@ -1299,13 +2277,6 @@ impl ChatView {
                 let filename = file.name.clone();
                 let data = bytes.to_vec();
 
-                // Check size limit (25MB)
-                const MAX_SIZE: usize = 25 * 1024 * 1024;
-                if data.len() > MAX_SIZE {
-                    tracing::warn!("File {} is too large (max 25MB)", filename);
-                    continue;
-                }
-
                 // Determine content type from filename extension
                 let content_type = std::path::Path::new(&filename)
                     .extension()
@@ -1314,10 +2285,11 @@ impl ChatView {
                     .unwrap_or("application/octet-stream")
                     .to_string();
 
-                self.pending_attachments.push(PendingAttachment {
+                self.pending_attachments.push(UploadFile {
                     filename,
                     content_type,
-                    data,
+                    size: data.len() as u64,
+                    source: UploadSource::Bytes(std::sync::Arc::new(data)),
                 });
             }
         }
@@ -1342,21 +2314,13 @@ impl ChatView {
         }
     }
 
-    /// Add a file from a path
+    /// Add a file from a path. It's read while uploading, so size is only checked by the server.
     fn add_file_from_path(&mut self, path: &std::path::Path) {
         if let Some(filename) = path.file_name() {
             let filename = filename.to_string_lossy().to_string();
 
-            // Read file
-            match std::fs::read(path) {
-                Ok(data) => {
-                    // Check size limit (25MB)
-                    const MAX_SIZE: usize = 25 * 1024 * 1024;
-                    if data.len() > MAX_SIZE {
-                        tracing::warn!("File {} is too large (max 25MB)", filename);
-                        return;
-                    }
-
+            match std::fs::metadata(path) {
+                Ok(metadata) => {
                     // Determine content type from extension
                     let content_type = path
                         .extension()
@@ -1365,10 +2329,11 @@ impl ChatView {
                         .unwrap_or("application/octet-stream")
                         .to_string();
 
-                    self.pending_attachments.push(PendingAttachment {
+                    self.pending_attachments.push(UploadFile {
                         filename,
                         content_type,
-                        data,
+                        size: metadata.len(),
+                        source: UploadSource::Path(path.to_path_buf()),
                     });
                 }
                 Err(e) => {
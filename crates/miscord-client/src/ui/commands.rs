@@ -0,0 +1,154 @@
+//! Slash command parsing for the message composer.
+//! Options are typed as `name:value`; text before the first named option
+//! fills the first option that wasn't named.
+
+use miscord_protocol::{CommandOptionData, CommandOptionType, InteractionOptionData, SlashCommandData};
+use uuid::Uuid;
+
+/// The command being typed: the word after a leading '/', when the input starts with one
+pub fn command_name(input: &str) -> Option<&str> {
+    let rest = input.strip_prefix('/')?;
+    Some(rest.split_whitespace().next().unwrap_or(""))
+}
+
+/// Whether the user is still typing the command name (no space after it yet)
+pub fn is_typing_name(input: &str) -> bool {
+    input.starts_with('/') && !input.contains(char::is_whitespace)
+}
+
+/// Find the command the input invokes
+pub fn find_command<'a>(input: &str, commands: &'a [SlashCommandData]) -> Option<&'a SlashCommandData> {
+    let name = command_name(input)?;
+    commands.iter().find(|c| c.name == name)
+}
+
+fn type_name(kind: CommandOptionType) -> &'static str {
+    match kind {
+        CommandOptionType::String => "text",
+        CommandOptionType::Integer => "whole number",
+        CommandOptionType::Number => "number",
+        CommandOptionType::Boolean => "true/false",
+        CommandOptionType::User => "@user",
+        CommandOptionType::Channel => "#channel",
+    }
+}
+
+/// Usage line such as `/roll sides:<whole number> [label:<text>]`
+pub fn usage(command: &SlashCommandData) -> String {
+    let mut usage = format!("/{}", command.name);
+    for option in &command.options {
+        let hint = if option.choices.is_empty() {
+            format!("{}:<{}>", option.name, type_name(option.kind))
+        } else {
+            format!("{}:<{}>", option.name, option.choices.join("|"))
+        };
+        if option.required {
+            usage.push_str(&format!(" {}", hint));
+        } else {
+            usage.push_str(&format!(" [{}]", hint));
+        }
+    }
+    usage
+}
+
+/// Convert what was typed for an option into the value sent to the server
+fn option_value(
+    option: &CommandOptionData,
+    text: &str,
+    members: &[(Uuid, String, String)],
+    channels: &[(Uuid, String)],
+) -> Result<serde_json::Value, String> {
+    match option.kind {
+        CommandOptionType::String => {
+            if !option.choices.is_empty() && !option.choices.iter().any(|c| c == text) {
+                return Err(format!("{} must be one of: {}", option.name, option.choices.join(", ")));
+            }
+            Ok(text.into())
+        }
+        CommandOptionType::Integer => text
+            .parse::<i64>()
+            .map(Into::into)
+            .map_err(|_| format!("{} must be a whole number", option.name)),
+        CommandOptionType::Number => text
+            .parse::<f64>()
+            .map(Into::into)
+            .map_err(|_| format!("{} must be a number", option.name)),
+        CommandOptionType::Boolean => match text.to_lowercase().as_str() {
+            "true" | "yes" => Ok(true.into()),
+            "false" | "no" => Ok(false.into()),
+            _ => Err(format!("{} must be true or false", option.name)),
+        },
+        CommandOptionType::User => {
+            let name = text.trim_start_matches('@');
+            members
+                .iter()
+                .find(|(_, username, _)| username.eq_ignore_ascii_case(name))
+                .map(|(id, _, _)| id.to_string().into())
+                .ok_or_else(|| format!("No member named @{}", name))
+        }
+        CommandOptionType::Channel => {
+            let name = text.trim_start_matches('#');
+            channels
+                .iter()
+                .find(|(_, channel_name)| channel_name.eq_ignore_ascii_case(name))
+                .map(|(id, _)| id.to_string().into())
+                .ok_or_else(|| format!("No channel named #{}", name))
+        }
+    }
+}
+
+/// Parse the options typed after the command name
+pub fn parse_options(
+    input: &str,
+    command: &SlashCommandData,
+    members: &[(Uuid, String, String)],
+    channels: &[(Uuid, String)],
+) -> Result<Vec<InteractionOptionData>, String> {
+    let rest = input
+        .trim_start_matches('/')
+        .trim_start()
+        .strip_prefix(command.name.as_str())
+        .unwrap_or("");
+
+    let mut leading = String::new();
+    let mut named: Vec<(String, String)> = Vec::new();
+    for word in rest.split_whitespace() {
+        if let Some((name, value)) = word.split_once(':') {
+            if command.options.iter().any(|o| o.name == name) {
+                named.push((name.to_string(), value.to_string()));
+                continue;
+            }
+        }
+        let current = match named.last_mut() {
+            Some((_, value)) => value,
+            None => &mut leading,
+        };
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+
+    if !leading.is_empty() {
+        let first_unnamed = command
+            .options
+            .iter()
+            .find(|o| !named.iter().any(|(name, _)| *name == o.name))
+            .ok_or_else(|| format!("Unexpected \"{}\"", leading))?;
+        named.insert(0, (first_unnamed.name.clone(), leading));
+    }
+
+    let mut options = Vec::new();
+    for option in &command.options {
+        match named.iter().find(|(name, value)| *name == option.name && !value.is_empty()) {
+            Some((_, text)) => options.push(InteractionOptionData {
+                name: option.name.clone(),
+                value: option_value(option, text, members, channels)?,
+            }),
+            None if option.required => return Err(format!("Missing {}", option.name)),
+            None => {}
+        }
+    }
+
+    Ok(options)
+}
@@ -83,6 +83,16 @@ impl CommunityList {
                         if let Ok(members) = network.get_members(community_id).await {
                             state.set_members(community_id, members).await;
                         }
+
+                        // Load custom emoji for this community
+                        if let Ok(emojis) = network.get_community_emojis(community_id).await {
+                            state.set_custom_emojis(community_id, emojis).await;
+                        }
+
+                        // Load slash commands for this community
+                        if let Ok(commands) = network.get_commands(community_id).await {
+                            state.set_commands(community_id, commands).await;
+                        }
                     });
                 }
 
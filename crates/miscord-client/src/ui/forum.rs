@@ -0,0 +1,479 @@
+use eframe::egui;
+use miscord_protocol::{ForumPostData, ForumTagData};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::network::NetworkClient;
+use crate::state::AppState;
+use super::message::format_relative_time;
+use super::theme;
+
+/// Characters of a post's content shown in the post list
+const POST_PREVIEW_CHARS: usize = 160;
+
+/// Forum channel view: posts sorted by activity, filtered by tag
+pub struct ForumView {
+    /// Forum currently shown (to reset filters and reload on switch)
+    current_channel_id: Option<Uuid>,
+    /// Tags to filter by; posts with any of them are listed
+    selected_tags: HashSet<Uuid>,
+    /// Filter the loaded post list was fetched with
+    loaded_filter: Option<(Uuid, Vec<Uuid>)>,
+    /// New post dialog state
+    show_new_post_dialog: bool,
+    new_post_title: String,
+    new_post_content: String,
+    new_post_tags: HashSet<Uuid>,
+    /// Tag management dialog state
+    show_tags_dialog: bool,
+    new_tag_name: String,
+}
+
+impl ForumView {
+    pub fn new() -> Self {
+        Self {
+            current_channel_id: None,
+            selected_tags: HashSet::new(),
+            loaded_filter: None,
+            show_new_post_dialog: false,
+            new_post_title: String::new(),
+            new_post_content: String::new(),
+            new_post_tags: HashSet::new(),
+            show_tags_dialog: false,
+            new_tag_name: String::new(),
+        }
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        state: &AppState,
+        network: &NetworkClient,
+        runtime: &tokio::runtime::Runtime,
+    ) {
+        let (channel, posts, tags, current_user_id, is_moderator, has_scroll_target) = runtime.block_on(async {
+            let s = state.read().await;
+            let channel = s.current_channel_id.and_then(|id| s.channels.get(&id)).cloned();
+            let posts = channel
+                .as_ref()
+                .and_then(|c| s.forum_posts.get(&c.id))
+                .cloned()
+                .unwrap_or_default();
+            let tags = channel
+                .as_ref()
+                .and_then(|c| s.forum_tags.get(&c.id))
+                .cloned()
+                .unwrap_or_default();
+            let current_user_id = s.current_user.as_ref().map(|u| u.id);
+            // Only the community owner manages forum tags
+            let is_moderator = channel
+                .as_ref()
+                .and_then(|c| c.community_id)
+                .and_then(|id| s.communities.get(&id))
+                .zip(current_user_id)
+                .is_some_and(|(community, user_id)| community.owner_id == user_id);
+            (channel, posts, tags, current_user_id, is_moderator, s.scroll_to_message_id.is_some())
+        });
+
+        // Search results and followed threads jump to the parent message, which only
+        // the chat view scrolls to; here the thread panel shows the post instead
+        if has_scroll_target {
+            runtime.block_on(async {
+                state.write().await.scroll_to_message_id = None;
+            });
+        }
+
+        let Some(channel) = channel else {
+            return;
+        };
+
+        // Reset filters and load tags when switching forums
+        if self.current_channel_id != Some(channel.id) {
+            self.current_channel_id = Some(channel.id);
+            self.selected_tags.clear();
+            self.loaded_filter = None;
+            self.show_new_post_dialog = false;
+            self.show_tags_dialog = false;
+
+            let channel_id = channel.id;
+            let state = state.clone();
+            let network = network.clone();
+            runtime.spawn(async move {
+                match network.get_forum_tags(channel_id).await {
+                    Ok(tags) => state.set_forum_tags(channel_id, tags).await,
+                    Err(e) => tracing::error!("Failed to load forum tags: {}", e),
+                }
+            });
+        }
+
+        // Drop filters for tags that were deleted
+        self.selected_tags.retain(|id| tags.iter().any(|t| t.id == *id));
+
+        // Reload posts when the filter changes
+        let mut filter: Vec<Uuid> = self.selected_tags.iter().copied().collect();
+        filter.sort();
+        let wanted = (channel.id, filter);
+        if self.loaded_filter.as_ref() != Some(&wanted) {
+            let (channel_id, tag_ids) = wanted.clone();
+            self.loaded_filter = Some(wanted);
+            let state = state.clone();
+            let network = network.clone();
+            runtime.spawn(async move {
+                match network.get_forum_posts(channel_id, &tag_ids).await {
+                    Ok(posts) => state.set_forum_posts(channel_id, posts).await,
+                    Err(e) => tracing::error!("Failed to load forum posts: {}", e),
+                }
+            });
+        }
+
+        // Header with the forum name and post/tag actions
+        egui::TopBottomPanel::top("forum_header")
+            .show_inside(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(format!("💬 {}", channel.name));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("New Post").clicked() {
+                            self.show_new_post_dialog = true;
+                        }
+                        if is_moderator && ui.button("Tags").on_hover_text("Manage forum tags").clicked() {
+                            self.show_tags_dialog = true;
+                        }
+                    });
+                });
+
+                // Tag filter chips
+                if !tags.is_empty() {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(egui::RichText::new("Filter:").size(12.0).color(theme::TEXT_MUTED));
+                        if ui.selectable_label(self.selected_tags.is_empty(), "All").clicked() {
+                            self.selected_tags.clear();
+                        }
+                        for tag in &tags {
+                            let selected = self.selected_tags.contains(&tag.id);
+                            if ui.selectable_label(selected, &tag.name).clicked() {
+                                if selected {
+                                    self.selected_tags.remove(&tag.id);
+                                } else {
+                                    self.selected_tags.insert(tag.id);
+                                }
+                            }
+                        }
+                    });
+                }
+                ui.add_space(4.0);
+            });
+
+        // Posts, most recently active first
+        let visible_posts: Vec<&ForumPostData> = posts
+            .iter()
+            .filter(|p| {
+                self.selected_tags.is_empty() || p.tag_ids.iter().any(|id| self.selected_tags.contains(id))
+            })
+            .collect();
+
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            if visible_posts.is_empty() {
+                ui.centered_and_justified(|ui| {
+                    let text = if self.selected_tags.is_empty() {
+                        "No posts yet. Start a discussion with New Post."
+                    } else {
+                        "No posts with the selected tags."
+                    };
+                    ui.label(egui::RichText::new(text).color(theme::TEXT_MUTED));
+                });
+                return;
+            }
+
+            egui::ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    for post in visible_posts {
+                        let can_retag = is_moderator || current_user_id == Some(post.author_id);
+                        if let Some(tag_ids) = show_post_card(ui, post, &tags, can_retag, state, runtime) {
+                            let network = network.clone();
+                            let state = state.clone();
+                            let message_id = post.message_id;
+                            runtime.spawn(async move {
+                                match network.set_forum_post_tags(message_id, &tag_ids).await {
+                                    Ok(post) => state.upsert_forum_post(post).await,
+                                    Err(e) => tracing::error!("Failed to update post tags: {}", e),
+                                }
+                            });
+                        }
+                        ui.add_space(6.0);
+                    }
+                });
+        });
+
+        if self.show_new_post_dialog {
+            self.new_post_dialog(ui, channel.id, &tags, state, network, runtime);
+        }
+
+        if self.show_tags_dialog {
+            self.tags_dialog(ui, channel.id, &tags, state, network, runtime);
+        }
+    }
+
+    fn new_post_dialog(
+        &mut self,
+        ui: &mut egui::Ui,
+        channel_id: Uuid,
+        tags: &[ForumTagData],
+        state: &AppState,
+        network: &NetworkClient,
+        runtime: &tokio::runtime::Runtime,
+    ) {
+        let mut open = true;
+        egui::Window::new("New Post")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ui.ctx(), |ui| {
+                ui.label("Title");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.new_post_title)
+                        .char_limit(100)
+                        .desired_width(360.0),
+                );
+
+                ui.label("Message");
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.new_post_content)
+                        .desired_rows(6)
+                        .desired_width(360.0),
+                );
+
+                if !tags.is_empty() {
+                    ui.label("Tags");
+                    ui.horizontal_wrapped(|ui| {
+                        for tag in tags {
+                            let selected = self.new_post_tags.contains(&tag.id);
+                            if ui.selectable_label(selected, &tag.name).clicked() {
+                                if selected {
+                                    self.new_post_tags.remove(&tag.id);
+                                } else {
+                                    self.new_post_tags.insert(tag.id);
+                                }
+                            }
+                        }
+                    });
+                }
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    let can_post = !self.new_post_title.trim().is_empty()
+                        && !self.new_post_content.trim().is_empty();
+                    if ui.add_enabled(can_post, egui::Button::new("Post")).clicked() {
+                        let title = self.new_post_title.trim().to_string();
+                        let content = self.new_post_content.trim().to_string();
+                        let tag_ids: Vec<Uuid> = self.new_post_tags.iter().copied().collect();
+                        let network = network.clone();
+                        let state = state.clone();
+
+                        runtime.spawn(async move {
+                            match network.create_forum_post(channel_id, &title, &content, &tag_ids).await {
+                                Ok(post) => {
+                                    let message_id = post.message_id;
+                                    state.upsert_forum_post(post).await;
+                                    state.open_thread(message_id).await;
+                                }
+                                Err(e) => tracing::error!("Failed to create post: {}", e),
+                            }
+                        });
+
+                        self.reset_new_post();
+                    }
+
+                    if ui.button("Cancel").clicked() {
+                        self.reset_new_post();
+                    }
+                });
+            });
+
+        if !open {
+            self.reset_new_post();
+        }
+    }
+
+    fn reset_new_post(&mut self) {
+        self.show_new_post_dialog = false;
+        self.new_post_title.clear();
+        self.new_post_content.clear();
+        self.new_post_tags.clear();
+    }
+
+    fn tags_dialog(
+        &mut self,
+        ui: &mut egui::Ui,
+        channel_id: Uuid,
+        tags: &[ForumTagData],
+        state: &AppState,
+        network: &NetworkClient,
+        runtime: &tokio::runtime::Runtime,
+    ) {
+        let mut open = true;
+        egui::Window::new("Forum Tags")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ui.ctx(), |ui| {
+                if tags.is_empty() {
+                    ui.label(egui::RichText::new("No tags yet").color(theme::TEXT_MUTED));
+                }
+
+                for tag in tags {
+                    ui.horizontal(|ui| {
+                        ui.label(&tag.name);
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button("🗑").on_hover_text("Delete tag").clicked() {
+                                let network = network.clone();
+                                let state = state.clone();
+                                let tag_id = tag.id;
+                                runtime.spawn(async move {
+                                    if let Err(e) = network.delete_forum_tag(tag_id).await {
+                                        tracing::error!("Failed to delete tag: {}", e);
+                                        return;
+                                    }
+                                    reload_tags(&state, &network, channel_id).await;
+                                });
+                            }
+                        });
+                    });
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.new_tag_name)
+                            .hint_text("New tag")
+                            .char_limit(32)
+                            .desired_width(200.0),
+                    );
+                    let name = self.new_tag_name.trim().to_string();
+                    if ui.add_enabled(!name.is_empty(), egui::Button::new("Add")).clicked() {
+                        let network = network.clone();
+                        let state = state.clone();
+                        runtime.spawn(async move {
+                            if let Err(e) = network.create_forum_tag(channel_id, &name).await {
+                                tracing::error!("Failed to create tag: {}", e);
+                                return;
+                            }
+                            reload_tags(&state, &network, channel_id).await;
+                        });
+                        self.new_tag_name.clear();
+                    }
+                });
+            });
+
+        if !open {
+            self.show_tags_dialog = false;
+            self.new_tag_name.clear();
+        }
+    }
+}
+
+impl Default for ForumView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn reload_tags(state: &AppState, network: &NetworkClient, channel_id: Uuid) {
+    match network.get_forum_tags(channel_id).await {
+        Ok(tags) => state.set_forum_tags(channel_id, tags).await,
+        Err(e) => tracing::error!("Failed to load forum tags: {}", e),
+    }
+}
+
+/// Render a post in the list. Clicking the title opens the post's thread.
+/// Returns the new tag IDs if the user changed the post's tags.
+fn show_post_card(
+    ui: &mut egui::Ui,
+    post: &ForumPostData,
+    tags: &[ForumTagData],
+    can_retag: bool,
+    state: &AppState,
+    runtime: &tokio::runtime::Runtime,
+) -> Option<Vec<Uuid>> {
+    let mut new_tags = None;
+
+    egui::Frame::none()
+        .fill(theme::BG_ELEVATED)
+        .rounding(6.0)
+        .inner_margin(10.0)
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+
+            ui.horizontal(|ui| {
+                let title = ui.add(
+                    egui::Button::new(
+                        egui::RichText::new(&post.title)
+                            .size(16.0)
+                            .strong()
+                            .color(theme::TEXT_BRIGHT),
+                    )
+                    .frame(false),
+                );
+                if title.on_hover_text("Open post").clicked() {
+                    let state = state.clone();
+                    let message_id = post.message_id;
+                    runtime.spawn(async move {
+                        state.open_thread(message_id).await;
+                    });
+                }
+                for tag in tags.iter().filter(|t| post.tag_ids.contains(&t.id)) {
+                    ui.label(
+                        egui::RichText::new(&tag.name)
+                            .size(11.0)
+                            .color(theme::TEXT_NORMAL)
+                            .background_color(theme::BG_ACCENT),
+                    );
+                }
+
+                if can_retag && !tags.is_empty() {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.menu_button("🏷", |ui| {
+                            for tag in tags {
+                                let mut selected = post.tag_ids.contains(&tag.id);
+                                if ui.checkbox(&mut selected, &tag.name).changed() {
+                                    let mut tag_ids: Vec<Uuid> = post
+                                        .tag_ids
+                                        .iter()
+                                        .copied()
+                                        .filter(|id| *id != tag.id)
+                                        .collect();
+                                    if selected {
+                                        tag_ids.push(tag.id);
+                                    }
+                                    new_tags = Some(tag_ids);
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Edit tags");
+                    });
+                }
+            });
+
+            let mut preview: String = post.content.chars().take(POST_PREVIEW_CHARS).collect();
+            if post.content.chars().count() > POST_PREVIEW_CHARS {
+                preview.push('…');
+            }
+            ui.label(egui::RichText::new(preview).color(theme::TEXT_NORMAL));
+
+            ui.add_space(2.0);
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} · {} {} · active {}",
+                    post.author_name,
+                    post.reply_count,
+                    if post.reply_count == 1 { "reply" } else { "replies" },
+                    format_relative_time(post.last_activity_at()),
+                ))
+                .size(12.0)
+                .color(theme::TEXT_MUTED),
+            );
+        });
+
+    new_tags
+}
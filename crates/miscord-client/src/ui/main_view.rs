@@ -8,6 +8,7 @@ use crate::state::{AppState, UiState};
 use super::channel_list::ChannelList;
 use super::chat::ChatView;
 use super::community_list::CommunityList;
+use super::forum::ForumView;
 use super::member_list::MemberList;
 use super::thread_panel::ThreadPanel;
 use super::voice::VoicePanel;
@@ -17,6 +18,7 @@ pub struct MainView {
     community_list: CommunityList,
     channel_list: ChannelList,
     chat_view: ChatView,
+    forum_view: ForumView,
     member_list: MemberList,
     thread_panel: ThreadPanel,
     voice_panel: VoicePanel,
@@ -42,6 +44,7 @@ impl MainView {
             community_list: CommunityList::new(),
             channel_list: ChannelList::new(),
             chat_view: ChatView::new(),
+            forum_view: ForumView::new(),
             member_list: MemberList::new(),
             thread_panel: ThreadPanel::new(),
             voice_panel: VoicePanel::new(),
@@ -115,6 +118,16 @@ impl MainView {
                             state_clone.set_members(community_id, members).await;
                         }
 
+                        // Load custom emoji for this community
+                        if let Ok(emojis) = network_clone.get_community_emojis(community_id).await {
+                            state_clone.set_custom_emojis(community_id, emojis).await;
+                        }
+
+                        // Load slash commands for this community
+                        if let Ok(commands) = network_clone.get_commands(community_id).await {
+                            state_clone.set_commands(community_id, commands).await;
+                        }
+
                         // Determine which channel to select: saved one if valid, otherwise first text channel
                         let target_channel = saved_channel_id
                             .and_then(|id| channels.iter().find(|c| c.id == id))
@@ -231,21 +244,11 @@ impl MainView {
             .min_width(200.0)
             .max_width(300.0)
             .show(ctx, |ui| {
-                let (text_expanded, voice_expanded) = self.channel_list.show(
-                    ui,
-                    state,
-                    network,
-                    runtime,
-                    self.ui_state.text_channels_expanded,
-                    self.ui_state.voice_channels_expanded,
-                );
-
-                // Save expanded states if they changed
-                if text_expanded != self.ui_state.text_channels_expanded
-                    || voice_expanded != self.ui_state.voice_channels_expanded
-                {
-                    self.ui_state.text_channels_expanded = text_expanded;
-                    self.ui_state.voice_channels_expanded = voice_expanded;
+                let ui_state_changed =
+                    self.channel_list.show(ui, state, network, runtime, &mut self.ui_state);
+
+                // Save expanded sections and categories if they changed
+                if ui_state_changed {
                     self.ui_state.save();
                 }
 
@@ -296,10 +299,19 @@ impl MainView {
                 });
         }
 
-        // Main content area - show voice channel view or chat view
+        let in_forum = runtime.block_on(async {
+            let s = state.read().await;
+            s.current_channel_id
+                .and_then(|id| s.channels.get(&id))
+                .is_some_and(|c| c.channel_type == miscord_protocol::ChannelType::Forum)
+        });
+
+        // Main content area - show voice channel view, forum view or chat view
         egui::CentralPanel::default().show(ctx, |ui| {
             if in_voice {
                 self.voice_channel_view.show(ui, ctx, state, network, runtime);
+            } else if in_forum {
+                self.forum_view.show(ui, state, network, runtime);
             } else {
                 self.chat_view.show(ui, state, network, runtime);
             }
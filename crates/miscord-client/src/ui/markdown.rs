@@ -7,6 +7,7 @@
 //! - Inline code (`code`)
 //! - Code blocks (```lang\ncode\n```)
 //! - Lists (- item or * item)
+//! - Custom community emoji (<:name:id>)
 //!
 //! Code blocks have basic syntax highlighting for:
 //! - Keywords (blue)
@@ -15,9 +16,17 @@
 //! - Numbers (orange)
 
 use eframe::egui::{self, Color32, FontId, RichText, Ui};
+use miscord_protocol::CustomEmojiData;
 use regex::Regex;
+use std::collections::HashMap;
 use std::sync::LazyLock;
 
+/// Loaded custom emoji textures (`<:name:id>` key -> texture)
+pub type EmojiTextures = HashMap<String, egui::TextureHandle>;
+
+/// Height of custom emoji rendered inline with text
+const INLINE_EMOJI_SIZE: f32 = 20.0;
+
 // Colors for syntax highlighting (Discord-like dark theme)
 const COLOR_KEYWORD: Color32 = Color32::from_rgb(86, 156, 214); // Blue
 const COLOR_STRING: Color32 = Color32::from_rgb(152, 195, 121); // Green
@@ -50,6 +59,14 @@ static RE_MENTION: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"@(\w+)").unwrap()
 });
 const COLOR_MENTION: Color32 = Color32::from_rgb(88, 101, 242); // Discord blurple
+// Custom emoji pattern - matches <:name:id>
+static RE_CUSTOM_EMOJI: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"<:([A-Za-z0-9_]{2,32}):([0-9a-fA-F-]{36})>").unwrap()
+});
+// Typed custom emoji (:name:), skipping ones already written as <:name:id>
+static RE_TYPED_CUSTOM_EMOJI: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"<:[A-Za-z0-9_]{2,32}:[0-9a-fA-F-]{36}>|:([A-Za-z0-9_]{2,32}):").unwrap()
+});
 
 // Syntax highlighting patterns
 static RE_COMMENT_LINE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"//.*$").unwrap());
@@ -70,8 +87,34 @@ pub fn extract_urls(text: &str) -> Vec<String> {
         .collect()
 }
 
+/// Replace typed `:name:` custom emoji with their `<:name:id>` keys.
+/// `emojis` is ordered by preference, so the first emoji with a name wins.
+pub fn expand_custom_emoji(text: &str, emojis: &[CustomEmojiData]) -> String {
+    RE_TYPED_CUSTOM_EMOJI
+        .replace_all(text, |cap: &regex::Captures| {
+            let typed = cap.get(0).map_or("", |m| m.as_str());
+            match cap.get(1) {
+                Some(name) => emojis
+                    .iter()
+                    .find(|e| e.name == name.as_str())
+                    .map(|e| e.key())
+                    .unwrap_or_else(|| typed.to_string()),
+                None => typed.to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Build an image widget for a custom emoji, scaled to the given height
+pub fn custom_emoji_image(texture: &egui::TextureHandle, height: f32) -> egui::Image<'static> {
+    let size = texture.size_vec2();
+    let scale = height / size.x.max(size.y).max(1.0);
+    egui::Image::new((texture.id(), size * scale))
+}
+
 /// Render markdown text in the UI
-pub fn render_markdown(ui: &mut Ui, text: &str) {
+/// `emojis` holds the custom emoji that can be shown inline; others are shown as `:name:`
+pub fn render_markdown(ui: &mut Ui, text: &str, emojis: &EmojiTextures) {
     // First, split by code blocks
     let mut last_end = 0;
     let mut parts: Vec<MarkdownPart> = Vec::new();
@@ -107,7 +150,7 @@ pub fn render_markdown(ui: &mut Ui, text: &str) {
     // Render each part
     for part in parts {
         match part {
-            MarkdownPart::Text(text) => render_text_block(ui, &text),
+            MarkdownPart::Text(text) => render_text_block(ui, &text, emojis),
             MarkdownPart::CodeBlock { language, code } => render_code_block(ui, &language, &code),
         }
     }
@@ -120,7 +163,7 @@ enum MarkdownPart {
 }
 
 /// Render a text block (non-code content)
-fn render_text_block(ui: &mut Ui, text: &str) {
+fn render_text_block(ui: &mut Ui, text: &str, emojis: &EmojiTextures) {
     for line in text.lines() {
         let trimmed = line.trim();
         if trimmed.is_empty() {
@@ -153,18 +196,18 @@ fn render_text_block(ui: &mut Ui, text: &str) {
             let content = caps.get(1).map(|m| m.as_str()).unwrap_or(trimmed);
             ui.horizontal(|ui| {
                 ui.label(RichText::new("  \u{2022}  ").color(COLOR_TEXT)); // Bullet
-                render_inline_markdown(ui, content);
+                render_inline_markdown(ui, content, emojis);
             });
             continue;
         }
 
         // Regular text with inline formatting
-        render_inline_markdown(ui, trimmed);
+        render_inline_markdown(ui, trimmed, emojis);
     }
 }
 
 /// Render inline markdown (bold, italic, code) on a single line
-fn render_inline_markdown(ui: &mut Ui, text: &str) {
+fn render_inline_markdown(ui: &mut Ui, text: &str, emojis: &EmojiTextures) {
     // Build segments with formatting
     let segments = parse_inline_formatting(text);
 
@@ -220,6 +263,20 @@ fn render_inline_markdown(ui: &mut Ui, text: &str) {
                             .background_color(Color32::from_rgb(88, 101, 242).gamma_multiply(0.3)),
                     );
                 }
+                InlineSegment::CustomEmoji(key) => {
+                    let name = miscord_protocol::parse_custom_emoji_key(&key)
+                        .map(|(name, _)| format!(":{}:", name))
+                        .unwrap_or_else(|| key.clone());
+                    match emojis.get(&key) {
+                        Some(texture) => {
+                            ui.add(custom_emoji_image(texture, INLINE_EMOJI_SIZE))
+                                .on_hover_text(name);
+                        }
+                        None => {
+                            ui.label(RichText::new(name).color(COLOR_TEXT));
+                        }
+                    }
+                }
             }
         }
     });
@@ -233,6 +290,7 @@ enum InlineSegment {
     Code(String),
     Link(String),    // URL that should be clickable
     Mention(String), // @username mention
+    CustomEmoji(String), // <:name:id> custom emoji key
 }
 
 /// Parse inline formatting and return segments
@@ -258,6 +316,7 @@ fn parse_inline_formatting(text: &str) -> Vec<InlineSegment> {
         Italic,
         Link,
         Mention,
+        CustomEmoji,
     }
 
     let mut matches: Vec<Match> = Vec::new();
@@ -329,6 +388,16 @@ fn parse_inline_formatting(text: &str) -> Vec<InlineSegment> {
         });
     }
 
+    // Find custom emoji; ones without a texture are drawn as their name
+    for full in RE_CUSTOM_EMOJI.find_iter(&remaining) {
+        matches.push(Match {
+            start: full.start(),
+            end: full.end(),
+            content: full.as_str().to_string(),
+            kind: MatchKind::CustomEmoji,
+        });
+    }
+
     // Sort by start position
     matches.sort_by_key(|m| m.start);
 
@@ -354,6 +423,7 @@ fn parse_inline_formatting(text: &str) -> Vec<InlineSegment> {
             MatchKind::Italic => segments.push(InlineSegment::Italic(m.content)),
             MatchKind::Link => segments.push(InlineSegment::Link(m.content)),
             MatchKind::Mention => segments.push(InlineSegment::Mention(m.content)),
+            MatchKind::CustomEmoji => segments.push(InlineSegment::CustomEmoji(m.content)),
         }
         pos = m.end;
     }
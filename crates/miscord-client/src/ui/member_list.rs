@@ -120,10 +120,26 @@ impl MemberList {
 
             // Name and status
             ui.vertical(|ui| {
-                ui.label(
-                    egui::RichText::new(&member.display_name)
-                        .color(theme::TEXT_NORMAL)
-                );
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(&member.display_name)
+                            .color(theme::TEXT_NORMAL)
+                    );
+                    if member.bot {
+                        egui::Frame::none()
+                            .fill(egui::Color32::from_rgb(88, 101, 242))
+                            .rounding(egui::Rounding::same(3.0))
+                            .inner_margin(egui::Margin::symmetric(4.0, 1.0))
+                            .show(ui, |ui| {
+                                ui.label(
+                                    egui::RichText::new("BOT")
+                                        .size(9.0)
+                                        .strong()
+                                        .color(egui::Color32::WHITE),
+                                );
+                            });
+                    }
+                });
                 if let Some(custom_status) = &member.custom_status {
                     ui.label(
                         egui::RichText::new(custom_status)
@@ -8,7 +8,9 @@ use uuid::Uuid;
 use crate::media::{AudioPlayer, AudioPlayerState, format_duration};
 use crate::network::{NetworkClient, OpenGraphData};
 use crate::state::AppState;
-use miscord_protocol::MessageData;
+use miscord_protocol::{ButtonData, ButtonStyle, EmbedData, EmbedFieldData, ForwardedFromData, MessageData};
+
+use super::markdown::{custom_emoji_image, EmojiTextures};
 
 /// Common reaction emojis - using simpler Unicode that renders well
 pub const REACTION_EMOJIS: &[&str] = &["👍", "❤️", "😄", "😮", "😢", "🎉"];
@@ -57,6 +59,10 @@ pub enum MessageAction {
     Pin(Uuid),
     /// User wants to unpin this message
     Unpin(Uuid),
+    /// User wants to forward this message to another channel
+    Forward(MessageData),
+    /// User wants to publish this announcement to following channels
+    Publish(Uuid),
 }
 
 /// Options for rendering a message
@@ -67,6 +73,8 @@ pub struct MessageRenderOptions {
     pub show_thread_indicator: bool,
     /// Whether to show the reply button
     pub show_reply_button: bool,
+    /// Whether to show the publish button on own messages (announcement channels)
+    pub show_publish_button: bool,
     /// Prefix for egui IDs to avoid conflicts
     pub id_prefix: &'static str,
 }
@@ -77,6 +85,7 @@ impl Default for MessageRenderOptions {
             show_thread_button: true,
             show_thread_indicator: true,
             show_reply_button: true,
+            show_publish_button: false,
             id_prefix: "chat",
         }
     }
@@ -98,6 +107,9 @@ pub enum DownloadProgress {
 pub struct LightboxState {
     /// The URL/key of the image being viewed
     pub image_key: String,
+    /// The full-size image's URL/key, when `image_key` is only its thumbnail. The
+    /// thumbnail is shown until it loads.
+    pub full_image_key: Option<String>,
     /// Original dimensions
     pub width: u32,
     pub height: u32,
@@ -119,6 +131,8 @@ pub struct MessageRendererState {
     pub link_preview_textures: std::collections::HashMap<String, egui::TextureHandle>,
     /// Texture cache for attachment images (url -> texture handle)
     pub attachment_textures: std::collections::HashMap<String, egui::TextureHandle>,
+    /// Texture cache for attachment blurhash placeholders (hash -> texture handle)
+    pub blurhash_textures: std::collections::HashMap<String, egui::TextureHandle>,
     /// Download progress for attachments (attachment_id -> progress)
     pub download_progress: std::sync::Arc<std::sync::RwLock<std::collections::HashMap<Uuid, DownloadProgress>>>,
     /// Audio player instance
@@ -129,6 +143,8 @@ pub struct MessageRendererState {
     pub lightbox: Option<LightboxState>,
     /// Cached audio data for attachments (attachment_id -> data)
     pub audio_cache: std::collections::HashMap<Uuid, Vec<u8>>,
+    /// Texture cache for custom emoji images (url -> texture handle)
+    pub emoji_textures: std::collections::HashMap<String, egui::TextureHandle>,
 }
 
 impl MessageRendererState {
@@ -137,11 +153,13 @@ impl MessageRendererState {
             emoji_picker_open_for: None,
             link_preview_textures: std::collections::HashMap::new(),
             attachment_textures: std::collections::HashMap::new(),
+            blurhash_textures: std::collections::HashMap::new(),
             download_progress: std::sync::Arc::new(std::sync::RwLock::new(std::collections::HashMap::new())),
             audio_player: AudioPlayer::new().ok(),
             audio_state: None,
             lightbox: None,
             audio_cache: std::collections::HashMap::new(),
+            emoji_textures: std::collections::HashMap::new(),
         }
     }
 }
@@ -172,6 +190,164 @@ impl Default for MessageRendererState {
 /// Reaction data for rendering (emoji, count, whether current user reacted)
 pub type ReactionInfo = (String, usize, bool);
 
+/// Collect textures for the custom emoji usable in the current view (`<:name:id>` key -> texture).
+/// Emoji images that aren't loaded yet are fetched in the background and show up on a later frame.
+pub fn load_custom_emoji_textures(
+    ctx: &egui::Context,
+    state: &AppState,
+    network: &NetworkClient,
+    runtime: &tokio::runtime::Runtime,
+    renderer_state: &mut MessageRendererState,
+) -> EmojiTextures {
+    let mut textures = EmojiTextures::new();
+
+    for emoji in state.get_custom_emojis_sync() {
+        if !renderer_state.emoji_textures.contains_key(&emoji.url) {
+            match state.get_image_sync(&emoji.url) {
+                Some(cached_img) => {
+                    let (rgba_data, width, height) = cached_img.as_ref();
+                    let color_image = egui::ColorImage::from_rgba_unmultiplied(
+                        [*width as usize, *height as usize],
+                        rgba_data,
+                    );
+                    let handle = ctx.load_texture(
+                        format!("custom_emoji_{}", emoji.id),
+                        color_image,
+                        egui::TextureOptions::LINEAR,
+                    );
+                    renderer_state.emoji_textures.insert(emoji.url.clone(), handle);
+                }
+                None => {
+                    if state.mark_image_pending_sync(&emoji.url) == Some(true) {
+                        let network = network.clone();
+                        let state = state.clone();
+                        let url = emoji.url.clone();
+                        runtime.spawn(async move {
+                            match network.fetch_attachment_image(&url, 500).await {
+                                Ok((bytes, width, height)) => {
+                                    state.set_image(url, bytes, width, height).await;
+                                }
+                                Err(e) => {
+                                    tracing::warn!("Failed to fetch emoji image {}: {}", url, e);
+                                    state.mark_image_failed(&url).await;
+                                }
+                            }
+                        });
+                    }
+                    continue;
+                }
+            }
+        }
+
+        if let Some(texture) = renderer_state.emoji_textures.get(&emoji.url) {
+            textures.insert(emoji.key(), texture.clone());
+        }
+    }
+
+    textures
+}
+
+/// Render one bot message button; link buttons open their URL, others notify the bot
+fn render_component_button(
+    ui: &mut egui::Ui,
+    message_id: Uuid,
+    button: &ButtonData,
+    network: &NetworkClient,
+    runtime: &tokio::runtime::Runtime,
+) {
+    let fill = match button.style {
+        ButtonStyle::Primary => egui::Color32::from_rgb(88, 101, 242),
+        ButtonStyle::Success => egui::Color32::from_rgb(59, 165, 93),
+        ButtonStyle::Danger => egui::Color32::from_rgb(237, 66, 69),
+        ButtonStyle::Secondary | ButtonStyle::Link => egui::Color32::from_rgb(79, 84, 92),
+    };
+    let label = if button.style == ButtonStyle::Link {
+        format!("{} ↗", button.label)
+    } else {
+        button.label.clone()
+    };
+
+    let response = ui.add_enabled(
+        !button.disabled,
+        egui::Button::new(egui::RichText::new(label).size(13.0).color(egui::Color32::WHITE))
+            .fill(fill)
+            .rounding(egui::Rounding::same(4.0))
+            .min_size(egui::vec2(60.0, 30.0)),
+    );
+    if !response.clicked() {
+        return;
+    }
+
+    if button.style == ButtonStyle::Link {
+        if let Some(url) = &button.url {
+            if let Err(e) = open::that(url) {
+                tracing::warn!("Failed to open URL: {}", e);
+            }
+        }
+    } else if let Some(custom_id) = button.custom_id.clone() {
+        let network = network.clone();
+        runtime.spawn(async move {
+            if let Err(e) = network.click_button(message_id, &custom_id).await {
+                tracing::warn!("Failed to click button: {}", e);
+            }
+        });
+    }
+}
+
+/// Render a reaction pill ("👍 3"), drawing custom emoji ("<:name:id>") as images
+fn reaction_button(
+    ui: &mut egui::Ui,
+    emoji: &str,
+    count: usize,
+    reacted_by_me: bool,
+    emojis: &EmojiTextures,
+) -> egui::Response {
+    let fill_color = if reacted_by_me {
+        REACTION_BG_ACTIVE
+    } else {
+        REACTION_BG_INACTIVE
+    };
+    let text_color = if reacted_by_me {
+        egui::Color32::WHITE
+    } else {
+        egui::Color32::from_rgb(220, 221, 222)
+    };
+
+    let custom_texture = emojis.get(emoji);
+    // Custom emoji whose image isn't available are shown by name
+    let label = match miscord_protocol::parse_custom_emoji_key(emoji) {
+        Some((name, _)) => format!(":{}:", name),
+        None => emoji.to_string(),
+    };
+
+    let button = match custom_texture {
+        Some(texture) => egui::Button::image_and_text(
+            custom_emoji_image(texture, 18.0),
+            egui::RichText::new(count.to_string())
+                .size(14.0)
+                .color(text_color),
+        ),
+        None => egui::Button::new(
+            egui::RichText::new(format!("{} {}", label, count))
+                .size(14.0)
+                .color(text_color)
+        ),
+    };
+
+    let btn = ui.add(
+        button
+            .fill(fill_color)
+            .rounding(egui::Rounding::same(6.0))
+            .min_size(egui::vec2(0.0, 28.0))
+    );
+
+    if custom_texture.is_some() {
+        btn.on_hover_text(emoji)
+    } else {
+        btn
+    }
+}
+
 /// Render a single message with all its UI elements
 /// Returns an optional MessageAction if the user triggered one
 ///
@@ -190,12 +366,20 @@ pub fn render_message(
     let mut action = None;
     let is_own_message = current_user_id.map_or(false, |uid| uid == message.author_id);
 
+    // Custom emoji for inline rendering, reactions and the picker
+    let emojis = load_custom_emoji_textures(ui.ctx(), state, network, runtime, renderer_state);
+
     // Track react button rect for emoji picker positioning
     let mut react_btn_rect: Option<egui::Rect> = None;
     let mut should_toggle_picker = false;
 
     // Message header: author name, timestamp, and action buttons
     ui.horizontal(|ui| {
+        // Webhook messages show the avatar they were posted with
+        if let Some(avatar_url) = message.webhook.as_ref().and_then(|w| w.avatar_url.as_ref()) {
+            render_webhook_avatar(ui, avatar_url, state, network, runtime, renderer_state);
+        }
+
         // Author name - using theme brand color
         ui.label(
             egui::RichText::new(&message.author_name)
@@ -203,6 +387,23 @@ pub fn render_message(
                 .color(egui::Color32::from_rgb(96, 165, 250)),  // Softer blue for names
         );
 
+        if message.webhook.is_some() {
+            egui::Frame::none()
+                .fill(egui::Color32::from_rgb(88, 101, 242))
+                .rounding(egui::Rounding::same(3.0))
+                .inner_margin(egui::Margin::symmetric(4.0, 1.0))
+                .show(ui, |ui| {
+                    ui.label(
+                        egui::RichText::new("WEBHOOK")
+                            .size(9.0)
+                            .strong()
+                            .color(egui::Color32::WHITE),
+                    );
+                })
+                .response
+                .on_hover_text("Posted by an incoming webhook");
+        }
+
         // Timestamp
         let relative_time = format_relative_time(message.created_at);
         let full_time = format_full_timestamp(message.created_at);
@@ -233,6 +434,24 @@ pub fn render_message(
             }
         }
 
+        // Ephemeral interaction responses only exist on this client
+        if message.ephemeral {
+            ui.add_space(12.0);
+            ui.label(
+                egui::RichText::new("Only you can see this ·")
+                    .small()
+                    .color(egui::Color32::GRAY),
+            );
+            if ui.small_button("Dismiss").clicked() {
+                let state = state.clone();
+                let (channel_id, message_id) = (message.channel_id, message.id);
+                runtime.spawn(async move {
+                    state.dismiss_ephemeral_message(channel_id, message_id).await;
+                });
+            }
+            return;
+        }
+
         // Action buttons - subtle icons that appear on hover
         ui.add_space(12.0);
         ui.spacing_mut().item_spacing.x = 2.0;
@@ -275,6 +494,20 @@ pub fn render_message(
             }
         }
 
+        // Forward button
+        let forward_btn = action_btn(ui, "↪", "Forward");
+        if forward_btn.clicked() {
+            action = Some(MessageAction::Forward(message.clone()));
+        }
+
+        // Publish button (own announcements that haven't been published yet)
+        if options.show_publish_button && is_own_message && message.published_at.is_none() {
+            let publish_btn = action_btn(ui, "📢", "Publish to following channels");
+            if publish_btn.clicked() {
+                action = Some(MessageAction::Publish(message.id));
+            }
+        }
+
         // Edit button (only for own messages)
         if is_own_message {
             let edit_btn = action_btn(ui, "✎", "Edit");
@@ -338,6 +571,8 @@ pub fn render_message(
                         .rounding(egui::Rounding::same(8.0))
                         .fill(egui::Color32::from_rgb(30, 32, 36))  // BG_PRIMARY
                         .show(ui, |ui| {
+                            let mut picked: Option<String> = None;
+
                             ui.horizontal(|ui| {
                                 ui.spacing_mut().item_spacing.x = 4.0;
                                 for emoji in REACTION_EMOJIS {
@@ -350,18 +585,47 @@ pub fn render_message(
                                         .rounding(egui::Rounding::same(6.0))
                                     );
                                     if btn.clicked() {
-                                        let network = network.clone();
-                                        let msg_id = message.id;
-                                        let emoji_str = emoji.to_string();
-                                        runtime.spawn(async move {
-                                            if let Err(e) = network.add_reaction(msg_id, &emoji_str).await {
-                                                tracing::warn!("Failed to add reaction: {}", e);
-                                            }
-                                        });
-                                        close_picker = true;
+                                        picked = Some(emoji.to_string());
                                     }
                                 }
                             });
+
+                            // Custom community emoji
+                            if !emojis.is_empty() {
+                                ui.add_space(4.0);
+                                ui.separator();
+                                let mut keys: Vec<&String> = emojis.keys().collect();
+                                keys.sort();
+                                ui.set_max_width(260.0);
+                                ui.horizontal_wrapped(|ui| {
+                                    ui.spacing_mut().item_spacing = egui::vec2(4.0, 4.0);
+                                    for key in keys {
+                                        let Some((name, _)) = miscord_protocol::parse_custom_emoji_key(key) else {
+                                            continue;
+                                        };
+                                        let btn = ui.add(
+                                            egui::Button::image(custom_emoji_image(&emojis[key], 24.0))
+                                                .fill(egui::Color32::TRANSPARENT)
+                                                .min_size(egui::vec2(36.0, 36.0))
+                                                .rounding(egui::Rounding::same(6.0))
+                                        );
+                                        if btn.on_hover_text(format!(":{}:", name)).clicked() {
+                                            picked = Some(key.clone());
+                                        }
+                                    }
+                                });
+                            }
+
+                            if let Some(emoji_str) = picked {
+                                let network = network.clone();
+                                let msg_id = message.id;
+                                runtime.spawn(async move {
+                                    if let Err(e) = network.add_reaction(msg_id, &emoji_str).await {
+                                        tracing::warn!("Failed to add reaction: {}", e);
+                                    }
+                                });
+                                close_picker = true;
+                            }
                         });
                 });
 
@@ -373,7 +637,11 @@ pub fn render_message(
 
     // Message content with markdown rendering
     ui.indent(format!("{}_msg_content_{}", options.id_prefix, message.id), |ui| {
-        super::markdown::render_markdown(ui, &message.content);
+        if let Some(forwarded) = &message.forwarded_from {
+            render_forwarded_content(ui, forwarded, &message.content, &emojis);
+        } else {
+            super::markdown::render_markdown(ui, &message.content, &emojis);
+        }
     });
 
     // Link previews - extract URLs and show preview cards (limit to first URL only)
@@ -414,6 +682,11 @@ pub fn render_message(
         }
     }
 
+    // Embeds posted by bots and webhooks
+    for embed in &message.embeds {
+        render_embed(ui, embed, &emojis, state, network, runtime, renderer_state);
+    }
+
     // Display attachments
     if !message.attachments.is_empty() {
         ui.add_space(4.0);
@@ -422,6 +695,17 @@ pub fn render_message(
         }
     }
 
+    // Buttons added by the bot that posted the message
+    if !message.components.is_empty() {
+        ui.add_space(4.0);
+        ui.horizontal_wrapped(|ui| {
+            ui.add_space(16.0);
+            for button in &message.components {
+                render_component_button(ui, message.id, button, network, runtime);
+            }
+        });
+    }
+
     // Display existing reactions (clickable to toggle)
     // Use provided reactions parameter if available, otherwise fall back to message.reactions
     let has_reactions = reactions.map(|r| !r.is_empty()).unwrap_or(!message.reactions.is_empty());
@@ -434,27 +718,7 @@ pub fn render_message(
             if let Some(reaction_list) = reactions {
                 // Use reactions from state (real-time updates)
                 for (emoji, count, i_reacted) in reaction_list {
-                    let reaction_text = format!("{} {}", emoji, count);
-                    let fill_color = if *i_reacted {
-                        REACTION_BG_ACTIVE
-                    } else {
-                        REACTION_BG_INACTIVE
-                    };
-                    let text_color = if *i_reacted {
-                        egui::Color32::WHITE
-                    } else {
-                        egui::Color32::from_rgb(220, 221, 222)
-                    };
-                    let btn = ui.add(
-                        egui::Button::new(
-                            egui::RichText::new(reaction_text)
-                                .size(14.0)
-                                .color(text_color)
-                        )
-                        .fill(fill_color)
-                        .rounding(egui::Rounding::same(6.0))
-                        .min_size(egui::vec2(0.0, 28.0))
-                    );
+                    let btn = reaction_button(ui, emoji, *count, *i_reacted, &emojis);
                     if btn.clicked() {
                         let network = network.clone();
                         let msg_id = message.id;
@@ -476,27 +740,7 @@ pub fn render_message(
             } else {
                 // Fall back to message.reactions (initial load)
                 for reaction in &message.reactions {
-                    let reaction_text = format!("{} {}", reaction.emoji, reaction.count());
-                    let fill_color = if reaction.reacted_by_me {
-                        REACTION_BG_ACTIVE
-                    } else {
-                        REACTION_BG_INACTIVE
-                    };
-                    let text_color = if reaction.reacted_by_me {
-                        egui::Color32::WHITE
-                    } else {
-                        egui::Color32::from_rgb(220, 221, 222)
-                    };
-                    let btn = ui.add(
-                        egui::Button::new(
-                            egui::RichText::new(reaction_text)
-                                .size(14.0)
-                                .color(text_color)
-                        )
-                        .fill(fill_color)
-                        .rounding(egui::Rounding::same(6.0))
-                        .min_size(egui::vec2(0.0, 28.0))
-                    );
+                    let btn = reaction_button(ui, &reaction.emoji, reaction.count(), reaction.reacted_by_me, &emojis);
                     if btn.clicked() {
                         let network = network.clone();
                         let msg_id = message.id;
@@ -568,223 +812,425 @@ pub fn render_message(
 }
 
 /// Render a link preview card with optional image
-fn render_link_preview(
+/// Render the content of a forwarded message inside a quote-style card
+/// with a header naming where it came from
+fn render_forwarded_content(
     ui: &mut egui::Ui,
-    data: &OpenGraphData,
+    forwarded: &ForwardedFromData,
+    content: &str,
+    emojis: &EmojiTextures,
+) {
+    egui::Frame::none()
+        .fill(egui::Color32::from_rgb(38, 40, 46))  // BG_ELEVATED
+        .rounding(egui::Rounding::same(4.0))
+        .inner_margin(egui::Margin {
+            left: 10.0,
+            right: 8.0,
+            top: 6.0,
+            bottom: 6.0,
+        })
+        .show(ui, |ui| {
+            // Left accent bar, painted after layout so it spans the full card height
+            let card_rect = ui.max_rect();
+
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 4.0;
+                let label = if forwarded.published { "📢 Published" } else { "↪ Forwarded" };
+                ui.label(
+                    egui::RichText::new(label)
+                        .small()
+                        .italics()
+                        .color(egui::Color32::from_rgb(160, 160, 160)),
+                );
+
+                let mut origin = String::new();
+                if let Some(channel_name) = &forwarded.channel_name {
+                    origin.push_str(&format!("from #{}", channel_name));
+                    if let Some(community_name) = &forwarded.community_name {
+                        origin.push_str(&format!(" in {}", community_name));
+                    }
+                    origin.push_str(" · ");
+                }
+                origin.push_str(&forwarded.author_name);
+                ui.label(
+                    egui::RichText::new(origin)
+                        .small()
+                        .color(egui::Color32::from_rgb(140, 140, 140)),
+                );
+
+                ui.label(
+                    egui::RichText::new(format_relative_time(forwarded.created_at))
+                        .small()
+                        .color(egui::Color32::GRAY),
+                )
+                .on_hover_text(format_full_timestamp(forwarded.created_at));
+            });
+
+            super::markdown::render_markdown(ui, content, emojis);
+
+            let accent_rect = egui::Rect::from_min_max(
+                egui::pos2(card_rect.left() - 10.0, card_rect.top() - 6.0),
+                egui::pos2(card_rect.left() - 7.0, ui.min_rect().bottom() + 6.0),
+            );
+            ui.painter().rect_filled(
+                accent_rect,
+                egui::Rounding {
+                    nw: 4.0,
+                    sw: 4.0,
+                    ne: 0.0,
+                    se: 0.0,
+                },
+                egui::Color32::from_rgb(128, 132, 142),
+            );
+        });
+}
+
+/// Texture and size of an external image, fetching it into the shared image cache on first use.
+/// Returns None until the image has loaded.
+fn external_image_texture(
+    ui: &egui::Ui,
+    url: &str,
     state: &AppState,
     network: &NetworkClient,
     runtime: &tokio::runtime::Runtime,
     renderer_state: &mut MessageRendererState,
-) {
-    ui.add_space(4.0);
+) -> Option<(egui::TextureHandle, egui::Vec2)> {
+    let cached = state.get_image_sync(url);
+    if cached.is_none() && state.mark_image_pending_sync(url) == Some(true) {
+        let network = network.clone();
+        let state = state.clone();
+        let url = url.to_string();
+        runtime.spawn(async move {
+            match network.fetch_image(&url).await {
+                Ok((bytes, width, height)) => {
+                    state.set_image(url, bytes, width, height).await;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to fetch image {}: {}", url, e);
+                    state.mark_image_failed(&url).await;
+                }
+            }
+        });
+    }
 
-    // Try to load image with actual network fetch
-    let image_data = if let Some(image_url) = &data.image {
-        let cached = state.get_image_sync(image_url);
+    let cached_img = cached?;
+    let (rgba_data, width, height) = cached_img.as_ref();
+    let texture = renderer_state
+        .link_preview_textures
+        .entry(url.to_string())
+        .or_insert_with(|| {
+            let color_image = egui::ColorImage::from_rgba_unmultiplied(
+                [*width as usize, *height as usize],
+                rgba_data,
+            );
+            ui.ctx().load_texture(
+                format!("external_image_{}", url),
+                color_image,
+                egui::TextureOptions::LINEAR,
+            )
+        })
+        .clone();
 
-        if cached.is_none() {
-            if state.mark_image_pending_sync(image_url) == Some(true) {
-                let network = network.clone();
-                let state = state.clone();
-                let url = image_url.clone();
-                runtime.spawn(async move {
-                    match network.fetch_image(&url).await {
-                        Ok((bytes, width, height)) => {
-                            state.set_image(url, bytes, width, height).await;
-                        }
-                        Err(e) => {
-                            tracing::warn!("Failed to fetch image {}: {}", url, e);
-                            state.mark_image_failed(&url).await;
-                        }
-                    }
-                });
-            }
-        }
+    Some((texture, egui::vec2(*width as f32, *height as f32)))
+}
 
-        cached
-    } else {
-        None
-    };
+/// Small round avatar in front of a webhook message's author name
+fn render_webhook_avatar(
+    ui: &mut egui::Ui,
+    url: &str,
+    state: &AppState,
+    network: &NetworkClient,
+    runtime: &tokio::runtime::Runtime,
+    renderer_state: &mut MessageRendererState,
+) {
+    const SIZE: f32 = 20.0;
+
+    let texture = external_image_texture(ui, url, state, network, runtime, renderer_state);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(SIZE, SIZE), egui::Sense::hover());
+    match texture {
+        Some((texture, _)) => {
+            egui::Image::new((texture.id(), rect.size()))
+                .rounding(egui::Rounding::same(SIZE / 2.0))
+                .paint_at(ui, rect);
+        }
+        None => {
+            ui.painter().circle_filled(rect.center(), SIZE / 2.0, egui::Color32::from_rgb(60, 63, 69));
+        }
+    }
+}
 
-    // Preview card with left accent bar (like Discord/Slack)
-    egui::Frame::none()
+/// Card with a colored bar on its left edge (like Discord/Slack), shared by link previews and embeds
+fn render_card(ui: &mut egui::Ui, accent: egui::Color32, add_contents: impl FnOnce(&mut egui::Ui)) {
+    let response = egui::Frame::none()
         .fill(egui::Color32::from_rgb(38, 40, 46))  // BG_ELEVATED
         .rounding(egui::Rounding::same(4.0))
-        .inner_margin(egui::Margin::same(0.0))
+        .inner_margin(egui::Margin {
+            left: 12.0,
+            right: 12.0,
+            top: 8.0,
+            bottom: 8.0,
+        })
         .show(ui, |ui| {
-            ui.horizontal(|ui| {
-                // Left accent bar
-                let content_height = if image_data.is_some() { 120.0 } else { 60.0 };
-                let accent_rect = ui.allocate_exact_size(
-                    egui::vec2(4.0, content_height),
-                    egui::Sense::hover()
-                ).0;
-                ui.painter().rect_filled(
-                    accent_rect,
-                    egui::Rounding {
-                        nw: 4.0,
-                        sw: 4.0,
-                        ne: 0.0,
-                        se: 0.0,
-                    },
-                    egui::Color32::from_rgb(88, 101, 242),  // Discord blurple
-                );
+            ui.set_min_width(300.0);
+            ui.set_max_width(400.0);
+            ui.vertical(add_contents);
+        })
+        .response;
+
+    // Left accent bar
+    let accent_rect = egui::Rect::from_min_size(response.rect.min, egui::vec2(4.0, response.rect.height()));
+    ui.painter().rect_filled(
+        accent_rect,
+        egui::Rounding {
+            nw: 4.0,
+            sw: 4.0,
+            ne: 0.0,
+            se: 0.0,
+        },
+        accent,
+    );
+}
 
-                // Content area
-                ui.vertical(|ui| {
-                    ui.add_space(8.0);
-                    ui.set_min_width(300.0);
-                    ui.set_max_width(400.0);
+/// Image scaled down to fit `max_width`, keeping its aspect ratio
+fn fitted_size(size: egui::Vec2, max_width: f32) -> egui::Vec2 {
+    let width = size.x.min(max_width);
+    egui::vec2(width, width * size.y / size.x.max(1.0))
+}
 
-                    // Site name (if available)
-                    if let Some(site_name) = &data.site_name {
-                        ui.label(
-                            egui::RichText::new(site_name)
-                                .size(11.0)
-                                .color(egui::Color32::from_rgb(140, 140, 140))
-                        );
-                    }
+/// Clickable title of a card, opening `url` when there is one
+fn render_card_title(ui: &mut egui::Ui, title: &str, url: Option<&str>) {
+    let Some(url) = url else {
+        ui.label(egui::RichText::new(title).size(14.0).strong().color(egui::Color32::WHITE));
+        return;
+    };
 
-                    // Author/channel name for videos
-                    if let Some(author) = &data.author_name {
-                        ui.label(
-                            egui::RichText::new(author)
-                                .size(12.0)
-                                .color(egui::Color32::from_rgb(160, 160, 160))
-                        );
-                    }
+    let title_label = ui.add(
+        egui::Label::new(
+            egui::RichText::new(title)
+                .size(14.0)
+                .strong()
+                .color(egui::Color32::from_rgb(0, 168, 252))  // Link blue
+        )
+        .sense(egui::Sense::click())
+    );
 
-                    // Title (clickable link)
-                    if let Some(title) = &data.title {
-                        let title_label = ui.add(
-                            egui::Label::new(
-                                egui::RichText::new(title)
-                                    .size(14.0)
-                                    .strong()
-                                    .color(egui::Color32::from_rgb(0, 168, 252))  // Link blue
-                            )
-                            .sense(egui::Sense::click())
-                        );
+    if title_label.hovered() {
+        ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+    }
 
-                        if title_label.hovered() {
-                            ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
-                        }
+    if title_label.clicked() {
+        if let Err(e) = open::that(url) {
+            tracing::warn!("Failed to open URL: {}", e);
+        }
+    }
 
-                        if title_label.clicked() {
-                            if let Err(e) = open::that(&data.url) {
-                                tracing::warn!("Failed to open URL: {}", e);
-                            }
-                        }
+    title_label.on_hover_text(url);
+}
 
-                        title_label.on_hover_text(&data.url);
-                    }
+fn render_link_preview(
+    ui: &mut egui::Ui,
+    data: &OpenGraphData,
+    state: &AppState,
+    network: &NetworkClient,
+    runtime: &tokio::runtime::Runtime,
+    renderer_state: &mut MessageRendererState,
+) {
+    ui.add_space(4.0);
 
-                    // Description (truncated)
-                    if let Some(description) = &data.description {
-                        let truncated: String = description.chars().take(150).collect();
-                        let display_text = if description.len() > 150 {
-                            format!("{}...", truncated)
-                        } else {
-                            truncated
-                        };
+    let image = data
+        .image
+        .as_deref()
+        .and_then(|url| external_image_texture(ui, url, state, network, runtime, renderer_state));
 
-                        ui.label(
-                            egui::RichText::new(display_text)
-                                .size(13.0)
-                                .color(egui::Color32::from_rgb(180, 180, 180))
-                        );
-                    }
+    render_card(ui, egui::Color32::from_rgb(88, 101, 242), |ui| {  // Discord blurple
+        // Site name (if available)
+        if let Some(site_name) = &data.site_name {
+            ui.label(
+                egui::RichText::new(site_name)
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(140, 140, 140))
+            );
+        }
 
-                    // Image (if available and loaded)
-                    if let Some(cached_img) = &image_data {
-                        let image_url = data.image.as_ref().unwrap();
-                        let (rgba_data, width, height) = cached_img.as_ref();
-                        ui.add_space(8.0);
-
-                        // Get or create texture from renderer state cache
-                        if !renderer_state.link_preview_textures.contains_key(image_url) {
-                            let color_image = egui::ColorImage::from_rgba_unmultiplied(
-                                [*width as usize, *height as usize],
-                                rgba_data,
-                            );
-                            let handle = ui.ctx().load_texture(
-                                format!("link_preview_{}", image_url),
-                                color_image,
-                                egui::TextureOptions::LINEAR,
-                            );
-                            renderer_state.link_preview_textures.insert(image_url.clone(), handle);
-                        }
+        // Author/channel name for videos
+        if let Some(author) = &data.author_name {
+            ui.label(
+                egui::RichText::new(author)
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(160, 160, 160))
+            );
+        }
 
-                        if let Some(texture) = renderer_state.link_preview_textures.get(image_url) {
-                            // Calculate display size (max 300px wide, maintain aspect ratio)
-                            let aspect = *width as f32 / *height as f32;
-                            let display_width = (*width as f32).min(300.0);
-                            let display_height = display_width / aspect;
+        if let Some(title) = &data.title {
+            render_card_title(ui, title, Some(&data.url));
+        }
 
-                            let is_video = data.video_type.is_some();
+        // Description (truncated)
+        if let Some(description) = &data.description {
+            let truncated: String = description.chars().take(150).collect();
+            let display_text = if description.len() > 150 {
+                format!("{}...", truncated)
+            } else {
+                truncated
+            };
 
-                            // For videos, make the thumbnail clickable with a play button overlay
-                            let (rect, response) = ui.allocate_exact_size(
-                                egui::vec2(display_width, display_height),
-                                egui::Sense::click(),
-                            );
+            ui.label(
+                egui::RichText::new(display_text)
+                    .size(13.0)
+                    .color(egui::Color32::from_rgb(180, 180, 180))
+            );
+        }
 
-                            if ui.is_rect_visible(rect) {
-                                // Draw the thumbnail
-                                ui.painter().image(
-                                    texture.id(),
-                                    rect,
-                                    egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
-                                    egui::Color32::WHITE,
-                                );
+        // Image (if available and loaded)
+        if let Some((texture, size)) = &image {
+            ui.add_space(8.0);
 
-                                // Draw play button overlay for videos
-                                if is_video {
-                                    let center = rect.center();
-                                    let play_radius = 24.0;
+            // Max 300px wide, keeping the aspect ratio
+            let display_size = fitted_size(*size, 300.0);
+            let is_video = data.video_type.is_some();
 
-                                    // Semi-transparent dark circle
-                                    ui.painter().circle_filled(
-                                        center,
-                                        play_radius,
-                                        egui::Color32::from_rgba_unmultiplied(0, 0, 0, 180),
-                                    );
+            // For videos, make the thumbnail clickable with a play button overlay
+            let (rect, response) = ui.allocate_exact_size(display_size, egui::Sense::click());
 
-                                    // Play triangle
-                                    let triangle_size = 12.0;
-                                    let triangle = vec![
-                                        egui::pos2(center.x - triangle_size * 0.4, center.y - triangle_size),
-                                        egui::pos2(center.x - triangle_size * 0.4, center.y + triangle_size),
-                                        egui::pos2(center.x + triangle_size * 0.8, center.y),
-                                    ];
-                                    ui.painter().add(egui::Shape::convex_polygon(
-                                        triangle,
-                                        egui::Color32::WHITE,
-                                        egui::Stroke::NONE,
-                                    ));
-                                }
-                            }
+            if ui.is_rect_visible(rect) {
+                // Draw the thumbnail
+                ui.painter().image(
+                    texture.id(),
+                    rect,
+                    egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                    egui::Color32::WHITE,
+                );
 
-                            if response.hovered() {
-                                ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
-                            }
+                // Draw play button overlay for videos
+                if is_video {
+                    let center = rect.center();
+                    let play_radius = 24.0;
 
-                            if response.clicked() {
-                                if let Err(e) = open::that(&data.url) {
-                                    tracing::warn!("Failed to open URL: {}", e);
-                                }
-                            }
+                    // Semi-transparent dark circle
+                    ui.painter().circle_filled(
+                        center,
+                        play_radius,
+                        egui::Color32::from_rgba_unmultiplied(0, 0, 0, 180),
+                    );
 
-                            response.on_hover_text(if is_video { "Click to watch video" } else { &data.url });
-                        }
-                    }
+                    // Play triangle
+                    let triangle_size = 12.0;
+                    let triangle = vec![
+                        egui::pos2(center.x - triangle_size * 0.4, center.y - triangle_size),
+                        egui::pos2(center.x - triangle_size * 0.4, center.y + triangle_size),
+                        egui::pos2(center.x + triangle_size * 0.8, center.y),
+                    ];
+                    ui.painter().add(egui::Shape::convex_polygon(
+                        triangle,
+                        egui::Color32::WHITE,
+                        egui::Stroke::NONE,
+                    ));
+                }
+            }
 
-                    ui.add_space(8.0);
-                });
+            if response.hovered() {
+                ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+            }
+
+            if response.clicked() {
+                if let Err(e) = open::that(&data.url) {
+                    tracing::warn!("Failed to open URL: {}", e);
+                }
+            }
+
+            response.on_hover_text(if is_video { "Click to watch video" } else { &data.url });
+        }
+    });
+}
+
+/// Render a bot or webhook embed as a card in its accent color
+fn render_embed(
+    ui: &mut egui::Ui,
+    embed: &EmbedData,
+    emojis: &EmojiTextures,
+    state: &AppState,
+    network: &NetworkClient,
+    runtime: &tokio::runtime::Runtime,
+    renderer_state: &mut MessageRendererState,
+) {
+    const THUMBNAIL_SIZE: f32 = 80.0;
+
+    ui.add_space(4.0);
+
+    let thumbnail = embed
+        .thumbnail_url
+        .as_deref()
+        .and_then(|url| external_image_texture(ui, url, state, network, runtime, renderer_state));
+    let image = embed
+        .image_url
+        .as_deref()
+        .and_then(|url| external_image_texture(ui, url, state, network, runtime, renderer_state));
+    let accent = embed.color.map_or(egui::Color32::from_rgb(88, 101, 242), |color| {
+        egui::Color32::from_rgb((color >> 16) as u8, (color >> 8) as u8, color as u8)
+    });
+
+    render_card(ui, accent, |ui| {
+        ui.horizontal_top(|ui| {
+            ui.vertical(|ui| {
+                if thumbnail.is_some() {
+                    ui.set_max_width(400.0 - THUMBNAIL_SIZE - 8.0);
+                }
 
-                ui.add_space(12.0);
+                if let Some(title) = &embed.title {
+                    render_card_title(ui, title, embed.url.as_deref());
+                }
+
+                if let Some(description) = &embed.description {
+                    super::markdown::render_markdown(ui, description, emojis);
+                }
+
+                // Inline fields share a row; other fields get their own
+                let mut rows: Vec<Vec<&EmbedFieldData>> = Vec::new();
+                for field in &embed.fields {
+                    match rows.last_mut() {
+                        Some(row) if field.inline && row.last().is_some_and(|f| f.inline) => row.push(field),
+                        _ => rows.push(vec![field]),
+                    }
+                }
+                for row in rows {
+                    ui.add_space(4.0);
+                    ui.horizontal_top(|ui| {
+                        for field in row {
+                            ui.vertical(|ui| {
+                                ui.label(
+                                    egui::RichText::new(&field.name)
+                                        .size(13.0)
+                                        .strong()
+                                        .color(egui::Color32::from_rgb(220, 220, 220))
+                                );
+                                super::markdown::render_markdown(ui, &field.value, emojis);
+                            });
+                            ui.add_space(12.0);
+                        }
+                    });
+                }
             });
+
+            if let Some((texture, size)) = &thumbnail {
+                let scale = (THUMBNAIL_SIZE / size.x.max(size.y)).min(1.0);
+                ui.add(egui::Image::new((texture.id(), *size * scale)).rounding(egui::Rounding::same(4.0)));
+            }
         });
+
+        if let Some((texture, size)) = &image {
+            ui.add_space(8.0);
+            ui.add(egui::Image::new((texture.id(), fitted_size(*size, 376.0))).rounding(egui::Rounding::same(4.0)));
+        }
+
+        if let Some(footer) = &embed.footer {
+            ui.add_space(4.0);
+            ui.label(
+                egui::RichText::new(footer)
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(140, 140, 140))
+            );
+        }
+    });
 }
 
 /// Render a file attachment (image inline, audio with player, other files as download cards)
@@ -798,9 +1244,15 @@ fn render_attachment(
 ) {
     let is_image = attachment.content_type.starts_with("image/");
     let is_audio = attachment.content_type.starts_with("audio/");
+    let video_thumbnail = attachment
+        .thumbnail_url
+        .as_deref()
+        .filter(|_| attachment.content_type.starts_with("video/"));
 
     if is_image {
         render_image_attachment(ui, attachment, state, network, runtime, renderer_state);
+    } else if let Some(thumbnail_url) = video_thumbnail {
+        render_video_attachment(ui, attachment, thumbnail_url, state, network, runtime, renderer_state);
     } else if is_audio {
         render_audio_attachment(ui, attachment, state, network, runtime, renderer_state);
     } else {
@@ -808,109 +1260,158 @@ fn render_attachment(
     }
 }
 
-/// Render an image attachment inline
-fn render_image_attachment(
-    ui: &mut egui::Ui,
-    attachment: &miscord_protocol::AttachmentData,
+/// Inline images and video previews fit within this many pixels on each side, the size of
+/// the thumbnails the server makes
+const INLINE_IMAGE_SIZE: u32 = 400;
+
+/// Full-size images are shrunk to this many pixels on a side for the lightbox
+const LIGHTBOX_IMAGE_SIZE: u32 = 2048;
+
+/// Start fetching an attachment image into the image cache, unless it's cached or already on its way
+fn request_attachment_image(
+    url: &str,
+    max_size: u32,
     state: &AppState,
     network: &NetworkClient,
     runtime: &tokio::runtime::Runtime,
-    renderer_state: &mut MessageRendererState,
 ) {
-    // Use attachment URL as cache key
-    let cache_key = &attachment.url;
-
-    // Try to get cached image data
-    let cached = state.get_image_sync(cache_key);
+    if state.get_image_sync(url).is_some() || state.mark_image_pending_sync(url) != Some(true) {
+        return;
+    }
 
-    if cached.is_none() {
-        // Not cached - trigger fetch if not already pending
-        if state.mark_image_pending_sync(cache_key) == Some(true) {
-            let network = network.clone();
-            let state = state.clone();
-            let url = attachment.url.clone();
-            runtime.spawn(async move {
-                match network.fetch_attachment_image(&url).await {
-                    Ok((bytes, width, height)) => {
-                        state.set_image(url, bytes, width, height).await;
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to fetch attachment image {}: {}", url, e);
-                        state.mark_image_failed(&url).await;
-                    }
-                }
-            });
+    let network = network.clone();
+    let state = state.clone();
+    let url = url.to_string();
+    runtime.spawn(async move {
+        match network.fetch_attachment_image(&url, max_size).await {
+            Ok((bytes, width, height)) => {
+                state.set_image(url, bytes, width, height).await;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to fetch attachment image {}: {}", url, e);
+                state.mark_image_failed(&url).await;
+            }
         }
+    });
+}
+
+/// Texture for a fetched attachment image and its size, once it's in the image cache
+fn attachment_texture(
+    ctx: &egui::Context,
+    url: &str,
+    state: &AppState,
+    renderer_state: &mut MessageRendererState,
+) -> Option<egui::TextureHandle> {
+    if let Some(texture) = renderer_state.attachment_textures.get(url) {
+        return Some(texture.clone());
     }
 
-    // Render the image if we have cached data
-    if let Some(cached_img) = cached {
-        let (rgba_data, width, height) = cached_img.as_ref();
+    let cached = state.get_image_sync(url)?;
+    let (rgba_data, width, height) = cached.as_ref();
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(
+        [*width as usize, *height as usize],
+        rgba_data,
+    );
+    let handle = ctx.load_texture(
+        format!("attachment_{}", url),
+        color_image,
+        egui::TextureOptions::LINEAR,
+    );
+    renderer_state.attachment_textures.insert(url.to_string(), handle.clone());
+    Some(handle)
+}
 
-        // Get or create texture from renderer state cache
-        if !renderer_state.attachment_textures.contains_key(cache_key) {
-            let color_image = egui::ColorImage::from_rgba_unmultiplied(
-                [*width as usize, *height as usize],
-                rgba_data,
-            );
-            let handle = ui.ctx().load_texture(
-                format!("attachment_{}", attachment.id),
-                color_image,
-                egui::TextureOptions::LINEAR,
-            );
-            renderer_state.attachment_textures.insert(cache_key.clone(), handle);
-        }
+/// Texture for an attachment's blurhash placeholder
+fn blurhash_texture(
+    ctx: &egui::Context,
+    hash: &str,
+    renderer_state: &mut MessageRendererState,
+) -> Option<egui::TextureHandle> {
+    const SIZE: u32 = 32;
 
-        if let Some(texture) = renderer_state.attachment_textures.get(cache_key) {
-            // Calculate display size (max 400px wide, maintain aspect ratio)
-            let aspect = *width as f32 / *height as f32;
-            let display_width = (*width as f32).min(400.0);
-            let display_height = display_width / aspect;
+    if let Some(texture) = renderer_state.blurhash_textures.get(hash) {
+        return Some(texture.clone());
+    }
 
-            ui.add_space(4.0);
+    let rgba_data = super::blurhash::decode(hash, SIZE, SIZE)?;
+    let color_image = egui::ColorImage::from_rgba_unmultiplied([SIZE as usize, SIZE as usize], &rgba_data);
+    let handle = ctx.load_texture(
+        format!("blurhash_{}", hash),
+        color_image,
+        egui::TextureOptions::LINEAR,
+    );
+    renderer_state.blurhash_textures.insert(hash.to_string(), handle.clone());
+    Some(handle)
+}
 
-            // Make the image clickable to open in browser/viewer
-            let (rect, response) = ui.allocate_exact_size(
-                egui::vec2(display_width, display_height),
-                egui::Sense::click(),
-            );
+/// Size an image or video preview is shown at: its own size, shrunk to fit the inline box
+fn inline_display_size(width: f32, height: f32) -> egui::Vec2 {
+    let scale = (INLINE_IMAGE_SIZE as f32 / width.max(height).max(1.0)).min(1.0);
+    egui::vec2(width * scale, height * scale)
+}
 
-            if ui.is_rect_visible(rect) {
-                ui.painter().image(
-                    texture.id(),
-                    rect,
-                    egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
-                    egui::Color32::WHITE,
-                );
+/// Pixel size the server recorded for an attachment, when it could read one
+fn attachment_dimensions(attachment: &miscord_protocol::AttachmentData) -> Option<(f32, f32)> {
+    match (attachment.width, attachment.height) {
+        (Some(width), Some(height)) if width > 0 && height > 0 => Some((width as f32, height as f32)),
+        _ => None,
+    }
+}
 
-                // Show slight border on hover
-                if response.hovered() {
-                    ui.painter().rect_stroke(
-                        rect,
-                        egui::Rounding::same(4.0),
-                        egui::Stroke::new(2.0, egui::Color32::from_rgb(88, 101, 242)),
-                    );
-                    ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
-                }
-            }
+/// Paint an image or video preview, or its blurhash while the preview is still loading
+fn paint_attachment_preview(
+    ui: &egui::Ui,
+    rect: egui::Rect,
+    texture: Option<&egui::TextureHandle>,
+    attachment: &miscord_protocol::AttachmentData,
+    renderer_state: &mut MessageRendererState,
+) {
+    let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
 
-            // Click to open lightbox
-            if response.clicked() {
-                renderer_state.lightbox = Some(LightboxState {
-                    image_key: cache_key.clone(),
-                    width: *width,
-                    height: *height,
-                });
-            }
+    if let Some(texture) = texture {
+        ui.painter().image(texture.id(), rect, uv, egui::Color32::WHITE);
+        return;
+    }
 
-            // Show filename and size on hover
-            response.on_hover_text(format!(
-                "{} ({}) - Click to view full size",
-                attachment.filename,
-                format_file_size(attachment.size_bytes)
-            ));
+    let placeholder = attachment
+        .blurhash
+        .as_deref()
+        .and_then(|hash| blurhash_texture(ui.ctx(), hash, renderer_state));
+    match placeholder {
+        Some(placeholder) => {
+            ui.painter().image(placeholder.id(), rect, uv, egui::Color32::WHITE);
         }
-    } else {
+        None => {
+            ui.painter().rect_filled(rect, egui::Rounding::same(4.0), egui::Color32::from_rgb(38, 40, 46));
+        }
+    }
+}
+
+/// Render an image attachment inline
+fn render_image_attachment(
+    ui: &mut egui::Ui,
+    attachment: &miscord_protocol::AttachmentData,
+    state: &AppState,
+    network: &NetworkClient,
+    runtime: &tokio::runtime::Runtime,
+    renderer_state: &mut MessageRendererState,
+) {
+    // Large images come with a thumbnail to show inline; small ones are shown as they are
+    let (inline_url, inline_size) = match &attachment.thumbnail_url {
+        Some(thumbnail_url) => (thumbnail_url, INLINE_IMAGE_SIZE),
+        None => (&attachment.url, LIGHTBOX_IMAGE_SIZE),
+    };
+
+    request_attachment_image(inline_url, inline_size, state, network, runtime);
+    let texture = attachment_texture(ui.ctx(), inline_url, state, renderer_state);
+
+    // With the size known up front, the placeholder takes the image's space and nothing
+    // jumps when it loads
+    let size = attachment_dimensions(attachment).or_else(|| {
+        texture.as_ref().map(|t| (t.size()[0] as f32, t.size()[1] as f32))
+    });
+
+    let Some((width, height)) = size else {
         // Show loading placeholder
         ui.add_space(4.0);
         egui::Frame::none()
@@ -926,7 +1427,130 @@ fn render_image_attachment(
                     );
                 });
             });
+        return;
+    };
+
+    ui.add_space(4.0);
+
+    let (rect, response) = ui.allocate_exact_size(inline_display_size(width, height), egui::Sense::click());
+
+    if ui.is_rect_visible(rect) {
+        paint_attachment_preview(ui, rect, texture.as_ref(), attachment, renderer_state);
+
+        // Show slight border on hover
+        if texture.is_some() && response.hovered() {
+            ui.painter().rect_stroke(
+                rect,
+                egui::Rounding::same(4.0),
+                egui::Stroke::new(2.0, egui::Color32::from_rgb(88, 101, 242)),
+            );
+            ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+        }
+    }
+
+    // Click to open lightbox, which loads the full image when only the thumbnail is shown
+    if texture.is_some() && response.clicked() {
+        let full_image_key = attachment.thumbnail_url.is_some().then(|| attachment.url.clone());
+        if let Some(full_url) = &full_image_key {
+            request_attachment_image(full_url, LIGHTBOX_IMAGE_SIZE, state, network, runtime);
+        }
+
+        renderer_state.lightbox = Some(LightboxState {
+            image_key: inline_url.clone(),
+            full_image_key,
+            width: width as u32,
+            height: height as u32,
+        });
+    }
+
+    // Show filename and size on hover
+    response.on_hover_text(format!(
+        "{} ({}) - Click to view full size",
+        attachment.filename,
+        format_file_size(attachment.size_bytes)
+    ));
+}
+
+/// Render a video attachment as its first frame, which opens the video when clicked,
+/// above the usual download card
+fn render_video_attachment(
+    ui: &mut egui::Ui,
+    attachment: &miscord_protocol::AttachmentData,
+    thumbnail_url: &str,
+    state: &AppState,
+    network: &NetworkClient,
+    runtime: &tokio::runtime::Runtime,
+    renderer_state: &mut MessageRendererState,
+) {
+    request_attachment_image(thumbnail_url, INLINE_IMAGE_SIZE, state, network, runtime);
+    let texture = attachment_texture(ui.ctx(), thumbnail_url, state, renderer_state);
+
+    let size = attachment_dimensions(attachment).or_else(|| {
+        texture.as_ref().map(|t| (t.size()[0] as f32, t.size()[1] as f32))
+    });
+
+    if let Some((width, height)) = size {
+        ui.add_space(4.0);
+
+        let (rect, response) = ui.allocate_exact_size(inline_display_size(width, height), egui::Sense::click());
+
+        if ui.is_rect_visible(rect) {
+            paint_attachment_preview(ui, rect, texture.as_ref(), attachment, renderer_state);
+
+            // Play button
+            let button_fill = if response.hovered() {
+                egui::Color32::from_rgb(88, 101, 242)
+            } else {
+                egui::Color32::from_rgba_unmultiplied(0, 0, 0, 160)
+            };
+            ui.painter().circle_filled(rect.center(), 24.0, button_fill);
+            ui.painter().text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "▶",
+                egui::FontId::proportional(22.0),
+                egui::Color32::WHITE,
+            );
+
+            if let Some(duration) = attachment.duration_secs {
+                let galley = ui.painter().layout_no_wrap(
+                    format_duration((duration * 1000.0) as u64),
+                    egui::FontId::proportional(12.0),
+                    egui::Color32::WHITE,
+                );
+                let label_rect = egui::Rect::from_min_size(
+                    rect.right_bottom() - galley.size() - egui::vec2(14.0, 10.0),
+                    galley.size() + egui::vec2(8.0, 4.0),
+                );
+                ui.painter().rect_filled(
+                    label_rect,
+                    egui::Rounding::same(4.0),
+                    egui::Color32::from_rgba_unmultiplied(0, 0, 0, 180),
+                );
+                ui.painter().galley(label_rect.min + egui::vec2(4.0, 2.0), galley, egui::Color32::WHITE);
+            }
+
+            if response.hovered() {
+                ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+            }
+        }
+
+        // Play it in the system's video player, via the browser
+        if response.clicked() {
+            let network = network.clone();
+            let url = attachment.url.clone();
+            runtime.spawn(async move {
+                let full_url = format!("{}{}", network.get_base_url().await, url);
+                if let Err(e) = open::that(&full_url) {
+                    tracing::warn!("Failed to open video {}: {}", full_url, e);
+                }
+            });
+        }
+
+        response.on_hover_text(format!("{} - Click to play", attachment.filename));
     }
+
+    render_file_attachment(ui, attachment, network, runtime, renderer_state);
 }
 
 /// Render an audio attachment with inline player
@@ -1354,6 +1978,7 @@ async fn download_attachment_with_progress(
 /// This should be called after rendering all other UI elements
 pub fn render_lightbox(
     ctx: &egui::Context,
+    state: &AppState,
     renderer_state: &mut MessageRendererState,
 ) {
     // Check if lightbox should be shown
@@ -1362,11 +1987,17 @@ pub fn render_lightbox(
         None => return,
     };
 
-    let LightboxState { image_key, width, height } = lightbox_data;
+    let LightboxState { image_key, full_image_key, width, height } = lightbox_data;
 
-    // Get the texture
-    let texture = match renderer_state.attachment_textures.get(&image_key) {
-        Some(t) => t.clone(),
+    // Get the texture, the full image's once it has loaded
+    let full_texture = full_image_key
+        .as_deref()
+        .and_then(|key| attachment_texture(ctx, key, state, renderer_state));
+    if full_image_key.is_some() && full_texture.is_none() {
+        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+    }
+    let texture = match full_texture.or_else(|| renderer_state.attachment_textures.get(&image_key).cloned()) {
+        Some(t) => t,
         None => {
             renderer_state.lightbox = None;
             return;
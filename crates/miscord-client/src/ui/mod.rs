@@ -2,10 +2,13 @@ mod app;
 mod login;
 mod main_view;
 mod chat;
+mod forum;
 mod community_list;
 mod channel_list;
 mod member_list;
 mod message;
+mod blurhash;
+mod commands;
 mod voice;
 mod voice_channel_view;
 mod markdown;
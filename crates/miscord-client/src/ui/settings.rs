@@ -9,7 +9,10 @@ use tokio::sync::mpsc;
 
 use crate::media::audio::{list_input_devices, list_output_devices, AudioCapture, AudioPlayback, linear_to_db};
 use crate::media::gst_video::{GstVideoCapture, VideoDeviceInfo};
+use crate::network::NetworkClient;
 use crate::state::{AppState, PersistentSettings};
+use crate::ui::message::format_file_size;
+use miscord_protocol::StorageUsageData;
 
 /// The settings view component
 pub struct SettingsView {
@@ -30,12 +33,18 @@ pub struct SettingsView {
     video_devices: Vec<VideoDeviceInfo>,
     // Error message
     error_message: Option<String>,
+    // Whether privacy settings have been requested from the server
+    privacy_requested: bool,
+    // Whether storage usage has been requested from the server
+    storage_requested: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum SettingsSection {
     Audio,
     Video,
+    Privacy,
+    Storage,
     // Future sections
     // Appearance,
     // Notifications,
@@ -57,6 +66,8 @@ impl SettingsView {
             video_texture: None,
             video_devices: Vec::new(),
             error_message: None,
+            privacy_requested: false,
+            storage_requested: false,
         }
     }
 
@@ -233,6 +244,7 @@ impl SettingsView {
         &mut self,
         ctx: &egui::Context,
         state: &AppState,
+        network: &NetworkClient,
         runtime: &tokio::runtime::Runtime,
     ) -> bool {
         let mut close_requested = false;
@@ -330,6 +342,36 @@ impl SettingsView {
                         self.current_section = SettingsSection::Video;
                     }
 
+                    // Privacy section
+                    let privacy_selected = self.current_section == SettingsSection::Privacy;
+                    let privacy_text = if privacy_selected {
+                        RichText::new("Privacy").strong()
+                    } else {
+                        RichText::new("Privacy")
+                    };
+
+                    if ui
+                        .selectable_label(privacy_selected, privacy_text)
+                        .clicked()
+                    {
+                        self.current_section = SettingsSection::Privacy;
+                    }
+
+                    // Storage section
+                    let storage_selected = self.current_section == SettingsSection::Storage;
+                    let storage_text = if storage_selected {
+                        RichText::new("Storage").strong()
+                    } else {
+                        RichText::new("Storage")
+                    };
+
+                    if ui
+                        .selectable_label(storage_selected, storage_text)
+                        .clicked()
+                    {
+                        self.current_section = SettingsSection::Storage;
+                    }
+
                     // Future sections can be added here
                     // ui.selectable_label(false, "Appearance");
                 });
@@ -347,6 +389,12 @@ impl SettingsView {
                         SettingsSection::Video => {
                             self.show_video_settings(ui, state, runtime);
                         }
+                        SettingsSection::Privacy => {
+                            self.show_privacy_settings(ui, state, network, runtime);
+                        }
+                        SettingsSection::Storage => {
+                            self.show_storage_settings(ui, state, network, runtime);
+                        }
                     }
                 });
             });
@@ -357,6 +405,15 @@ impl SettingsView {
             self.stop_audio_test();
             self.stop_video_test();
             self.save_settings(state, runtime);
+            // Retry loading privacy settings next time if the last request failed
+            self.privacy_requested = false;
+            // Usage changes with every upload, so load it afresh next time
+            self.storage_requested = false;
+            runtime.block_on(async {
+                let mut state = state.write().await;
+                state.storage_usage = None;
+                state.community_storage_usage.clear();
+            });
         }
 
         close_requested
@@ -836,6 +893,172 @@ impl SettingsView {
                 .small(),
         );
     }
+
+    /// Render privacy settings section (stored on the server)
+    fn show_privacy_settings(
+        &mut self,
+        ui: &mut Ui,
+        state: &AppState,
+        network: &NetworkClient,
+        runtime: &tokio::runtime::Runtime,
+    ) {
+        ui.heading("Privacy Settings");
+        ui.add_space(16.0);
+
+        let privacy = runtime.block_on(async { state.read().await.privacy_settings.clone() });
+
+        let Some(mut privacy) = privacy else {
+            if !self.privacy_requested {
+                self.privacy_requested = true;
+                let state = state.clone();
+                let network = network.clone();
+                runtime.spawn(async move {
+                    match network.get_privacy_settings().await {
+                        Ok(settings) => state.write().await.privacy_settings = Some(settings),
+                        Err(e) => tracing::warn!("Failed to load privacy settings: {}", e),
+                    }
+                });
+            }
+            ui.spinner();
+            return;
+        };
+
+        ui.label(RichText::new("Direct Messages").strong());
+        ui.add_space(4.0);
+
+        if ui
+            .checkbox(&mut privacy.read_receipts_enabled, "Send read receipts")
+            .changed()
+        {
+            // Apply locally right away, then sync with the server. Receipts are
+            // reciprocal, so turning them off hides the ones already fetched.
+            runtime.block_on(async {
+                let mut s = state.write().await;
+                s.privacy_settings = Some(privacy.clone());
+                if !privacy.read_receipts_enabled {
+                    s.read_receipts.clear();
+                }
+            });
+            let state = state.clone();
+            let network = network.clone();
+            runtime.spawn(async move {
+                match network.update_privacy_settings(&privacy).await {
+                    Ok(settings) => state.write().await.privacy_settings = Some(settings),
+                    Err(e) => tracing::warn!("Failed to update privacy settings: {}", e),
+                }
+            });
+        }
+
+        ui.add_space(4.0);
+        ui.label(
+            RichText::new(
+                "When off, people you message won't see when you've read their messages, \
+                 and you won't see when they've read yours.",
+            )
+                .weak()
+                .small(),
+        );
+    }
+
+    /// Render storage usage section (counted on the server)
+    fn show_storage_settings(
+        &mut self,
+        ui: &mut Ui,
+        state: &AppState,
+        network: &NetworkClient,
+        runtime: &tokio::runtime::Runtime,
+    ) {
+        ui.heading("Storage");
+        ui.add_space(16.0);
+
+        let (usage, community, community_usage) = runtime.block_on(async {
+            let state = state.read().await;
+            let community = state
+                .current_community_id
+                .and_then(|id| state.communities.get(&id))
+                .map(|c| (c.id, c.name.clone()));
+            let community_usage = community
+                .as_ref()
+                .and_then(|(id, _)| state.community_storage_usage.get(id).cloned());
+            (state.storage_usage.clone(), community, community_usage)
+        });
+
+        if !self.storage_requested {
+            self.storage_requested = true;
+            let state = state.clone();
+            let network = network.clone();
+            let community_id = community.as_ref().map(|(id, _)| *id);
+            runtime.spawn(async move {
+                match network.get_storage_usage().await {
+                    Ok(usage) => state.write().await.storage_usage = Some(usage),
+                    Err(e) => tracing::warn!("Failed to load storage usage: {}", e),
+                }
+                if let Some(community_id) = community_id {
+                    match network.get_community_storage_usage(community_id).await {
+                        Ok(usage) => {
+                            state.write().await.community_storage_usage.insert(community_id, usage);
+                        }
+                        Err(e) => tracing::warn!("Failed to load community storage usage: {}", e),
+                    }
+                }
+            });
+        }
+
+        ui.label(RichText::new("Your Files").strong());
+        ui.add_space(4.0);
+        match usage {
+            Some(usage) => show_storage_usage(ui, &usage),
+            None => {
+                ui.spinner();
+            }
+        }
+
+        if let Some((_, name)) = community {
+            ui.add_space(16.0);
+            ui.label(RichText::new(name).strong());
+            ui.add_space(4.0);
+            match community_usage {
+                Some(usage) => show_storage_usage(ui, &usage),
+                None => {
+                    ui.spinner();
+                }
+            }
+        }
+
+        ui.add_space(8.0);
+        ui.label(
+            RichText::new("Files you forward to other channels aren't counted again.")
+                .weak()
+                .small(),
+        );
+    }
+}
+
+/// Show how much of a quota is used, as a bar when there is a quota
+fn show_storage_usage(ui: &mut Ui, usage: &StorageUsageData) {
+    match usage.quota_bytes {
+        Some(quota) if quota > 0 => {
+            let fraction = (usage.used_bytes as f32 / quota as f32).clamp(0.0, 1.0);
+            let color = if fraction > 0.9 {
+                Color32::from_rgb(240, 71, 71)
+            } else {
+                Color32::from_rgb(88, 101, 242)
+            };
+            ui.add(
+                egui::ProgressBar::new(fraction)
+                    .desired_width(300.0)
+                    .fill(color),
+            );
+            ui.label(format!(
+                "{} of {} used",
+                format_file_size(usage.used_bytes),
+                format_file_size(quota)
+            ));
+        }
+        _ => {
+            ui.label(format!("{} used (no limit)", format_file_size(usage.used_bytes)));
+        }
+    }
 }
 
 impl Default for SettingsView {
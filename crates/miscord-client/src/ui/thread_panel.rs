@@ -1,10 +1,12 @@
 use eframe::egui;
+use miscord_protocol::ThreadSummaryData;
 use std::collections::HashMap;
 use std::time::Instant;
 use uuid::Uuid;
 
 use crate::network::NetworkClient;
 use crate::state::AppState;
+use super::theme;
 
 use super::message::{
     render_message, MessageAction, MessageRenderOptions, MessageRendererState, ReactionInfo,
@@ -22,6 +24,8 @@ pub struct ThreadPanel {
     subscribed_thread: Option<Uuid>,
     /// Shared message renderer state
     renderer_state: MessageRendererState,
+    /// Number of replies when the thread was last marked read
+    read_reply_count: Option<usize>,
 }
 
 impl ThreadPanel {
@@ -33,6 +37,7 @@ impl ThreadPanel {
             is_loading: false,
             subscribed_thread: None,
             renderer_state: MessageRendererState::new(),
+            read_reply_count: None,
         }
     }
 
@@ -47,7 +52,7 @@ impl ThreadPanel {
         let mut should_close = false;
 
         // Get thread state
-        let (open_thread, thread_messages, parent_message, current_user_id, message_reactions) =
+        let (open_thread, thread_messages, parent_message, post_title, current_user_id, message_reactions) =
             runtime.block_on(async {
                 let s = state.read().await;
                 let open_thread = s.open_thread;
@@ -55,15 +60,25 @@ impl ThreadPanel {
                     .map(|id| s.thread_messages.get(&id).cloned().unwrap_or_default())
                     .unwrap_or_default();
 
-                // Find parent message from channel messages
+                // Find parent message from channel messages, or the one loaded with the thread
                 let parent_message = open_thread.and_then(|parent_id| {
                     s.messages
                         .values()
                         .flatten()
                         .find(|m| m.id == parent_id)
+                        .or_else(|| s.thread_parents.get(&parent_id))
                         .cloned()
                 });
 
+                // Forum posts show their title instead of "Thread"
+                let post_title = open_thread.and_then(|parent_id| {
+                    s.forum_posts
+                        .values()
+                        .flatten()
+                        .find(|p| p.message_id == parent_id)
+                        .map(|p| p.title.clone())
+                });
+
                 let current_user_id = s.current_user.as_ref().map(|u| u.id);
 
                 // Get reactions for all messages (parent + thread replies)
@@ -94,7 +109,7 @@ impl ThreadPanel {
                     })
                     .collect();
 
-                (open_thread, thread_messages, parent_message, current_user_id, message_reactions)
+                (open_thread, thread_messages, parent_message, post_title, current_user_id, message_reactions)
             });
 
         let Some(parent_message_id) = open_thread else {
@@ -113,6 +128,7 @@ impl ThreadPanel {
 
             // Subscribe to new thread and load messages
             self.subscribed_thread = Some(parent_message_id);
+            self.read_reply_count = None;
             self.is_loading = true;
             let network = network.clone();
             let state = state.clone();
@@ -122,6 +138,7 @@ impl ThreadPanel {
                 // Load thread data
                 match network.get_thread(parent_message_id).await {
                     Ok(thread_data) => {
+                        state.set_thread_parent(thread_data.parent_message).await;
                         state
                             .set_thread_messages(parent_message_id, thread_data.replies)
                             .await;
@@ -134,15 +151,82 @@ impl ThreadPanel {
             self.is_loading = false;
         }
 
-        // Header with close button
+        // Mark the thread read when it's opened and as new replies arrive while it's visible
+        if self.read_reply_count != Some(thread_messages.len()) && ui.ctx().input(|i| i.focused) {
+            self.read_reply_count = Some(thread_messages.len());
+            let network = network.clone();
+            let state = state.clone();
+            runtime.spawn(async move {
+                state.mark_thread_read_local(parent_message_id).await;
+                if let Err(e) = network.mark_thread_read(parent_message_id).await {
+                    tracing::warn!("Failed to mark thread read: {}", e);
+                }
+            });
+        }
+
+        let following = state.is_following_thread_sync(parent_message_id);
+        let other_threads: Vec<_> = runtime.block_on(async {
+            let s = state.read().await;
+            s.followed_threads
+                .iter()
+                .filter(|t| t.parent_message_id != parent_message_id)
+                .filter(|t| {
+                    s.channels
+                        .get(&t.channel_id)
+                        .is_some_and(|c| c.community_id == s.current_community_id)
+                })
+                .cloned()
+                .collect()
+        });
+        let unread_elsewhere: i64 = other_threads.iter().map(|t| t.unread_count).sum();
+
+        // Header with follow toggle, followed threads and close button
         egui::TopBottomPanel::top("thread_header")
             .show_inside(ui, |ui| {
                 ui.horizontal(|ui| {
-                    ui.heading("Thread");
+                    ui.heading(post_title.as_deref().unwrap_or("Thread"));
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         if ui.button("✕").clicked() {
                             should_close = true;
                         }
+
+                        let follow_label = if following { "Following" } else { "Follow" };
+                        let follow_hover = if following {
+                            "Stop getting notified about new replies"
+                        } else {
+                            "Get notified about new replies"
+                        };
+                        if ui.button(follow_label).on_hover_text(follow_hover).clicked() {
+                            let network = network.clone();
+                            let state = state.clone();
+                            runtime.spawn(async move {
+                                let result = if following {
+                                    network.unfollow_thread(parent_message_id).await
+                                } else {
+                                    network.follow_thread(parent_message_id).await
+                                };
+                                match result {
+                                    Ok(()) => state.set_thread_following(parent_message_id, !following).await,
+                                    Err(e) => tracing::error!("Failed to update thread follow: {}", e),
+                                }
+                            });
+                        }
+
+                        if !other_threads.is_empty() {
+                            let threads_label = if unread_elsewhere > 0 {
+                                egui::RichText::new(format!("Threads ({})", unread_elsewhere)).color(theme::TEXT_BRIGHT)
+                            } else {
+                                egui::RichText::new("Threads")
+                            };
+                            ui.menu_button(threads_label, |ui| {
+                                for thread in &other_threads {
+                                    if followed_thread_button(ui, thread).clicked() {
+                                        open_followed_thread(state, network, runtime, thread.channel_id, thread.parent_message_id);
+                                        ui.close_menu();
+                                    }
+                                }
+                            });
+                        }
                     });
                 });
             });
@@ -193,6 +277,7 @@ impl ThreadPanel {
                             show_thread_button: false,
                             show_thread_indicator: false,
                             show_reply_button: false, // No inline reply in threads
+                            show_publish_button: false,
                             id_prefix: "thread_parent",
                         };
 
@@ -201,6 +286,7 @@ impl ThreadPanel {
                             show_thread_button: false,
                             show_thread_indicator: false,
                             show_reply_button: false,
+                            show_publish_button: false,
                             id_prefix: "thread_reply",
                         };
 
@@ -301,6 +387,73 @@ impl ThreadPanel {
     }
 }
 
+/// Render a followed thread entry with its unread count. Used by the thread
+/// panel's threads menu and the channel list.
+pub fn followed_thread_button(ui: &mut egui::Ui, thread: &ThreadSummaryData) -> egui::Response {
+    let title: String = thread.content.lines().next().unwrap_or_default().chars().take(40).collect();
+    let title = if title.is_empty() { "Thread".to_string() } else { title };
+    let has_unread = thread.unread_count > 0;
+
+    let response = ui.add(
+        egui::Button::new(
+            egui::RichText::new(format!("🧵 {}", title))
+                .size(13.0)
+                .color(if has_unread { theme::TEXT_BRIGHT } else { theme::TEXT_MUTED }),
+        )
+        .fill(egui::Color32::TRANSPARENT)
+        .rounding(egui::Rounding::same(4.0))
+        .min_size(egui::vec2(ui.available_width().max(160.0), 26.0)),
+    );
+
+    if has_unread {
+        let badge_center = egui::pos2(response.rect.right() - 14.0, response.rect.center().y);
+        let badge_text = if thread.unread_count > 99 {
+            "99+".to_string()
+        } else {
+            thread.unread_count.to_string()
+        };
+        ui.painter().circle_filled(badge_center, 9.0, theme::RED);
+        ui.painter().text(
+            badge_center,
+            egui::Align2::CENTER_CENTER,
+            &badge_text,
+            egui::FontId::proportional(10.0),
+            egui::Color32::WHITE,
+        );
+    }
+
+    response.on_hover_text(format!(
+        "#{} · started by {} · {} {}",
+        thread.channel_name,
+        thread.author_name,
+        thread.reply_count,
+        if thread.reply_count == 1 { "reply" } else { "replies" }
+    ))
+}
+
+/// Switch to a thread's channel, jump to its parent message and open the thread panel
+pub fn open_followed_thread(
+    state: &AppState,
+    network: &NetworkClient,
+    runtime: &tokio::runtime::Runtime,
+    channel_id: Uuid,
+    parent_message_id: Uuid,
+) {
+    let state = state.clone();
+    let network = network.clone();
+    runtime.spawn(async move {
+        let current_channel = state.read().await.current_channel_id;
+        if current_channel != Some(channel_id) {
+            state.select_channel(channel_id).await;
+            network.subscribe_channel(channel_id).await;
+        }
+
+        // The chat view loads history around the parent if it isn't loaded yet
+        state.write().await.scroll_to_message_id = Some(parent_message_id);
+        state.open_thread(parent_message_id).await;
+    });
+}
+
 impl Default for ThreadPanel {
     fn default() -> Self {
         Self::new()
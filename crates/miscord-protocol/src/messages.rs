@@ -2,7 +2,10 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::types::{MessageData, VoiceStateData};
+use crate::types::{
+    ChannelData, ChannelPositionData, CommunityData, ExportData, ForumPostData, ForumTagData,
+    InteractionData, MessageData, SlashCommandData, UserData, VoiceStateData,
+};
 
 /// Type of video track for SFU
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -221,4 +224,84 @@ pub enum ServerMessage {
         message_id: Uuid,
         channel_id: Uuid,
     },
+
+    /// Thread: A followed thread got a new reply (sent to followers)
+    FollowedThreadUpdated {
+        parent_message_id: Uuid,
+        channel_id: Uuid,
+        reply_count: i32,
+        last_reply_at: Option<DateTime<Utc>>,
+    },
+
+    /// Thread: Archived after a period of inactivity
+    ThreadArchived {
+        parent_message_id: Uuid,
+        channel_id: Uuid,
+    },
+
+    /// A channel was created in a community (sent to community members)
+    ChannelCreated { channel: ChannelData },
+
+    /// A channel's name or topic changed (sent to community members)
+    ChannelUpdated { channel: ChannelData },
+
+    /// A channel was deleted (sent to community members)
+    ChannelDeleted { channel_id: Uuid, community_id: Uuid },
+
+    /// A community's name, description or icon changed
+    CommunityUpdated { community: CommunityData },
+
+    /// A community was deleted
+    CommunityDeleted { community_id: Uuid },
+
+    /// A user joined a community
+    MemberJoined { community_id: Uuid, user: UserData },
+
+    /// A user left or was removed from a community
+    MemberLeft { community_id: Uuid, user_id: Uuid },
+
+    /// Channels in a community were reordered or moved between categories
+    ChannelsReordered {
+        community_id: Uuid,
+        channels: Vec<ChannelPositionData>,
+    },
+
+    /// An announcement was published to the channels following its channel
+    MessagePublished {
+        message_id: Uuid,
+        channel_id: Uuid,
+        published_at: DateTime<Utc>,
+    },
+
+    /// Forum: A post was created
+    ForumPostCreated { post: ForumPostData },
+
+    /// Forum: A post's tags changed. Reply counts arrive as ThreadMetadataUpdated.
+    ForumPostUpdated { post: ForumPostData },
+
+    /// Forum: The tags available in a forum changed
+    ForumTagsUpdated {
+        channel_id: Uuid,
+        tags: Vec<ForumTagData>,
+    },
+
+    /// A user's read position in a channel changed.
+    /// Sent to the user's other sessions, and to DM participants when receipts are shared.
+    ReadStateUpdated {
+        channel_id: Uuid,
+        user_id: Uuid,
+        last_read_message_id: Option<Uuid>,
+    },
+
+    /// An export requested by this user made progress, finished or failed
+    ExportUpdated { export: ExportData },
+
+    /// The slash commands available in a community changed
+    CommandsUpdated {
+        community_id: Uuid,
+        commands: Vec<SlashCommandData>,
+    },
+
+    /// A command or button of this user's application was used (sent to the application)
+    InteractionCreated { interaction: InteractionData },
 }
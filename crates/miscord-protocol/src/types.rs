@@ -11,6 +11,9 @@ pub struct UserData {
     pub avatar_url: Option<String>,
     pub status: UserStatus,
     pub custom_status: Option<String>,
+    /// Bot account, managed by a person and authenticated with a bot token
+    #[serde(default)]
+    pub bot: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -44,6 +47,13 @@ pub struct ChannelData {
     pub topic: Option<String>,
     pub channel_type: ChannelType,
     pub position: i32,
+    /// Category this channel is nested under
+    #[serde(default)]
+    pub parent_id: Option<Uuid>,
+    /// Messages older than this many seconds are purged
+    /// (the disappearing message timer in DMs)
+    #[serde(default)]
+    pub retention_seconds: Option<i32>,
     /// Number of unread messages in this channel (for the current user)
     #[serde(default)]
     pub unread_count: i64,
@@ -56,6 +66,17 @@ pub enum ChannelType {
     Voice,
     DirectMessage,
     GroupDm,
+    Category,
+    Forum,
+    Announcement,
+}
+
+/// Position and category of a channel, used for bulk reordering
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChannelPositionData {
+    pub id: Uuid,
+    pub position: i32,
+    pub parent_id: Option<Uuid>,
 }
 
 /// Message data
@@ -81,6 +102,303 @@ pub struct MessageData {
     // Pinned message fields
     pub pinned_at: Option<DateTime<Utc>>,
     pub pinned_by: Option<String>,
+    // Set when this message was forwarded from another channel
+    pub forwarded_from: Option<ForwardedFromData>,
+    // Set when this announcement was published to following channels
+    #[serde(default)]
+    pub published_at: Option<DateTime<Utc>>,
+    // Set when this message was posted through an incoming webhook
+    #[serde(default)]
+    pub webhook: Option<WebhookAuthorData>,
+    // Rich cards posted by bots and webhooks
+    #[serde(default)]
+    pub embeds: Vec<EmbedData>,
+    // Buttons a bot attached to this message
+    #[serde(default)]
+    pub components: Vec<ButtonData>,
+    // Interaction response only the invoking user can see; never stored
+    #[serde(default)]
+    pub ephemeral: bool,
+}
+
+/// A rich card shown below a message's text
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct EmbedData {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Opened when the title is clicked
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Accent color as 0xRRGGBB
+    #[serde(default)]
+    pub color: Option<u32>,
+    #[serde(default)]
+    pub fields: Vec<EmbedFieldData>,
+    /// Small image shown to the right of the text
+    #[serde(default)]
+    pub thumbnail_url: Option<String>,
+    /// Large image shown below the text
+    #[serde(default)]
+    pub image_url: Option<String>,
+    #[serde(default)]
+    pub footer: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EmbedFieldData {
+    pub name: String,
+    pub value: String,
+    /// Shown side by side with neighbouring inline fields
+    #[serde(default)]
+    pub inline: bool,
+}
+
+/// Reference to the original message of a forwarded message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForwardedFromData {
+    /// Original message (None if it has since been deleted)
+    pub message_id: Option<Uuid>,
+    pub channel_id: Option<Uuid>,
+    pub channel_name: Option<String>,
+    pub author_id: Option<Uuid>,
+    pub author_name: String,
+    pub created_at: DateTime<Utc>,
+    /// Community of the original channel, when it differs from this message's
+    #[serde(default)]
+    pub community_name: Option<String>,
+    /// True for copies of a published announcement
+    #[serde(default)]
+    pub published: bool,
+}
+
+/// The name and avatar a webhook message was posted with.
+/// `MessageData::author_name` carries the same name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookAuthorData {
+    /// None once the webhook has been deleted
+    pub webhook_id: Option<Uuid>,
+    pub username: String,
+    pub avatar_url: Option<String>,
+}
+
+/// An incoming webhook, including the secret token of its URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookData {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub name: String,
+    pub avatar_url: Option<String>,
+    pub token: String,
+    /// Path to POST messages to, relative to the server URL
+    pub url: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An outgoing webhook that receives a community's events, including its signing secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventWebhookData {
+    pub id: Uuid,
+    pub community_id: Uuid,
+    pub url: String,
+    /// Event types delivered, named like `ServerMessage` variants (e.g. "message_created")
+    pub events: Vec<String>,
+    /// Key of the HMAC-SHA256 signature sent with each delivery
+    pub secret: String,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+/// An event sent to an outgoing webhook, as shown in its delivery log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDeliveryData {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: String,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    /// When the next retry is due, while pending
+    pub next_attempt_at: DateTime<Utc>,
+    /// HTTP status of the last attempt, if the endpoint answered
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// A slash command registered in a community by a bot or integration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlashCommandData {
+    pub id: Uuid,
+    pub community_id: Uuid,
+    /// The user that registered the command and receives its interactions
+    pub application_id: Uuid,
+    pub application_name: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub options: Vec<CommandOptionData>,
+}
+
+/// A typed option of a slash command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandOptionData {
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "type")]
+    pub kind: CommandOptionType,
+    #[serde(default)]
+    pub required: bool,
+    /// Allowed values of a string option; empty allows any value
+    #[serde(default)]
+    pub choices: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandOptionType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    /// A user ID
+    User,
+    /// A channel ID
+    Channel,
+}
+
+/// The value given for a command option
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractionOptionData {
+    pub name: String,
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InteractionKind {
+    /// A slash command was invoked
+    Command,
+    /// A button was clicked
+    Component,
+}
+
+/// A command invocation or button click, delivered to the application that owns it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractionData {
+    pub id: Uuid,
+    pub kind: InteractionKind,
+    pub application_id: Uuid,
+    pub community_id: Option<Uuid>,
+    pub channel_id: Uuid,
+    /// The user who invoked the command or clicked the button
+    pub user: UserData,
+    /// Set for commands
+    pub command_name: Option<String>,
+    #[serde(default)]
+    pub options: Vec<InteractionOptionData>,
+    /// Set for button clicks: the message holding the button
+    pub message_id: Option<Uuid>,
+    /// Set for button clicks
+    pub custom_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ButtonStyle {
+    Primary,
+    #[default]
+    Secondary,
+    Success,
+    Danger,
+    /// Opens `url` instead of sending an interaction
+    Link,
+}
+
+/// A button attached to a message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ButtonData {
+    pub label: String,
+    #[serde(default)]
+    pub style: ButtonStyle,
+    /// Sent back to the bot when the button is clicked (all styles but link)
+    #[serde(default)]
+    pub custom_id: Option<String>,
+    /// Set for link buttons
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+/// An announcement channel that a channel follows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelFollowData {
+    pub source_channel_id: Uuid,
+    pub source_channel_name: String,
+    pub source_community_id: Uuid,
+    pub source_community_name: String,
+    pub target_channel_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Output format of a channel export
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Json,
+    Html,
+    Markdown,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// A channel or thread export and its progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportData {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub thread_parent_id: Option<Uuid>,
+    pub format: ExportFormat,
+    pub status: ExportStatus,
+    pub exported_messages: i32,
+    pub total_messages: i32,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// How far a user has read in a channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadStateData {
+    pub channel_id: Uuid,
+    pub user_id: Uuid,
+    pub last_read_message_id: Option<Uuid>,
+    pub last_read_at: DateTime<Utc>,
+}
+
+/// Per-user privacy settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivacySettingsData {
+    /// Share "seen by" receipts in DMs and group DMs, and see other people's
+    pub read_receipts_enabled: bool,
 }
 
 /// Thread data with parent message and replies
@@ -91,6 +409,23 @@ pub struct ThreadData {
     pub total_reply_count: i32,
 }
 
+/// Thread as shown in thread lists, with the viewer's follow and read state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadSummaryData {
+    pub parent_message_id: Uuid,
+    pub channel_id: Uuid,
+    pub channel_name: String,
+    pub author_id: Uuid,
+    pub author_name: String,
+    pub content: String,
+    pub reply_count: i32,
+    pub last_reply_at: Option<DateTime<Utc>>,
+    pub archived: bool,
+    pub following: bool,
+    /// Replies since the viewer last read the thread
+    pub unread_count: i64,
+}
+
 /// Voice state data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoiceStateData {
@@ -118,6 +453,104 @@ pub struct AttachmentData {
     pub content_type: String,
     pub size_bytes: i64,
     pub url: String,
+    /// Pixel size of an image or video, so it can be laid out before it loads
+    #[serde(default)]
+    pub width: Option<i32>,
+    #[serde(default)]
+    pub height: Option<i32>,
+    /// Length of a video or audio file
+    #[serde(default)]
+    pub duration_secs: Option<f64>,
+    /// Blurred placeholder to show while the image loads (see blurha.sh)
+    #[serde(default)]
+    pub blurhash: Option<String>,
+    /// Downscaled preview of an image or a video's first frame
+    #[serde(default)]
+    pub thumbnail_url: Option<String>,
+}
+
+/// A file being uploaded in chunks. Chunks are sent in order starting at
+/// `received_bytes`, each `chunk_size` long except the last.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadSessionData {
+    pub id: Uuid,
+    pub filename: String,
+    pub size_bytes: i64,
+    pub chunk_size: i64,
+    pub received_bytes: i64,
+}
+
+/// How much attachment storage a user or community is using
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageUsageData {
+    pub used_bytes: i64,
+    /// None when there's no quota
+    #[serde(default)]
+    pub quota_bytes: Option<i64>,
+}
+
+/// Tag that moderators define on a forum channel
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ForumTagData {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub name: String,
+    pub position: i32,
+}
+
+/// A post in a forum channel. The post is the parent message of a thread;
+/// replies go through the regular thread endpoints.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForumPostData {
+    pub message_id: Uuid,
+    pub channel_id: Uuid,
+    pub title: String,
+    pub author_id: Uuid,
+    pub author_name: String,
+    pub content: String,
+    pub tag_ids: Vec<Uuid>,
+    pub reply_count: i32,
+    pub last_reply_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ForumPostData {
+    /// Time of the last activity, used to sort the post list
+    pub fn last_activity_at(&self) -> DateTime<Utc> {
+        self.last_reply_at.unwrap_or(self.created_at)
+    }
+}
+
+/// Custom emoji uploaded to a community, referenced as `<:name:id>`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomEmojiData {
+    pub id: Uuid,
+    pub community_id: Uuid,
+    pub name: String,
+    pub url: String,
+}
+
+impl CustomEmojiData {
+    /// Key used in message text and reactions. It carries the id because
+    /// emoji in different communities may share a name.
+    pub fn key(&self) -> String {
+        custom_emoji_key(&self.name, self.id)
+    }
+}
+
+/// Build the `<:name:id>` key for a custom emoji
+pub fn custom_emoji_key(name: &str, id: Uuid) -> String {
+    format!("<:{}:{}>", name, id)
+}
+
+/// Split a `<:name:id>` key into its name and id
+pub fn parse_custom_emoji_key(key: &str) -> Option<(&str, Uuid)> {
+    let inner = key.strip_prefix("<:")?.strip_suffix('>')?;
+    let (name, id) = inner.rsplit_once(':')?;
+    if name.is_empty() {
+        return None;
+    }
+    Some((name, Uuid::parse_str(id).ok()?))
 }
 
 /// Reaction data with user IDs
@@ -157,6 +590,9 @@ mod tests {
             Voice,
             DirectMessage,
             GroupDm,
+            Category,
+            Forum,
+            Announcement,
         }
 
         /// Server's Channel model
@@ -232,6 +668,7 @@ mod tests {
 
         assert_eq!(channel_data.name, "general");
         assert_eq!(channel_data.channel_type, ChannelType::Text);
+        assert_eq!(channel_data.retention_seconds, None);
     }
 
     /// Test all channel type variants for correct snake_case serialization.
@@ -252,6 +689,18 @@ mod tests {
         // GroupDm -> group_dm
         let json = serde_json::to_string(&ChannelType::GroupDm).unwrap();
         assert_eq!(json, r#""group_dm""#);
+
+        // Category
+        let json = serde_json::to_string(&ChannelType::Category).unwrap();
+        assert_eq!(json, r#""category""#);
+
+        // Forum
+        let json = serde_json::to_string(&ChannelType::Forum).unwrap();
+        assert_eq!(json, r#""forum""#);
+
+        // Announcement
+        let json = serde_json::to_string(&ChannelType::Announcement).unwrap();
+        assert_eq!(json, r#""announcement""#);
     }
 
     /// Test server-client round-trip compatibility for all channel types.
@@ -263,6 +712,9 @@ mod tests {
             (server_types::ChannelType::Voice, ChannelType::Voice),
             (server_types::ChannelType::DirectMessage, ChannelType::DirectMessage),
             (server_types::ChannelType::GroupDm, ChannelType::GroupDm),
+            (server_types::ChannelType::Category, ChannelType::Category),
+            (server_types::ChannelType::Forum, ChannelType::Forum),
+            (server_types::ChannelType::Announcement, ChannelType::Announcement),
         ];
 
         for (server_type, expected_client_type) in variants {
@@ -271,4 +723,23 @@ mod tests {
             assert_eq!(client_type, expected_client_type, "Failed for {:?}", server_type);
         }
     }
+
+    /// Test that custom emoji keys round-trip and malformed keys are refused.
+    #[test]
+    fn test_custom_emoji_keys() {
+        let id = Uuid::new_v4();
+        let key = custom_emoji_key("party_parrot", id);
+        assert_eq!(key, format!("<:party_parrot:{}>", id));
+        assert_eq!(parse_custom_emoji_key(&key), Some(("party_parrot", id)));
+
+        for key in [
+            ":party_parrot:".to_string(),
+            "<:party_parrot:not-a-uuid>".to_string(),
+            format!("<::{}>", id),
+            format!("<:party_parrot:{}", id),
+            "👍".to_string(),
+        ] {
+            assert_eq!(parse_custom_emoji_key(&key), None, "{}", key);
+        }
+    }
 }
@@ -0,0 +1,13 @@
+-- Custom community emoji
+-- The image itself is stored as an attachment; this table maps a name to it
+CREATE TABLE IF NOT EXISTS community_emojis (
+    id UUID PRIMARY KEY,
+    community_id UUID NOT NULL REFERENCES communities(id) ON DELETE CASCADE,
+    name VARCHAR(32) NOT NULL,
+    attachment_id UUID NOT NULL REFERENCES message_attachments(id) ON DELETE CASCADE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(community_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_community_emojis_community ON community_emojis(community_id);
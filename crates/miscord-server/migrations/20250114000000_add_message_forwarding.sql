@@ -0,0 +1,13 @@
+-- Add message forwarding support
+-- The source channel, author and timestamp are copied so the reference survives deletion of the original
+ALTER TABLE messages ADD COLUMN forwarded_from_message_id UUID REFERENCES messages(id) ON DELETE SET NULL;
+ALTER TABLE messages ADD COLUMN forwarded_from_channel_id UUID REFERENCES channels(id) ON DELETE SET NULL;
+ALTER TABLE messages ADD COLUMN forwarded_from_author_id UUID REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE messages ADD COLUMN forwarded_from_created_at TIMESTAMPTZ;
+
+-- Forwarded attachments share the file of the original upload instead of copying it
+ALTER TABLE message_attachments ADD COLUMN file_id UUID;
+UPDATE message_attachments SET file_id = id WHERE file_id IS NULL;
+ALTER TABLE message_attachments ALTER COLUMN file_id SET NOT NULL;
+
+CREATE INDEX idx_message_attachments_file ON message_attachments(file_id);
@@ -0,0 +1,17 @@
+-- Track the last read message per channel (for unread counts and DM read receipts)
+ALTER TABLE channel_read_states ADD COLUMN last_read_message_id UUID REFERENCES messages(id) ON DELETE SET NULL;
+
+-- last_read_at now holds the created_at of the last read message, so unread counts
+-- stay correct even after that message is deleted
+UPDATE channel_read_states crs
+SET last_read_message_id = (
+    SELECT m.id FROM messages m
+    WHERE m.channel_id = crs.channel_id
+      AND m.thread_parent_id IS NULL
+      AND m.created_at <= crs.last_read_at
+    ORDER BY m.created_at DESC
+    LIMIT 1
+);
+
+-- Users can opt out of sharing read receipts
+ALTER TABLE users ADD COLUMN read_receipts_enabled BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,18 @@
+-- Thread management: following, per-thread read state and auto-archiving
+
+-- Set when a thread has had no activity for a while; cleared by a new reply
+ALTER TABLE messages ADD COLUMN thread_archived_at TIMESTAMPTZ;
+
+-- Users following a thread and how far they have read in it
+CREATE TABLE thread_members (
+    parent_message_id UUID NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    following BOOLEAN NOT NULL DEFAULT TRUE,
+    last_read_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (parent_message_id, user_id)
+);
+
+CREATE INDEX idx_thread_members_user_id ON thread_members(user_id) WHERE following;
+
+-- Index for finding active threads in a channel
+CREATE INDEX idx_messages_channel_threads ON messages(channel_id, last_reply_at DESC) WHERE reply_count > 0;
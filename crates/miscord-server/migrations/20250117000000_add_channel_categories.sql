@@ -0,0 +1,8 @@
+-- Channel categories: a channel type that other community channels can be nested under
+
+ALTER TYPE channel_type ADD VALUE IF NOT EXISTS 'category';
+
+-- Category a channel is nested under (NULL for top-level channels and categories)
+ALTER TABLE channels ADD COLUMN parent_id UUID REFERENCES channels(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_channels_parent_id ON channels(parent_id);
@@ -0,0 +1,30 @@
+-- Forum channels: every post is a titled thread, optionally tagged
+
+ALTER TYPE channel_type ADD VALUE IF NOT EXISTS 'forum';
+
+-- Tags defined by moderators for the posts of a forum channel
+CREATE TABLE forum_tags (
+    id UUID PRIMARY KEY,
+    channel_id UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    name VARCHAR(32) NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (channel_id, name)
+);
+
+-- A post is the parent message of a thread, plus its title
+CREATE TABLE forum_posts (
+    message_id UUID PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+    channel_id UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    title VARCHAR(100) NOT NULL
+);
+
+CREATE INDEX idx_forum_posts_channel_id ON forum_posts(channel_id);
+
+CREATE TABLE forum_post_tags (
+    message_id UUID NOT NULL REFERENCES forum_posts(message_id) ON DELETE CASCADE,
+    tag_id UUID NOT NULL REFERENCES forum_tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (message_id, tag_id)
+);
+
+CREATE INDEX idx_forum_post_tags_tag_id ON forum_post_tags(tag_id);
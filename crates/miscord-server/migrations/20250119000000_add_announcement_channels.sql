@@ -0,0 +1,28 @@
+-- Announcement channels: published messages are copied into every following channel
+
+ALTER TYPE channel_type ADD VALUE IF NOT EXISTS 'announcement';
+
+-- A channel (usually in another community) that receives an announcement channel's posts
+CREATE TABLE channel_follows (
+    id UUID PRIMARY KEY,
+    source_channel_id UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    target_channel_id UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    created_by_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (source_channel_id, target_channel_id)
+);
+
+CREATE INDEX idx_channel_follows_target ON channel_follows(target_channel_id);
+
+-- Announcements that have been published; a message is published at most once
+CREATE TABLE message_publications (
+    message_id UUID PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+    published_by_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    published_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Copies created by publishing; attribution lives in the forwarded_from columns
+CREATE TABLE message_crossposts (
+    message_id UUID PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+    source_message_id UUID REFERENCES messages(id) ON DELETE SET NULL
+);
@@ -0,0 +1,7 @@
+-- Message retention: messages older than this many seconds are purged.
+-- Used as the retention period for community channels and the disappearing
+-- message timer for DMs. NULL keeps history forever.
+
+ALTER TABLE channels ADD COLUMN retention_seconds INTEGER CHECK (retention_seconds > 0);
+
+CREATE INDEX idx_channels_retention ON channels(id) WHERE retention_seconds IS NOT NULL;
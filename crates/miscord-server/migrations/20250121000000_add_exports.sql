@@ -0,0 +1,22 @@
+-- Channel and thread exports, produced by a background job as a zip file
+
+CREATE TYPE export_format AS ENUM ('json', 'html', 'markdown');
+CREATE TYPE export_status AS ENUM ('pending', 'running', 'completed', 'failed');
+
+CREATE TABLE exports (
+    id UUID PRIMARY KEY,
+    channel_id UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    -- Set when only a single thread is exported
+    thread_parent_id UUID REFERENCES messages(id) ON DELETE CASCADE,
+    requested_by_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    format export_format NOT NULL,
+    status export_status NOT NULL DEFAULT 'pending',
+    exported_messages INTEGER NOT NULL DEFAULT 0,
+    total_messages INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_exports_pending ON exports(created_at) WHERE status = 'pending';
+CREATE INDEX idx_exports_requested_by ON exports(requested_by_id);
@@ -0,0 +1,22 @@
+-- Importing a community from a DiscordChatExporter archive
+
+-- Authors of imported messages. They cannot log in; the community owner can
+-- later hand their messages and reactions over to a real account.
+CREATE TABLE placeholder_users (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    community_id UUID NOT NULL REFERENCES communities(id) ON DELETE CASCADE,
+    -- Discord user ID
+    external_id TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (community_id, external_id)
+);
+
+-- Discord IDs of imported channels and messages, so an archive can be re-imported
+-- (e.g. a newer export of the same server) without creating duplicates
+CREATE TABLE imported_records (
+    community_id UUID NOT NULL REFERENCES communities(id) ON DELETE CASCADE,
+    kind VARCHAR(16) NOT NULL,
+    external_id TEXT NOT NULL,
+    local_id UUID NOT NULL,
+    PRIMARY KEY (community_id, kind, external_id)
+);
@@ -0,0 +1,25 @@
+-- Incoming webhooks that post messages into a channel
+
+-- Each webhook posts as its own user, which cannot log in. The user is kept when
+-- the webhook is deleted so its messages keep their author.
+CREATE TABLE webhooks (
+    id UUID PRIMARY KEY,
+    channel_id UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(80) NOT NULL,
+    avatar_url TEXT,
+    -- Secret part of the webhook URL
+    token VARCHAR(64) NOT NULL,
+    created_by_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_webhooks_channel ON webhooks(channel_id);
+
+-- Name and avatar each webhook message was posted with
+CREATE TABLE webhook_messages (
+    message_id UUID PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+    webhook_id UUID REFERENCES webhooks(id) ON DELETE SET NULL,
+    username VARCHAR(80) NOT NULL,
+    avatar_url TEXT
+);
@@ -0,0 +1,18 @@
+-- Bot accounts, managed by a person and authenticated with long-lived tokens
+
+ALTER TABLE users ADD COLUMN bot BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE users ADD COLUMN bot_owner_id UUID REFERENCES users(id) ON DELETE CASCADE;
+
+CREATE INDEX idx_users_bot_owner ON users(bot_owner_id) WHERE bot_owner_id IS NOT NULL;
+
+CREATE TABLE bot_tokens (
+    id UUID PRIMARY KEY,
+    bot_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    -- SHA-256 of the token; the token itself is only shown when it is created
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_bot_tokens_bot ON bot_tokens(bot_id);
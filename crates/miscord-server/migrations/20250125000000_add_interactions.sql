@@ -0,0 +1,36 @@
+-- Slash commands registered by bots and integrations, and the interactions they receive
+
+CREATE TABLE slash_commands (
+    id UUID PRIMARY KEY,
+    community_id UUID NOT NULL REFERENCES communities(id) ON DELETE CASCADE,
+    -- The user that registered the command and receives its interactions
+    application_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(32) NOT NULL,
+    description VARCHAR(100) NOT NULL,
+    options JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (community_id, name)
+);
+
+CREATE INDEX idx_slash_commands_application ON slash_commands(application_id);
+
+-- Command invocations and button clicks waiting for the application to respond
+CREATE TABLE interactions (
+    id UUID PRIMARY KEY,
+    application_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel_id UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    -- Set for button clicks
+    message_id UUID REFERENCES messages(id) ON DELETE CASCADE,
+    responded_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_interactions_created ON interactions(created_at);
+
+-- Buttons attached to messages
+CREATE TABLE message_components (
+    message_id UUID PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+    components JSONB NOT NULL
+);
@@ -0,0 +1,6 @@
+-- Rich embeds posted by bots and webhooks
+
+CREATE TABLE message_embeds (
+    message_id UUID PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+    embeds JSONB NOT NULL
+);
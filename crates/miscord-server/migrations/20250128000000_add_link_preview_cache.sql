@@ -0,0 +1,10 @@
+-- Link previews fetched by the server, shared by everyone who posts or views the URL
+
+CREATE TABLE link_previews (
+    url TEXT PRIMARY KEY,
+    -- NULL when the URL couldn't be previewed, so failures aren't refetched either
+    data JSONB,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_link_previews_fetched_at ON link_previews(fetched_at);
@@ -0,0 +1,23 @@
+-- Large files are uploaded in chunks so an interrupted upload can pick up where it stopped
+
+CREATE TABLE upload_sessions (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel_id UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    filename VARCHAR(255) NOT NULL,
+    content_type VARCHAR(100) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    chunk_size BIGINT NOT NULL,
+    received_bytes BIGINT NOT NULL DEFAULT 0,
+    -- The storage backend's handle for the partly written file
+    storage_upload_id TEXT NOT NULL,
+    -- Tags the backend returned for each stored chunk, in order
+    part_tags TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_upload_sessions_updated_at ON upload_sessions(updated_at);
+
+-- Largest attachment members may upload; NULL uses the server default
+ALTER TABLE communities ADD COLUMN max_upload_bytes BIGINT;
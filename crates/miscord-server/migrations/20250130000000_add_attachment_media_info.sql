@@ -0,0 +1,10 @@
+-- Worked out when a file is uploaded, so clients can lay attachments out and show
+-- previews without downloading the whole file
+
+ALTER TABLE message_attachments
+    ADD COLUMN width INTEGER,
+    ADD COLUMN height INTEGER,
+    ADD COLUMN duration_secs DOUBLE PRECISION,
+    ADD COLUMN blurhash TEXT,
+    -- A downscaled image or video frame is stored alongside the file
+    ADD COLUMN has_thumbnail BOOLEAN NOT NULL DEFAULT FALSE;
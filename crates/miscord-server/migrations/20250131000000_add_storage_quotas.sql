@@ -0,0 +1,29 @@
+-- Storage quotas: each stored file counts against the user who uploaded it and the
+-- community it was uploaded to. Forwarded copies share the original's file, so only
+-- originals (id = file_id) are counted.
+
+ALTER TABLE message_attachments
+    ADD COLUMN uploader_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN community_id UUID REFERENCES communities(id) ON DELETE SET NULL;
+
+UPDATE message_attachments a
+SET uploader_id = m.author_id, community_id = c.community_id
+FROM messages m
+JOIN channels c ON c.id = m.channel_id
+WHERE a.message_id = m.id;
+
+-- Custom emoji images are attachments that never belong to a message
+UPDATE message_attachments a
+SET uploader_id = e.created_by, community_id = e.community_id
+FROM community_emojis e
+WHERE e.attachment_id = a.id;
+
+CREATE INDEX idx_message_attachments_uploader ON message_attachments(uploader_id) WHERE id = file_id;
+CREATE INDEX idx_message_attachments_community ON message_attachments(community_id) WHERE id = file_id;
+
+-- Uploads never sent in a message are cleaned up after a while
+CREATE INDEX idx_message_attachments_unsent ON message_attachments(created_at) WHERE message_id IS NULL;
+
+-- Overrides of the server's storage quotas; NULL uses the default
+ALTER TABLE users ADD COLUMN storage_quota_bytes BIGINT;
+ALTER TABLE communities ADD COLUMN storage_quota_bytes BIGINT;
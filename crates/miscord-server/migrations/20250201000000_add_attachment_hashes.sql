@@ -0,0 +1,9 @@
+-- Content addressing: identical uploads share one stored file. Attachments already
+-- share files through file_id, and a file is kept while any attachment references it,
+-- so the hash only needs finding a file with the same contents.
+
+-- Hex SHA-256 of the file as it was uploaded, before any metadata was stripped, so a
+-- client can hash its copy and ask whether the server has it. NULL for older uploads.
+ALTER TABLE message_attachments ADD COLUMN sha256 TEXT;
+
+CREATE INDEX idx_message_attachments_sha256 ON message_attachments(sha256) WHERE sha256 IS NOT NULL;
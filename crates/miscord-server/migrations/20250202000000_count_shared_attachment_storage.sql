@@ -0,0 +1,18 @@
+-- Storage quotas count each stored file once against every user and community holding
+-- an attachment of it, rather than only against the original upload. Otherwise a file
+-- kept alive by forwarded copies stopped counting against anyone once the original
+-- attachment was deleted.
+
+-- Forwarded copies were created without an owner; they belong to whoever forwarded
+-- them and the community they were forwarded into
+UPDATE message_attachments a
+SET uploader_id = m.author_id, community_id = c.community_id
+FROM messages m
+JOIN channels c ON c.id = m.channel_id
+WHERE a.message_id = m.id AND a.id <> a.file_id AND a.uploader_id IS NULL;
+
+DROP INDEX idx_message_attachments_uploader;
+DROP INDEX idx_message_attachments_community;
+
+CREATE INDEX idx_message_attachments_uploader ON message_attachments(uploader_id, file_id);
+CREATE INDEX idx_message_attachments_community ON message_attachments(community_id, file_id);
@@ -0,0 +1,8 @@
+-- Members asking to take over an imported author's history. The community owner
+-- approves a request by claiming the placeholder for that member.
+CREATE TABLE placeholder_claim_requests (
+    placeholder_id UUID NOT NULL REFERENCES placeholder_users(user_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (placeholder_id, user_id)
+);
@@ -0,0 +1,118 @@
+use crate::auth::AuthUser;
+use crate::error::{AppError, Result};
+use crate::models::{Channel, ChannelFollow, ChannelType, Community, CreateChannelFollow};
+use crate::state::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use miscord_protocol::ChannelFollowData;
+use uuid::Uuid;
+
+fn to_channel_follow_data(follow: ChannelFollow) -> ChannelFollowData {
+    ChannelFollowData {
+        source_channel_id: follow.source_channel_id,
+        source_channel_name: follow.source_channel_name,
+        source_community_id: follow.source_community_id,
+        source_community_name: follow.source_community_name,
+        target_channel_id: follow.target_channel_id,
+        created_at: follow.created_at,
+    }
+}
+
+/// Only the owner of the destination community can manage what its channels follow
+async fn require_channel_manager(state: &AppState, channel: &Channel, user_id: Uuid) -> Result<()> {
+    let community_id = channel
+        .community_id
+        .ok_or_else(|| AppError::BadRequest("Only community channels can follow announcements".to_string()))?;
+
+    let community = sqlx::query_as!(
+        Community,
+        "SELECT id, name, description, icon_url, owner_id, created_at, updated_at FROM communities WHERE id = $1",
+        community_id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Community not found".to_string()))?;
+
+    if community.owner_id != user_id {
+        return Err(AppError::Forbidden);
+    }
+
+    Ok(())
+}
+
+/// List the announcement channels a channel follows
+/// GET /api/channels/:id/follows
+pub async fn list_follows(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<Uuid>,
+) -> Result<Json<Vec<ChannelFollowData>>> {
+    if !state.channel_service.user_has_access(channel_id, auth.user_id).await? {
+        return Err(AppError::Forbidden);
+    }
+
+    let follows = state.announcement_service.list_follows(channel_id).await?;
+
+    Ok(Json(follows.into_iter().map(to_channel_follow_data).collect()))
+}
+
+/// Follow an announcement channel from a channel the user manages
+/// POST /api/channels/:id/followers
+pub async fn follow_channel(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(source_channel_id): Path<Uuid>,
+    Json(input): Json<CreateChannelFollow>,
+) -> Result<Json<ChannelFollowData>> {
+    // The user must be able to see the announcement channel
+    if !state.channel_service.user_has_access(source_channel_id, auth.user_id).await? {
+        return Err(AppError::Forbidden);
+    }
+
+    let source = state.channel_service.get_by_id(source_channel_id).await?;
+    if source.channel_type != ChannelType::Announcement {
+        return Err(AppError::BadRequest(
+            "Only announcement channels can be followed".to_string(),
+        ));
+    }
+
+    let target = state.channel_service.get_by_id(input.target_channel_id).await?;
+    require_channel_manager(&state, &target, auth.user_id).await?;
+
+    if target.id == source.id {
+        return Err(AppError::BadRequest("A channel cannot follow itself".to_string()));
+    }
+    if !matches!(target.channel_type, ChannelType::Text | ChannelType::Announcement) {
+        return Err(AppError::BadRequest(
+            "Announcements can only be followed into text channels".to_string(),
+        ));
+    }
+
+    let follow = state
+        .announcement_service
+        .follow(source.id, target.id, auth.user_id)
+        .await?;
+
+    Ok(Json(to_channel_follow_data(follow)))
+}
+
+/// Stop copying an announcement channel's posts into a channel
+/// DELETE /api/channels/:id/followers/:target_id
+pub async fn unfollow_channel(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((source_channel_id, target_channel_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode> {
+    let target = state.channel_service.get_by_id(target_channel_id).await?;
+    require_channel_manager(&state, &target, auth.user_id).await?;
+
+    state
+        .announcement_service
+        .unfollow(source_channel_id, target_channel_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
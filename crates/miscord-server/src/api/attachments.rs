@@ -1,5 +1,7 @@
 use crate::auth::AuthUser;
 use crate::error::{AppError, Result};
+use crate::file_type;
+use crate::models::{AttachmentOwner, CommunityRole};
 use crate::state::AppState;
 use axum::{
     body::Body,
@@ -12,6 +14,8 @@ use miscord_protocol::AttachmentData;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::bots::require_bot_permission;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UploadResponse {
     pub attachments: Vec<AttachmentData>,
@@ -23,15 +27,25 @@ pub struct UploadResponse {
 /// They can be linked later using the link_to_message endpoint or by the client.
 pub async fn upload_files(
     State(state): State<AppState>,
-    _auth: AuthUser,
+    auth: AuthUser,
     Path(channel_id): Path<Uuid>,
     mut multipart: Multipart,
 ) -> Result<Json<UploadResponse>> {
+    require_bot_permission(&state, &auth, channel_id, CommunityRole::SEND_MESSAGES).await?;
+
     // Verify channel exists
-    let _channel = state.channel_service.get_by_id(channel_id).await?;
+    let channel = state.channel_service.get_by_id(channel_id).await?;
+    let limit = state.upload_service.limit_for_channel(&channel).await?;
+
+    // Upload without linking to a message initially (message_id = None)
+    let owner = AttachmentOwner {
+        message_id: None,
+        uploader_id: auth.user_id,
+        community_id: channel.community_id,
+    };
 
     // Ensure upload directory exists
-    state.attachment_service.ensure_upload_dir().await?;
+    state.attachment_service.ensure_storage_ready().await?;
 
     let mut attachments = Vec::new();
 
@@ -43,28 +57,24 @@ pub async fn upload_files(
             .map(String::from)
             .ok_or_else(|| AppError::BadRequest("Missing filename".to_string()))?;
 
-        let content_type = field
-            .content_type()
-            .map(String::from)
-            .unwrap_or_else(|| "application/octet-stream".to_string());
-
         let data = field.bytes().await.map_err(|e| {
             AppError::BadRequest(format!("Failed to read file data: {}", e))
         })?;
 
-        // Upload without linking to a message initially (message_id = None)
+        if data.len() as i64 > limit {
+            return Err(AppError::BadRequest(format!(
+                "File too large. Maximum size is {} MB",
+                limit / 1024 / 1024
+            )));
+        }
+
+        state.quota_service.check(&owner, data.len() as i64).await?;
         let attachment = state
             .attachment_service
-            .save_file(None, &filename, &content_type, &data)
+            .save_file(owner, &filename, &data)
             .await?;
 
-        attachments.push(AttachmentData {
-            id: attachment.id,
-            filename: attachment.filename,
-            content_type: attachment.content_type,
-            size_bytes: attachment.size_bytes,
-            url: attachment.url,
-        });
+        attachments.push(attachment.into());
     }
 
     if attachments.is_empty() {
@@ -74,7 +84,26 @@ pub async fn upload_files(
     Ok(Json(UploadResponse { attachments }))
 }
 
-/// Download/serve a file
+/// Content-Disposition header value for a file (RFC 6266). Older clients get `filename`
+/// with anything but printable ASCII replaced; the rest read the exact name from
+/// `filename*`, percent-encoded as UTF-8.
+fn content_disposition(disposition: &str, filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| match c {
+            ' '..='~' if c != '"' && c != '\\' => c,
+            _ => '_',
+        })
+        .collect();
+    format!(
+        "{}; filename=\"{}\"; filename*=UTF-8''{}",
+        disposition,
+        fallback,
+        urlencoding::encode(filename)
+    )
+}
+
+/// Download/serve a file, redirecting to the storage bucket when it hands out presigned links
 /// GET /api/files/:id
 pub async fn download_file(
     State(state): State<AppState>,
@@ -82,31 +111,71 @@ pub async fn download_file(
 ) -> Result<Response> {
     let attachment = state.attachment_service.get_by_id(id).await?;
 
-    let data = state
+    // Determine if we should inline (display) or download. Files a browser would run
+    // scripts in, such as SVG and HTML, are only ever downloaded.
+    let is_active = file_type::is_active_content(&attachment.filename, &attachment.content_type);
+    let is_inline = !is_active
+        && (attachment.content_type.starts_with("image/")
+            || attachment.content_type.starts_with("video/")
+            || attachment.content_type.starts_with("audio/")
+            || attachment.content_type == "application/pdf");
+
+    let disposition = content_disposition(
+        if is_inline { "inline" } else { "attachment" },
+        &attachment.filename,
+    );
+
+    if let Some(url) = state.attachment_service.download_url(&attachment, &disposition) {
+        return Response::builder()
+            .status(StatusCode::TEMPORARY_REDIRECT)
+            .header(header::LOCATION, url)
+            // Presigned links expire, so the redirect mustn't outlive them
+            .header(header::CACHE_CONTROL, "private, max-age=600")
+            .body(Body::empty())
+            .map_err(|e| AppError::Internal(e.into()));
+    }
+
+    let stream = state
         .attachment_service
-        .read_file(id, &attachment.filename)
+        .open_file(attachment.file_id, &attachment.filename)
         .await?;
 
-    // Determine if we should inline (display) or download
-    let is_inline = attachment.content_type.starts_with("image/")
-        || attachment.content_type.starts_with("video/")
-        || attachment.content_type.starts_with("audio/")
-        || attachment.content_type == "application/pdf";
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, &attachment.content_type)
+        .header(header::CONTENT_DISPOSITION, disposition)
+        // Browsers mustn't second-guess the content type into something they'd run
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .header(header::CONTENT_LENGTH, attachment.size_bytes)
+        .header(header::CACHE_CONTROL, "public, max-age=31536000") // Cache for 1 year
+        .body(Body::from_stream(stream))
+        .map_err(|e| AppError::Internal(e.into()))
+}
+
+/// Serve the thumbnail of an image or video attachment
+/// GET /api/files/:id/thumbnail
+pub async fn download_thumbnail(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Response> {
+    let attachment = state.attachment_service.get_by_id(id).await?;
+    if !attachment.has_thumbnail {
+        return Err(AppError::NotFound("Attachment has no thumbnail".to_string()));
+    }
 
-    let disposition = if is_inline {
-        format!("inline; filename=\"{}\"", attachment.filename)
-    } else {
-        format!("attachment; filename=\"{}\"", attachment.filename)
+    let data = state.attachment_service.read_thumbnail(attachment.file_id).await?;
+    let content_type = match image::guess_format(&data) {
+        Ok(image::ImageFormat::Png) => "image/png",
+        _ => "image/jpeg",
     };
 
-    Ok(Response::builder()
+    Response::builder()
         .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, &attachment.content_type)
-        .header(header::CONTENT_DISPOSITION, disposition)
-        .header(header::CONTENT_LENGTH, data.len())
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
         .header(header::CACHE_CONTROL, "public, max-age=31536000") // Cache for 1 year
         .body(Body::from(data))
-        .unwrap())
+        .map_err(|e| AppError::Internal(e.into()))
 }
 
 /// Get attachment metadata
@@ -118,13 +187,7 @@ pub async fn get_attachment(
 ) -> Result<Json<AttachmentData>> {
     let attachment = state.attachment_service.get_by_id(id).await?;
 
-    Ok(Json(AttachmentData {
-        id: attachment.id,
-        filename: attachment.filename,
-        content_type: attachment.content_type,
-        size_bytes: attachment.size_bytes,
-        url: attachment.url,
-    }))
+    Ok(Json(attachment.into()))
 }
 
 /// Delete an attachment
@@ -136,6 +199,11 @@ pub async fn delete_attachment(
 ) -> Result<StatusCode> {
     let attachment = state.attachment_service.get_by_id(id).await?;
 
+    // Emoji images are managed through the emoji endpoints
+    if state.emoji_service.is_emoji_attachment(id).await? {
+        return Err(AppError::Forbidden);
+    }
+
     // If attachment is linked to a message, check ownership
     if let Some(message_id) = attachment.message_id {
         let message = state.message_service.get_by_id(message_id).await?;
@@ -151,3 +219,44 @@ pub async fn delete_attachment(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_dispositions() {
+        let cases = [
+            (
+                "inline",
+                "cat.png",
+                "inline; filename=\"cat.png\"; filename*=UTF-8''cat.png",
+            ),
+            (
+                "attachment",
+                "my report (final).pdf",
+                "attachment; filename=\"my report (final).pdf\"; \
+                 filename*=UTF-8''my%20report%20%28final%29.pdf",
+            ),
+            (
+                "attachment",
+                "r\u{e9}sum\u{e9}.txt",
+                "attachment; filename=\"r_sum_.txt\"; filename*=UTF-8''r%C3%A9sum%C3%A9.txt",
+            ),
+            (
+                "attachment",
+                "\u{1f431}.gif",
+                "attachment; filename=\"_.gif\"; filename*=UTF-8''%F0%9F%90%B1.gif",
+            ),
+            // Older uploads weren't checked for these
+            (
+                "attachment",
+                "a\"b\\c;\r\nd.txt",
+                "attachment; filename=\"a_b_c;__d.txt\"; filename*=UTF-8''a%22b%5Cc%3B%0D%0Ad.txt",
+            ),
+        ];
+        for (disposition, filename, expected) in cases {
+            assert_eq!(content_disposition(disposition, filename), expected, "{filename}");
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use crate::auth::AuthUser;
+use crate::error::{AppError, Result};
+use crate::models::{
+    AddCommunityBot, BotToken, CommunityRole, CreateBot, CreateBotToken, CreatedBotToken, PublicUser,
+    UpdateCommunityBot,
+};
+use crate::state::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+
+use super::communities::to_user_data;
+
+/// Bots are managed by people, not by other bots
+pub(crate) fn require_person(auth: &AuthUser) -> Result<()> {
+    if auth.bot {
+        return Err(AppError::Forbidden);
+    }
+    Ok(())
+}
+
+/// Check that a bot may do something in a channel. People are not limited by roles,
+/// and bots can act in DMs they are part of.
+pub async fn require_bot_permission(
+    state: &AppState,
+    auth: &AuthUser,
+    channel_id: Uuid,
+    permission: i64,
+) -> Result<()> {
+    if !auth.bot {
+        return Ok(());
+    }
+
+    let channel = state.channel_service.get_by_id(channel_id).await?;
+    let Some(community_id) = channel.community_id else {
+        return Ok(());
+    };
+
+    let permissions = state
+        .bot_service
+        .get_permissions(community_id, auth.user_id)
+        .await?;
+    if permissions & permission == 0 {
+        return Err(AppError::Forbidden);
+    }
+
+    Ok(())
+}
+
+fn validate_username(username: &str) -> Result<()> {
+    let valid_chars = username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if !(2..=32).contains(&username.len()) || !valid_chars {
+        return Err(AppError::BadRequest(
+            "Bot usernames must be 2 to 32 letters, digits, '_', '-' or '.'".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// List the bots the user manages
+/// GET /api/bots
+pub async fn list_bots(State(state): State<AppState>, auth: AuthUser) -> Result<Json<Vec<PublicUser>>> {
+    require_person(&auth)?;
+
+    let bots = state.bot_service.list_by_owner(auth.user_id).await?;
+
+    Ok(Json(bots.into_iter().map(PublicUser::from).collect()))
+}
+
+/// Create a bot account
+/// POST /api/bots
+pub async fn create_bot(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(input): Json<CreateBot>,
+) -> Result<Json<PublicUser>> {
+    require_person(&auth)?;
+
+    let username = input.username.trim();
+    validate_username(username)?;
+    let display_name = input.display_name.trim();
+    if display_name.is_empty() || display_name.chars().count() > 64 {
+        return Err(AppError::BadRequest(
+            "Display name must be 1 to 64 characters".to_string(),
+        ));
+    }
+
+    let bot = state
+        .bot_service
+        .create(auth.user_id, username, display_name)
+        .await?;
+
+    Ok(Json(bot.into()))
+}
+
+/// Delete a bot along with its messages
+/// DELETE /api/bots/:id
+pub async fn delete_bot(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(bot_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    require_person(&auth)?;
+    state.bot_service.get_owned(bot_id, auth.user_id).await?;
+
+    state.bot_service.delete(bot_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List a bot's tokens (without their secrets)
+/// GET /api/bots/:id/tokens
+pub async fn list_bot_tokens(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(bot_id): Path<Uuid>,
+) -> Result<Json<Vec<BotToken>>> {
+    require_person(&auth)?;
+    state.bot_service.get_owned(bot_id, auth.user_id).await?;
+
+    let tokens = state.bot_service.list_tokens(bot_id).await?;
+
+    Ok(Json(tokens))
+}
+
+/// Create a token for a bot. The secret is only returned here.
+/// POST /api/bots/:id/tokens
+pub async fn create_bot_token(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(bot_id): Path<Uuid>,
+    Json(input): Json<CreateBotToken>,
+) -> Result<Json<CreatedBotToken>> {
+    require_person(&auth)?;
+    state.bot_service.get_owned(bot_id, auth.user_id).await?;
+
+    let name = input.name.trim();
+    if name.is_empty() || name.chars().count() > 64 {
+        return Err(AppError::BadRequest(
+            "Token name must be 1 to 64 characters".to_string(),
+        ));
+    }
+
+    let (token, secret) = state.bot_service.create_token(bot_id, name).await?;
+
+    Ok(Json(CreatedBotToken { token, secret }))
+}
+
+/// Revoke a bot token
+/// DELETE /api/bots/:id/tokens/:token_id
+pub async fn revoke_bot_token(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((bot_id, token_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode> {
+    require_person(&auth)?;
+    state.bot_service.get_owned(bot_id, auth.user_id).await?;
+
+    state.bot_service.revoke_token(bot_id, token_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub(crate) async fn require_community_owner(state: &AppState, community_id: Uuid, user_id: Uuid) -> Result<()> {
+    let owner_id = sqlx::query_scalar!("SELECT owner_id FROM communities WHERE id = $1", community_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Community not found".to_string()))?;
+
+    if owner_id != user_id {
+        return Err(AppError::Forbidden);
+    }
+    Ok(())
+}
+
+fn validate_permissions(permissions: i64) -> Result<()> {
+    if permissions & !CommunityRole::ALL_PERMISSIONS != 0 {
+        return Err(AppError::BadRequest("Unknown permission bits".to_string()));
+    }
+    Ok(())
+}
+
+/// Add a bot to a community with a role limited to the given permissions
+/// POST /api/communities/:id/bots
+pub async fn add_community_bot(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(community_id): Path<Uuid>,
+    Json(input): Json<AddCommunityBot>,
+) -> Result<StatusCode> {
+    require_community_owner(&state, community_id, auth.user_id).await?;
+    validate_permissions(input.permissions)?;
+
+    let bot = state.user_service.get_by_id(input.bot_id).await?;
+    if !bot.bot {
+        return Err(AppError::BadRequest("Only bots can be added this way".to_string()));
+    }
+
+    state
+        .bot_service
+        .add_to_community(community_id, &bot, input.permissions)
+        .await?;
+
+    state
+        .connections
+        .add_user_to_community(bot.id, community_id)
+        .await;
+    state
+        .connections
+        .broadcast_to_community(
+            community_id,
+            &miscord_protocol::ServerMessage::MemberJoined {
+                community_id,
+                user: to_user_data(bot.into()),
+            },
+        )
+        .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Change what a bot may do in a community
+/// PATCH /api/communities/:id/bots/:bot_id
+pub async fn update_community_bot(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((community_id, bot_id)): Path<(Uuid, Uuid)>,
+    Json(input): Json<UpdateCommunityBot>,
+) -> Result<StatusCode> {
+    require_community_owner(&state, community_id, auth.user_id).await?;
+    validate_permissions(input.permissions)?;
+
+    state
+        .bot_service
+        .set_permissions(community_id, bot_id, input.permissions)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,6 +1,9 @@
 use crate::auth::AuthUser;
-use crate::error::Result;
-use crate::models::{Channel, UpdateChannel, VoiceState};
+use crate::error::{AppError, Result};
+use crate::models::{
+    Channel, ChannelReadState, ChannelType, Community, MarkChannelRead, SetChannelRetention, UpdateChannel,
+    VoiceState,
+};
 use crate::state::AppState;
 use axum::{
     extract::{Path, State},
@@ -9,6 +12,30 @@ use axum::{
 use serde::Deserialize;
 use uuid::Uuid;
 
+use super::bots::require_person;
+
+pub(super) fn to_channel_data(channel: Channel, unread_count: i64) -> miscord_protocol::ChannelData {
+    miscord_protocol::ChannelData {
+        id: channel.id,
+        community_id: channel.community_id,
+        name: channel.name,
+        topic: channel.topic,
+        channel_type: match channel.channel_type {
+            ChannelType::Text => miscord_protocol::ChannelType::Text,
+            ChannelType::Voice => miscord_protocol::ChannelType::Voice,
+            ChannelType::DirectMessage => miscord_protocol::ChannelType::DirectMessage,
+            ChannelType::GroupDm => miscord_protocol::ChannelType::GroupDm,
+            ChannelType::Category => miscord_protocol::ChannelType::Category,
+            ChannelType::Forum => miscord_protocol::ChannelType::Forum,
+            ChannelType::Announcement => miscord_protocol::ChannelType::Announcement,
+        },
+        position: channel.position,
+        parent_id: channel.parent_id,
+        retention_seconds: channel.retention_seconds,
+        unread_count,
+    }
+}
+
 pub async fn get_channel(
     State(state): State<AppState>,
     _auth: AuthUser,
@@ -25,6 +52,19 @@ pub async fn update_channel(
     Json(input): Json<UpdateChannel>,
 ) -> Result<Json<Channel>> {
     let channel = state.channel_service.update(id, input).await?;
+
+    if let Some(community_id) = channel.community_id {
+        state
+            .connections
+            .broadcast_to_community(
+                community_id,
+                &miscord_protocol::ServerMessage::ChannelUpdated {
+                    channel: to_channel_data(channel.clone(), 0),
+                },
+            )
+            .await;
+    }
+
     Ok(Json(channel))
 }
 
@@ -33,10 +73,95 @@ pub async fn delete_channel(
     _auth: AuthUser,
     Path(id): Path<Uuid>,
 ) -> Result<()> {
+    let channel = state.channel_service.get_by_id(id).await?;
     state.channel_service.delete(id).await?;
+
+    if let Some(community_id) = channel.community_id {
+        state
+            .connections
+            .broadcast_to_community(
+                community_id,
+                &miscord_protocol::ServerMessage::ChannelDeleted {
+                    channel_id: id,
+                    community_id,
+                },
+            )
+            .await;
+    }
+
     Ok(())
 }
 
+/// Shortest retention period or disappearing timer: one minute
+const MIN_RETENTION_SECONDS: i32 = 60;
+
+/// Longest retention period: ten years
+const MAX_RETENTION_SECONDS: i32 = 10 * 365 * 24 * 60 * 60;
+
+/// Set a channel's message retention period, or a DM's disappearing message timer.
+/// Community channels are managed by the owner; any participant can set a DM timer.
+/// PUT /api/channels/:id/retention
+pub async fn set_channel_retention(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(input): Json<SetChannelRetention>,
+) -> Result<Json<miscord_protocol::ChannelData>> {
+    if let Some(seconds) = input.retention_seconds {
+        if !(MIN_RETENTION_SECONDS..=MAX_RETENTION_SECONDS).contains(&seconds) {
+            return Err(AppError::BadRequest(format!(
+                "Retention must be between {} seconds and {} days",
+                MIN_RETENTION_SECONDS,
+                MAX_RETENTION_SECONDS / 86400
+            )));
+        }
+    }
+
+    let channel = state.channel_service.get_by_id(id).await?;
+    if matches!(channel.channel_type, ChannelType::Voice | ChannelType::Category) {
+        return Err(AppError::BadRequest("This channel has no messages to retain".to_string()));
+    }
+
+    match channel.community_id {
+        Some(community_id) => {
+            let community = sqlx::query_as!(
+                Community,
+                "SELECT id, name, description, icon_url, owner_id, created_at, updated_at FROM communities WHERE id = $1",
+                community_id
+            )
+            .fetch_optional(&state.db)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Community not found".to_string()))?;
+
+            if community.owner_id != auth.user_id {
+                return Err(AppError::Forbidden);
+            }
+        }
+        None => {
+            if !state.channel_service.user_has_access(id, auth.user_id).await? {
+                return Err(AppError::Forbidden);
+            }
+        }
+    }
+
+    let channel = state.channel_service.set_retention(id, input.retention_seconds).await?;
+    let channel_data = to_channel_data(channel.clone(), 0);
+    let event = miscord_protocol::ServerMessage::ChannelUpdated {
+        channel: channel_data.clone(),
+    };
+
+    match channel.community_id {
+        Some(community_id) => state.connections.broadcast_to_community(community_id, &event).await,
+        None => {
+            for user_id in state.channel_service.get_dm_participants(id).await? {
+                state.connections.send_to_user(user_id, &event).await;
+            }
+        }
+    }
+
+    Ok(Json(channel_data))
+}
+
 // Voice channel endpoints
 
 pub async fn join_voice(
@@ -178,6 +303,8 @@ pub async fn create_dm(
     auth: AuthUser,
     Path(user_id): Path<Uuid>,
 ) -> Result<Json<Channel>> {
+    require_person(&auth)?;
+
     let channel = state
         .channel_service
         .get_or_create_dm(auth.user_id, user_id)
@@ -187,17 +314,86 @@ pub async fn create_dm(
 
 // Channel read state endpoints
 
-/// Mark a channel as read
+fn to_read_state_data(read_state: ChannelReadState) -> miscord_protocol::ReadStateData {
+    miscord_protocol::ReadStateData {
+        channel_id: read_state.channel_id,
+        user_id: read_state.user_id,
+        last_read_message_id: read_state.last_read_message_id,
+        last_read_at: read_state.last_read_at,
+    }
+}
+
+/// Mark a channel as read, up to the given message or the newest one
+/// POST /api/channels/:id/read
 pub async fn mark_channel_read(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(channel_id): Path<Uuid>,
-) -> Result<()> {
-    state
+    input: Option<Json<MarkChannelRead>>,
+) -> Result<Json<miscord_protocol::ReadStateData>> {
+    if !state.channel_service.user_has_access(channel_id, auth.user_id).await? {
+        return Err(AppError::Forbidden);
+    }
+
+    let input = input.map(|Json(input)| input).unwrap_or_default();
+    let read_state = state
         .channel_service
-        .mark_channel_read(channel_id, auth.user_id)
+        .mark_channel_read(channel_id, auth.user_id, input.message_id)
         .await?;
-    Ok(())
+
+    let event = miscord_protocol::ServerMessage::ReadStateUpdated {
+        channel_id,
+        user_id: auth.user_id,
+        last_read_message_id: read_state.last_read_message_id,
+    };
+
+    // Sync unread badges across the user's other sessions
+    state.connections.send_to_user(auth.user_id, &event).await;
+
+    // Share the receipt with the other DM participants. Receipts are reciprocal: only
+    // users who share their own receipts see anyone else's.
+    let channel = state.channel_service.get_by_id(channel_id).await?;
+    if matches!(channel.channel_type, ChannelType::DirectMessage | ChannelType::GroupDm) {
+        let privacy = state.user_service.get_privacy_settings(auth.user_id).await?;
+        if privacy.read_receipts_enabled {
+            for user_id in state.channel_service.get_read_receipt_participants(channel_id).await? {
+                if user_id != auth.user_id {
+                    state.connections.send_to_user(user_id, &event).await;
+                }
+            }
+        }
+    }
+
+    Ok(Json(to_read_state_data(read_state)))
+}
+
+/// Get "seen by" read receipts for a DM or group DM. Users who don't share their own
+/// receipts get none.
+/// GET /api/channels/:id/read-receipts
+pub async fn get_read_receipts(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<Uuid>,
+) -> Result<Json<Vec<miscord_protocol::ReadStateData>>> {
+    if !state.channel_service.user_has_access(channel_id, auth.user_id).await? {
+        return Err(AppError::Forbidden);
+    }
+
+    let channel = state.channel_service.get_by_id(channel_id).await?;
+    if !matches!(channel.channel_type, ChannelType::DirectMessage | ChannelType::GroupDm) {
+        return Err(AppError::BadRequest(
+            "Read receipts are only available in direct messages".to_string(),
+        ));
+    }
+
+    let privacy = state.user_service.get_privacy_settings(auth.user_id).await?;
+    if !privacy.read_receipts_enabled {
+        return Ok(Json(Vec::new()));
+    }
+
+    let receipts = state.channel_service.get_read_receipts(channel_id).await?;
+
+    Ok(Json(receipts.into_iter().map(to_read_state_data).collect()))
 }
 
 /// Get unread count for a single channel
@@ -1,6 +1,6 @@
 use crate::auth::AuthUser;
 use crate::error::{AppError, Result};
-use crate::models::{Channel, ChannelType, Community, CommunityInvite, CreateChannel, CreateCommunity, PublicUser, UpdateCommunity, UserStatus};
+use crate::models::{Channel, ChannelPosition, Community, CommunityInvite, CreateChannel, CreateCommunity, PublicUser, UpdateCommunity, UserStatus};
 use crate::state::AppState;
 use axum::{
     extract::{Path, State},
@@ -9,11 +9,48 @@ use axum::{
 use rand::Rng;
 use uuid::Uuid;
 
+use super::channels::to_channel_data;
+use super::interactions::broadcast_commands;
+
+fn to_community_data(community: Community) -> miscord_protocol::CommunityData {
+    miscord_protocol::CommunityData {
+        id: community.id,
+        name: community.name,
+        description: community.description,
+        icon_url: community.icon_url,
+        owner_id: community.owner_id,
+        created_at: community.created_at,
+    }
+}
+
+pub(crate) fn to_user_data(user: PublicUser) -> miscord_protocol::UserData {
+    miscord_protocol::UserData {
+        id: user.id,
+        username: user.username,
+        display_name: user.display_name,
+        avatar_url: user.avatar_url,
+        status: match user.status {
+            UserStatus::Offline => miscord_protocol::UserStatus::Offline,
+            UserStatus::Online => miscord_protocol::UserStatus::Online,
+            UserStatus::Idle => miscord_protocol::UserStatus::Idle,
+            UserStatus::DoNotDisturb => miscord_protocol::UserStatus::DoNotDisturb,
+            UserStatus::Invisible => miscord_protocol::UserStatus::Invisible,
+        },
+        custom_status: user.custom_status,
+        bot: user.bot,
+    }
+}
+
 pub async fn create_community(
     State(state): State<AppState>,
     auth: AuthUser,
     Json(input): Json<CreateCommunity>,
 ) -> Result<Json<Community>> {
+    // Bots are added to communities by their owners
+    if auth.bot {
+        return Err(AppError::Forbidden);
+    }
+
     let community = sqlx::query_as!(
         Community,
         r#"
@@ -42,6 +79,12 @@ pub async fn create_community(
     .execute(&state.db)
     .await?;
 
+    // The owner's sessions receive events for the new community
+    state
+        .connections
+        .add_user_to_community(auth.user_id, community.id)
+        .await;
+
     // Create default channels
     state
         .channel_service
@@ -51,6 +94,7 @@ pub async fn create_community(
                 name: "general".to_string(),
                 topic: Some("General discussion".to_string()),
                 channel_type: crate::models::ChannelType::Text,
+                parent_id: None,
             },
         )
         .await?;
@@ -63,6 +107,7 @@ pub async fn create_community(
                 name: "General".to_string(),
                 topic: Some("Voice chat".to_string()),
                 channel_type: crate::models::ChannelType::Voice,
+                parent_id: None,
             },
         )
         .await?;
@@ -167,6 +212,16 @@ pub async fn update_community(
     .fetch_one(&state.db)
     .await?;
 
+    state
+        .connections
+        .broadcast_to_community(
+            id,
+            &miscord_protocol::ServerMessage::CommunityUpdated {
+                community: to_community_data(updated.clone()),
+            },
+        )
+        .await;
+
     Ok(Json(updated))
 }
 
@@ -193,6 +248,12 @@ pub async fn delete_community(
         .execute(&state.db)
         .await?;
 
+    state
+        .connections
+        .broadcast_to_community(id, &miscord_protocol::ServerMessage::CommunityDeleted { community_id: id })
+        .await;
+    state.connections.remove_community(id).await;
+
     Ok(())
 }
 
@@ -229,20 +290,7 @@ pub async fn list_channels(
         .into_iter()
         .map(|c| {
             let unread_count = unread_counts.get(&c.id).copied().unwrap_or(0);
-            miscord_protocol::ChannelData {
-                id: c.id,
-                community_id: c.community_id,
-                name: c.name,
-                topic: c.topic,
-                channel_type: match c.channel_type {
-                    ChannelType::Text => miscord_protocol::ChannelType::Text,
-                    ChannelType::Voice => miscord_protocol::ChannelType::Voice,
-                    ChannelType::DirectMessage => miscord_protocol::ChannelType::DirectMessage,
-                    ChannelType::GroupDm => miscord_protocol::ChannelType::GroupDm,
-                },
-                position: c.position,
-                unread_count,
-            }
+            to_channel_data(c, unread_count)
         })
         .collect();
 
@@ -270,14 +318,77 @@ pub async fn create_channel(
     }
 
     let channel = state.channel_service.create(community_id, input).await?;
+
+    state
+        .connections
+        .broadcast_to_community(
+            community_id,
+            &miscord_protocol::ServerMessage::ChannelCreated {
+                channel: to_channel_data(channel.clone(), 0),
+            },
+        )
+        .await;
+
     Ok(Json(channel))
 }
 
+/// Reorder channels and move them between categories
+/// PATCH /api/communities/:id/channels
+pub async fn reorder_channels(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(community_id): Path<Uuid>,
+    Json(input): Json<Vec<ChannelPosition>>,
+) -> Result<Json<Vec<miscord_protocol::ChannelPositionData>>> {
+    // Check ownership (only owner can manage channels for now)
+    let community = sqlx::query_as!(
+        Community,
+        "SELECT id, name, description, icon_url, owner_id, created_at, updated_at FROM communities WHERE id = $1",
+        community_id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Community not found".to_string()))?;
+
+    if community.owner_id != auth.user_id {
+        return Err(AppError::Forbidden);
+    }
+
+    let channels = state.channel_service.reorder(community_id, &input).await?;
+
+    let positions: Vec<miscord_protocol::ChannelPositionData> = channels
+        .into_iter()
+        .map(|c| miscord_protocol::ChannelPositionData {
+            id: c.id,
+            position: c.position,
+            parent_id: c.parent_id,
+        })
+        .collect();
+
+    state
+        .connections
+        .broadcast_to_community(
+            community_id,
+            &miscord_protocol::ServerMessage::ChannelsReordered {
+                community_id,
+                channels: positions.clone(),
+            },
+        )
+        .await;
+
+    Ok(Json(positions))
+}
+
 pub async fn create_invite(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(community_id): Path<Uuid>,
 ) -> Result<Json<CommunityInvite>> {
+    // Invites are for people to hand out; bots are added by their owners
+    if auth.bot {
+        return Err(AppError::Forbidden);
+    }
+
     // Check membership
     let is_member = sqlx::query_scalar!(
         "SELECT EXISTS(SELECT 1 FROM community_members WHERE community_id = $1 AND user_id = $2)",
@@ -322,6 +433,10 @@ pub async fn join_community(
     auth: AuthUser,
     Path(code): Path<String>,
 ) -> Result<Json<Community>> {
+    if auth.bot {
+        return Err(AppError::Forbidden);
+    }
+
     // Find the invite
     let invite = sqlx::query_as!(
         CommunityInvite,
@@ -376,6 +491,23 @@ pub async fn join_community(
         )
         .execute(&state.db)
         .await?;
+
+        state
+            .connections
+            .add_user_to_community(auth.user_id, invite.community_id)
+            .await;
+
+        let user = state.user_service.get_by_id(auth.user_id).await?;
+        state
+            .connections
+            .broadcast_to_community(
+                invite.community_id,
+                &miscord_protocol::ServerMessage::MemberJoined {
+                    community_id: invite.community_id,
+                    user: to_user_data(user.into()),
+                },
+            )
+            .await;
     }
 
     let community = sqlx::query_as!(
@@ -413,7 +545,7 @@ pub async fn list_members(
         PublicUser,
         r#"
         SELECT u.id, u.username, u.display_name, u.avatar_url,
-               u.status as "status: UserStatus", u.custom_status
+               u.status as "status: UserStatus", u.custom_status, u.bot
         FROM users u
         INNER JOIN community_members m ON u.id = m.user_id
         WHERE m.community_id = $1
@@ -426,3 +558,70 @@ pub async fn list_members(
 
     Ok(Json(members))
 }
+
+/// Leave a community, or remove a member (owner only)
+/// DELETE /api/communities/:id/members/:user_id
+pub async fn remove_member(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((community_id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<()> {
+    let community = sqlx::query_as!(
+        Community,
+        "SELECT id, name, description, icon_url, owner_id, created_at, updated_at FROM communities WHERE id = $1",
+        community_id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Community not found".to_string()))?;
+
+    if user_id != auth.user_id && community.owner_id != auth.user_id {
+        return Err(AppError::Forbidden);
+    }
+    if user_id == community.owner_id {
+        return Err(AppError::BadRequest(
+            "The owner cannot leave their community; delete it instead".to_string(),
+        ));
+    }
+
+    // A bot's role only exists for its membership
+    let member = state.user_service.get_by_id(user_id).await?;
+    if member.bot {
+        state.bot_service.remove_roles(community_id, user_id).await?;
+    }
+
+    let result = sqlx::query!(
+        "DELETE FROM community_members WHERE community_id = $1 AND user_id = $2",
+        community_id,
+        user_id
+    )
+    .execute(&state.db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Member not found".to_string()));
+    }
+
+    let removed_commands = state
+        .interaction_service
+        .delete_application_commands(community_id, user_id)
+        .await?;
+    if removed_commands > 0 {
+        broadcast_commands(&state, community_id).await?;
+    }
+
+    // Broadcast before unregistering so the removed user's other sessions see it too
+    state
+        .connections
+        .broadcast_to_community(
+            community_id,
+            &miscord_protocol::ServerMessage::MemberLeft { community_id, user_id },
+        )
+        .await;
+    state
+        .connections
+        .remove_user_from_community(user_id, community_id)
+        .await;
+
+    Ok(())
+}
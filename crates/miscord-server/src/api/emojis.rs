@@ -0,0 +1,147 @@
+use crate::auth::AuthUser;
+use crate::error::{AppError, Result};
+use crate::models::{AttachmentOwner, CommunityEmoji, RenameEmoji};
+use crate::services::emoji::{process_emoji_image, validate_emoji_name};
+use crate::state::AppState;
+use axum::{
+    extract::{Multipart, Path, State},
+    http::StatusCode,
+    Json,
+};
+use miscord_protocol::CustomEmojiData;
+use uuid::Uuid;
+
+use super::bots::require_community_owner;
+
+fn to_emoji_data(emoji: CommunityEmoji) -> CustomEmojiData {
+    CustomEmojiData {
+        id: emoji.id,
+        community_id: emoji.community_id,
+        name: emoji.name,
+        url: emoji.url,
+    }
+}
+
+/// List custom emoji for a community
+/// GET /api/communities/:id/emojis
+pub async fn list_emojis(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(community_id): Path<Uuid>,
+) -> Result<Json<Vec<CustomEmojiData>>> {
+    // Check membership
+    let is_member = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM community_members WHERE community_id = $1 AND user_id = $2)",
+        community_id,
+        auth.user_id
+    )
+    .fetch_one(&state.db)
+    .await?
+    .unwrap_or(false);
+
+    if !is_member {
+        return Err(AppError::Forbidden);
+    }
+
+    let emojis = state.emoji_service.list_by_community(community_id).await?;
+
+    Ok(Json(emojis.into_iter().map(to_emoji_data).collect()))
+}
+
+/// Upload a custom emoji
+/// POST /api/communities/:id/emojis
+/// Multipart form with a `name` text field and a `file` image field.
+pub async fn upload_emoji(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(community_id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<CustomEmojiData>> {
+    require_community_owner(&state, community_id, auth.user_id).await?;
+
+    let mut name: Option<String> = None;
+    let mut data: Option<Vec<u8>> = None;
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        AppError::BadRequest(format!("Failed to read multipart field: {}", e))
+    })? {
+        match field.name() {
+            Some("name") => {
+                let text = field.text().await.map_err(|e| {
+                    AppError::BadRequest(format!("Failed to read emoji name: {}", e))
+                })?;
+                name = Some(text.trim().trim_matches(':').to_string());
+            }
+            Some("file") => {
+                let bytes = field.bytes().await.map_err(|e| {
+                    AppError::BadRequest(format!("Failed to read file data: {}", e))
+                })?;
+                data = Some(bytes.to_vec());
+            }
+            _ => {}
+        }
+    }
+
+    let name = name.ok_or_else(|| AppError::BadRequest("Missing emoji name".to_string()))?;
+    let data = data.ok_or_else(|| AppError::BadRequest("Missing emoji image".to_string()))?;
+
+    validate_emoji_name(&name)?;
+    state.emoji_service.check_can_create(community_id, &name).await?;
+
+    let (image, extension) = process_emoji_image(&data)?;
+
+    let owner = AttachmentOwner {
+        message_id: None,
+        uploader_id: auth.user_id,
+        community_id: Some(community_id),
+    };
+    state.quota_service.check(&owner, image.len() as i64).await?;
+
+    state.attachment_service.ensure_storage_ready().await?;
+    let attachment = state
+        .attachment_service
+        .save_file(owner, &format!("{}.{}", name, extension), &image)
+        .await?;
+
+    let emoji = state
+        .emoji_service
+        .create(community_id, &name, attachment.id, auth.user_id)
+        .await?;
+
+    Ok(Json(to_emoji_data(emoji)))
+}
+
+/// Rename a custom emoji
+/// PATCH /api/emojis/:id
+pub async fn rename_emoji(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(input): Json<RenameEmoji>,
+) -> Result<Json<CustomEmojiData>> {
+    let emoji = state.emoji_service.get_by_id(id).await?;
+    require_community_owner(&state, emoji.community_id, auth.user_id).await?;
+
+    let name = input.name.trim().trim_matches(':');
+    validate_emoji_name(name)?;
+
+    let emoji = state.emoji_service.rename(id, name).await?;
+
+    Ok(Json(to_emoji_data(emoji)))
+}
+
+/// Delete a custom emoji
+/// DELETE /api/emojis/:id
+pub async fn delete_emoji(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    let emoji = state.emoji_service.get_by_id(id).await?;
+    require_community_owner(&state, emoji.community_id, auth.user_id).await?;
+
+    // Deleting the image attachment removes the emoji row via ON DELETE CASCADE
+    state.attachment_service.delete(emoji.attachment_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
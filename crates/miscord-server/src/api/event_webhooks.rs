@@ -0,0 +1,211 @@
+use crate::auth::AuthUser;
+use crate::error::{AppError, Result};
+use crate::models::{
+    CreateEventWebhook, EventWebhook, UpdateEventWebhook, WebhookDelivery, WebhookDeliveryStatus,
+    WEBHOOK_EVENT_TYPES,
+};
+use crate::safe_http;
+use crate::state::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use miscord_protocol::{EventWebhookData, WebhookDeliveryData};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::bots::require_community_owner;
+
+const MAX_URL_LENGTH: usize = 2048;
+
+/// Outgoing webhooks per community
+const MAX_WEBHOOKS: usize = 10;
+
+fn to_event_webhook_data(webhook: EventWebhook) -> EventWebhookData {
+    EventWebhookData {
+        id: webhook.id,
+        community_id: webhook.community_id,
+        url: webhook.url,
+        events: webhook.events,
+        secret: webhook.secret,
+        enabled: webhook.enabled,
+        created_at: webhook.created_at,
+    }
+}
+
+fn to_delivery_data(delivery: WebhookDelivery) -> WebhookDeliveryData {
+    WebhookDeliveryData {
+        id: delivery.id,
+        webhook_id: delivery.webhook_id,
+        event: delivery.event,
+        status: match delivery.status {
+            WebhookDeliveryStatus::Pending => miscord_protocol::WebhookDeliveryStatus::Pending,
+            WebhookDeliveryStatus::Succeeded => miscord_protocol::WebhookDeliveryStatus::Succeeded,
+            WebhookDeliveryStatus::Failed => miscord_protocol::WebhookDeliveryStatus::Failed,
+        },
+        attempts: delivery.attempts,
+        next_attempt_at: delivery.next_attempt_at,
+        response_status: delivery.response_status,
+        error: delivery.error,
+        created_at: delivery.created_at,
+        completed_at: delivery.completed_at,
+    }
+}
+
+fn validate_url(url: &str) -> Result<String> {
+    let url = url.trim();
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(AppError::BadRequest("Webhook URL must be an http(s) URL".to_string()));
+    }
+    if url.len() > MAX_URL_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "Webhook URL must be at most {} characters",
+            MAX_URL_LENGTH
+        )));
+    }
+    // Hostnames are checked again when a delivery resolves them
+    safe_http::check_url(url)?;
+    Ok(url.to_string())
+}
+
+/// Check the event types are known, dropping duplicates
+fn validate_events(events: &[String]) -> Result<Vec<String>> {
+    let mut validated: Vec<String> = Vec::with_capacity(events.len());
+    for event in events {
+        if !WEBHOOK_EVENT_TYPES.contains(&event.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Unknown event type \"{}\"; expected one of: {}",
+                event,
+                WEBHOOK_EVENT_TYPES.join(", ")
+            )));
+        }
+        if !validated.contains(event) {
+            validated.push(event.clone());
+        }
+    }
+    if validated.is_empty() {
+        return Err(AppError::BadRequest("Subscribe to at least one event type".to_string()));
+    }
+    Ok(validated)
+}
+
+/// Load a webhook the user manages through owning its community
+async fn get_managed_webhook(state: &AppState, id: Uuid, user_id: Uuid) -> Result<EventWebhook> {
+    let webhook = state.event_webhook_service.get_by_id(id).await?;
+    require_community_owner(state, webhook.community_id, user_id).await?;
+    Ok(webhook)
+}
+
+/// List a community's outgoing webhooks
+/// GET /api/communities/:id/event-webhooks
+pub async fn list_event_webhooks(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(community_id): Path<Uuid>,
+) -> Result<Json<Vec<EventWebhookData>>> {
+    require_community_owner(&state, community_id, auth.user_id).await?;
+
+    let webhooks = state.event_webhook_service.list_by_community(community_id).await?;
+
+    Ok(Json(webhooks.into_iter().map(to_event_webhook_data).collect()))
+}
+
+/// Create an outgoing webhook for a community's events
+/// POST /api/communities/:id/event-webhooks
+pub async fn create_event_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(community_id): Path<Uuid>,
+    Json(input): Json<CreateEventWebhook>,
+) -> Result<Json<EventWebhookData>> {
+    require_community_owner(&state, community_id, auth.user_id).await?;
+
+    let url = validate_url(&input.url)?;
+    let events = validate_events(&input.events)?;
+
+    let existing = state.event_webhook_service.list_by_community(community_id).await?;
+    if existing.len() >= MAX_WEBHOOKS {
+        return Err(AppError::BadRequest(format!(
+            "A community can have at most {} outgoing webhooks",
+            MAX_WEBHOOKS
+        )));
+    }
+
+    let webhook = state
+        .event_webhook_service
+        .create(community_id, &url, &events, auth.user_id)
+        .await?;
+
+    Ok(Json(to_event_webhook_data(webhook)))
+}
+
+/// Change an outgoing webhook's URL or events, or pause it
+/// PATCH /api/event-webhooks/:id
+pub async fn update_event_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(input): Json<UpdateEventWebhook>,
+) -> Result<Json<EventWebhookData>> {
+    get_managed_webhook(&state, id, auth.user_id).await?;
+
+    let url = input.url.as_deref().map(validate_url).transpose()?;
+    let events = input.events.as_deref().map(validate_events).transpose()?;
+
+    let webhook = state
+        .event_webhook_service
+        .update(id, url.as_deref(), events.as_deref(), input.enabled)
+        .await?;
+
+    Ok(Json(to_event_webhook_data(webhook)))
+}
+
+/// Replace a webhook's signing secret; deliveries are signed with the new one from now on
+/// POST /api/event-webhooks/:id/secret
+pub async fn regenerate_event_webhook_secret(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EventWebhookData>> {
+    get_managed_webhook(&state, id, auth.user_id).await?;
+
+    let webhook = state.event_webhook_service.regenerate_secret(id).await?;
+
+    Ok(Json(to_event_webhook_data(webhook)))
+}
+
+/// Delete an outgoing webhook and its delivery log
+/// DELETE /api/event-webhooks/:id
+pub async fn delete_event_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    get_managed_webhook(&state, id, auth.user_id).await?;
+
+    state.event_webhook_service.delete(id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListDeliveriesQuery {
+    pub limit: Option<i64>,
+}
+
+/// Recent deliveries of an outgoing webhook, newest first
+/// GET /api/event-webhooks/:id/deliveries
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ListDeliveriesQuery>,
+) -> Result<Json<Vec<WebhookDeliveryData>>> {
+    get_managed_webhook(&state, id, auth.user_id).await?;
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+    let deliveries = state.event_webhook_service.list_deliveries(id, limit).await?;
+
+    Ok(Json(deliveries.into_iter().map(to_delivery_data).collect()))
+}
@@ -0,0 +1,126 @@
+use crate::auth::AuthUser;
+use crate::error::{AppError, Result};
+use crate::export::to_export_data;
+use crate::models::{ChannelType, CreateExport, Export, ExportFormat, ExportStatus};
+use crate::state::AppState;
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::Response,
+    Json,
+};
+use miscord_protocol::ExportData;
+use uuid::Uuid;
+
+use super::bots::require_person;
+
+/// Exports can only be seen and downloaded by the user who asked for them
+async fn get_own_export(state: &AppState, id: Uuid, user_id: Uuid) -> Result<Export> {
+    let export = state.export_service.get_by_id(id).await?;
+    if export.requested_by_id != user_id {
+        return Err(AppError::NotFound("Export not found".to_string()));
+    }
+    Ok(export)
+}
+
+/// Queue an export of a channel's (or one thread's) full history.
+/// Progress is reported to the requester with ExportUpdated events.
+/// POST /api/channels/:id/exports
+pub async fn create_export(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<Uuid>,
+    Json(input): Json<CreateExport>,
+) -> Result<(StatusCode, Json<ExportData>)> {
+    require_person(&auth)?;
+    if !state.channel_service.user_has_access(channel_id, auth.user_id).await? {
+        return Err(AppError::Forbidden);
+    }
+
+    let channel = state.channel_service.get_by_id(channel_id).await?;
+    if matches!(channel.channel_type, ChannelType::Voice | ChannelType::Category) {
+        return Err(AppError::BadRequest("This channel has no messages to export".to_string()));
+    }
+
+    if let Some(parent_id) = input.thread_parent_id {
+        let parent = state.message_service.get_by_id(parent_id).await?;
+        if parent.channel_id != channel_id || parent.thread_parent_id.is_some() {
+            return Err(AppError::BadRequest(
+                "Thread must start with a message in this channel".to_string(),
+            ));
+        }
+    }
+
+    let export = state
+        .export_service
+        .create(channel_id, input.thread_parent_id, auth.user_id, input.format)
+        .await?;
+
+    Ok((StatusCode::ACCEPTED, Json(to_export_data(export))))
+}
+
+/// Get an export's status and progress
+/// GET /api/exports/:id
+pub async fn get_export(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ExportData>> {
+    let export = get_own_export(&state, id, auth.user_id).await?;
+    Ok(Json(to_export_data(export)))
+}
+
+/// Download a finished export as a zip file, redirecting to the storage bucket when it
+/// hands out presigned links. Exports expire a week after they finish.
+/// GET /api/exports/:id/download
+pub async fn download_export(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response> {
+    let export = get_own_export(&state, id, auth.user_id).await?;
+    if export.status != ExportStatus::Completed {
+        return Err(AppError::Conflict("Export is not finished yet".to_string()));
+    }
+
+    let channel_name = state
+        .channel_service
+        .get_by_id(export.channel_id)
+        .await
+        .map(|c| c.name)
+        .unwrap_or_else(|_| "channel".to_string());
+    let safe_name: String = channel_name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let format = match export.format {
+        ExportFormat::Json => "json",
+        ExportFormat::Html => "html",
+        ExportFormat::Markdown => "markdown",
+    };
+    let disposition = format!(
+        "attachment; filename=\"{}-{}-{}.zip\"",
+        safe_name,
+        format,
+        export.created_at.format("%Y%m%d")
+    );
+
+    if let Some(url) = state.export_service.download_url(id, &disposition) {
+        return Response::builder()
+            .status(StatusCode::TEMPORARY_REDIRECT)
+            .header(header::LOCATION, url)
+            .header(header::CACHE_CONTROL, "private, no-store")
+            .body(Body::empty())
+            .map_err(|e| AppError::Internal(e.into()));
+    }
+
+    let stream = state.export_service.open_archive(id).await?;
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/zip")
+        .header(header::CONTENT_DISPOSITION, disposition)
+        .body(Body::from_stream(stream))
+        .map_err(|e| AppError::Internal(e.into()))
+}
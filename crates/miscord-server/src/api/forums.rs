@@ -0,0 +1,264 @@
+use crate::auth::AuthUser;
+use crate::error::{AppError, Result};
+use crate::models::{
+    Channel, ChannelType, Community, CommunityRole, CreateForumPost, CreateForumTag, ForumPost, ForumTag,
+    UpdateForumPostTags,
+};
+use crate::state::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use miscord_protocol::{ForumPostData, ForumTagData, ServerMessage};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::bots::require_bot_permission;
+
+fn to_forum_tag_data(tag: ForumTag) -> ForumTagData {
+    ForumTagData {
+        id: tag.id,
+        channel_id: tag.channel_id,
+        name: tag.name,
+        position: tag.position,
+    }
+}
+
+fn to_forum_post_data(post: ForumPost) -> ForumPostData {
+    ForumPostData {
+        message_id: post.message_id,
+        channel_id: post.channel_id,
+        title: post.title,
+        author_id: post.author_id,
+        author_name: post.author_name,
+        content: post.content,
+        tag_ids: post.tag_ids,
+        reply_count: post.reply_count,
+        last_reply_at: post.last_reply_at,
+        created_at: post.created_at,
+    }
+}
+
+/// Load a forum channel the user can see
+async fn require_forum(state: &AppState, channel_id: Uuid, user_id: Uuid) -> Result<Channel> {
+    if !state.channel_service.user_has_access(channel_id, user_id).await? {
+        return Err(AppError::Forbidden);
+    }
+
+    let channel = state.channel_service.get_by_id(channel_id).await?;
+    if channel.channel_type != ChannelType::Forum {
+        return Err(AppError::BadRequest("Not a forum channel".to_string()));
+    }
+
+    Ok(channel)
+}
+
+/// Only the community owner moderates forum tags
+async fn is_forum_moderator(state: &AppState, channel: &Channel, user_id: Uuid) -> Result<bool> {
+    let Some(community_id) = channel.community_id else {
+        return Ok(false);
+    };
+
+    let community = sqlx::query_as!(
+        Community,
+        "SELECT id, name, description, icon_url, owner_id, created_at, updated_at FROM communities WHERE id = $1",
+        community_id
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Community not found".to_string()))?;
+
+    Ok(community.owner_id == user_id)
+}
+
+/// Broadcast the full tag list after it changed
+async fn broadcast_tags(state: &AppState, channel_id: Uuid) -> Result<()> {
+    let tags = state.forum_service.list_tags(channel_id).await?;
+
+    state
+        .connections
+        .broadcast_to_channel(
+            channel_id,
+            &ServerMessage::ForumTagsUpdated {
+                channel_id,
+                tags: tags.into_iter().map(to_forum_tag_data).collect(),
+            },
+        )
+        .await;
+
+    Ok(())
+}
+
+/// List the tags of a forum
+/// GET /api/channels/:id/forum/tags
+pub async fn list_tags(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<Uuid>,
+) -> Result<Json<Vec<ForumTagData>>> {
+    require_forum(&state, channel_id, auth.user_id).await?;
+
+    let tags = state.forum_service.list_tags(channel_id).await?;
+
+    Ok(Json(tags.into_iter().map(to_forum_tag_data).collect()))
+}
+
+/// Create a tag in a forum
+/// POST /api/channels/:id/forum/tags
+pub async fn create_tag(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<Uuid>,
+    Json(input): Json<CreateForumTag>,
+) -> Result<Json<ForumTagData>> {
+    let channel = require_forum(&state, channel_id, auth.user_id).await?;
+    if !is_forum_moderator(&state, &channel, auth.user_id).await? {
+        return Err(AppError::Forbidden);
+    }
+
+    let tag = state.forum_service.create_tag(channel_id, &input.name).await?;
+    broadcast_tags(&state, channel_id).await?;
+
+    Ok(Json(to_forum_tag_data(tag)))
+}
+
+/// Delete a forum tag
+/// DELETE /api/forum/tags/:id
+pub async fn delete_tag(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    let tag = state.forum_service.get_tag(id).await?;
+    let channel = require_forum(&state, tag.channel_id, auth.user_id).await?;
+    if !is_forum_moderator(&state, &channel, auth.user_id).await? {
+        return Err(AppError::Forbidden);
+    }
+
+    state.forum_service.delete_tag(id).await?;
+    broadcast_tags(&state, tag.channel_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListPostsQuery {
+    /// Comma-separated tag IDs; posts with any of them are listed
+    pub tags: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// List posts in a forum, most recently active first
+/// GET /api/channels/:id/posts
+pub async fn list_posts(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<Uuid>,
+    Query(query): Query<ListPostsQuery>,
+) -> Result<Json<Vec<ForumPostData>>> {
+    require_forum(&state, channel_id, auth.user_id).await?;
+
+    let tag_ids = query
+        .tags
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.parse::<Uuid>()
+                .map_err(|_| AppError::BadRequest(format!("Invalid tag ID: {}", s)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+    let posts = state
+        .forum_service
+        .list_posts(channel_id, &tag_ids, limit)
+        .await?;
+
+    Ok(Json(posts.into_iter().map(to_forum_post_data).collect()))
+}
+
+/// Create a post in a forum
+/// POST /api/channels/:id/posts
+pub async fn create_post(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<Uuid>,
+    Json(input): Json<CreateForumPost>,
+) -> Result<Json<ForumPostData>> {
+    require_forum(&state, channel_id, auth.user_id).await?;
+    require_bot_permission(&state, &auth, channel_id, CommunityRole::SEND_MESSAGES).await?;
+
+    if input.content.trim().is_empty() {
+        return Err(AppError::BadRequest("Post content cannot be empty".to_string()));
+    }
+
+    let post = state
+        .forum_service
+        .create_post(
+            channel_id,
+            auth.user_id,
+            &input.title,
+            &input.content,
+            &input.tag_ids,
+        )
+        .await?;
+
+    // The author follows their own post so replies show up in their thread list
+    state
+        .thread_service
+        .set_following(post.message_id, auth.user_id, true)
+        .await?;
+
+    let post_data = to_forum_post_data(post);
+    state
+        .connections
+        .broadcast_to_channel(
+            channel_id,
+            &ServerMessage::ForumPostCreated {
+                post: post_data.clone(),
+            },
+        )
+        .await;
+
+    Ok(Json(post_data))
+}
+
+/// Replace the tags of a post
+/// PUT /api/posts/:id/tags
+pub async fn set_post_tags(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(message_id): Path<Uuid>,
+    Json(input): Json<UpdateForumPostTags>,
+) -> Result<Json<ForumPostData>> {
+    let post = state.forum_service.get_post(message_id).await?;
+    let channel = require_forum(&state, post.channel_id, auth.user_id).await?;
+
+    // The author and moderators can retag a post
+    if post.author_id != auth.user_id
+        && !is_forum_moderator(&state, &channel, auth.user_id).await?
+    {
+        return Err(AppError::Forbidden);
+    }
+
+    let post = state
+        .forum_service
+        .set_post_tags(message_id, &input.tag_ids)
+        .await?;
+
+    let post_data = to_forum_post_data(post);
+    state
+        .connections
+        .broadcast_to_channel(
+            post_data.channel_id,
+            &ServerMessage::ForumPostUpdated {
+                post: post_data.clone(),
+            },
+        )
+        .await;
+
+    Ok(Json(post_data))
+}
@@ -0,0 +1,106 @@
+use crate::auth::AuthUser;
+use crate::error::{AppError, Result};
+use crate::models::{ClaimPlaceholderUser, PlaceholderUser};
+use crate::state::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+
+use super::bots::{require_community_owner, require_person};
+
+/// Claims need a real member of the community
+async fn require_member(state: &AppState, community_id: Uuid, user_id: Uuid) -> Result<()> {
+    if state.import_service.is_placeholder(user_id).await? {
+        return Err(AppError::BadRequest(
+            "Placeholders can only be claimed by real accounts".to_string(),
+        ));
+    }
+
+    let is_member = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM community_members WHERE community_id = $1 AND user_id = $2)",
+        community_id,
+        user_id
+    )
+    .fetch_one(&state.db)
+    .await?
+    .unwrap_or(false);
+
+    if !is_member {
+        return Err(AppError::BadRequest(
+            "The account must be a member of the community".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// List the imported authors that haven't been claimed yet, so members can find
+/// their own history and the owner can see who asked for what
+/// GET /api/communities/:id/placeholder-users
+pub async fn list_placeholder_users(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(community_id): Path<Uuid>,
+) -> Result<Json<Vec<PlaceholderUser>>> {
+    require_person(&auth)?;
+    require_member(&state, community_id, auth.user_id).await?;
+
+    let placeholders = state.import_service.list_placeholders(community_id).await?;
+    Ok(Json(placeholders))
+}
+
+/// Ask for an imported author's history to be given to your account. The community
+/// owner approves by claiming the placeholder for you.
+/// POST /api/communities/:id/placeholder-users/:user_id/claim-request
+pub async fn request_placeholder_claim(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((community_id, placeholder_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode> {
+    require_person(&auth)?;
+    require_member(&state, community_id, auth.user_id).await?;
+
+    state
+        .import_service
+        .request_claim(community_id, placeholder_id, auth.user_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Withdraw your request to claim an imported author
+/// DELETE /api/communities/:id/placeholder-users/:user_id/claim-request
+pub async fn cancel_placeholder_claim(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((_community_id, placeholder_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode> {
+    state
+        .import_service
+        .cancel_claim_request(placeholder_id, auth.user_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Approve a member's request: give an imported author's messages and reactions to
+/// the member's account
+/// POST /api/communities/:id/placeholder-users/:user_id/claim
+pub async fn claim_placeholder_user(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((community_id, placeholder_id)): Path<(Uuid, Uuid)>,
+    Json(input): Json<ClaimPlaceholderUser>,
+) -> Result<StatusCode> {
+    require_community_owner(&state, community_id, auth.user_id).await?;
+    require_member(&state, community_id, input.user_id).await?;
+
+    state
+        .import_service
+        .claim(community_id, placeholder_id, input.user_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,611 @@
+use crate::auth::AuthUser;
+use crate::error::{AppError, Result};
+use crate::models::{
+    ChannelType, ClickButton, CommunityRole, CreateMessage, CreateSlashCommand, Interaction,
+    InteractionResponse, InteractionResponseKind, InvokeCommand, SlashCommand, UpdateMessage, User,
+};
+use crate::state::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use miscord_protocol::{
+    ButtonData, ButtonStyle, CommandOptionData, CommandOptionType, InteractionData, InteractionKind,
+    InteractionOptionData, MessageData, SlashCommandData,
+};
+use serde_json::Value;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use super::bots::{require_bot_permission, require_community_owner};
+use super::communities::to_user_data;
+use super::messages::{message_data, validate_embeds};
+
+/// Same as Discord's limits
+const MAX_NAME_LENGTH: usize = 32;
+const MAX_DESCRIPTION_LENGTH: usize = 100;
+const MAX_OPTIONS: usize = 25;
+const MAX_CHOICES: usize = 25;
+const MAX_COMPONENTS: usize = 25;
+const MAX_LABEL_LENGTH: usize = 80;
+const MAX_CUSTOM_ID_LENGTH: usize = 100;
+
+fn to_command_data(command: SlashCommand) -> SlashCommandData {
+    SlashCommandData {
+        id: command.id,
+        community_id: command.community_id,
+        application_id: command.application_id,
+        application_name: command.application_name,
+        name: command.name,
+        description: command.description,
+        options: command.options.0,
+    }
+}
+
+/// Tell a community's members its commands changed
+pub async fn broadcast_commands(state: &AppState, community_id: Uuid) -> Result<()> {
+    let commands = state.interaction_service.list_commands(community_id).await?;
+
+    state
+        .connections
+        .broadcast_to_community(
+            community_id,
+            &miscord_protocol::ServerMessage::CommandsUpdated {
+                community_id,
+                commands: commands.into_iter().map(to_command_data).collect(),
+            },
+        )
+        .await;
+
+    Ok(())
+}
+
+async fn require_member(state: &AppState, community_id: Uuid, user_id: Uuid) -> Result<()> {
+    let is_member = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM community_members WHERE community_id = $1 AND user_id = $2)",
+        community_id,
+        user_id
+    )
+    .fetch_one(&state.db)
+    .await?
+    .unwrap_or(false);
+
+    if !is_member {
+        return Err(AppError::Forbidden);
+    }
+    Ok(())
+}
+
+/// Lowercase letters, digits, '-' and '_', as typed after the slash
+fn validate_name(name: &str, what: &str) -> Result<()> {
+    let valid_chars = name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH || !valid_chars {
+        return Err(AppError::BadRequest(format!(
+            "{} names must be 1 to {} lowercase letters, digits, '-' or '_'",
+            what, MAX_NAME_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+fn validate_description(description: &str) -> Result<()> {
+    if description.trim().is_empty() || description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "Descriptions must be 1 to {} characters",
+            MAX_DESCRIPTION_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+fn validate_command(input: &CreateSlashCommand) -> Result<()> {
+    validate_name(&input.name, "Command")?;
+    validate_description(&input.description)?;
+
+    if input.options.len() > MAX_OPTIONS {
+        return Err(AppError::BadRequest(format!(
+            "Commands can have at most {} options",
+            MAX_OPTIONS
+        )));
+    }
+
+    let mut names = HashSet::new();
+    let mut seen_optional = false;
+    for option in &input.options {
+        validate_name(&option.name, "Option")?;
+        validate_description(&option.description)?;
+
+        if !names.insert(option.name.as_str()) {
+            return Err(AppError::BadRequest(format!("Duplicate option {}", option.name)));
+        }
+        if option.required && seen_optional {
+            return Err(AppError::BadRequest(
+                "Required options must come before optional ones".to_string(),
+            ));
+        }
+        seen_optional |= !option.required;
+
+        if !option.choices.is_empty() && option.kind != CommandOptionType::String {
+            return Err(AppError::BadRequest("Only string options can have choices".to_string()));
+        }
+        if option.choices.len() > MAX_CHOICES
+            || option.choices.iter().any(|c| c.is_empty() || c.chars().count() > MAX_DESCRIPTION_LENGTH)
+        {
+            return Err(AppError::BadRequest(format!(
+                "Options can have at most {} choices of 1 to {} characters",
+                MAX_CHOICES, MAX_DESCRIPTION_LENGTH
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_components(components: &[ButtonData]) -> Result<()> {
+    if components.len() > MAX_COMPONENTS {
+        return Err(AppError::BadRequest(format!(
+            "Messages can have at most {} buttons",
+            MAX_COMPONENTS
+        )));
+    }
+
+    let mut custom_ids = HashSet::new();
+    for button in components {
+        let label_length = button.label.trim().chars().count();
+        if label_length == 0 || label_length > MAX_LABEL_LENGTH {
+            return Err(AppError::BadRequest(format!(
+                "Button labels must be 1 to {} characters",
+                MAX_LABEL_LENGTH
+            )));
+        }
+
+        if button.style == ButtonStyle::Link {
+            let valid_url = button
+                .url
+                .as_deref()
+                .is_some_and(|url| url.starts_with("https://") || url.starts_with("http://"));
+            if !valid_url || button.custom_id.is_some() {
+                return Err(AppError::BadRequest(
+                    "Link buttons need an http(s) URL and no custom ID".to_string(),
+                ));
+            }
+            continue;
+        }
+
+        let custom_id = match button.custom_id.as_deref() {
+            Some(id) if !id.is_empty() && id.chars().count() <= MAX_CUSTOM_ID_LENGTH => id,
+            _ => {
+                return Err(AppError::BadRequest(format!(
+                    "Buttons need a custom ID of 1 to {} characters",
+                    MAX_CUSTOM_ID_LENGTH
+                )));
+            }
+        };
+        if button.url.is_some() {
+            return Err(AppError::BadRequest("Only link buttons can have a URL".to_string()));
+        }
+        if !custom_ids.insert(custom_id) {
+            return Err(AppError::BadRequest(format!("Duplicate button custom ID {}", custom_id)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check one option value against its type, normalizing strings typed for numbers,
+/// booleans, users and channels
+async fn resolve_option(
+    state: &AppState,
+    community_id: Uuid,
+    option: &CommandOptionData,
+    value: Value,
+) -> Result<Value> {
+    let invalid = || AppError::BadRequest(format!("Invalid value for option {}", option.name));
+    let text = value.as_str().map(str::trim);
+
+    let resolved = match option.kind {
+        CommandOptionType::String => {
+            let text = text.filter(|t| !t.is_empty()).ok_or_else(invalid)?;
+            if !option.choices.is_empty() && !option.choices.iter().any(|c| c == text) {
+                return Err(AppError::BadRequest(format!(
+                    "Option {} must be one of: {}",
+                    option.name,
+                    option.choices.join(", ")
+                )));
+            }
+            Value::from(text)
+        }
+        CommandOptionType::Integer => value
+            .as_i64()
+            .or_else(|| text.and_then(|t| t.parse().ok()))
+            .map(Value::from)
+            .ok_or_else(invalid)?,
+        CommandOptionType::Number => value
+            .as_f64()
+            .or_else(|| text.and_then(|t| t.parse().ok()))
+            .filter(|n| n.is_finite())
+            .map(Value::from)
+            .ok_or_else(invalid)?,
+        CommandOptionType::Boolean => value
+            .as_bool()
+            .or_else(|| text.and_then(|t| t.parse().ok()))
+            .map(Value::from)
+            .ok_or_else(invalid)?,
+        CommandOptionType::User => {
+            let user_id: Uuid = text.and_then(|t| t.parse().ok()).ok_or_else(invalid)?;
+            state.user_service.get_by_id(user_id).await?;
+            Value::from(user_id.to_string())
+        }
+        CommandOptionType::Channel => {
+            let channel_id: Uuid = text.and_then(|t| t.parse().ok()).ok_or_else(invalid)?;
+            let channel = state.channel_service.get_by_id(channel_id).await?;
+            if channel.community_id != Some(community_id) {
+                return Err(invalid());
+            }
+            Value::from(channel_id.to_string())
+        }
+    };
+
+    Ok(resolved)
+}
+
+/// Check the given options against the command's, in the command's order
+async fn resolve_options(
+    state: &AppState,
+    command: &SlashCommand,
+    given: Vec<InteractionOptionData>,
+) -> Result<Vec<InteractionOptionData>> {
+    if let Some(unknown) = given
+        .iter()
+        .find(|g| !command.options.iter().any(|o| o.name == g.name))
+    {
+        return Err(AppError::BadRequest(format!("Unknown option {}", unknown.name)));
+    }
+
+    let mut resolved = Vec::new();
+    for option in command.options.iter() {
+        match given.iter().find(|g| g.name == option.name) {
+            Some(g) => resolved.push(InteractionOptionData {
+                name: option.name.clone(),
+                value: resolve_option(state, command.community_id, option, g.value.clone()).await?,
+            }),
+            None if option.required => {
+                return Err(AppError::BadRequest(format!("Missing option {}", option.name)));
+            }
+            None => {}
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Send an interaction to its application's connections
+async fn deliver(state: &AppState, interaction: InteractionData) -> Result<()> {
+    let application_id = interaction.application_id;
+    if !state.connections.is_user_online(application_id).await {
+        return Err(AppError::Conflict("The application is not connected".to_string()));
+    }
+
+    state
+        .connections
+        .send_to_user(
+            application_id,
+            &miscord_protocol::ServerMessage::InteractionCreated { interaction },
+        )
+        .await;
+
+    Ok(())
+}
+
+fn to_interaction_data(
+    interaction: &Interaction,
+    kind: InteractionKind,
+    community_id: Option<Uuid>,
+    user: User,
+) -> InteractionData {
+    InteractionData {
+        id: interaction.id,
+        kind,
+        application_id: interaction.application_id,
+        community_id,
+        channel_id: interaction.channel_id,
+        user: to_user_data(user.into()),
+        command_name: None,
+        options: Vec::new(),
+        message_id: interaction.message_id,
+        custom_id: None,
+        created_at: interaction.created_at,
+    }
+}
+
+/// List a community's slash commands
+/// GET /api/communities/:id/commands
+pub async fn list_commands(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(community_id): Path<Uuid>,
+) -> Result<Json<Vec<SlashCommandData>>> {
+    require_member(&state, community_id, auth.user_id).await?;
+
+    let commands = state.interaction_service.list_commands(community_id).await?;
+
+    Ok(Json(commands.into_iter().map(to_command_data).collect()))
+}
+
+/// Register a slash command, or replace the caller's command of the same name.
+/// Bots in the community and the community owner (for integrations) can register commands.
+/// POST /api/communities/:id/commands
+pub async fn register_command(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(community_id): Path<Uuid>,
+    Json(input): Json<CreateSlashCommand>,
+) -> Result<Json<SlashCommandData>> {
+    require_member(&state, community_id, auth.user_id).await?;
+    if !auth.bot {
+        require_community_owner(&state, community_id, auth.user_id).await?;
+    }
+
+    validate_command(&input)?;
+
+    let command = state
+        .interaction_service
+        .upsert_command(community_id, auth.user_id, input)
+        .await?;
+
+    broadcast_commands(&state, community_id).await?;
+
+    Ok(Json(to_command_data(command)))
+}
+
+/// Remove a slash command (its application or the community owner)
+/// DELETE /api/commands/:id
+pub async fn delete_command(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    let command = state.interaction_service.get_command(id).await?;
+
+    if command.application_id != auth.user_id {
+        require_community_owner(&state, command.community_id, auth.user_id).await?;
+    }
+
+    state.interaction_service.delete_command(id).await?;
+    broadcast_commands(&state, command.community_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Invoke a slash command in a channel; the application answers through
+/// the interaction response endpoint
+/// POST /api/channels/:id/interactions
+pub async fn invoke_command(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<Uuid>,
+    Json(input): Json<InvokeCommand>,
+) -> Result<(StatusCode, Json<InteractionData>)> {
+    if !state.channel_service.user_has_access(channel_id, auth.user_id).await? {
+        return Err(AppError::Forbidden);
+    }
+
+    let channel = state.channel_service.get_by_id(channel_id).await?;
+    if matches!(
+        channel.channel_type,
+        ChannelType::Voice | ChannelType::Category | ChannelType::Forum
+    ) {
+        return Err(AppError::BadRequest(
+            "Commands can only be used in text channels".to_string(),
+        ));
+    }
+
+    let command = state.interaction_service.get_command(input.command_id).await?;
+    if channel.community_id != Some(command.community_id) {
+        return Err(AppError::NotFound("Command not found".to_string()));
+    }
+
+    let options = resolve_options(&state, &command, input.options).await?;
+    let user = state.user_service.get_by_id(auth.user_id).await?;
+
+    let interaction = state
+        .interaction_service
+        .create_interaction(command.application_id, auth.user_id, channel_id, None)
+        .await?;
+
+    let mut data = to_interaction_data(&interaction, InteractionKind::Command, channel.community_id, user);
+    data.command_name = Some(command.name);
+    data.options = options;
+
+    deliver(&state, data.clone()).await?;
+
+    Ok((StatusCode::ACCEPTED, Json(data)))
+}
+
+/// Click a button on a message; the click goes to the bot that posted it
+/// POST /api/messages/:id/interactions
+pub async fn click_button(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(message_id): Path<Uuid>,
+    Json(input): Json<ClickButton>,
+) -> Result<(StatusCode, Json<InteractionData>)> {
+    let message = state.message_service.get_by_id(message_id).await?;
+    if !state
+        .channel_service
+        .user_has_access(message.channel_id, auth.user_id)
+        .await?
+    {
+        return Err(AppError::Forbidden);
+    }
+
+    let components = state
+        .interaction_service
+        .get_components(&[message_id])
+        .await?
+        .remove(&message_id)
+        .unwrap_or_default();
+    let clickable = components
+        .iter()
+        .any(|b| !b.disabled && b.custom_id.as_deref() == Some(input.custom_id.as_str()));
+    if !clickable {
+        return Err(AppError::NotFound("Button not found".to_string()));
+    }
+
+    let channel = state.channel_service.get_by_id(message.channel_id).await?;
+    let user = state.user_service.get_by_id(auth.user_id).await?;
+
+    let interaction = state
+        .interaction_service
+        .create_interaction(message.author_id, auth.user_id, message.channel_id, Some(message_id))
+        .await?;
+
+    let mut data = to_interaction_data(&interaction, InteractionKind::Component, channel.community_id, user);
+    data.custom_id = Some(input.custom_id);
+
+    deliver(&state, data.clone()).await?;
+
+    Ok((StatusCode::ACCEPTED, Json(data)))
+}
+
+/// Answer an interaction with a new message (public or only for the invoker),
+/// or by editing the message whose button was clicked. Each interaction takes one response.
+/// POST /api/interactions/:id/response
+pub async fn respond(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(input): Json<InteractionResponse>,
+) -> Result<StatusCode> {
+    let interaction = state.interaction_service.get_interaction(id).await?;
+    if interaction.application_id != auth.user_id {
+        return Err(AppError::NotFound("Interaction not found".to_string()));
+    }
+
+    if let Some(components) = &input.components {
+        validate_components(components)?;
+    }
+    if let Some(embeds) = &input.embeds {
+        validate_embeds(embeds)?;
+    }
+
+    match input.kind {
+        InteractionResponseKind::ChannelMessage => {
+            let content = input.content.unwrap_or_default();
+            let embeds = input.embeds.unwrap_or_default();
+            let components = input.components.unwrap_or_default();
+            if content.trim().is_empty() && embeds.is_empty() {
+                return Err(AppError::BadRequest("Responses need content or embeds".to_string()));
+            }
+
+            if input.ephemeral {
+                // Ephemeral messages aren't stored, so there is nothing for a click to refer to
+                if !components.is_empty() {
+                    return Err(AppError::BadRequest(
+                        "Ephemeral responses cannot have buttons".to_string(),
+                    ));
+                }
+                state.interaction_service.mark_responded(id).await?;
+
+                let application = state.user_service.get_by_id(auth.user_id).await?;
+                let message = MessageData {
+                    id: Uuid::new_v4(),
+                    channel_id: interaction.channel_id,
+                    author_id: application.id,
+                    author_name: application.display_name,
+                    content,
+                    edited_at: None,
+                    reply_to_id: None,
+                    reactions: vec![],
+                    attachments: vec![],
+                    created_at: Utc::now(),
+                    thread_parent_id: None,
+                    reply_count: 0,
+                    last_reply_at: None,
+                    pinned_at: None,
+                    pinned_by: None,
+                    forwarded_from: None,
+                    published_at: None,
+                    webhook: None,
+                    embeds,
+                    components: Vec::new(),
+                    ephemeral: true,
+                };
+                state
+                    .connections
+                    .send_to_user(
+                        interaction.user_id,
+                        &miscord_protocol::ServerMessage::MessageCreated { message },
+                    )
+                    .await;
+
+                return Ok(StatusCode::NO_CONTENT);
+            }
+
+            require_bot_permission(&state, &auth, interaction.channel_id, CommunityRole::SEND_MESSAGES)
+                .await?;
+
+            let message = state
+                .interaction_service
+                .respond_with_message(
+                    id,
+                    interaction.channel_id,
+                    auth.user_id,
+                    CreateMessage {
+                        content,
+                        reply_to_id: None,
+                        attachment_ids: Vec::new(),
+                        embeds,
+                    },
+                    &components,
+                )
+                .await?;
+
+            let message = message_data(&state, message, auth.user_id).await;
+            state
+                .connections
+                .broadcast_to_channel(
+                    interaction.channel_id,
+                    &miscord_protocol::ServerMessage::MessageCreated { message },
+                )
+                .await;
+        }
+        InteractionResponseKind::UpdateMessage => {
+            let message_id = interaction.message_id.ok_or_else(|| {
+                AppError::BadRequest("Only button clicks can update their message".to_string())
+            })?;
+            if input.content.as_deref().is_some_and(|c| c.trim().is_empty()) {
+                return Err(AppError::BadRequest("Messages need content".to_string()));
+            }
+
+            state
+                .interaction_service
+                .respond_with_update(
+                    id,
+                    message_id,
+                    auth.user_id,
+                    input.content.map(|content| UpdateMessage { content }),
+                    input.embeds.as_deref(),
+                    input.components.as_deref(),
+                )
+                .await?;
+
+            let message = state.message_service.get_by_id(message_id).await?;
+
+            let message = message_data(&state, message, auth.user_id).await;
+            state
+                .connections
+                .broadcast_to_channel(
+                    interaction.channel_id,
+                    &miscord_protocol::ServerMessage::MessageUpdated { message },
+                )
+                .await;
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
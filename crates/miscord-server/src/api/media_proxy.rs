@@ -0,0 +1,93 @@
+use crate::auth::AuthUser;
+use crate::error::{AppError, Result};
+use crate::safe_http;
+use crate::services::media_proxy::{process_proxied_image, MAX_PROXY_IMAGE_SIZE};
+use crate::state::AppState;
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::Response,
+};
+use serde::Deserialize;
+use std::time::Duration;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Display size used when the client doesn't ask for one
+const DEFAULT_SIZE: u32 = 400;
+
+const MIN_SIZE: u32 = 16;
+const MAX_SIZE: u32 = 1600;
+
+#[derive(Debug, Deserialize)]
+pub struct MediaProxyQuery {
+    pub url: String,
+    /// Largest width or height to return, in pixels
+    pub size: Option<u32>,
+}
+
+fn image_response(data: Vec<u8>, content_type: &str) -> Result<Response> {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_LENGTH, data.len())
+        .header(header::CACHE_CONTROL, "private, max-age=86400")
+        .header("X-Content-Type-Options", "nosniff")
+        .body(Body::from(data))
+        .map_err(|e| AppError::Internal(e.into()))
+}
+
+/// Fetch an external image on the user's behalf, so the site hosting it never sees their IP
+/// GET /api/media-proxy?url=...&size=...
+pub async fn proxy_media(
+    State(state): State<AppState>,
+    _auth: AuthUser,
+    Query(query): Query<MediaProxyQuery>,
+) -> Result<Response> {
+    let url = safe_http::check_url(&query.url)?;
+    let size = query.size.unwrap_or(DEFAULT_SIZE).clamp(MIN_SIZE, MAX_SIZE);
+
+    if let Some((data, content_type)) = state.media_proxy_service.get_cached(&query.url, size).await {
+        return image_response(data, content_type);
+    }
+
+    let client = safe_http::client(FETCH_TIMEOUT)?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Failed to fetch image: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(AppError::BadRequest(format!(
+            "Image URL returned status {}",
+            response.status()
+        )));
+    }
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_PROXY_IMAGE_SIZE as u64)
+    {
+        return Err(AppError::BadRequest("Image is too large".to_string()));
+    }
+
+    // Read one byte past the limit to tell a body that fits from one that doesn't
+    let data = safe_http::read_prefix(response, MAX_PROXY_IMAGE_SIZE + 1)
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Failed to read image: {}", e)))?;
+    if data.len() > MAX_PROXY_IMAGE_SIZE {
+        return Err(AppError::BadRequest("Image is too large".to_string()));
+    }
+
+    // Decoding and resizing are CPU-bound
+    let (data, content_type) = tokio::task::spawn_blocking(move || process_proxied_image(&data, size))
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Image processing panicked: {}", e)))??;
+
+    if let Err(e) = state.media_proxy_service.store(&query.url, size, &data).await {
+        tracing::warn!("Failed to cache proxied image {}: {}", query.url, e);
+    }
+
+    image_response(data, content_type)
+}
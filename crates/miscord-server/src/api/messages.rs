@@ -1,32 +1,399 @@
 use crate::auth::AuthUser;
-use crate::error::Result;
-use crate::models::{CreateMessage, UpdateMessage};
+use crate::error::{AppError, Result};
+use crate::models::{
+    AttachmentOwner, ChannelType, CommunityRole, CreateMessage, ForwardMessage, Message, MessageAttachment,
+    UpdateMessage,
+};
+use crate::services::emoji::{is_custom_emoji_key, parse_custom_emoji};
+use crate::services::quota;
 use crate::state::AppState;
 use axum::{
     extract::{Path, Query, State},
+    http::StatusCode,
     Json,
 };
-use miscord_protocol::MessageData;
+use chrono::{DateTime, Utc};
+use miscord_protocol::{EmbedData, MessageData};
 use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+use super::bots::{require_bot_permission, require_community_owner};
+use super::webhooks::to_webhook_author_data;
+
+/// Same as Discord's limits
+const MAX_EMBEDS: usize = 10;
+const MAX_EMBED_FIELDS: usize = 25;
+const MAX_EMBED_TITLE_LENGTH: usize = 256;
+const MAX_EMBED_DESCRIPTION_LENGTH: usize = 4096;
+const MAX_EMBED_FIELD_NAME_LENGTH: usize = 256;
+const MAX_EMBED_FIELD_VALUE_LENGTH: usize = 1024;
+const MAX_EMBED_FOOTER_LENGTH: usize = 2048;
+/// Total text across all embeds of a message
+const MAX_EMBED_TOTAL_LENGTH: usize = 6000;
+
+fn check_length(text: &str, max: usize, what: &str) -> Result<usize> {
+    let length = text.chars().count();
+    if length > max {
+        return Err(AppError::BadRequest(format!("Embed {} must be at most {} characters", what, max)));
+    }
+    Ok(length)
+}
+
+fn check_embed_url(url: &str, what: &str) -> Result<()> {
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(AppError::BadRequest(format!("Embed {} must be an http(s) URL", what)));
+    }
+    Ok(())
+}
+
+/// Check embeds against the size limits before they're stored
+pub fn validate_embeds(embeds: &[EmbedData]) -> Result<()> {
+    if embeds.len() > MAX_EMBEDS {
+        return Err(AppError::BadRequest(format!("At most {} embeds are allowed", MAX_EMBEDS)));
+    }
+
+    let mut total = 0;
+    for embed in embeds {
+        if let Some(title) = &embed.title {
+            total += check_length(title, MAX_EMBED_TITLE_LENGTH, "titles")?;
+        }
+        if let Some(description) = &embed.description {
+            total += check_length(description, MAX_EMBED_DESCRIPTION_LENGTH, "descriptions")?;
+        }
+        if let Some(footer) = &embed.footer {
+            total += check_length(footer, MAX_EMBED_FOOTER_LENGTH, "footers")?;
+        }
+
+        if embed.fields.len() > MAX_EMBED_FIELDS {
+            return Err(AppError::BadRequest(format!(
+                "Embeds can have at most {} fields",
+                MAX_EMBED_FIELDS
+            )));
+        }
+        for field in &embed.fields {
+            if field.name.trim().is_empty() || field.value.trim().is_empty() {
+                return Err(AppError::BadRequest("Embed fields need a name and a value".to_string()));
+            }
+            total += check_length(&field.name, MAX_EMBED_FIELD_NAME_LENGTH, "field names")?;
+            total += check_length(&field.value, MAX_EMBED_FIELD_VALUE_LENGTH, "field values")?;
+        }
+
+        if let Some(url) = &embed.url {
+            check_embed_url(url, "URLs")?;
+        }
+        if let Some(url) = &embed.thumbnail_url {
+            check_embed_url(url, "thumbnails")?;
+        }
+        if let Some(url) = &embed.image_url {
+            check_embed_url(url, "images")?;
+        }
+        if embed.color.is_some_and(|color| color > 0xFFFFFF) {
+            return Err(AppError::BadRequest("Embed colors must be 0xRRGGBB values".to_string()));
+        }
+
+        let is_empty = embed.title.as_deref().is_none_or(str::is_empty)
+            && embed.description.as_deref().is_none_or(str::is_empty)
+            && embed.fields.is_empty()
+            && embed.thumbnail_url.is_none()
+            && embed.image_url.is_none();
+        if is_empty {
+            return Err(AppError::BadRequest("Embeds need a title, description, fields or an image".to_string()));
+        }
+    }
+
+    if total > MAX_EMBED_TOTAL_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "Embeds of a message must have at most {} characters in total",
+            MAX_EMBED_TOTAL_LENGTH
+        )));
+    }
+
+    Ok(())
+}
+
+/// Only bots and webhooks post embeds
+fn check_embeds_allowed(auth: &AuthUser, embeds: &[EmbedData]) -> Result<()> {
+    if embeds.is_empty() {
+        return Ok(());
+    }
+    if !auth.bot {
+        return Err(AppError::BadRequest("Only bots can send embeds".to_string()));
+    }
+    validate_embeds(embeds)
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ListMessagesQuery {
+    /// Messages older than this message
     pub before: Option<Uuid>,
+    /// Messages newer than this message
+    pub after: Option<Uuid>,
+    /// Messages on both sides of this message, including it
+    pub around: Option<Uuid>,
     pub limit: Option<i64>,
 }
 
+/// Resolve the origin of forwarded messages for display, in one query for all of them.
+/// The source channel is only named to a `viewer` who can access it, except on published
+/// announcements, which credit their source on purpose. Without a viewer, as for a broadcast,
+/// it's named when the copy is in the same community, since every member can see it.
+async fn forwarded_from_map(
+    state: &AppState,
+    messages: &[Message],
+    viewer: Option<Uuid>,
+) -> HashMap<Uuid, miscord_protocol::ForwardedFromData> {
+    let forwarded: Vec<&Message> = messages
+        .iter()
+        .filter(|m| m.forwarded_from_created_at.is_some())
+        .collect();
+    if forwarded.is_empty() {
+        return HashMap::new();
+    }
+
+    let ids: Vec<Uuid> = forwarded.iter().map(|m| m.id).collect();
+    let rows = match sqlx::query!(
+        r#"
+        SELECT m.id,
+               u.display_name AS "author_name?",
+               src.name AS "channel_name?",
+               src.community_id AS "source_community_id?",
+               sc.name AS "source_community_name?",
+               dst.community_id AS "community_id?",
+               EXISTS(SELECT 1 FROM message_crossposts x WHERE x.message_id = m.id) AS "published!"
+        FROM messages m
+        JOIN channels dst ON dst.id = m.channel_id
+        LEFT JOIN users u ON u.id = m.forwarded_from_author_id
+        LEFT JOIN channels src ON src.id = m.forwarded_from_channel_id
+        LEFT JOIN communities sc ON sc.id = src.community_id
+        WHERE m.id = ANY($1)
+        "#,
+        &ids
+    )
+    .fetch_all(&state.db)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::warn!("Failed to look up forwarded messages: {}", e);
+            return HashMap::new();
+        }
+    };
+    let rows: HashMap<Uuid, _> = rows.into_iter().map(|row| (row.id, row)).collect();
+
+    let accessible = match viewer {
+        Some(viewer) => {
+            let source_ids: Vec<Uuid> = forwarded
+                .iter()
+                .filter_map(|m| m.forwarded_from_channel_id)
+                .collect();
+            state
+                .channel_service
+                .accessible_channel_ids(&source_ids, viewer)
+                .await
+                .unwrap_or_default()
+        }
+        None => HashSet::new(),
+    };
+
+    let mut result = HashMap::with_capacity(forwarded.len());
+    for message in forwarded {
+        let (Some(created_at), Some(row)) = (message.forwarded_from_created_at, rows.get(&message.id)) else {
+            continue;
+        };
+
+        let visible = row.published
+            || match (viewer, message.forwarded_from_channel_id) {
+                (Some(_), Some(channel_id)) => accessible.contains(&channel_id),
+                (None, Some(_)) => row.source_community_id.is_some() && row.source_community_id == row.community_id,
+                (_, None) => false,
+            };
+
+        // Name the source community when the message came from another one
+        let community_name = if visible && row.source_community_id != row.community_id {
+            row.source_community_name.clone()
+        } else {
+            None
+        };
+
+        result.insert(
+            message.id,
+            miscord_protocol::ForwardedFromData {
+                message_id: message.forwarded_from_message_id,
+                channel_id: message.forwarded_from_channel_id.filter(|_| visible),
+                channel_name: row.channel_name.clone().filter(|_| visible),
+                author_id: message.forwarded_from_author_id,
+                author_name: row.author_name.clone().unwrap_or_else(|| "Unknown".to_string()),
+                created_at,
+                community_name,
+                published: row.published,
+            },
+        );
+    }
+
+    result
+}
+
+/// Resolve the origin of a single forwarded message, as `forwarded_from_map` does
+async fn forwarded_from_data(
+    state: &AppState,
+    message: &Message,
+    viewer: Option<Uuid>,
+) -> Option<miscord_protocol::ForwardedFromData> {
+    forwarded_from_map(state, std::slice::from_ref(message), viewer)
+        .await
+        .remove(&message.id)
+}
+
+/// Name and avatar the message was posted with, if it came through a webhook
+async fn webhook_author(state: &AppState, message_id: Uuid) -> Option<miscord_protocol::WebhookAuthorData> {
+    state
+        .webhook_service
+        .get_messages(&[message_id])
+        .await
+        .ok()
+        .and_then(|mut messages| messages.remove(&message_id))
+        .map(to_webhook_author_data)
+}
+
+/// Buttons a bot attached to the message
+async fn message_components(state: &AppState, message_id: Uuid) -> Vec<miscord_protocol::ButtonData> {
+    state
+        .interaction_service
+        .get_components(&[message_id])
+        .await
+        .ok()
+        .and_then(|mut components| components.remove(&message_id))
+        .unwrap_or_default()
+}
+
+/// Rich embeds stored with the message
+async fn message_embeds(state: &AppState, message_id: Uuid) -> Vec<EmbedData> {
+    state
+        .message_service
+        .get_embeds(&[message_id])
+        .await
+        .ok()
+        .and_then(|mut embeds| embeds.remove(&message_id))
+        .unwrap_or_default()
+}
+
+/// When an announcement was published, if it was
+async fn published_at(state: &AppState, message_id: Uuid) -> Option<DateTime<Utc>> {
+    state
+        .announcement_service
+        .get_published_at(&[message_id])
+        .await
+        .ok()
+        .and_then(|mut published| published.remove(&message_id))
+}
+
+/// Everything the client shows for a stored message, as seen by `user_id`
+pub async fn message_data(state: &AppState, message: Message, user_id: Uuid) -> MessageData {
+    // Get author name
+    let author_name = state
+        .user_service
+        .get_by_id(message.author_id)
+        .await
+        .map(|u| u.display_name)
+        .unwrap_or_else(|_| "Unknown".to_string());
+
+    // Get reactions, marking the ones by `user_id`
+    let reactions = state
+        .message_service
+        .get_reactions(message.id, user_id)
+        .await
+        .map(|r| {
+            r.into_iter()
+                .map(|(emoji, user_ids, reacted_by_me)| miscord_protocol::ReactionData {
+                    emoji,
+                    user_ids,
+                    reacted_by_me,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Get attachments for the message
+    let attachments = state
+        .attachment_service
+        .get_by_message_id(message.id)
+        .await
+        .map(|atts| {
+            atts.into_iter()
+                .map(miscord_protocol::AttachmentData::from)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Get pinned_by display name if message is pinned
+    let pinned_by = if let Some(pinned_by_id) = message.pinned_by_id {
+        state.user_service.get_by_id(pinned_by_id).await.ok().map(|u| u.display_name)
+    } else {
+        None
+    };
+
+    let webhook = webhook_author(state, message.id).await;
+    let embeds = message_embeds(state, message.id).await;
+    let components = message_components(state, message.id).await;
+    let forwarded_from = forwarded_from_data(state, &message, Some(user_id)).await;
+    MessageData {
+        id: message.id,
+        channel_id: message.channel_id,
+        author_id: message.author_id,
+        author_name: webhook.as_ref().map_or(author_name, |w| w.username.clone()),
+        content: message.content,
+        edited_at: message.edited_at,
+        reply_to_id: message.reply_to_id,
+        reactions,
+        attachments,
+        created_at: message.created_at,
+        thread_parent_id: message.thread_parent_id,
+        reply_count: message.reply_count,
+        last_reply_at: message.last_reply_at,
+        pinned_at: message.pinned_at,
+        pinned_by,
+        forwarded_from,
+        published_at: published_at(state, message.id).await,
+        webhook,
+        embeds,
+        components,
+        ephemeral: false,
+    }
+}
+
 pub async fn list_messages(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(channel_id): Path<Uuid>,
     Query(query): Query<ListMessagesQuery>,
 ) -> Result<Json<Vec<MessageData>>> {
-    let limit = query.limit.unwrap_or(50).min(100);
-    let messages = state
-        .message_service
-        .list_by_channel(channel_id, query.before, limit)
-        .await?;
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+    let messages = match (query.before, query.after, query.around) {
+        (before, None, None) => {
+            state
+                .message_service
+                .list_by_channel(channel_id, before, limit)
+                .await?
+        }
+        (None, Some(after), None) => {
+            state
+                .message_service
+                .list_after(channel_id, after, limit)
+                .await?
+        }
+        (None, None, Some(around)) => {
+            state
+                .message_service
+                .list_around(channel_id, around, limit)
+                .await?
+        }
+        _ => {
+            return Err(AppError::BadRequest(
+                "Only one of before, after and around may be given".to_string(),
+            ));
+        }
+    };
 
     // Get message IDs for batch reaction and attachment lookup
     let message_ids: Vec<Uuid> = messages.iter().map(|m| m.id).collect();
@@ -38,6 +405,34 @@ pub async fn list_messages(
         .await
         .unwrap_or_default();
 
+    // Get publish times of announcements in one query
+    let published_map = state
+        .announcement_service
+        .get_published_at(&message_ids)
+        .await
+        .unwrap_or_default();
+
+    // Get webhook names and avatars in one query
+    let mut webhook_map = state
+        .webhook_service
+        .get_messages(&message_ids)
+        .await
+        .unwrap_or_default();
+
+    // Get bot buttons in one query
+    let mut components_map = state
+        .interaction_service
+        .get_components(&message_ids)
+        .await
+        .unwrap_or_default();
+
+    // Get embeds in one query
+    let mut embeds_map = state
+        .message_service
+        .get_embeds(&message_ids)
+        .await
+        .unwrap_or_default();
+
     // Get attachments for all messages in one query
     let attachments_list = state
         .attachment_service
@@ -53,17 +448,12 @@ pub async fn list_messages(
             attachments_map
                 .entry(message_id)
                 .or_default()
-                .push(miscord_protocol::AttachmentData {
-                    id: att.id,
-                    filename: att.filename,
-                    content_type: att.content_type,
-                    size_bytes: att.size_bytes,
-                    url: att.url,
-                });
+                .push(att.into());
         }
     }
 
     // Convert to MessageData with author names, reactions, and attachments
+    let mut forwarded_map = forwarded_from_map(&state, &messages, Some(auth.user_id)).await;
     let mut result = Vec::with_capacity(messages.len());
     for msg in messages {
         let author_name = state
@@ -97,11 +487,15 @@ pub async fn list_messages(
             None
         };
 
+        let webhook = webhook_map.remove(&msg.id).map(to_webhook_author_data);
+        let embeds = embeds_map.remove(&msg.id).unwrap_or_default();
+        let components = components_map.remove(&msg.id).unwrap_or_default();
+        let forwarded_from = forwarded_map.remove(&msg.id);
         result.push(MessageData {
             id: msg.id,
             channel_id: msg.channel_id,
             author_id: msg.author_id,
-            author_name,
+            author_name: webhook.as_ref().map_or(author_name, |w| w.username.clone()),
             content: msg.content,
             edited_at: msg.edited_at,
             reply_to_id: msg.reply_to_id,
@@ -113,6 +507,12 @@ pub async fn list_messages(
             last_reply_at: msg.last_reply_at,
             pinned_at: msg.pinned_at,
             pinned_by,
+            forwarded_from,
+            published_at: published_map.get(&msg.id).copied(),
+            webhook,
+            embeds,
+            components,
+            ephemeral: false,
         });
     }
 
@@ -125,9 +525,30 @@ pub async fn create_message(
     Path(channel_id): Path<Uuid>,
     Json(input): Json<CreateMessage>,
 ) -> Result<Json<MessageData>> {
+    // Forums only take titled posts, which go through the forum endpoints
+    let channel = state.channel_service.get_by_id(channel_id).await?;
+    if matches!(channel.channel_type, ChannelType::Forum | ChannelType::Category) {
+        return Err(AppError::BadRequest(
+            "Messages cannot be posted directly in this channel".to_string(),
+        ));
+    }
+    require_bot_permission(&state, &auth, channel_id, CommunityRole::SEND_MESSAGES).await?;
+    check_embeds_allowed(&auth, &input.embeds)?;
+
     // Extract attachment_ids before passing to service
     let attachment_ids = input.attachment_ids.clone();
 
+    // Only the sender's own unsent uploads may be attached, checked before the message exists
+    let attachment_owner = AttachmentOwner {
+        message_id: None,
+        uploader_id: auth.user_id,
+        community_id: channel.community_id,
+    };
+    state
+        .attachment_service
+        .check_linkable(&attachment_ids, &attachment_owner)
+        .await?;
+
     let message = state
         .message_service
         .create(channel_id, auth.user_id, input)
@@ -137,7 +558,13 @@ pub async fn create_message(
     let attachments = if !attachment_ids.is_empty() {
         state
             .attachment_service
-            .link_to_message(&attachment_ids, message.id)
+            .link_to_message(
+                &attachment_ids,
+                &AttachmentOwner {
+                    message_id: Some(message.id),
+                    ..attachment_owner
+                },
+            )
             .await?;
 
         // Fetch the linked attachments
@@ -147,13 +574,7 @@ pub async fn create_message(
             .await
             .map(|atts| {
                 atts.into_iter()
-                    .map(|a| miscord_protocol::AttachmentData {
-                        id: a.id,
-                        filename: a.filename,
-                        content_type: a.content_type,
-                        size_bytes: a.size_bytes,
-                        url: a.url,
-                    })
+                    .map(miscord_protocol::AttachmentData::from)
                     .collect()
             })
             .unwrap_or_default()
@@ -185,6 +606,12 @@ pub async fn create_message(
         last_reply_at: message.last_reply_at,
         pinned_at: None, // New messages are not pinned
         pinned_by: None,
+        forwarded_from: None,
+        published_at: None,
+        webhook: None,
+        embeds,
+        components: Vec::new(),
+        ephemeral: false,
     };
 
     // Broadcast to channel subscribers
@@ -209,54 +636,48 @@ pub async fn update_message(
         .update(id, auth.user_id, input)
         .await?;
 
-    // Get author name for the broadcast
+    let message_data = message_data(&state, message, auth.user_id).await;
+
+    // Broadcast update
+    state.connections.broadcast_to_channel(
+        message_data.channel_id,
+        &miscord_protocol::ServerMessage::MessageUpdated {
+            message: message_data.clone(),
+        },
+    ).await;
+
+    Ok(Json(message_data))
+}
+
+/// Copy a message and its attachments into a channel with a reference to the original,
+/// and broadcast it. `crosspost` marks the copy as a published announcement rather than
+/// a forward.
+async fn copy_message(
+    state: &AppState,
+    original: &Message,
+    original_attachments: &[MessageAttachment],
+    channel_id: Uuid,
+    author_id: Uuid,
+    crosspost: bool,
+) -> Result<MessageData> {
+    let embeds = message_embeds(state, original.id).await;
+    let (message, attachments) = state
+        .message_service
+        .forward(original, channel_id, author_id, crosspost, original_attachments, &embeds)
+        .await?;
+    let attachments = attachments
+        .into_iter()
+        .map(miscord_protocol::AttachmentData::from)
+        .collect();
+
     let author_name = state
         .user_service
-        .get_by_id(message.author_id)
+        .get_by_id(author_id)
         .await
         .map(|u| u.display_name)
         .unwrap_or_else(|_| "Unknown".to_string());
 
-    // Get reactions for the updated message
-    let reactions = state
-        .message_service
-        .get_reactions(message.id, auth.user_id)
-        .await
-        .map(|r| {
-            r.into_iter()
-                .map(|(emoji, user_ids, reacted_by_me)| miscord_protocol::ReactionData {
-                    emoji,
-                    user_ids,
-                    reacted_by_me,
-                })
-                .collect()
-        })
-        .unwrap_or_default();
-
-    // Get attachments for the message
-    let attachments = state
-        .attachment_service
-        .get_by_message_id(message.id)
-        .await
-        .map(|atts| {
-            atts.into_iter()
-                .map(|a| miscord_protocol::AttachmentData {
-                    id: a.id,
-                    filename: a.filename,
-                    content_type: a.content_type,
-                    size_bytes: a.size_bytes,
-                    url: a.url,
-                })
-                .collect()
-        })
-        .unwrap_or_default();
-
-    // Get pinned_by display name if message is pinned
-    let pinned_by = if let Some(pinned_by_id) = message.pinned_by_id {
-        state.user_service.get_by_id(pinned_by_id).await.ok().map(|u| u.display_name)
-    } else {
-        None
-    };
+    let forwarded_from = forwarded_from_data(state, &message, None).await;
 
     let message_data = MessageData {
         id: message.id,
@@ -266,27 +687,185 @@ pub async fn update_message(
         content: message.content,
         edited_at: message.edited_at,
         reply_to_id: message.reply_to_id,
-        reactions,
+        reactions: vec![],
         attachments,
         created_at: message.created_at,
         thread_parent_id: message.thread_parent_id,
         reply_count: message.reply_count,
         last_reply_at: message.last_reply_at,
-        pinned_at: message.pinned_at,
-        pinned_by,
+        pinned_at: None,
+        pinned_by: None,
+        forwarded_from,
+        published_at: None,
+        webhook: None,
+        embeds,
+        components: Vec::new(),
+        ephemeral: false,
     };
 
-    // Broadcast update
     state.connections.broadcast_to_channel(
-        message.channel_id,
-        &miscord_protocol::ServerMessage::MessageUpdated {
+        channel_id,
+        &miscord_protocol::ServerMessage::MessageCreated {
             message: message_data.clone(),
         },
     ).await;
 
+    Ok(message_data)
+}
+
+/// Forward a message into another channel or DM
+/// POST /api/messages/:id/forward
+pub async fn forward_message(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(input): Json<ForwardMessage>,
+) -> Result<Json<MessageData>> {
+    let original = state.message_service.get_by_id(id).await?;
+
+    // The user must be able to see the original and post in the target
+    if !state.channel_service.user_has_access(original.channel_id, auth.user_id).await?
+        || !state.channel_service.user_has_access(input.channel_id, auth.user_id).await?
+    {
+        return Err(AppError::Forbidden);
+    }
+
+    let target = state.channel_service.get_by_id(input.channel_id).await?;
+    if matches!(
+        target.channel_type,
+        ChannelType::Voice | ChannelType::Category | ChannelType::Forum
+    ) {
+        return Err(AppError::BadRequest(
+            "Messages can only be forwarded to text channels and DMs".to_string(),
+        ));
+    }
+    require_bot_permission(&state, &auth, input.channel_id, CommunityRole::SEND_MESSAGES).await?;
+
+    // The copies share the original's files but count against the forwarder's quotas
+    let attachments = state.attachment_service.get_by_message_id(original.id).await?;
+    let owner = AttachmentOwner {
+        message_id: None,
+        uploader_id: auth.user_id,
+        community_id: target.community_id,
+    };
+    state
+        .quota_service
+        .check(&owner, quota::distinct_file_size(&attachments))
+        .await?;
+
+    let message_data = copy_message(
+        &state,
+        &original,
+        &attachments,
+        input.channel_id,
+        auth.user_id,
+        false,
+    )
+    .await?;
+
     Ok(Json(message_data))
 }
 
+/// Publish an announcement: copy it into every channel following its channel
+/// POST /api/messages/:id/publish
+pub async fn publish_message(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    let original = state.message_service.get_by_id(id).await?;
+    let channel = state.channel_service.get_by_id(original.channel_id).await?;
+
+    if channel.channel_type != ChannelType::Announcement {
+        return Err(AppError::BadRequest(
+            "Only messages in announcement channels can be published".to_string(),
+        ));
+    }
+    if original.thread_parent_id.is_some() {
+        return Err(AppError::BadRequest(
+            "Thread replies cannot be published".to_string(),
+        ));
+    }
+
+    // The author or the community owner can publish
+    if original.author_id != auth.user_id {
+        let community_id = channel.community_id.ok_or(AppError::Forbidden)?;
+        require_community_owner(&state, community_id, auth.user_id).await?;
+    }
+
+    // The copies share the original's files, so they count once against the publisher's
+    // quota however many channels follow
+    let attachments = state.attachment_service.get_by_message_id(original.id).await?;
+    let size = quota::distinct_file_size(&attachments);
+    let publisher = AttachmentOwner {
+        message_id: None,
+        uploader_id: auth.user_id,
+        community_id: None,
+    };
+    state.quota_service.check(&publisher, size).await?;
+
+    let published_at = state
+        .announcement_service
+        .mark_published(original.id, auth.user_id)
+        .await?;
+
+    state.connections.broadcast_to_channel(
+        channel.id,
+        &miscord_protocol::ServerMessage::MessagePublished {
+            message_id: original.id,
+            channel_id: channel.id,
+            published_at,
+        },
+    ).await;
+
+    // Copying into every follower can take a while, so it runs in the background
+    tokio::spawn(publish_to_followers(
+        state,
+        original,
+        attachments,
+        auth.user_id,
+    ));
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Copy a published announcement into each channel following its channel. Destinations
+/// whose community is out of storage are skipped, and one failing doesn't stop the others.
+async fn publish_to_followers(
+    state: AppState,
+    original: Message,
+    attachments: Vec<MessageAttachment>,
+    publisher_id: Uuid,
+) {
+    let channel_ids = match state
+        .announcement_service
+        .get_follower_channel_ids(original.channel_id)
+        .await
+    {
+        Ok(ids) => ids,
+        Err(e) => {
+            tracing::warn!("Failed to list followers of channel {}: {}", original.channel_id, e);
+            return;
+        }
+    };
+
+    let size = quota::distinct_file_size(&attachments);
+    for channel_id in channel_ids {
+        let result = async {
+            let target = state.channel_service.get_by_id(channel_id).await?;
+            if let Some(community_id) = target.community_id {
+                state.quota_service.check_community(community_id, size).await?;
+            }
+            copy_message(&state, &original, &attachments, channel_id, publisher_id, true).await
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to publish message {} to channel {}: {}", original.id, channel_id, e);
+        }
+    }
+}
+
 pub async fn delete_message(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -326,6 +905,25 @@ pub async fn add_reaction(
     auth: AuthUser,
     Path((id, emoji)): Path<(Uuid, String)>,
 ) -> Result<()> {
+    let channel_id = state.message_service.get_by_id(id).await?.channel_id;
+    require_bot_permission(&state, &auth, channel_id, CommunityRole::ADD_REACTIONS).await?;
+
+    // Custom emoji reactions ("<:name:id>") must exist in one of the user's communities
+    if is_custom_emoji_key(&emoji) {
+        let available = match parse_custom_emoji(&emoji) {
+            Some((name, emoji_id)) => {
+                state
+                    .emoji_service
+                    .is_available_to_user(auth.user_id, emoji_id, name)
+                    .await?
+            }
+            None => false,
+        };
+        if !available {
+            return Err(AppError::BadRequest(format!("Unknown emoji {}", emoji)));
+        }
+    }
+
     state
         .message_service
         .add_reaction(id, auth.user_id, &emoji)
@@ -405,6 +1003,34 @@ pub async fn get_thread(
         .await
         .unwrap_or_default();
 
+    // Get publish times of announcements in one query
+    let published_map = state
+        .announcement_service
+        .get_published_at(&all_message_ids)
+        .await
+        .unwrap_or_default();
+
+    // Get webhook names and avatars in one query
+    let mut webhook_map = state
+        .webhook_service
+        .get_messages(&all_message_ids)
+        .await
+        .unwrap_or_default();
+
+    // Get bot buttons in one query
+    let mut components_map = state
+        .interaction_service
+        .get_components(&all_message_ids)
+        .await
+        .unwrap_or_default();
+
+    // Get embeds in one query
+    let mut embeds_map = state
+        .message_service
+        .get_embeds(&all_message_ids)
+        .await
+        .unwrap_or_default();
+
     // Get attachments for all messages
     let attachments_list = state
         .attachment_service
@@ -419,13 +1045,7 @@ pub async fn get_thread(
             attachments_map
                 .entry(message_id)
                 .or_default()
-                .push(miscord_protocol::AttachmentData {
-                    id: att.id,
-                    filename: att.filename,
-                    content_type: att.content_type,
-                    size_bytes: att.size_bytes,
-                    url: att.url,
-                });
+                .push(att.into());
         }
     }
 
@@ -452,11 +1072,15 @@ pub async fn get_thread(
         None
     };
 
+    let webhook = webhook_map.remove(&parent.id).map(to_webhook_author_data);
+    let embeds = embeds_map.remove(&parent.id).unwrap_or_default();
+    let components = components_map.remove(&parent.id).unwrap_or_default();
+    let forwarded_from = forwarded_from_data(&state, &parent, Some(auth.user_id)).await;
     let parent_data = MessageData {
         id: parent.id,
         channel_id: parent.channel_id,
         author_id: parent.author_id,
-        author_name: parent_author,
+        author_name: webhook.as_ref().map_or(parent_author, |w| w.username.clone()),
         content: parent.content,
         edited_at: parent.edited_at,
         reply_to_id: parent.reply_to_id,
@@ -468,9 +1092,16 @@ pub async fn get_thread(
         last_reply_at: parent.last_reply_at,
         pinned_at: parent.pinned_at,
         pinned_by: parent_pinned_by,
+        forwarded_from,
+        published_at: published_map.get(&parent.id).copied(),
+        webhook,
+        embeds,
+        components,
+        ephemeral: false,
     };
 
     // Build reply MessageData list
+    let mut forwarded_map = forwarded_from_map(&state, &replies, Some(auth.user_id)).await;
     let mut replies_data = Vec::with_capacity(replies.len());
     for msg in replies {
         let author_name = state
@@ -502,11 +1133,15 @@ pub async fn get_thread(
             None
         };
 
+        let webhook = webhook_map.remove(&msg.id).map(to_webhook_author_data);
+        let embeds = embeds_map.remove(&msg.id).unwrap_or_default();
+        let components = components_map.remove(&msg.id).unwrap_or_default();
+        let forwarded_from = forwarded_map.remove(&msg.id);
         replies_data.push(MessageData {
             id: msg.id,
             channel_id: msg.channel_id,
             author_id: msg.author_id,
-            author_name,
+            author_name: webhook.as_ref().map_or(author_name, |w| w.username.clone()),
             content: msg.content,
             edited_at: msg.edited_at,
             reply_to_id: msg.reply_to_id,
@@ -518,6 +1153,12 @@ pub async fn get_thread(
             last_reply_at: msg.last_reply_at,
             pinned_at: msg.pinned_at,
             pinned_by,
+            forwarded_from,
+            published_at: None,
+            webhook,
+            embeds,
+            components,
+            ephemeral: false,
         });
     }
 
@@ -535,9 +1176,13 @@ pub async fn create_thread_reply(
     Path(parent_id): Path<Uuid>,
     Json(input): Json<CreateMessage>,
 ) -> Result<Json<MessageData>> {
+    let channel_id = state.message_service.get_by_id(parent_id).await?.channel_id;
+    require_bot_permission(&state, &auth, channel_id, CommunityRole::SEND_MESSAGES).await?;
+    check_embeds_allowed(&auth, &input.embeds)?;
+
     let message = state
         .message_service
-        .create_thread_reply(parent_id, auth.user_id, input.content, input.reply_to_id)
+        .create_thread_reply(parent_id, auth.user_id, &input)
         .await?;
 
     // Get author name
@@ -564,6 +1209,12 @@ pub async fn create_thread_reply(
         last_reply_at: message.last_reply_at,
         pinned_at: None, // Thread replies are not pinned by default
         pinned_by: None,
+        forwarded_from: None,
+        published_at: None,
+        webhook: None,
+        embeds: input.embeds,
+        components: Vec::new(),
+        ephemeral: false,
     };
 
     // Get updated parent for metadata
@@ -588,6 +1239,24 @@ pub async fn create_thread_reply(
         },
     ).await;
 
+    // Unarchive the thread, auto-follow it and let followers know about the reply
+    state
+        .thread_service
+        .on_reply(parent_id, parent.author_id, auth.user_id)
+        .await?;
+
+    let followed_update = miscord_protocol::ServerMessage::FollowedThreadUpdated {
+        parent_message_id: parent_id,
+        channel_id: message.channel_id,
+        reply_count: parent.reply_count,
+        last_reply_at: parent.last_reply_at,
+    };
+    for follower_id in state.thread_service.get_followers(parent_id).await? {
+        if follower_id != auth.user_id {
+            state.connections.send_to_user(follower_id, &followed_update).await;
+        }
+    }
+
     Ok(Json(message_data))
 }
 
@@ -644,6 +1313,34 @@ pub async fn search_messages(
         .await
         .unwrap_or_default();
 
+    // Get publish times of announcements in one query
+    let published_map = state
+        .announcement_service
+        .get_published_at(&message_ids)
+        .await
+        .unwrap_or_default();
+
+    // Get webhook names and avatars in one query
+    let mut webhook_map = state
+        .webhook_service
+        .get_messages(&message_ids)
+        .await
+        .unwrap_or_default();
+
+    // Get bot buttons in one query
+    let mut components_map = state
+        .interaction_service
+        .get_components(&message_ids)
+        .await
+        .unwrap_or_default();
+
+    // Get embeds in one query
+    let mut embeds_map = state
+        .message_service
+        .get_embeds(&message_ids)
+        .await
+        .unwrap_or_default();
+
     // Get attachments for all messages
     let attachments_list = state
         .attachment_service
@@ -659,17 +1356,12 @@ pub async fn search_messages(
             attachments_map
                 .entry(message_id)
                 .or_default()
-                .push(miscord_protocol::AttachmentData {
-                    id: att.id,
-                    filename: att.filename,
-                    content_type: att.content_type,
-                    size_bytes: att.size_bytes,
-                    url: att.url,
-                });
+                .push(att.into());
         }
     }
 
     // Build results with channel and community names
+    let mut forwarded_map = forwarded_from_map(&state, &messages, Some(auth.user_id)).await;
     let mut results = Vec::with_capacity(messages.len());
     for msg in messages {
         // Get author name
@@ -756,11 +1448,15 @@ pub async fn search_messages(
             None
         };
 
+        let webhook = webhook_map.remove(&msg.id).map(to_webhook_author_data);
+        let embeds = embeds_map.remove(&msg.id).unwrap_or_default();
+        let components = components_map.remove(&msg.id).unwrap_or_default();
+        let forwarded_from = forwarded_map.remove(&msg.id);
         let message_data = MessageData {
             id: msg.id,
             channel_id: msg.channel_id,
             author_id: msg.author_id,
-            author_name,
+            author_name: webhook.as_ref().map_or(author_name, |w| w.username.clone()),
             content: msg.content,
             edited_at: msg.edited_at,
             reply_to_id: msg.reply_to_id,
@@ -772,6 +1468,12 @@ pub async fn search_messages(
             last_reply_at: msg.last_reply_at,
             pinned_at: msg.pinned_at,
             pinned_by,
+            forwarded_from,
+            published_at: published_map.get(&msg.id).copied(),
+            webhook,
+            embeds,
+            components,
+            ephemeral: false,
         };
 
         results.push(MessageSearchResult {
@@ -792,6 +1494,9 @@ pub async fn pin_message(
     auth: AuthUser,
     Path(id): Path<Uuid>,
 ) -> Result<Json<MessageData>> {
+    let channel_id = state.message_service.get_by_id(id).await?.channel_id;
+    require_bot_permission(&state, &auth, channel_id, CommunityRole::MANAGE_PINS).await?;
+
     let message = state
         .message_service
         .pin_message(id, auth.user_id)
@@ -836,22 +1541,20 @@ pub async fn pin_message(
         .await
         .map(|atts| {
             atts.into_iter()
-                .map(|a| miscord_protocol::AttachmentData {
-                    id: a.id,
-                    filename: a.filename,
-                    content_type: a.content_type,
-                    size_bytes: a.size_bytes,
-                    url: a.url,
-                })
+                .map(miscord_protocol::AttachmentData::from)
                 .collect()
         })
         .unwrap_or_default();
 
+    let webhook = webhook_author(&state, message.id).await;
+    let embeds = message_embeds(&state, message.id).await;
+    let components = message_components(&state, message.id).await;
+    let forwarded_from = forwarded_from_data(&state, &message, Some(auth.user_id)).await;
     let message_data = MessageData {
         id: message.id,
         channel_id: message.channel_id,
         author_id: message.author_id,
-        author_name,
+        author_name: webhook.as_ref().map_or(author_name, |w| w.username.clone()),
         content: message.content,
         edited_at: message.edited_at,
         reply_to_id: message.reply_to_id,
@@ -863,6 +1566,12 @@ pub async fn pin_message(
         last_reply_at: message.last_reply_at,
         pinned_at: message.pinned_at,
         pinned_by: Some(pinned_by_name.clone()),
+        forwarded_from,
+        published_at: published_at(&state, message.id).await,
+        webhook,
+        embeds,
+        components,
+        ephemeral: false,
     };
 
     // Broadcast pinned event
@@ -889,6 +1598,7 @@ pub async fn unpin_message(
     // Get message before unpinning to get channel_id
     let original_message = state.message_service.get_by_id(id).await?;
     let channel_id = original_message.channel_id;
+    require_bot_permission(&state, &auth, channel_id, CommunityRole::MANAGE_PINS).await?;
 
     let message = state
         .message_service
@@ -926,22 +1636,20 @@ pub async fn unpin_message(
         .await
         .map(|atts| {
             atts.into_iter()
-                .map(|a| miscord_protocol::AttachmentData {
-                    id: a.id,
-                    filename: a.filename,
-                    content_type: a.content_type,
-                    size_bytes: a.size_bytes,
-                    url: a.url,
-                })
+                .map(miscord_protocol::AttachmentData::from)
                 .collect()
         })
         .unwrap_or_default();
 
+    let webhook = webhook_author(&state, message.id).await;
+    let embeds = message_embeds(&state, message.id).await;
+    let components = message_components(&state, message.id).await;
+    let forwarded_from = forwarded_from_data(&state, &message, Some(auth.user_id)).await;
     let message_data = MessageData {
         id: message.id,
         channel_id: message.channel_id,
         author_id: message.author_id,
-        author_name,
+        author_name: webhook.as_ref().map_or(author_name, |w| w.username.clone()),
         content: message.content,
         edited_at: message.edited_at,
         reply_to_id: message.reply_to_id,
@@ -953,6 +1661,12 @@ pub async fn unpin_message(
         last_reply_at: message.last_reply_at,
         pinned_at: None,
         pinned_by: None,
+        forwarded_from,
+        published_at: published_at(&state, message.id).await,
+        webhook,
+        embeds,
+        components,
+        ephemeral: false,
     };
 
     // Broadcast unpinned event
@@ -990,6 +1704,34 @@ pub async fn get_pinned_messages(
         .await
         .unwrap_or_default();
 
+    // Get publish times of announcements in one query
+    let published_map = state
+        .announcement_service
+        .get_published_at(&message_ids)
+        .await
+        .unwrap_or_default();
+
+    // Get webhook names and avatars in one query
+    let mut webhook_map = state
+        .webhook_service
+        .get_messages(&message_ids)
+        .await
+        .unwrap_or_default();
+
+    // Get bot buttons in one query
+    let mut components_map = state
+        .interaction_service
+        .get_components(&message_ids)
+        .await
+        .unwrap_or_default();
+
+    // Get embeds in one query
+    let mut embeds_map = state
+        .message_service
+        .get_embeds(&message_ids)
+        .await
+        .unwrap_or_default();
+
     // Get attachments for all messages in one query
     let attachments_list = state
         .attachment_service
@@ -1005,17 +1747,12 @@ pub async fn get_pinned_messages(
             attachments_map
                 .entry(message_id)
                 .or_default()
-                .push(miscord_protocol::AttachmentData {
-                    id: att.id,
-                    filename: att.filename,
-                    content_type: att.content_type,
-                    size_bytes: att.size_bytes,
-                    url: att.url,
-                });
+                .push(att.into());
         }
     }
 
     // Convert to MessageData with author names, reactions, and attachments
+    let mut forwarded_map = forwarded_from_map(&state, &messages, Some(auth.user_id)).await;
     let mut result = Vec::with_capacity(messages.len());
     for msg in messages {
         let author_name = state
@@ -1049,11 +1786,15 @@ pub async fn get_pinned_messages(
             None
         };
 
+        let webhook = webhook_map.remove(&msg.id).map(to_webhook_author_data);
+        let embeds = embeds_map.remove(&msg.id).unwrap_or_default();
+        let components = components_map.remove(&msg.id).unwrap_or_default();
+        let forwarded_from = forwarded_map.remove(&msg.id);
         result.push(MessageData {
             id: msg.id,
             channel_id: msg.channel_id,
             author_id: msg.author_id,
-            author_name,
+            author_name: webhook.as_ref().map_or(author_name, |w| w.username.clone()),
             content: msg.content,
             edited_at: msg.edited_at,
             reply_to_id: msg.reply_to_id,
@@ -1065,6 +1806,12 @@ pub async fn get_pinned_messages(
             last_reply_at: msg.last_reply_at,
             pinned_at: msg.pinned_at,
             pinned_by,
+            forwarded_from,
+            published_at: published_map.get(&msg.id).copied(),
+            webhook,
+            embeds,
+            components,
+            ephemeral: false,
         });
     }
 
@@ -1,11 +1,23 @@
+mod announcements;
 mod attachments;
 mod auth;
+mod bots;
 mod channels;
 mod communities;
+mod emojis;
+mod event_webhooks;
+mod exports;
+mod forums;
+mod imports;
+mod interactions;
+mod media_proxy;
 mod messages;
 mod opengraph;
 mod tenor;
+mod threads;
+mod uploads;
 mod users;
+mod webhooks;
 
 use crate::state::AppState;
 use crate::ws;
@@ -17,7 +29,7 @@ use axum::{
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 
-/// Maximum upload size: 25 MB (matching client-side limit)
+/// Maximum request body: 25 MB. Larger files are sent in chunks through /api/uploads.
 const MAX_BODY_SIZE: usize = 25 * 1024 * 1024;
 
 pub fn create_router(state: AppState) -> Router {
@@ -36,6 +48,11 @@ pub fn create_router(state: AppState) -> Router {
         .route("/api/users/me", get(users::get_me).patch(users::update_me))
         .route("/api/users/{id}", get(users::get_user))
         .route("/api/users/me/friends", get(users::get_friends))
+        .route(
+            "/api/users/me/privacy",
+            get(users::get_privacy_settings).patch(users::update_privacy_settings),
+        )
+        .route("/api/users/me/storage", get(uploads::get_user_storage))
         // Community routes
         .route("/api/communities", post(communities::create_community).get(communities::list_communities))
         .route(
@@ -46,11 +63,44 @@ pub fn create_router(state: AppState) -> Router {
         )
         .route(
             "/api/communities/{id}/channels",
-            get(communities::list_channels).post(communities::create_channel),
+            get(communities::list_channels)
+                .post(communities::create_channel)
+                .patch(communities::reorder_channels),
         )
         .route("/api/communities/{id}/members", get(communities::list_members))
+        .route(
+            "/api/communities/{id}/members/{user_id}",
+            axum::routing::delete(communities::remove_member),
+        )
         .route("/api/communities/{id}/invites", post(communities::create_invite))
+        .route(
+            "/api/communities/{id}/upload-limit",
+            get(uploads::get_upload_limit).put(uploads::set_upload_limit),
+        )
+        .route("/api/communities/{id}/storage", get(uploads::get_community_storage))
         .route("/api/invites/{code}", post(communities::join_community))
+        // Imported Discord authors
+        .route(
+            "/api/communities/{id}/placeholder-users",
+            get(imports::list_placeholder_users),
+        )
+        .route(
+            "/api/communities/{id}/placeholder-users/{user_id}/claim-request",
+            post(imports::request_placeholder_claim).delete(imports::cancel_placeholder_claim),
+        )
+        .route(
+            "/api/communities/{id}/placeholder-users/{user_id}/claim",
+            post(imports::claim_placeholder_user),
+        )
+        // Custom emoji routes
+        .route(
+            "/api/communities/{id}/emojis",
+            get(emojis::list_emojis).post(emojis::upload_emoji),
+        )
+        .route(
+            "/api/emojis/{id}",
+            axum::routing::patch(emojis::rename_emoji).delete(emojis::delete_emoji),
+        )
         // Channel routes
         .route(
             "/api/channels/{id}",
@@ -58,6 +108,7 @@ pub fn create_router(state: AppState) -> Router {
                 .patch(channels::update_channel)
                 .delete(channels::delete_channel),
         )
+        .route("/api/channels/{id}/retention", axum::routing::put(channels::set_channel_retention))
         .route(
             "/api/channels/{id}/messages",
             get(messages::list_messages).post(messages::create_message),
@@ -74,6 +125,11 @@ pub fn create_router(state: AppState) -> Router {
             "/api/messages/{id}/reactions/{emoji}",
             post(messages::add_reaction).delete(messages::remove_reaction),
         )
+        .route(
+            "/api/messages/{id}/forward",
+            post(messages::forward_message),
+        )
+        .route("/api/messages/{id}/publish", post(messages::publish_message))
         // Thread routes
         .route(
             "/api/messages/{id}/thread",
@@ -83,6 +139,89 @@ pub fn create_router(state: AppState) -> Router {
             "/api/messages/{id}/replies",
             post(messages::create_thread_reply),
         )
+        .route(
+            "/api/messages/{id}/thread/follow",
+            axum::routing::put(threads::follow_thread).delete(threads::unfollow_thread),
+        )
+        .route(
+            "/api/messages/{id}/thread/read",
+            post(threads::mark_thread_read),
+        )
+        .route("/api/channels/{id}/threads", get(threads::list_channel_threads))
+        .route("/api/users/me/threads", get(threads::list_followed_threads))
+        // Announcement channel follows
+        .route("/api/channels/{id}/follows", get(announcements::list_follows))
+        .route("/api/channels/{id}/followers", post(announcements::follow_channel))
+        .route(
+            "/api/channels/{id}/followers/{target_id}",
+            axum::routing::delete(announcements::unfollow_channel),
+        )
+        // Channel and thread exports
+        .route("/api/channels/{id}/exports", post(exports::create_export))
+        .route("/api/exports/{id}", get(exports::get_export))
+        .route("/api/exports/{id}/download", get(exports::download_export))
+        // Incoming webhooks
+        .route(
+            "/api/channels/{id}/webhooks",
+            get(webhooks::list_webhooks).post(webhooks::create_webhook),
+        )
+        .route(
+            "/api/webhooks/{id}",
+            axum::routing::patch(webhooks::update_webhook).delete(webhooks::delete_webhook),
+        )
+        .route("/api/webhooks/{id}/token", post(webhooks::regenerate_webhook_token))
+        .route("/api/webhooks/{id}/{token}", post(webhooks::execute_webhook))
+        // Outgoing event webhooks
+        .route(
+            "/api/communities/{id}/event-webhooks",
+            get(event_webhooks::list_event_webhooks).post(event_webhooks::create_event_webhook),
+        )
+        .route(
+            "/api/event-webhooks/{id}",
+            axum::routing::patch(event_webhooks::update_event_webhook)
+                .delete(event_webhooks::delete_event_webhook),
+        )
+        .route(
+            "/api/event-webhooks/{id}/secret",
+            post(event_webhooks::regenerate_event_webhook_secret),
+        )
+        .route("/api/event-webhooks/{id}/deliveries", get(event_webhooks::list_deliveries))
+        // Bot accounts
+        .route("/api/bots", get(bots::list_bots).post(bots::create_bot))
+        .route("/api/bots/{id}", axum::routing::delete(bots::delete_bot))
+        .route(
+            "/api/bots/{id}/tokens",
+            get(bots::list_bot_tokens).post(bots::create_bot_token),
+        )
+        .route(
+            "/api/bots/{id}/tokens/{token_id}",
+            axum::routing::delete(bots::revoke_bot_token),
+        )
+        .route("/api/communities/{id}/bots", post(bots::add_community_bot))
+        .route(
+            "/api/communities/{id}/bots/{bot_id}",
+            axum::routing::patch(bots::update_community_bot),
+        )
+        // Slash commands and interactions
+        .route(
+            "/api/communities/{id}/commands",
+            get(interactions::list_commands).post(interactions::register_command),
+        )
+        .route("/api/commands/{id}", axum::routing::delete(interactions::delete_command))
+        .route("/api/channels/{id}/interactions", post(interactions::invoke_command))
+        .route("/api/messages/{id}/interactions", post(interactions::click_button))
+        .route("/api/interactions/{id}/response", post(interactions::respond))
+        // Forum routes
+        .route(
+            "/api/channels/{id}/forum/tags",
+            get(forums::list_tags).post(forums::create_tag),
+        )
+        .route("/api/forum/tags/{id}", axum::routing::delete(forums::delete_tag))
+        .route(
+            "/api/channels/{id}/posts",
+            get(forums::list_posts).post(forums::create_post),
+        )
+        .route("/api/posts/{id}/tags", axum::routing::put(forums::set_post_tags))
         // Voice routes
         .route("/api/channels/{id}/voice/join", post(channels::join_voice))
         .route("/api/channels/{id}/voice/participants", get(channels::get_voice_participants))
@@ -90,9 +229,15 @@ pub fn create_router(state: AppState) -> Router {
         .route("/api/voice/state", axum::routing::patch(channels::update_voice_state))
         // Channel read state routes
         .route("/api/channels/{id}/read", post(channels::mark_channel_read))
+        .route(
+            "/api/channels/{id}/read-receipts",
+            get(channels::get_read_receipts),
+        )
         .route("/api/channels/{id}/unread", get(channels::get_unread_count))
         // OpenGraph metadata endpoint
         .route("/api/opengraph", get(opengraph::fetch_opengraph))
+        // External images fetched by the server so users' IPs stay private
+        .route("/api/media-proxy", get(media_proxy::proxy_media))
         // GIF search endpoints (Tenor API proxy)
         .route("/api/gifs/search", get(tenor::search_gifs))
         .route("/api/gifs/trending", get(tenor::trending_gifs))
@@ -110,10 +255,20 @@ pub fn create_router(state: AppState) -> Router {
         // File attachment routes
         .route("/api/channels/{id}/upload", post(attachments::upload_files))
         .route("/api/files/{id}", get(attachments::download_file))
+        .route("/api/files/{id}/thumbnail", get(attachments::download_thumbnail))
         .route(
             "/api/attachments/{id}",
             get(attachments::get_attachment).delete(attachments::delete_attachment),
         )
+        // Resumable chunked uploads
+        .route("/api/uploads", post(uploads::create_upload))
+        .route("/api/uploads/existing", post(uploads::reuse_upload))
+        .route(
+            "/api/uploads/{id}",
+            get(uploads::get_upload).delete(uploads::cancel_upload),
+        )
+        .route("/api/uploads/{id}/chunks", axum::routing::put(uploads::append_chunk))
+        .route("/api/uploads/{id}/complete", post(uploads::complete_upload))
         // WebSocket endpoint
         .route("/ws", get(ws::handler::ws_handler))
         .layer(DefaultBodyLimit::max(MAX_BODY_SIZE))
@@ -1,26 +1,24 @@
 use crate::auth::AuthUser;
 use crate::error::{AppError, Result};
-use axum::{extract::Query, Json};
+use crate::models::{LinkPreview, OpenGraphData};
+use crate::safe_http;
+use crate::state::AppState;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
 use regex::Regex;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use std::sync::LazyLock;
 use std::time::Duration;
 
-/// OpenGraph metadata response
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct OpenGraphData {
-    pub url: String,
-    pub title: Option<String>,
-    pub description: Option<String>,
-    pub image: Option<String>,
-    pub site_name: Option<String>,
-    /// For video embeds: "youtube", "vimeo", etc.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub video_type: Option<String>,
-    /// Channel/author name for videos
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub author_name: Option<String>,
-}
+/// Only the start of a page is read; OpenGraph tags live in its <head>
+const MAX_BODY_BYTES: usize = 1_000_000;
+
+/// oEmbed responses are a few hundred bytes; anything past this is cut off
+const MAX_OEMBED_BYTES: usize = 64 * 1024;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Deserialize)]
 pub struct FetchOpenGraphQuery {
@@ -49,35 +47,60 @@ static RE_YOUTUBE: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r#"(?i)(?:youtube\.com/watch\?v=|youtu\.be/|youtube\.com/embed/|youtube\.com/shorts/)([a-zA-Z0-9_-]+)"#).unwrap()
 });
 
-/// Fetch OpenGraph metadata for a URL
+fn cached_response(preview: LinkPreview) -> Result<Json<OpenGraphData>> {
+    match preview.data {
+        Some(data) => Ok(Json(data.0)),
+        None => Err(AppError::BadRequest("This URL can't be previewed".to_string())),
+    }
+}
+
+/// Fetch OpenGraph metadata for a URL. Results are cached for everyone, so a link
+/// posted in a busy channel is only fetched once.
 pub async fn fetch_opengraph(
+    State(state): State<AppState>,
     _auth: AuthUser,
     Query(query): Query<FetchOpenGraphQuery>,
 ) -> Result<Json<OpenGraphData>> {
     let url = &query.url;
+    let parsed = safe_http::check_url(url)?;
 
-    // Basic URL validation
-    if !url.starts_with("http://") && !url.starts_with("https://") {
-        return Err(AppError::BadRequest("Invalid URL".to_string()));
+    let service = &state.link_preview_service;
+    if let Some(preview) = service.get_fresh(url).await? {
+        return cached_response(preview);
     }
 
+    let _guard = service.lock_fetch(url).await;
+
+    // Another request may have fetched it while this one waited
+    if let Some(preview) = service.get_fresh(url).await? {
+        return cached_response(preview);
+    }
+
+    let result = fetch_preview(url, parsed).await;
+
+    // Unreachable or unusable URLs are cached too; our own errors aren't
+    match &result {
+        Ok(data) => service.store(url, Some(data)).await?,
+        Err(AppError::BadRequest(_)) => service.store(url, None).await?,
+        Err(_) => {}
+    }
+
+    result.map(Json)
+}
+
+async fn fetch_preview(url: &str, parsed: reqwest::Url) -> Result<OpenGraphData> {
     // Check if this is a YouTube URL - use oEmbed API for better metadata
     if let Some(video_id) = extract_youtube_id(url) {
         return fetch_youtube_oembed(url, &video_id).await;
     }
 
-    // Fetch the page with timeout
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(5))
-        .user_agent("MiscordBot/1.0")
-        .build()
-        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to create HTTP client: {}", e)))?;
+    let client = safe_http::client(FETCH_TIMEOUT)?;
 
     let response = client
-        .get(url)
+        .get(parsed)
         .send()
         .await
-        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to fetch URL: {}", e)))?;
+        .map_err(|e| AppError::BadRequest(format!("Failed to fetch URL: {}", e)))?;
 
     if !response.status().is_success() {
         return Err(AppError::BadRequest(format!(
@@ -94,23 +117,17 @@ pub async fn fetch_opengraph(
         .unwrap_or("");
 
     if !content_type.contains("text/html") {
-        return Ok(Json(OpenGraphData {
-            url: url.clone(),
+        return Ok(OpenGraphData {
+            url: url.to_string(),
             ..Default::default()
-        }));
+        });
     }
 
-    // Limit body size to 1MB
-    let body = response
-        .text()
+    let body = safe_http::read_prefix(response, MAX_BODY_BYTES)
         .await
-        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to read response: {}", e)))?;
-
-    let body = if body.len() > 1_000_000 {
-        &body[..1_000_000]
-    } else {
-        &body
-    };
+        .map_err(|e| AppError::BadRequest(format!("Failed to read response: {}", e)))?;
+    let body = String::from_utf8_lossy(&body);
+    let body = body.as_ref();
 
     // Extract OpenGraph metadata by parsing all meta tags
     let mut og_title = None;
@@ -153,15 +170,15 @@ pub async fn fetch_opengraph(
     // Fallback to domain name if no og:site_name
     let site_name = og_site_name.or_else(|| extract_domain(url));
 
-    Ok(Json(OpenGraphData {
-        url: url.clone(),
+    Ok(OpenGraphData {
+        url: url.to_string(),
         title: title.map(decode_html_entities),
         description: description.map(decode_html_entities),
         image: og_image.map(decode_html_entities),
         site_name: site_name.map(decode_html_entities),
         video_type: None,
         author_name: None,
-    }))
+    })
 }
 
 fn extract_match(re: &Regex, text: &str) -> Option<String> {
@@ -207,12 +224,8 @@ struct YouTubeOEmbed {
 }
 
 /// Fetch YouTube video metadata via oEmbed API
-async fn fetch_youtube_oembed(original_url: &str, video_id: &str) -> Result<Json<OpenGraphData>> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(5))
-        .user_agent("MiscordBot/1.0")
-        .build()
-        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to create HTTP client: {}", e)))?;
+async fn fetch_youtube_oembed(original_url: &str, video_id: &str) -> Result<OpenGraphData> {
+    let client = safe_http::client(FETCH_TIMEOUT)?;
 
     // Use canonical YouTube URL for oEmbed
     let video_url = format!("https://www.youtube.com/watch?v={}", video_id);
@@ -225,11 +238,11 @@ async fn fetch_youtube_oembed(original_url: &str, video_id: &str) -> Result<Json
         .get(&oembed_url)
         .send()
         .await
-        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to fetch YouTube oEmbed: {}", e)))?;
+        .map_err(|e| AppError::BadRequest(format!("Failed to fetch YouTube oEmbed: {}", e)))?;
 
     if !response.status().is_success() {
         // Fall back to basic info if oEmbed fails
-        return Ok(Json(OpenGraphData {
+        return Ok(OpenGraphData {
             url: original_url.to_string(),
             title: None,
             description: None,
@@ -237,20 +250,21 @@ async fn fetch_youtube_oembed(original_url: &str, video_id: &str) -> Result<Json
             site_name: Some("YouTube".to_string()),
             video_type: Some("youtube".to_string()),
             author_name: None,
-        }));
+        });
     }
 
-    let oembed: YouTubeOEmbed = response
-        .json()
+    let body = safe_http::read_prefix(response, MAX_OEMBED_BYTES)
         .await
-        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to parse YouTube oEmbed: {}", e)))?;
+        .map_err(|e| AppError::BadRequest(format!("Failed to read YouTube oEmbed: {}", e)))?;
+    let oembed: YouTubeOEmbed = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Failed to parse YouTube oEmbed: {}", e)))?;
 
     // Use high-quality thumbnail (maxresdefault if available, fallback to hqdefault)
     let thumbnail = oembed.thumbnail_url.unwrap_or_else(|| {
         format!("https://img.youtube.com/vi/{}/hqdefault.jpg", video_id)
     });
 
-    Ok(Json(OpenGraphData {
+    Ok(OpenGraphData {
         url: original_url.to_string(),
         title: oembed.title,
         description: None, // YouTube oEmbed doesn't include description
@@ -258,5 +272,5 @@ async fn fetch_youtube_oembed(original_url: &str, video_id: &str) -> Result<Json
         site_name: Some("YouTube".to_string()),
         video_type: Some("youtube".to_string()),
         author_name: oembed.author_name,
-    }))
+    })
 }
@@ -0,0 +1,133 @@
+use crate::auth::AuthUser;
+use crate::error::{AppError, Result};
+use crate::models::ThreadSummary;
+use crate::state::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use miscord_protocol::ThreadSummaryData;
+use serde::Deserialize;
+use uuid::Uuid;
+
+fn to_thread_summary_data(thread: ThreadSummary) -> ThreadSummaryData {
+    ThreadSummaryData {
+        parent_message_id: thread.parent_message_id,
+        channel_id: thread.channel_id,
+        channel_name: thread.channel_name,
+        author_id: thread.author_id,
+        author_name: thread.author_name,
+        content: thread.content,
+        reply_count: thread.reply_count,
+        last_reply_at: thread.last_reply_at,
+        archived: thread.archived_at.is_some(),
+        following: thread.following,
+        unread_count: thread.unread_count,
+    }
+}
+
+/// Check that a message can have a thread and the user can see it
+async fn require_thread_access(state: &AppState, parent_id: Uuid, user_id: Uuid) -> Result<()> {
+    let parent = state.message_service.get_by_id(parent_id).await?;
+
+    if parent.thread_parent_id.is_some() {
+        return Err(AppError::BadRequest(
+            "Thread replies cannot have threads".to_string(),
+        ));
+    }
+
+    if !state.channel_service.user_has_access(parent.channel_id, user_id).await? {
+        return Err(AppError::Forbidden);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListThreadsQuery {
+    /// List archived threads instead of active ones
+    #[serde(default)]
+    pub archived: bool,
+    pub limit: Option<i64>,
+}
+
+/// List threads in a channel
+/// GET /api/channels/:id/threads
+pub async fn list_channel_threads(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<Uuid>,
+    Query(query): Query<ListThreadsQuery>,
+) -> Result<Json<Vec<ThreadSummaryData>>> {
+    if !state.channel_service.user_has_access(channel_id, auth.user_id).await? {
+        return Err(AppError::Forbidden);
+    }
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+    let threads = state
+        .thread_service
+        .list_by_channel(channel_id, auth.user_id, query.archived, limit)
+        .await?;
+
+    Ok(Json(threads.into_iter().map(to_thread_summary_data).collect()))
+}
+
+/// List threads the current user follows
+/// GET /api/users/me/threads
+pub async fn list_followed_threads(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Vec<ThreadSummaryData>>> {
+    let threads = state.thread_service.list_followed(auth.user_id, 50).await?;
+
+    Ok(Json(threads.into_iter().map(to_thread_summary_data).collect()))
+}
+
+/// Follow a thread
+/// PUT /api/messages/:id/thread/follow
+pub async fn follow_thread(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(parent_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    require_thread_access(&state, parent_id, auth.user_id).await?;
+
+    state
+        .thread_service
+        .set_following(parent_id, auth.user_id, true)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Unfollow a thread
+/// DELETE /api/messages/:id/thread/follow
+pub async fn unfollow_thread(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(parent_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    require_thread_access(&state, parent_id, auth.user_id).await?;
+
+    state
+        .thread_service
+        .set_following(parent_id, auth.user_id, false)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Mark all replies in a thread as read
+/// POST /api/messages/:id/thread/read
+pub async fn mark_thread_read(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(parent_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    require_thread_access(&state, parent_id, auth.user_id).await?;
+
+    state.thread_service.mark_read(parent_id, auth.user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
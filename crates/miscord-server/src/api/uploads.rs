@@ -0,0 +1,309 @@
+use crate::auth::AuthUser;
+use crate::error::{AppError, Result};
+use crate::models::{
+    AppendChunkQuery, AttachmentOwner, CommunityRole, CreateUpload, ReuseUpload, SetUploadLimit,
+    UploadLimit, UploadSession,
+};
+use crate::state::AppState;
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use miscord_protocol::{AttachmentData, StorageUsageData, UploadSessionData};
+use uuid::Uuid;
+
+use super::bots::{require_bot_permission, require_community_owner};
+
+fn to_session_data(session: UploadSession) -> UploadSessionData {
+    UploadSessionData {
+        id: session.id,
+        filename: session.filename,
+        size_bytes: session.size_bytes,
+        chunk_size: session.chunk_size,
+        received_bytes: session.received_bytes,
+    }
+}
+
+/// Start a chunked upload, for files of any size up to the channel's limit
+/// POST /api/uploads
+pub async fn create_upload(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(input): Json<CreateUpload>,
+) -> Result<Json<UploadSessionData>> {
+    let channel = state.channel_service.get_by_id(input.channel_id).await?;
+    if !state.channel_service.user_has_access(channel.id, auth.user_id).await? {
+        return Err(AppError::Forbidden);
+    }
+    require_bot_permission(&state, &auth, channel.id, CommunityRole::SEND_MESSAGES).await?;
+
+    let content_type = state.attachment_service.validate_extension(&input.filename)?;
+
+    let limit = state.upload_service.limit_for_channel(&channel).await?;
+    if input.size_bytes <= 0 {
+        return Err(AppError::BadRequest("File is empty".to_string()));
+    }
+    if input.size_bytes > limit {
+        return Err(AppError::BadRequest(format!(
+            "File too large. Maximum size is {} MB",
+            limit / 1024 / 1024
+        )));
+    }
+
+    // The whole file is counted against the quotas from the start
+    let owner = AttachmentOwner {
+        message_id: None,
+        uploader_id: auth.user_id,
+        community_id: channel.community_id,
+    };
+    state.quota_service.check(&owner, input.size_bytes).await?;
+
+    state.attachment_service.ensure_storage_ready().await?;
+    let session = state
+        .upload_service
+        .create(auth.user_id, channel.id, &input.filename, content_type, input.size_bytes)
+        .await?;
+
+    Ok(Json(to_session_data(session)))
+}
+
+/// Attach a file the server already has instead of uploading it again, identified by the
+/// SHA-256 of its contents. Returns null when it has to be uploaded after all.
+/// POST /api/uploads/existing
+pub async fn reuse_upload(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(input): Json<ReuseUpload>,
+) -> Result<Json<Option<AttachmentData>>> {
+    let channel = state.channel_service.get_by_id(input.channel_id).await?;
+    if !state.channel_service.user_has_access(channel.id, auth.user_id).await? {
+        return Err(AppError::Forbidden);
+    }
+    require_bot_permission(&state, &auth, channel.id, CommunityRole::SEND_MESSAGES).await?;
+
+    let content_type = state.attachment_service.validate_extension(&input.filename)?;
+
+    let sha256 = input.sha256.to_ascii_lowercase();
+    if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(AppError::BadRequest("Invalid SHA-256 hash".to_string()));
+    }
+
+    // Only files the user or the community already has are offered, so the hash alone
+    // can't be used to fetch someone else's file
+    let owner = AttachmentOwner {
+        message_id: None,
+        uploader_id: auth.user_id,
+        community_id: channel.community_id,
+    };
+    let existing = state
+        .attachment_service
+        .find_by_hash(&sha256, &input.filename, Some(&owner))
+        .await?;
+    let Some(existing) = existing else {
+        return Ok(Json(None));
+    };
+
+    let limit = state.upload_service.limit_for_channel(&channel).await?;
+    if existing.size_bytes > limit {
+        return Err(AppError::BadRequest(format!(
+            "File too large. Maximum size is {} MB",
+            limit / 1024 / 1024
+        )));
+    }
+
+    // The copy counts against the user's and community's quotas like an upload would
+    state.quota_service.check(&owner, existing.size_bytes).await?;
+
+    let attachment = state
+        .attachment_service
+        .reuse_file(&existing, owner, &input.filename, content_type)
+        .await?;
+
+    Ok(Json(attachment.map(Into::into)))
+}
+
+/// How much of an upload has been received, to resume it after a disconnect
+/// GET /api/uploads/:id
+pub async fn get_upload(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<UploadSessionData>> {
+    let session = state.upload_service.get(id, auth.user_id).await?;
+    Ok(Json(to_session_data(session)))
+}
+
+/// Send the next chunk of an upload as the raw request body
+/// PUT /api/uploads/:id/chunks?offset=N
+pub async fn append_chunk(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<AppendChunkQuery>,
+    body: Bytes,
+) -> Result<Json<UploadSessionData>> {
+    let session = state.upload_service.get(id, auth.user_id).await?;
+
+    // The file type is recognizable from the first chunk
+    if query.offset == 0 {
+        state.attachment_service.check_contents(&session.filename, &body)?;
+    }
+
+    let session = state.upload_service.append(&session, query.offset, &body).await?;
+    Ok(Json(to_session_data(session)))
+}
+
+/// Finish an upload once every chunk is in, turning it into an attachment
+/// POST /api/uploads/:id/complete
+pub async fn complete_upload(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AttachmentData>> {
+    let session = state.upload_service.get(id, auth.user_id).await?;
+    require_bot_permission(&state, &auth, session.channel_id, CommunityRole::SEND_MESSAGES).await?;
+    let channel = state.channel_service.get_by_id(session.channel_id).await?;
+    state.upload_service.complete(&session).await?;
+
+    let owner = AttachmentOwner {
+        message_id: None,
+        uploader_id: session.user_id,
+        community_id: channel.community_id,
+    };
+
+    // When an identical file is already stored, that's used and this copy is dropped
+    let sha256 = state
+        .attachment_service
+        .hash_stored_file(session.id, &session.filename)
+        .await?;
+    let existing = state
+        .attachment_service
+        .reuse_identical(&sha256, owner, &session.filename, &session.content_type)
+        .await?;
+    if let Some(attachment) = existing {
+        if let Err(e) = state.attachment_service.remove_file(session.id, &session.filename).await {
+            tracing::warn!("Failed to remove duplicate upload {}: {}", session.id, e);
+        }
+        return Ok(Json(attachment.into()));
+    }
+
+    let size_bytes = state
+        .attachment_service
+        .strip_stored_metadata(session.id, &session.filename, &session.content_type, session.size_bytes)
+        .await?;
+    let info = state
+        .attachment_service
+        .analyze(session.id, &session.filename, &session.content_type, size_bytes, None)
+        .await;
+
+    // Like other uploads, it's linked to a message when the message is sent
+    let attachment = state
+        .attachment_service
+        .create_record(
+            session.id,
+            owner,
+            &session.filename,
+            &session.content_type,
+            size_bytes,
+            &sha256,
+            &info,
+        )
+        .await?;
+
+    Ok(Json(attachment.into()))
+}
+
+/// Abandon an upload
+/// DELETE /api/uploads/:id
+pub async fn cancel_upload(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    let session = state.upload_service.get(id, auth.user_id).await?;
+    state.upload_service.cancel(&session).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// How much storage the current user's files take up
+/// GET /api/users/me/storage
+pub async fn get_user_storage(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<StorageUsageData>> {
+    state.quota_service.user_usage(auth.user_id).await.map(Json)
+}
+
+/// How much storage the community's files take up
+/// GET /api/communities/:id/storage
+pub async fn get_community_storage(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(community_id): Path<Uuid>,
+) -> Result<Json<StorageUsageData>> {
+    let is_member = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM community_members WHERE community_id = $1 AND user_id = $2)",
+        community_id,
+        auth.user_id
+    )
+    .fetch_one(&state.db)
+    .await?
+    .unwrap_or(false);
+
+    if !is_member {
+        return Err(AppError::Forbidden);
+    }
+
+    state.quota_service.community_usage(community_id).await.map(Json)
+}
+
+/// How large members' attachments may be
+/// GET /api/communities/:id/upload-limit
+pub async fn get_upload_limit(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(community_id): Path<Uuid>,
+) -> Result<Json<UploadLimit>> {
+    let is_member = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM community_members WHERE community_id = $1 AND user_id = $2)",
+        community_id,
+        auth.user_id
+    )
+    .fetch_one(&state.db)
+    .await?
+    .unwrap_or(false);
+
+    if !is_member {
+        return Err(AppError::Forbidden);
+    }
+
+    upload_limit(&state, community_id).await.map(Json)
+}
+
+/// Set the community's upload limit, or clear it to use the server default (owner only)
+/// PUT /api/communities/:id/upload-limit
+pub async fn set_upload_limit(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(community_id): Path<Uuid>,
+    Json(input): Json<SetUploadLimit>,
+) -> Result<Json<UploadLimit>> {
+    require_community_owner(&state, community_id, auth.user_id).await?;
+
+    state
+        .upload_service
+        .set_community_limit(community_id, input.max_upload_bytes)
+        .await?;
+
+    upload_limit(&state, community_id).await.map(Json)
+}
+
+async fn upload_limit(state: &AppState, community_id: Uuid) -> Result<UploadLimit> {
+    Ok(UploadLimit {
+        max_upload_bytes: state.upload_service.effective_community_limit(community_id).await?,
+        community_max_upload_bytes: state.upload_service.community_limit(community_id).await?,
+        server_max_upload_bytes: state.upload_service.max_limit(),
+    })
+}
@@ -1,11 +1,12 @@
 use crate::auth::AuthUser;
 use crate::error::Result;
-use crate::models::{PublicUser, UpdateUser};
+use crate::models::{PublicUser, UpdatePrivacySettings, UpdateUser};
 use crate::state::AppState;
 use axum::{
     extract::{Path, State},
     Json,
 };
+use miscord_protocol::PrivacySettingsData;
 use uuid::Uuid;
 
 pub async fn get_me(
@@ -25,6 +26,30 @@ pub async fn update_me(
     Ok(Json(user.into()))
 }
 
+pub async fn get_privacy_settings(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<PrivacySettingsData>> {
+    let settings = state.user_service.get_privacy_settings(auth.user_id).await?;
+    Ok(Json(PrivacySettingsData {
+        read_receipts_enabled: settings.read_receipts_enabled,
+    }))
+}
+
+pub async fn update_privacy_settings(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(input): Json<UpdatePrivacySettings>,
+) -> Result<Json<PrivacySettingsData>> {
+    let settings = state
+        .user_service
+        .update_privacy_settings(auth.user_id, input)
+        .await?;
+    Ok(Json(PrivacySettingsData {
+        read_receipts_enabled: settings.read_receipts_enabled,
+    }))
+}
+
 pub async fn get_user(
     State(state): State<AppState>,
     _auth: AuthUser,
@@ -0,0 +1,372 @@
+use crate::auth::AuthUser;
+use crate::error::{AppError, Result};
+use crate::models::{
+    AttachmentOwner, Channel, ChannelType, CreateMessage, CreateWebhook, ExecuteWebhook, UpdateWebhook, Webhook,
+    WebhookEmbed, WebhookMessage,
+};
+use crate::state::AppState;
+use axum::{
+    extract::{FromRequest, Multipart, Path, Request, State},
+    http::{header, StatusCode},
+    Json,
+};
+use miscord_protocol::{EmbedData, EmbedFieldData, MessageData, WebhookAuthorData, WebhookData};
+use uuid::Uuid;
+
+use super::bots::require_community_owner;
+use super::messages::validate_embeds;
+
+/// Same as Discord's limits
+const MAX_NAME_LENGTH: usize = 80;
+const MAX_FILES: usize = 10;
+
+fn to_webhook_data(webhook: Webhook) -> WebhookData {
+    WebhookData {
+        url: format!("/api/webhooks/{}/{}", webhook.id, webhook.token),
+        id: webhook.id,
+        channel_id: webhook.channel_id,
+        name: webhook.name,
+        avatar_url: webhook.avatar_url,
+        token: webhook.token,
+        created_at: webhook.created_at,
+    }
+}
+
+pub fn to_webhook_author_data(message: WebhookMessage) -> WebhookAuthorData {
+    WebhookAuthorData {
+        webhook_id: message.webhook_id,
+        username: message.username,
+        avatar_url: message.avatar_url,
+    }
+}
+
+/// Webhooks are managed by the owner of the channel's community
+async fn require_manager(state: &AppState, channel: &Channel, user_id: Uuid) -> Result<()> {
+    let community_id = channel
+        .community_id
+        .ok_or_else(|| AppError::BadRequest("Webhooks can only post in community channels".to_string()))?;
+
+    require_community_owner(state, community_id, user_id).await
+}
+
+fn validate_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "Webhook name must be 1 to {} characters",
+            MAX_NAME_LENGTH
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_avatar_url(url: &str) -> Result<()> {
+    if !url.is_empty() && !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(AppError::BadRequest("Avatar URL must be an http(s) URL".to_string()));
+    }
+    Ok(())
+}
+
+/// List a channel's webhooks
+/// GET /api/channels/:id/webhooks
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<Uuid>,
+) -> Result<Json<Vec<WebhookData>>> {
+    let channel = state.channel_service.get_by_id(channel_id).await?;
+    require_manager(&state, &channel, auth.user_id).await?;
+
+    let webhooks = state.webhook_service.list_by_channel(channel_id).await?;
+
+    Ok(Json(webhooks.into_iter().map(to_webhook_data).collect()))
+}
+
+/// Create an incoming webhook for a channel
+/// POST /api/channels/:id/webhooks
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<Uuid>,
+    Json(input): Json<CreateWebhook>,
+) -> Result<Json<WebhookData>> {
+    let channel = state.channel_service.get_by_id(channel_id).await?;
+    require_manager(&state, &channel, auth.user_id).await?;
+
+    if !matches!(channel.channel_type, ChannelType::Text | ChannelType::Announcement) {
+        return Err(AppError::BadRequest(
+            "Webhooks can only post in text and announcement channels".to_string(),
+        ));
+    }
+
+    let name = validate_name(&input.name)?;
+    let avatar_url = input.avatar_url.filter(|url| !url.is_empty());
+    if let Some(url) = &avatar_url {
+        validate_avatar_url(url)?;
+    }
+
+    let webhook = state
+        .webhook_service
+        .create(channel_id, &name, avatar_url.as_deref(), auth.user_id)
+        .await?;
+
+    Ok(Json(to_webhook_data(webhook)))
+}
+
+/// Rename a webhook or change its avatar
+/// PATCH /api/webhooks/:id
+pub async fn update_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(input): Json<UpdateWebhook>,
+) -> Result<Json<WebhookData>> {
+    let webhook = state.webhook_service.get_by_id(id).await?;
+    let channel = state.channel_service.get_by_id(webhook.channel_id).await?;
+    require_manager(&state, &channel, auth.user_id).await?;
+
+    let name = input.name.as_deref().map(validate_name).transpose()?;
+    if let Some(url) = &input.avatar_url {
+        validate_avatar_url(url)?;
+    }
+
+    let webhook = state
+        .webhook_service
+        .update(id, name.as_deref(), input.avatar_url.as_deref())
+        .await?;
+
+    Ok(Json(to_webhook_data(webhook)))
+}
+
+/// Replace a webhook's token; the old URL stops working
+/// POST /api/webhooks/:id/token
+pub async fn regenerate_webhook_token(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<WebhookData>> {
+    let webhook = state.webhook_service.get_by_id(id).await?;
+    let channel = state.channel_service.get_by_id(webhook.channel_id).await?;
+    require_manager(&state, &channel, auth.user_id).await?;
+
+    let webhook = state.webhook_service.regenerate_token(id).await?;
+
+    Ok(Json(to_webhook_data(webhook)))
+}
+
+/// Delete a webhook; messages it posted are kept
+/// DELETE /api/webhooks/:id
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    let webhook = state.webhook_service.get_by_id(id).await?;
+    let channel = state.channel_service.get_by_id(webhook.channel_id).await?;
+    require_manager(&state, &channel, auth.user_id).await?;
+
+    state.webhook_service.delete(id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Convert an embed from Discord's nested format; empty strings count as absent
+fn to_embed_data(embed: WebhookEmbed) -> EmbedData {
+    let non_empty = |text: Option<String>| text.filter(|t| !t.trim().is_empty());
+    EmbedData {
+        title: non_empty(embed.title),
+        description: non_empty(embed.description),
+        url: non_empty(embed.url),
+        color: embed.color,
+        fields: embed
+            .fields
+            .into_iter()
+            .map(|f| EmbedFieldData {
+                name: f.name,
+                value: f.value,
+                inline: f.inline,
+            })
+            .collect(),
+        thumbnail_url: non_empty(embed.thumbnail.map(|t| t.url)),
+        image_url: non_empty(embed.image.map(|i| i.url)),
+        footer: non_empty(embed.footer.map(|f| f.text)),
+    }
+}
+
+/// A file sent along with a webhook call
+struct WebhookFile {
+    filename: String,
+    data: Vec<u8>,
+}
+
+/// Read a webhook call, either JSON or multipart with a `payload_json` field and files
+async fn read_webhook_body(state: &AppState, request: Request) -> Result<(ExecuteWebhook, Vec<WebhookFile>)> {
+    let is_multipart = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("multipart/form-data"));
+
+    if !is_multipart {
+        let Json(payload) = Json::<ExecuteWebhook>::from_request(request, state)
+            .await
+            .map_err(|e| AppError::BadRequest(e.body_text()))?;
+        return Ok((payload, Vec::new()));
+    }
+
+    let mut multipart = Multipart::from_request(request, state)
+        .await
+        .map_err(|e| AppError::BadRequest(e.body_text()))?;
+    let mut payload = ExecuteWebhook::default();
+    let mut files = Vec::new();
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        AppError::BadRequest(format!("Failed to read multipart field: {}", e))
+    })? {
+        let name = field.name().unwrap_or_default().to_string();
+
+        if let Some(filename) = field.file_name().map(String::from) {
+            let data = field.bytes().await.map_err(|e| {
+                AppError::BadRequest(format!("Failed to read file data: {}", e))
+            })?;
+            files.push(WebhookFile {
+                filename,
+                data: data.to_vec(),
+            });
+        } else if name == "payload_json" {
+            let text = field.text().await.map_err(|e| {
+                AppError::BadRequest(format!("Failed to read payload_json: {}", e))
+            })?;
+            payload = serde_json::from_str(&text)
+                .map_err(|e| AppError::BadRequest(format!("Invalid payload_json: {}", e)))?;
+        } else if name == "content" {
+            payload.content = field.text().await.map_err(|e| {
+                AppError::BadRequest(format!("Failed to read content: {}", e))
+            })?;
+        }
+    }
+
+    Ok((payload, files))
+}
+
+/// Post a message through a webhook. Needs no login; the token in the URL is the secret.
+/// POST /api/webhooks/:id/:token
+pub async fn execute_webhook(
+    State(state): State<AppState>,
+    Path((id, token)): Path<(Uuid, String)>,
+    request: Request,
+) -> Result<Json<MessageData>> {
+    let webhook = state.webhook_service.get_by_token(id, &token).await?;
+    let (payload, files) = read_webhook_body(&state, request).await?;
+
+    let embeds: Vec<EmbedData> = payload.embeds.into_iter().map(to_embed_data).collect();
+    validate_embeds(&embeds)?;
+    if files.len() > MAX_FILES {
+        return Err(AppError::BadRequest(format!("At most {} files are allowed", MAX_FILES)));
+    }
+    for file in &files {
+        state.attachment_service.validate_file(&file.filename, &file.data)?;
+    }
+
+    let channel = state.channel_service.get_by_id(webhook.channel_id).await?;
+    let owner = AttachmentOwner {
+        message_id: None,
+        uploader_id: webhook.user_id,
+        community_id: channel.community_id,
+    };
+    if !files.is_empty() {
+        let total: i64 = files.iter().map(|file| file.data.len() as i64).sum();
+        state.quota_service.check(&owner, total).await?;
+    }
+
+    let content = payload.content.trim_end().to_string();
+    if content.trim().is_empty() && embeds.is_empty() && files.is_empty() {
+        return Err(AppError::BadRequest(
+            "Webhook messages need content, embeds or files".to_string(),
+        ));
+    }
+
+    let username = match payload.username.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => validate_name(name)?,
+        _ => webhook.name.clone(),
+    };
+    let avatar_url = match payload.avatar_url.filter(|url| !url.is_empty()) {
+        Some(url) => {
+            validate_avatar_url(&url)?;
+            Some(url)
+        }
+        None => webhook.avatar_url.clone(),
+    };
+
+    // Files are stored first as unsent uploads; the message then takes them over in
+    // the same transaction that creates it. Should that fail, the uploads are purged
+    // with other unsent ones.
+    if !files.is_empty() {
+        state.attachment_service.ensure_storage_ready().await?;
+    }
+    let mut saved = Vec::with_capacity(files.len());
+    for file in files {
+        saved.push(
+            state
+                .attachment_service
+                .save_file(owner, &file.filename, &file.data)
+                .await?,
+        );
+    }
+
+    let (message, webhook_message) = state
+        .webhook_service
+        .post_message(
+            &webhook,
+            &username,
+            avatar_url.as_deref(),
+            CreateMessage {
+                content,
+                reply_to_id: None,
+                attachment_ids: saved.iter().map(|attachment| attachment.id).collect(),
+                embeds: embeds.clone(),
+            },
+        )
+        .await?;
+
+    let attachments = saved
+        .into_iter()
+        .map(miscord_protocol::AttachmentData::from)
+        .collect();
+
+    let message_data = MessageData {
+        id: message.id,
+        channel_id: message.channel_id,
+        author_id: message.author_id,
+        author_name: username,
+        content: message.content,
+        edited_at: None,
+        reply_to_id: None,
+        reactions: vec![],
+        attachments,
+        created_at: message.created_at,
+        thread_parent_id: None,
+        reply_count: 0,
+        last_reply_at: None,
+        pinned_at: None,
+        pinned_by: None,
+        forwarded_from: None,
+        published_at: None,
+        webhook: Some(to_webhook_author_data(webhook_message)),
+        embeds,
+        components: Vec::new(),
+        ephemeral: false,
+    };
+
+    state
+        .connections
+        .broadcast_to_channel(
+            webhook.channel_id,
+            &miscord_protocol::ServerMessage::MessageCreated {
+                message: message_data.clone(),
+            },
+        )
+        .await;
+
+    Ok(Json(message_data))
+}
@@ -1,4 +1,5 @@
 use crate::error::{AppError, Result};
+use crate::services::bot::BOT_TOKEN_PREFIX;
 use crate::state::AppState;
 use axum::{
     extract::FromRequestParts,
@@ -56,6 +57,29 @@ pub fn verify_token(token: &str, secret: &str) -> Result<Claims> {
 pub struct AuthUser {
     pub user_id: Uuid,
     pub username: String,
+    /// Signed in with a bot token
+    pub bot: bool,
+}
+
+/// Check a login JWT or a bot token, as sent in the Authorization header or the
+/// WebSocket Authenticate message
+pub async fn authenticate(state: &AppState, token: &str) -> Result<AuthUser> {
+    if token.starts_with(BOT_TOKEN_PREFIX) {
+        let (user_id, username) = state.bot_service.authenticate(token).await?;
+        return Ok(AuthUser {
+            user_id,
+            username,
+            bot: true,
+        });
+    }
+
+    let claims = verify_token(token, &state.config.jwt_secret).map_err(|_| AppError::Unauthorized)?;
+
+    Ok(AuthUser {
+        user_id: claims.sub,
+        username: claims.username,
+        bot: false,
+    })
 }
 
 impl FromRequestParts<AppState> for AuthUser {
@@ -72,24 +96,20 @@ impl FromRequestParts<AppState> for AuthUser {
             .and_then(|h| h.to_str().ok())
             .map(|s| s.to_string());
 
-        let jwt_secret = state.config.jwt_secret.clone();
+        let state = state.clone();
 
         async move {
             let auth_header = auth_header.ok_or(AppError::Unauthorized)?;
 
-            // Extract the bearer token
+            // Bearer for login tokens; bots may also use the "Bot" scheme
             let token = auth_header
                 .strip_prefix("Bearer ")
+                .or_else(|| auth_header.strip_prefix("Bot "))
                 .ok_or(AppError::Unauthorized)?;
 
-            // Verify the token using the JWT secret from config
-            let claims = verify_token(token, &jwt_secret)
-                .map_err(|_| AppError::Unauthorized)?;
-
-            Ok(AuthUser {
-                user_id: claims.sub,
-                username: claims.username,
-            })
+            authenticate(&state, token)
+                .await
+                .map_err(|_| AppError::Unauthorized)
         }
     }
 }
@@ -0,0 +1,157 @@
+//! Self-contained HTML rendering: inline styles, no scripts or remote resources
+
+use super::{ExportDocument, ExportedMessage, format_size, format_timestamp};
+use std::fmt::Write;
+
+const STYLE: &str = r#"
+body { background: #313338; color: #dbdee1; font-family: sans-serif; margin: 0; padding: 24px; }
+header { border-bottom: 1px solid #4e5058; margin-bottom: 16px; padding-bottom: 12px; }
+h1 { font-size: 20px; margin: 0 0 4px; }
+.meta, .time, .edited { color: #949ba4; font-size: 12px; }
+.message { padding: 6px 0; }
+.author { font-weight: bold; color: #f2f3f5; }
+.username { color: #949ba4; font-size: 12px; }
+.content { white-space: pre-wrap; word-wrap: break-word; margin-top: 2px; }
+.reply-to { color: #949ba4; font-size: 12px; }
+.reply-to a { color: #949ba4; }
+.pinned { color: #f0b232; font-size: 12px; }
+.reactions span { background: #2b2d31; border-radius: 8px; display: inline-block; font-size: 13px; margin: 4px 4px 0 0; padding: 2px 6px; }
+.attachments img { border-radius: 4px; display: block; margin-top: 4px; max-height: 300px; max-width: 400px; }
+.attachments a { color: #00a8fc; }
+.thread { border-left: 2px solid #4e5058; margin: 4px 0 4px 16px; padding-left: 12px; }
+"#;
+
+/// Escape text for use in HTML element content and attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub fn render(document: &ExportDocument) -> String {
+    let mut title = format!("#{}", document.channel.name);
+    if let Some(community) = &document.community {
+        let _ = write!(title, " - {}", community);
+    }
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n",
+        escape(&title),
+        STYLE
+    );
+
+    let _ = write!(html, "<header>\n<h1>{}</h1>\n", escape(&title));
+    if let Some(topic) = &document.channel.topic {
+        let _ = writeln!(html, "<div class=\"meta\">{}</div>", escape(topic));
+    }
+    let _ = write!(
+        html,
+        "<div class=\"meta\">{} messages &middot; exported {}</div>\n</header>\n",
+        document.message_count,
+        format_timestamp(document.exported_at)
+    );
+
+    for message in &document.messages {
+        render_message(&mut html, message);
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn render_message(html: &mut String, message: &ExportedMessage) {
+    let _ = writeln!(html, "<div class=\"message\" id=\"msg-{}\">", message.id);
+
+    if let Some(reply_to_id) = message.reply_to_id {
+        let _ = writeln!(
+            html,
+            "<div class=\"reply-to\">&#8617; <a href=\"#msg-{}\">Reply</a></div>",
+            reply_to_id
+        );
+    }
+
+    let _ = write!(
+        html,
+        "<span class=\"author\">{}</span> <span class=\"username\">@{}</span> <span class=\"time\">{}</span>",
+        escape(&message.author.display_name),
+        escape(&message.author.username),
+        format_timestamp(message.created_at)
+    );
+    if let Some(edited_at) = message.edited_at {
+        let _ = write!(html, " <span class=\"edited\">(edited {})</span>", format_timestamp(edited_at));
+    }
+    if let Some(pinned_at) = message.pinned_at {
+        let pinned_by = message.pinned_by.as_deref().unwrap_or("Unknown");
+        let _ = write!(
+            html,
+            " <span class=\"pinned\">&#128204; Pinned by {} {}</span>",
+            escape(pinned_by),
+            format_timestamp(pinned_at)
+        );
+    }
+    html.push('\n');
+
+    if !message.content.is_empty() {
+        let _ = writeln!(html, "<div class=\"content\">{}</div>", escape(&message.content));
+    }
+
+    if !message.attachments.is_empty() {
+        html.push_str("<div class=\"attachments\">\n");
+        for attachment in &message.attachments {
+            let path = escape(&attachment.path);
+            if attachment.content_type.starts_with("image/") {
+                let _ = writeln!(
+                    html,
+                    "<a href=\"{}\"><img src=\"{}\" alt=\"{}\"></a>",
+                    path,
+                    path,
+                    escape(&attachment.filename)
+                );
+            } else {
+                let _ = writeln!(
+                    html,
+                    "<div><a href=\"{}\">{}</a> ({})</div>",
+                    path,
+                    escape(&attachment.filename),
+                    format_size(attachment.size_bytes)
+                );
+            }
+        }
+        html.push_str("</div>\n");
+    }
+
+    if !message.reactions.is_empty() {
+        html.push_str("<div class=\"reactions\">");
+        for reaction in &message.reactions {
+            let _ = write!(
+                html,
+                "<span title=\"{}\">{} {}</span>",
+                escape(&reaction.users.join(", ")),
+                escape(&reaction.emoji),
+                reaction.users.len()
+            );
+        }
+        html.push_str("</div>\n");
+    }
+
+    if !message.replies.is_empty() {
+        html.push_str("<div class=\"thread\">\n");
+        for reply in &message.replies {
+            render_message(html, reply);
+        }
+        html.push_str("</div>\n");
+    }
+
+    html.push_str("</div>\n");
+}
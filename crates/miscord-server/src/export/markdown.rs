@@ -0,0 +1,96 @@
+//! Markdown rendering; thread replies are nested as block quotes
+
+use super::{ExportDocument, ExportedMessage, format_size, format_timestamp};
+use std::fmt::Write;
+
+pub fn render(document: &ExportDocument) -> String {
+    let mut markdown = format!("# #{}", document.channel.name);
+    if let Some(community) = &document.community {
+        let _ = write!(markdown, " ({})", community);
+    }
+    markdown.push_str("\n\n");
+
+    if let Some(topic) = &document.channel.topic {
+        let _ = write!(markdown, "{}\n\n", topic);
+    }
+    let _ = write!(
+        markdown,
+        "*{} messages, exported {}*\n\n---\n\n",
+        document.message_count,
+        format_timestamp(document.exported_at)
+    );
+
+    for message in &document.messages {
+        render_message(&mut markdown, message, "");
+    }
+
+    markdown
+}
+
+/// Write a message, with every line starting with `prefix`
+fn render_message(markdown: &mut String, message: &ExportedMessage, prefix: &str) {
+    let mut block = String::new();
+
+    let _ = write!(
+        block,
+        "**{}** (@{}) · {}",
+        message.author.display_name,
+        message.author.username,
+        format_timestamp(message.created_at)
+    );
+    if message.edited_at.is_some() {
+        block.push_str(" *(edited)*");
+    }
+    if message.pinned_at.is_some() {
+        let pinned_by = message.pinned_by.as_deref().unwrap_or("Unknown");
+        let _ = write!(block, " 📌 *pinned by {}*", pinned_by);
+    }
+    block.push('\n');
+
+    if let Some(reply_to_id) = message.reply_to_id {
+        let _ = writeln!(block, "↩ *in reply to message {}*", reply_to_id);
+    }
+
+    if !message.content.is_empty() {
+        let _ = writeln!(block, "\n{}", message.content);
+    }
+
+    if !message.attachments.is_empty() {
+        block.push('\n');
+        for attachment in &message.attachments {
+            let image = if attachment.content_type.starts_with("image/") { "!" } else { "" };
+            let _ = writeln!(
+                block,
+                "- {}[{}]({}) ({})",
+                image,
+                attachment.filename,
+                attachment.path,
+                format_size(attachment.size_bytes)
+            );
+        }
+    }
+
+    if !message.reactions.is_empty() {
+        let reactions: Vec<String> = message
+            .reactions
+            .iter()
+            .map(|r| format!("{} {} ({})", r.emoji, r.users.len(), r.users.join(", ")))
+            .collect();
+        let _ = writeln!(block, "\n{}", reactions.join(" · "));
+    }
+
+    for line in block.lines() {
+        let _ = writeln!(markdown, "{}{}", prefix, line);
+    }
+    let _ = writeln!(markdown, "{}", prefix.trim_end());
+
+    if !message.replies.is_empty() {
+        let reply_prefix = format!("{}> ", prefix);
+        for reply in &message.replies {
+            render_message(markdown, reply, &reply_prefix);
+        }
+        if prefix.is_empty() {
+            markdown.push('\n');
+        }
+    }
+}
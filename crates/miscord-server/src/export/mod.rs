@@ -0,0 +1,290 @@
+//! Rendering of channel and thread history into downloadable archives
+
+mod html;
+mod markdown;
+
+use crate::models::{Export, ExportFormat, ExportStatus};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Everything written to an export, in the shape of the JSON output
+#[derive(Debug, Serialize)]
+pub struct ExportDocument {
+    pub exported_at: DateTime<Utc>,
+    pub community: Option<String>,
+    pub channel: ExportedChannel,
+    /// Set when only a single thread was exported
+    pub thread_parent_id: Option<Uuid>,
+    pub message_count: usize,
+    /// Top-level messages, oldest first, with thread replies nested under their parent
+    pub messages: Vec<ExportedMessage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportedChannel {
+    pub id: Uuid,
+    pub name: String,
+    pub topic: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedAuthor {
+    pub id: Uuid,
+    pub username: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportedMessage {
+    pub id: Uuid,
+    pub author: ExportedAuthor,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub reply_to_id: Option<Uuid>,
+    pub pinned_at: Option<DateTime<Utc>>,
+    pub pinned_by: Option<String>,
+    pub reactions: Vec<ExportedReaction>,
+    pub attachments: Vec<ExportedAttachment>,
+    pub replies: Vec<ExportedMessage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportedReaction {
+    pub emoji: String,
+    /// Display names of the users who reacted
+    pub users: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportedAttachment {
+    pub id: Uuid,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    /// Location of the file inside the zip
+    pub path: String,
+}
+
+/// Convert an export record for the API and WebSocket events
+pub fn to_export_data(export: Export) -> miscord_protocol::ExportData {
+    miscord_protocol::ExportData {
+        id: export.id,
+        channel_id: export.channel_id,
+        thread_parent_id: export.thread_parent_id,
+        format: match export.format {
+            ExportFormat::Json => miscord_protocol::ExportFormat::Json,
+            ExportFormat::Html => miscord_protocol::ExportFormat::Html,
+            ExportFormat::Markdown => miscord_protocol::ExportFormat::Markdown,
+        },
+        status: match export.status {
+            ExportStatus::Pending => miscord_protocol::ExportStatus::Pending,
+            ExportStatus::Running => miscord_protocol::ExportStatus::Running,
+            ExportStatus::Completed => miscord_protocol::ExportStatus::Completed,
+            ExportStatus::Failed => miscord_protocol::ExportStatus::Failed,
+        },
+        exported_messages: export.exported_messages,
+        total_messages: export.total_messages,
+        error: export.error,
+        created_at: export.created_at,
+        completed_at: export.completed_at,
+    }
+}
+
+/// A stored file to copy into the archive
+pub struct ExportFile {
+    pub zip_path: String,
+    pub disk_path: PathBuf,
+}
+
+/// Location inside the zip for an attachment, safe to use as a relative link
+pub fn attachment_zip_path(attachment_id: Uuid, filename: &str) -> String {
+    let safe: String = filename
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    format!("attachments/{}-{}", attachment_id, safe)
+}
+
+/// Write the rendered document and its attachments to a zip file.
+/// The archive is built next to `dest` and only moved into place once complete.
+/// Blocking; run it on a blocking thread.
+pub fn write_zip(
+    dest: &Path,
+    document: &ExportDocument,
+    format: ExportFormat,
+    files: &[ExportFile],
+) -> anyhow::Result<()> {
+    let partial = dest.with_extension("zip.part");
+    let mut zip = ZipWriter::new(File::create(&partial)?);
+
+    let (name, contents) = match format {
+        ExportFormat::Json => ("messages.json", serde_json::to_vec_pretty(document)?),
+        ExportFormat::Html => ("messages.html", html::render(document).into_bytes()),
+        ExportFormat::Markdown => ("messages.md", markdown::render(document).into_bytes()),
+    };
+    zip.start_file(name, SimpleFileOptions::default().compression_method(CompressionMethod::Deflated))?;
+    zip.write_all(&contents)?;
+
+    // Media is usually compressed already, so attachments are stored as-is
+    let stored = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .large_file(true);
+    for file in files {
+        let mut source = match File::open(&file.disk_path) {
+            Ok(source) => source,
+            Err(e) => {
+                tracing::warn!("Skipping missing export file {:?}: {}", file.disk_path, e);
+                continue;
+            }
+        };
+        zip.start_file(file.zip_path.as_str(), stored)?;
+        io::copy(&mut source, &mut zip)?;
+    }
+
+    zip.finish()?;
+    std::fs::rename(&partial, dest)?;
+
+    Ok(())
+}
+
+/// Timestamp format shared by the HTML and Markdown renderers
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+/// Human readable file size
+fn format_size(bytes: i64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn author(name: &str) -> ExportedAuthor {
+        ExportedAuthor {
+            id: Uuid::new_v4(),
+            username: name.to_lowercase(),
+            display_name: name.to_string(),
+        }
+    }
+
+    fn message(author_name: &str, content: &str) -> ExportedMessage {
+        ExportedMessage {
+            id: Uuid::new_v4(),
+            author: author(author_name),
+            content: content.to_string(),
+            created_at: Utc.with_ymd_and_hms(2025, 1, 2, 3, 4, 0).unwrap(),
+            edited_at: None,
+            reply_to_id: None,
+            pinned_at: None,
+            pinned_by: None,
+            reactions: Vec::new(),
+            attachments: Vec::new(),
+            replies: Vec::new(),
+        }
+    }
+
+    fn document(messages: Vec<ExportedMessage>) -> ExportDocument {
+        ExportDocument {
+            exported_at: Utc.with_ymd_and_hms(2025, 2, 1, 12, 0, 0).unwrap(),
+            community: Some("Rustaceans".to_string()),
+            channel: ExportedChannel {
+                id: Uuid::new_v4(),
+                name: "general".to_string(),
+                topic: Some("Talk about <anything>".to_string()),
+            },
+            thread_parent_id: None,
+            message_count: messages.len(),
+            messages,
+        }
+    }
+
+    /// A message with an image, a file, a reaction and a two-line thread reply
+    fn sample() -> ExportDocument {
+        let mut parent = message("Alice", "Look at <b>this</b> & that");
+        let attachment_id = Uuid::new_v4();
+        parent.attachments = vec![
+            ExportedAttachment {
+                id: attachment_id,
+                filename: "cat.png".to_string(),
+                content_type: "image/png".to_string(),
+                size_bytes: 2048,
+                path: attachment_zip_path(attachment_id, "cat.png"),
+            },
+            ExportedAttachment {
+                id: Uuid::new_v4(),
+                filename: "notes.txt".to_string(),
+                content_type: "text/plain".to_string(),
+                size_bytes: 10,
+                path: "attachments/notes.txt".to_string(),
+            },
+        ];
+        parent.reactions = vec![ExportedReaction {
+            emoji: "👍".to_string(),
+            users: vec!["Bob".to_string(), "Carol".to_string()],
+        }];
+        parent.replies = vec![message("Bob", "first line\nsecond line")];
+        document(vec![parent])
+    }
+
+    #[test]
+    fn markdown_rendering() {
+        let markdown = markdown::render(&sample());
+
+        assert!(markdown.starts_with("# #general (Rustaceans)\n\nTalk about <anything>\n\n"));
+        assert!(markdown.contains("*1 messages, exported 2025-02-01 12:00 UTC*"));
+        assert!(markdown.contains("**Alice** (@alice) · 2025-01-02 03:04 UTC\n"));
+        assert!(markdown.contains("Look at <b>this</b> & that"));
+        assert!(markdown.contains("- ![cat.png](attachments/"));
+        assert!(markdown.contains("- [notes.txt](attachments/notes.txt) (10 B)"));
+        assert!(markdown.contains("👍 2 (Bob, Carol)"));
+        // Every line of a thread reply is quoted
+        assert!(markdown.contains("> **Bob** (@bob)"));
+        assert!(markdown.contains("> first line\n> second line\n"));
+    }
+
+    #[test]
+    fn html_rendering() {
+        let doc = sample();
+        let html = html::render(&doc);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>#general - Rustaceans</title>"));
+        assert!(html.contains("Talk about &lt;anything&gt;"));
+        // Message text is escaped, never interpreted
+        assert!(html.contains("Look at &lt;b&gt;this&lt;/b&gt; &amp; that"));
+        assert!(!html.contains("<b>this</b>"));
+        assert!(html.contains(&format!("id=\"msg-{}\"", doc.messages[0].id)));
+        assert!(html.contains("<img src=\"attachments/"));
+        assert!(html.contains("<a href=\"attachments/notes.txt\">notes.txt</a> (10 B)"));
+        assert!(html.contains("<span title=\"Bob, Carol\">👍 2</span>"));
+        assert!(html.contains("<div class=\"thread\">"));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn zip_paths_and_sizes() {
+        let id = Uuid::new_v4();
+        assert_eq!(attachment_zip_path(id, "my file?.png"), format!("attachments/{}-my_file_.png", id));
+        assert_eq!(attachment_zip_path(id, "../../etc/passwd"), format!("attachments/{}-.._.._etc_passwd", id));
+
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MB");
+    }
+}
@@ -0,0 +1,364 @@
+//! Which files attachments may be, judged by their contents rather than trusting the
+//! filename or the content type the client sends
+
+/// How many bytes of a text file are checked for binary content
+const TEXT_SNIFF_LEN: usize = 8192;
+
+/// A kind of file attachments may be
+pub struct FileType {
+    pub extensions: &'static [&'static str],
+    /// What the file is stored and served as
+    pub content_type: &'static str,
+    /// Whether the start of a file looks like this kind
+    matches: fn(&[u8]) -> bool,
+}
+
+/// Every allowed kind of attachment
+const FILE_TYPES: &[FileType] = &[
+    // Images
+    FileType { extensions: &["jpg", "jpeg"], content_type: "image/jpeg", matches: is_jpeg },
+    FileType { extensions: &["png"], content_type: "image/png", matches: is_png },
+    FileType { extensions: &["gif"], content_type: "image/gif", matches: is_gif },
+    FileType { extensions: &["webp"], content_type: "image/webp", matches: is_webp },
+    FileType { extensions: &["svg"], content_type: "image/svg+xml", matches: is_text },
+    FileType { extensions: &["ico"], content_type: "image/x-icon", matches: is_ico },
+    FileType { extensions: &["bmp"], content_type: "image/bmp", matches: is_bmp },
+    // Documents
+    FileType { extensions: &["pdf"], content_type: "application/pdf", matches: is_pdf },
+    FileType { extensions: &["doc"], content_type: "application/msword", matches: is_ole },
+    FileType { extensions: &["xls"], content_type: "application/vnd.ms-excel", matches: is_ole },
+    FileType { extensions: &["ppt"], content_type: "application/vnd.ms-powerpoint", matches: is_ole },
+    FileType {
+        extensions: &["docx"],
+        content_type: "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        matches: is_zip,
+    },
+    FileType {
+        extensions: &["xlsx"],
+        content_type: "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        matches: is_zip,
+    },
+    FileType {
+        extensions: &["pptx"],
+        content_type: "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        matches: is_zip,
+    },
+    FileType { extensions: &["odt"], content_type: "application/vnd.oasis.opendocument.text", matches: is_zip },
+    FileType {
+        extensions: &["ods"],
+        content_type: "application/vnd.oasis.opendocument.spreadsheet",
+        matches: is_zip,
+    },
+    FileType {
+        extensions: &["odp"],
+        content_type: "application/vnd.oasis.opendocument.presentation",
+        matches: is_zip,
+    },
+    FileType { extensions: &["txt"], content_type: "text/plain", matches: is_text },
+    FileType { extensions: &["rtf"], content_type: "application/rtf", matches: is_rtf },
+    FileType { extensions: &["csv"], content_type: "text/csv", matches: is_text },
+    FileType { extensions: &["md"], content_type: "text/markdown", matches: is_text },
+    // Archives
+    FileType { extensions: &["zip"], content_type: "application/zip", matches: is_zip },
+    FileType { extensions: &["tar"], content_type: "application/x-tar", matches: is_tar },
+    FileType { extensions: &["gz"], content_type: "application/gzip", matches: is_gzip },
+    FileType { extensions: &["7z"], content_type: "application/x-7z-compressed", matches: is_7z },
+    FileType { extensions: &["rar"], content_type: "application/vnd.rar", matches: is_rar },
+    // Audio
+    FileType { extensions: &["mp3"], content_type: "audio/mpeg", matches: is_mp3 },
+    FileType { extensions: &["wav"], content_type: "audio/wav", matches: is_wav },
+    FileType { extensions: &["ogg"], content_type: "audio/ogg", matches: is_ogg },
+    FileType { extensions: &["flac"], content_type: "audio/flac", matches: is_flac },
+    FileType { extensions: &["m4a"], content_type: "audio/mp4", matches: is_iso_media },
+    FileType { extensions: &["aac"], content_type: "audio/aac", matches: is_aac },
+    // Video
+    FileType { extensions: &["mp4"], content_type: "video/mp4", matches: is_iso_media },
+    FileType { extensions: &["webm"], content_type: "video/webm", matches: is_matroska },
+    FileType { extensions: &["mov"], content_type: "video/quicktime", matches: is_iso_media },
+    FileType { extensions: &["avi"], content_type: "video/x-msvideo", matches: is_avi },
+    FileType { extensions: &["mkv"], content_type: "video/x-matroska", matches: is_matroska },
+    // Code
+    FileType {
+        extensions: &[
+            "js", "ts", "py", "rs", "go", "java", "c", "cpp", "h", "hpp", "cs", "css", "toml", "sql",
+            "sh",
+        ],
+        content_type: "text/plain",
+        matches: is_text,
+    },
+    FileType { extensions: &["html"], content_type: "text/html", matches: is_text },
+    FileType { extensions: &["json"], content_type: "application/json", matches: is_text },
+    FileType { extensions: &["xml"], content_type: "application/xml", matches: is_text },
+    FileType { extensions: &["yaml", "yml"], content_type: "application/yaml", matches: is_text },
+];
+
+/// Content types a browser would run scripts in if it displayed them
+const ACTIVE_CONTENT_TYPES: &[&str] = &[
+    "text/html",
+    "application/xhtml+xml",
+    "image/svg+xml",
+    "text/xml",
+    "application/xml",
+];
+
+/// Extensions of files a browser would run scripts in, whatever their content type says
+const ACTIVE_EXTENSIONS: &[&str] = &["html", "htm", "xhtml", "svg", "xml"];
+
+fn extension(filename: &str) -> String {
+    filename
+        .rsplit('.')
+        .next()
+        .map(|s| s.to_lowercase())
+        .unwrap_or_default()
+}
+
+/// The kind of attachment a filename claims to be, or None when its extension isn't allowed
+pub fn for_filename(filename: &str) -> Option<&'static FileType> {
+    let extension = extension(filename);
+    FILE_TYPES
+        .iter()
+        .find(|file_type| file_type.extensions.contains(&extension.as_str()))
+}
+
+impl FileType {
+    /// Whether a file's first bytes are this kind's. Only the start of the file is looked at,
+    /// so the first chunk of an upload is enough.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        (self.matches)(data)
+    }
+}
+
+/// Whether serving a file inline could run scripts on the server's origin, so it must
+/// only ever be downloaded
+pub fn is_active_content(filename: &str, content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+
+    ACTIVE_CONTENT_TYPES.contains(&essence.as_str())
+        || ACTIVE_EXTENSIONS.contains(&extension(filename).as_str())
+}
+
+fn has_at(data: &[u8], offset: usize, magic: &[u8]) -> bool {
+    data.get(offset..offset + magic.len()) == Some(magic)
+}
+
+fn is_riff(data: &[u8], form: &[u8; 4]) -> bool {
+    data.starts_with(b"RIFF") && has_at(data, 8, form)
+}
+
+fn is_jpeg(data: &[u8]) -> bool {
+    data.starts_with(&[0xFF, 0xD8, 0xFF])
+}
+
+fn is_png(data: &[u8]) -> bool {
+    data.starts_with(b"\x89PNG\r\n\x1a\n")
+}
+
+fn is_gif(data: &[u8]) -> bool {
+    data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a")
+}
+
+fn is_webp(data: &[u8]) -> bool {
+    is_riff(data, b"WEBP")
+}
+
+fn is_ico(data: &[u8]) -> bool {
+    data.starts_with(&[0x00, 0x00, 0x01, 0x00])
+}
+
+fn is_bmp(data: &[u8]) -> bool {
+    data.starts_with(b"BM")
+}
+
+/// PDF readers accept the header anywhere in the first kilobyte
+fn is_pdf(data: &[u8]) -> bool {
+    data[..data.len().min(1024)].windows(5).any(|w| w == b"%PDF-")
+}
+
+/// Compound files, used by the older Office formats
+fn is_ole(data: &[u8]) -> bool {
+    data.starts_with(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1])
+}
+
+/// Zip archives, including Office Open XML and OpenDocument files. An empty archive
+/// starts with its end record.
+fn is_zip(data: &[u8]) -> bool {
+    data.starts_with(b"PK\x03\x04") || data.starts_with(b"PK\x05\x06")
+}
+
+fn is_rtf(data: &[u8]) -> bool {
+    data.starts_with(b"{\\rtf")
+}
+
+fn is_tar(data: &[u8]) -> bool {
+    has_at(data, 257, b"ustar")
+}
+
+fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&[0x1F, 0x8B])
+}
+
+fn is_7z(data: &[u8]) -> bool {
+    data.starts_with(&[b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C])
+}
+
+fn is_rar(data: &[u8]) -> bool {
+    data.starts_with(b"Rar!\x1a\x07")
+}
+
+/// An ID3 tag, or straight into an MPEG audio frame
+fn is_mp3(data: &[u8]) -> bool {
+    data.starts_with(b"ID3") || (data.len() >= 2 && data[0] == 0xFF && data[1] & 0xE0 == 0xE0)
+}
+
+fn is_wav(data: &[u8]) -> bool {
+    is_riff(data, b"WAVE")
+}
+
+fn is_ogg(data: &[u8]) -> bool {
+    data.starts_with(b"OggS")
+}
+
+fn is_flac(data: &[u8]) -> bool {
+    data.starts_with(b"fLaC")
+}
+
+/// ADTS frames, or an ADIF header
+fn is_aac(data: &[u8]) -> bool {
+    data.starts_with(b"ADIF") || (data.len() >= 2 && data[0] == 0xFF && data[1] & 0xF6 == 0xF0)
+}
+
+/// MP4, M4A and QuickTime files, which start with a box. Old QuickTime files may
+/// start with something other than `ftyp`.
+fn is_iso_media(data: &[u8]) -> bool {
+    [b"ftyp", b"moov", b"mdat", b"wide", b"free", b"skip"]
+        .iter()
+        .any(|box_type| has_at(data, 4, *box_type))
+}
+
+/// Matroska and WebM, which start with an EBML header
+fn is_matroska(data: &[u8]) -> bool {
+    data.starts_with(&[0x1A, 0x45, 0xDF, 0xA3])
+}
+
+fn is_avi(data: &[u8]) -> bool {
+    is_riff(data, b"AVI ")
+}
+
+/// Text files have no NUL bytes, which every binary format is full of
+fn is_text(data: &[u8]) -> bool {
+    !data[..data.len().min(TEXT_SNIFF_LEN)].contains(&0)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    const JPEG: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F'];
+    const GIF: &[u8] = b"GIF89a\x01\0\x01\0";
+    const WEBP: &[u8] = b"RIFF\x24\0\0\0WEBPVP8 ";
+    const WAV: &[u8] = b"RIFF\x24\0\0\0WAVEfmt ";
+    const PDF: &[u8] = b"%PDF-1.7\n";
+    const ZIP: &[u8] = b"PK\x03\x04\x14\0";
+    const MP4: &[u8] = b"\0\0\0\x20ftypisom";
+    const WEBM: &[u8] = &[0x1A, 0x45, 0xDF, 0xA3, 0x9F];
+    const HTML: &[u8] = b"<!DOCTYPE html><script>alert(document.cookie)</script>";
+    const SVG: &[u8] = b"<svg xmlns=\"http://www.w3.org/2000/svg\"><script>alert(1)</script></svg>";
+    const EXE: &[u8] = b"MZ\x90\0\x03\0\0\0";
+
+    #[test]
+    fn content_types_by_extension() {
+        let cases = [
+            ("cat.png", Some("image/png")),
+            ("CAT.JPG", Some("image/jpeg")),
+            ("photo.jpeg", Some("image/jpeg")),
+            ("drawing.svg", Some("image/svg+xml")),
+            ("backup.tar.gz", Some("application/gzip")),
+            ("main.rs", Some("text/plain")),
+            ("page.html", Some("text/html")),
+            ("clip.mov", Some("video/quicktime")),
+            ("setup.exe", None),
+            ("cat.png.exe", None),
+            ("README", None),
+            ("", None),
+        ];
+        for (filename, content_type) in cases {
+            assert_eq!(for_filename(filename).map(|t| t.content_type), content_type, "{filename}");
+        }
+    }
+
+    #[test]
+    fn contents_checked_against_extension() {
+        let cases: &[(&str, &[u8], bool)] = &[
+            // Matching
+            ("a.png", PNG, true),
+            ("a.jpg", JPEG, true),
+            ("a.gif", GIF, true),
+            ("a.webp", WEBP, true),
+            ("a.wav", WAV, true),
+            ("a.pdf", PDF, true),
+            ("a.docx", ZIP, true),
+            ("a.mp4", MP4, true),
+            ("a.webm", WEBM, true),
+            ("a.txt", b"hello\n", true),
+            ("a.txt", b"", true),
+            ("a.svg", SVG, true),
+            // Mismatched
+            ("a.png", JPEG, false),
+            ("a.jpg", GIF, false),
+            ("a.webp", WAV, false),
+            ("a.wav", WEBP, false),
+            ("a.pdf", ZIP, false),
+            ("a.mp4", WEBM, false),
+            ("a.txt", PNG, false),
+            ("a.png", b"", false),
+            ("a.png", &PNG[..4], false),
+            // Spoofed: pages and programs passed off as something harmless
+            ("a.png", HTML, false),
+            ("a.gif", SVG, false),
+            ("a.jpg", SVG, false),
+            ("a.webp", HTML, false),
+            ("a.pdf", EXE, false),
+            ("a.zip", EXE, false),
+            ("a.txt", EXE, false),
+        ];
+        for (filename, data, expected) in cases {
+            let file_type = for_filename(filename).unwrap();
+            assert_eq!(file_type.matches(data), *expected, "{filename} {:?}", &data[..data.len().min(8)]);
+        }
+    }
+
+    #[test]
+    fn active_content() {
+        let cases = [
+            ("cat.png", "image/png", false),
+            ("cat.jpg", "image/jpeg", false),
+            ("notes.txt", "text/plain", false),
+            ("report.pdf", "application/pdf", false),
+            ("page.html", "text/html", true),
+            ("page.html", "text/html; charset=utf-8", true),
+            ("feed.xml", "application/xml", true),
+            ("drawing.svg", "image/svg+xml", true),
+            // Scripts served under an image type, by extension or by content type
+            ("drawing.svg", "image/png", true),
+            ("page.HTM", "image/jpeg", true),
+            ("page.xhtml", "image/gif", true),
+            ("cat.png", "image/svg+xml", true),
+            ("cat.png", "Text/HTML", true),
+            ("cat.png", " application/xhtml+xml ; charset=utf-8", true),
+        ];
+        for (filename, content_type, expected) in cases {
+            assert_eq!(
+                is_active_content(filename, content_type),
+                expected,
+                "{filename} served as {content_type}"
+            );
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Reading an export from a directory, a single JSON file or a zip archive
+
+use anyhow::{Context, bail};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use zip::ZipArchive;
+
+pub enum Archive {
+    /// An export directory, or the directory holding a single JSON file
+    Directory {
+        root: PathBuf,
+        files: Vec<String>,
+    },
+    Zip(ZipArchive<File>),
+}
+
+impl Archive {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        if path.is_dir() {
+            let mut files = Vec::new();
+            collect_json_files(path, path, &mut files)?;
+            files.sort();
+            return Ok(Self::Directory {
+                root: path.to_path_buf(),
+                files,
+            });
+        }
+
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+        match extension.as_deref() {
+            Some("zip") => {
+                let file =
+                    File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+                Ok(Self::Zip(ZipArchive::new(file)?))
+            }
+            Some("json") => {
+                let root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+                let name = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .context("Invalid file name")?;
+                Ok(Self::Directory {
+                    root,
+                    files: vec![name.to_string()],
+                })
+            }
+            _ => bail!("Expected a directory, a .json file or a .zip archive"),
+        }
+    }
+
+    /// Relative paths of the exported channel files
+    pub fn json_files(&self) -> Vec<String> {
+        match self {
+            Self::Directory { files, .. } => files.clone(),
+            Self::Zip(zip) => {
+                let mut files: Vec<String> = zip
+                    .file_names()
+                    .filter(|name| name.to_lowercase().ends_with(".json"))
+                    .map(str::to_string)
+                    .collect();
+                files.sort();
+                files
+            }
+        }
+    }
+
+    /// Read a file by its path relative to the export root.
+    /// Paths come from the export itself, so anything escaping the root is refused.
+    pub fn read(&mut self, relative: &str) -> anyhow::Result<Vec<u8>> {
+        let relative = relative.replace('\\', "/");
+        let escapes = Path::new(&relative)
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            bail!("Refusing to read {:?} outside the export", relative);
+        }
+
+        match self {
+            Self::Directory { root, .. } => {
+                let path = root.join(&relative);
+                std::fs::read(&path).with_context(|| format!("Failed to read {:?}", path))
+            }
+            Self::Zip(zip) => {
+                let relative = relative.trim_start_matches("./");
+                let mut file = zip.by_name(relative)?;
+                let mut data = Vec::with_capacity(file.size() as usize);
+                file.read_to_end(&mut data)?;
+                Ok(data)
+            }
+        }
+    }
+}
+
+fn collect_json_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_json_files(root, &path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+        {
+            if let Ok(relative) = path.strip_prefix(root) {
+                files.push(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    Ok(())
+}
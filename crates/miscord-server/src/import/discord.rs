@@ -0,0 +1,240 @@
+//! The parts of the DiscordChatExporter JSON format that the importer uses
+
+use chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+
+/// One exported channel or thread (one JSON file)
+#[derive(Debug, Deserialize)]
+pub struct ChannelExport {
+    pub channel: Channel,
+    #[serde(default)]
+    pub messages: Vec<Message>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Channel {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Category of a channel, or the parent channel of a thread
+    pub category_id: Option<String>,
+    pub category: Option<String>,
+    pub name: String,
+    pub topic: Option<String>,
+}
+
+impl Channel {
+    pub fn is_thread(&self) -> bool {
+        matches!(
+            self.kind.as_str(),
+            "GuildPublicThread" | "GuildPrivateThread" | "GuildNewsThread"
+        )
+    }
+
+    pub fn is_direct(&self) -> bool {
+        matches!(self.kind.as_str(), "DirectTextChat" | "DirectGroupTextChat")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub timestamp: DateTime<FixedOffset>,
+    pub timestamp_edited: Option<DateTime<FixedOffset>>,
+    #[serde(default)]
+    pub is_pinned: bool,
+    #[serde(default)]
+    pub content: String,
+    pub author: Author,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    #[serde(default)]
+    pub embeds: Vec<Embed>,
+    #[serde(default)]
+    pub reactions: Vec<Reaction>,
+    pub reference: Option<Reference>,
+}
+
+impl Message {
+    /// Regular messages and replies; joins, pin notices and other system messages are skipped
+    pub fn is_user_message(&self) -> bool {
+        matches!(self.kind.as_str(), "Default" | "Reply")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Author {
+    pub id: String,
+    pub name: String,
+    pub nickname: Option<String>,
+}
+
+impl Author {
+    pub fn display_name(&self) -> &str {
+        self.nickname
+            .as_deref()
+            .filter(|n| !n.is_empty())
+            .unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    /// Remote URL, or a path relative to the JSON file when media was downloaded
+    pub url: String,
+    pub file_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Embed {
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Reaction {
+    pub emoji: Emoji,
+    /// Only present in newer exports; reactions without users can't be attributed
+    #[serde(default)]
+    pub users: Vec<Author>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Emoji {
+    /// Set for custom emoji
+    pub id: Option<String>,
+    pub name: String,
+}
+
+impl Emoji {
+    /// Unicode emoji as-is. Discord custom emoji have no counterpart here, so they
+    /// are kept as ":name:", which clients show as text
+    pub fn to_reaction(&self) -> String {
+        match self.id.as_deref() {
+            Some(id) if !id.is_empty() => format!(":{}:", self.name),
+            _ => self.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reference {
+    pub message_id: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trimmed from a DiscordChatExporter export, with fields the importer ignores left in
+    const EXPORT: &str = r#"{
+        "guild": { "id": "1", "name": "Test Server", "iconUrl": "" },
+        "channel": {
+            "id": "100",
+            "type": "GuildTextChat",
+            "categoryId": "50",
+            "category": "Text Channels",
+            "name": "general",
+            "topic": null
+        },
+        "dateRange": { "after": null, "before": null },
+        "messages": [
+            {
+                "id": "1000",
+                "type": "Default",
+                "timestamp": "2023-05-01T12:34:56.789+02:00",
+                "timestampEdited": null,
+                "callEndedTimestamp": null,
+                "isPinned": true,
+                "content": "Hello",
+                "author": { "id": "7", "name": "alice", "discriminator": "0000", "nickname": "Alice", "isBot": false },
+                "attachments": [{ "id": "9", "url": "general-Files/cat-1A2B.png", "fileName": "cat.png", "fileSizeBytes": 1234 }],
+                "embeds": [{ "title": "", "url": "https://example.com/" }],
+                "stickers": [],
+                "reactions": [
+                    { "emoji": { "id": "", "name": "👍", "isAnimated": false }, "count": 1, "users": [{ "id": "8", "name": "bob", "nickname": null }] },
+                    { "emoji": { "id": "555", "name": "party", "isAnimated": true }, "count": 2 }
+                ],
+                "mentions": []
+            },
+            {
+                "id": "1001",
+                "type": "Reply",
+                "timestamp": "2023-05-01T12:35:00+00:00",
+                "timestampEdited": "2023-05-01T12:36:00+00:00",
+                "content": "Hi!",
+                "author": { "id": "8", "name": "bob", "nickname": "" },
+                "reference": { "messageId": "1000", "channelId": "100", "guildId": "1" }
+            },
+            {
+                "id": "1002",
+                "type": "GuildMemberJoin",
+                "timestamp": "2023-05-01T12:37:00+00:00",
+                "author": { "id": "9", "name": "carol" }
+            }
+        ],
+        "messageCount": 3
+    }"#;
+
+    #[test]
+    fn parses_channel_exports() {
+        let export: ChannelExport = serde_json::from_str(EXPORT).unwrap();
+
+        assert_eq!(export.channel.id, "100");
+        assert_eq!(export.channel.category.as_deref(), Some("Text Channels"));
+        assert!(!export.channel.is_thread());
+        assert!(!export.channel.is_direct());
+        assert_eq!(export.messages.len(), 3);
+
+        let first = &export.messages[0];
+        assert!(first.is_user_message());
+        assert!(first.is_pinned);
+        assert_eq!(first.timestamp.timestamp_millis(), 1682937296789);
+        assert_eq!(first.author.display_name(), "Alice");
+        assert_eq!(first.attachments[0].file_name, "cat.png");
+        assert_eq!(first.embeds[0].url.as_deref(), Some("https://example.com/"));
+        assert_eq!(first.reactions[0].emoji.to_reaction(), "👍");
+        assert_eq!(first.reactions[0].users[0].id, "8");
+        assert_eq!(first.reactions[1].emoji.to_reaction(), ":party:");
+        assert!(first.reactions[1].users.is_empty());
+
+        let reply = &export.messages[1];
+        assert!(reply.is_user_message());
+        assert!(!reply.is_pinned);
+        assert!(reply.timestamp_edited.is_some());
+        // An empty nickname falls back to the username
+        assert_eq!(reply.author.display_name(), "bob");
+        assert_eq!(reply.reference.as_ref().unwrap().message_id.as_deref(), Some("1000"));
+
+        assert!(!export.messages[2].is_user_message());
+        assert!(export.messages[2].content.is_empty());
+    }
+
+    #[test]
+    fn channel_kinds() {
+        let cases = [
+            ("GuildTextChat", false, false),
+            ("GuildPublicThread", true, false),
+            ("GuildPrivateThread", true, false),
+            ("GuildNewsThread", true, false),
+            ("DirectTextChat", false, true),
+            ("DirectGroupTextChat", false, true),
+        ];
+
+        for (kind, thread, direct) in cases {
+            let channel: Channel = serde_json::from_value(serde_json::json!({
+                "id": "1",
+                "type": kind,
+                "name": "name",
+            }))
+            .unwrap();
+            assert_eq!(channel.is_thread(), thread, "{kind}");
+            assert_eq!(channel.is_direct(), direct, "{kind}");
+        }
+    }
+}
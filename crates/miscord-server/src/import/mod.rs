@@ -0,0 +1,415 @@
+//! Importing a Discord server from a DiscordChatExporter JSON export.
+//!
+//! Channels, threads, messages, pins, reactions and attachments are recreated in an
+//! existing community. Discord authors become placeholder users that real accounts
+//! can claim later. Everything imported is recorded, so running the import again
+//! with a newer export only adds what is missing.
+
+mod archive;
+mod discord;
+
+use crate::error::Result;
+use crate::models::{AttachmentOwner, ChannelType, CreateChannel};
+use crate::services::import::{ImportedMessage, RECORD_CHANNEL, RECORD_MESSAGE};
+use crate::state::AppState;
+use anyhow::bail;
+use archive::Archive;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Attachments that weren't downloaded with the export are fetched from Discord
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub channels: usize,
+    pub messages: usize,
+    pub reactions: usize,
+    pub attachments: usize,
+    /// System messages, empty messages and files that couldn't be read
+    pub skipped: usize,
+    pub placeholders: usize,
+}
+
+/// Import an export directory, a single exported channel or a zip of either
+pub async fn import_discord(
+    state: &AppState,
+    community_id: Uuid,
+    path: &Path,
+) -> anyhow::Result<ImportSummary> {
+    let community = sqlx::query_scalar!("SELECT name FROM communities WHERE id = $1", community_id)
+        .fetch_optional(&state.db)
+        .await?;
+    let Some(community) = community else {
+        bail!("Community {} not found", community_id);
+    };
+
+    state.attachment_service.ensure_storage_ready().await?;
+
+    let archive = Archive::open(path)?;
+    let files = archive.json_files();
+    tracing::info!(
+        "Importing {} exported channels into {}",
+        files.len(),
+        community
+    );
+
+    let mut importer = Importer {
+        state,
+        community_id,
+        archive,
+        http: reqwest::Client::builder()
+            .timeout(DOWNLOAD_TIMEOUT)
+            .build()?,
+        placeholders: HashMap::new(),
+        summary: ImportSummary::default(),
+    };
+
+    // Threads are attached to their parent channel, so every channel is created first
+    for threads in [false, true] {
+        for file in &files {
+            let export = match importer.load(file) {
+                Ok(export) => export,
+                Err(e) => {
+                    if !threads {
+                        tracing::warn!("Skipping {}: {}", file, e);
+                    }
+                    continue;
+                }
+            };
+            if export.channel.is_thread() != threads {
+                continue;
+            }
+
+            let base = file.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+            if threads {
+                importer.import_thread(export, base).await?;
+            } else {
+                importer.import_channel(export, base).await?;
+            }
+        }
+    }
+
+    let mut summary = importer.summary;
+    summary.placeholders = state
+        .import_service
+        .list_placeholders(community_id)
+        .await?
+        .len();
+
+    Ok(summary)
+}
+
+struct Importer<'a> {
+    state: &'a AppState,
+    community_id: Uuid,
+    archive: Archive,
+    http: reqwest::Client,
+    /// Discord user ID to placeholder user
+    placeholders: HashMap<String, Uuid>,
+    summary: ImportSummary,
+}
+
+impl Importer<'_> {
+    fn load(&mut self, file: &str) -> anyhow::Result<discord::ChannelExport> {
+        let data = self.archive.read(file)?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    async fn import_channel(&mut self, export: discord::ChannelExport, base: &str) -> Result<()> {
+        if export.channel.is_direct() {
+            tracing::warn!("Skipping direct message channel {}", export.channel.name);
+            return Ok(());
+        }
+
+        let channel_type = match export.channel.kind.as_str() {
+            "GuildNews" | "GuildAnnouncement" => ChannelType::Announcement,
+            "GuildForum" => ChannelType::Forum,
+            // Text chats of voice channels are imported as text channels too
+            _ => ChannelType::Text,
+        };
+
+        let parent_id = match (&export.channel.category_id, &export.channel.category) {
+            (Some(id), Some(name)) => Some(
+                self.channel(id, name, None, ChannelType::Category, None)
+                    .await?,
+            ),
+            _ => None,
+        };
+
+        let channel = &export.channel;
+        let channel_id = self
+            .channel(
+                &channel.id,
+                &channel.name,
+                channel.topic.clone(),
+                channel_type,
+                parent_id,
+            )
+            .await?;
+
+        tracing::info!(
+            "Importing #{} ({} messages)",
+            channel.name,
+            export.messages.len()
+        );
+
+        let mut messages = export.messages;
+        messages.sort_by_key(|m| m.timestamp);
+        for message in messages {
+            self.import_message(base, message, channel_id, None).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Threads become replies to their starter message. In forums, and when the starter
+    /// message wasn't exported, the first message of the thread starts it instead.
+    async fn import_thread(&mut self, export: discord::ChannelExport, base: &str) -> Result<()> {
+        let channel_id = match &export.channel.category_id {
+            Some(parent) => {
+                self.state
+                    .import_service
+                    .find_record(self.community_id, RECORD_CHANNEL, parent)
+                    .await?
+            }
+            None => None,
+        };
+        let Some(channel_id) = channel_id else {
+            tracing::warn!(
+                "Skipping thread {}: its channel wasn't imported",
+                export.channel.name
+            );
+            return Ok(());
+        };
+        let channel = self.state.channel_service.get_by_id(channel_id).await?;
+
+        tracing::info!(
+            "Importing thread {} ({} messages)",
+            export.channel.name,
+            export.messages.len()
+        );
+
+        // A thread shares its ID with the message it was started from
+        let mut parent_id = self
+            .state
+            .import_service
+            .find_record(self.community_id, RECORD_MESSAGE, &export.channel.id)
+            .await?;
+
+        let mut messages = export.messages;
+        messages.sort_by_key(|m| m.timestamp);
+        for message in messages {
+            match parent_id {
+                Some(parent_id) => {
+                    self.import_message(base, message, channel_id, Some(parent_id))
+                        .await?;
+                }
+                None => parent_id = self.import_message(base, message, channel_id, None).await?,
+            }
+        }
+
+        let Some(parent_id) = parent_id else {
+            return Ok(());
+        };
+        if channel.channel_type == ChannelType::Forum {
+            self.state
+                .import_service
+                .insert_forum_post(parent_id, channel_id, &export.channel.name)
+                .await?;
+        }
+        self.state.import_service.refresh_thread(parent_id).await?;
+
+        Ok(())
+    }
+
+    /// Find a previously imported channel or create it
+    async fn channel(
+        &mut self,
+        external_id: &str,
+        name: &str,
+        topic: Option<String>,
+        channel_type: ChannelType,
+        parent_id: Option<Uuid>,
+    ) -> Result<Uuid> {
+        let import = &self.state.import_service;
+        if let Some(id) = import
+            .find_record(self.community_id, RECORD_CHANNEL, external_id)
+            .await?
+        {
+            return Ok(id);
+        }
+
+        let channel = self
+            .state
+            .channel_service
+            .create(
+                self.community_id,
+                CreateChannel {
+                    name: name.to_string(),
+                    topic: topic.filter(|t| !t.is_empty()),
+                    channel_type,
+                    parent_id,
+                },
+            )
+            .await?;
+        import
+            .record(self.community_id, RECORD_CHANNEL, external_id, channel.id)
+            .await?;
+        self.summary.channels += 1;
+
+        Ok(channel.id)
+    }
+
+    async fn placeholder(&mut self, author: &discord::Author) -> Result<Uuid> {
+        if let Some(id) = self.placeholders.get(&author.id) {
+            return Ok(*id);
+        }
+
+        let id = self
+            .state
+            .import_service
+            .get_or_create_placeholder(self.community_id, &author.id, author.display_name())
+            .await?;
+        self.placeholders.insert(author.id.clone(), id);
+
+        Ok(id)
+    }
+
+    /// Import one message with its attachments and reactions.
+    /// Returns the local ID, which for messages imported earlier is the existing one.
+    async fn import_message(
+        &mut self,
+        base: &str,
+        message: discord::Message,
+        channel_id: Uuid,
+        thread_parent_id: Option<Uuid>,
+    ) -> Result<Option<Uuid>> {
+        let import = &self.state.import_service;
+        if !message.is_user_message() {
+            self.summary.skipped += 1;
+            return Ok(None);
+        }
+        if let Some(id) = import
+            .find_record(self.community_id, RECORD_MESSAGE, &message.id)
+            .await?
+        {
+            return Ok(Some(id));
+        }
+
+        // Link-only messages keep their links even though the embeds are gone
+        let mut content = message.content.clone();
+        if content.trim().is_empty() {
+            let links: Vec<&str> = message
+                .embeds
+                .iter()
+                .filter_map(|e| e.url.as_deref())
+                .collect();
+            content = links.join("\n");
+        }
+        if content.trim().is_empty() && message.attachments.is_empty() {
+            self.summary.skipped += 1;
+            return Ok(None);
+        }
+
+        let reply_to_id = match message
+            .reference
+            .as_ref()
+            .and_then(|r| r.message_id.as_deref())
+        {
+            Some(reference) => {
+                import
+                    .find_record(self.community_id, RECORD_MESSAGE, reference)
+                    .await?
+            }
+            None => None,
+        };
+
+        let author_id = self.placeholder(&message.author).await?;
+        let import = &self.state.import_service;
+        let id = import
+            .insert_message(ImportedMessage {
+                channel_id,
+                author_id,
+                content,
+                created_at: message.timestamp.with_timezone(&Utc),
+                edited_at: message.timestamp_edited.map(|t| t.with_timezone(&Utc)),
+                reply_to_id,
+                thread_parent_id,
+                pinned: message.is_pinned,
+            })
+            .await?;
+        import
+            .record(self.community_id, RECORD_MESSAGE, &message.id, id)
+            .await?;
+        self.summary.messages += 1;
+
+        // Imported files count towards the community's storage, but are never refused for it
+        let owner = AttachmentOwner {
+            message_id: Some(id),
+            uploader_id: author_id,
+            community_id: Some(self.community_id),
+        };
+        for attachment in &message.attachments {
+            let data = match self.attachment_data(base, &attachment.url).await {
+                Ok(data) => data,
+                Err(e) => {
+                    tracing::warn!("Skipping attachment {}: {}", attachment.file_name, e);
+                    self.summary.skipped += 1;
+                    continue;
+                }
+            };
+            let saved = self
+                .state
+                .attachment_service
+                .save_file(owner, &attachment.file_name, &data)
+                .await;
+            match saved {
+                Ok(_) => self.summary.attachments += 1,
+                Err(e) => {
+                    tracing::warn!("Skipping attachment {}: {}", attachment.file_name, e);
+                    self.summary.skipped += 1;
+                }
+            }
+        }
+
+        for reaction in &message.reactions {
+            let emoji = reaction.emoji.to_reaction();
+            for user in &reaction.users {
+                let user_id = self.placeholder(user).await?;
+                self.state
+                    .message_service
+                    .add_reaction(id, user_id, &emoji)
+                    .await?;
+                self.summary.reactions += 1;
+            }
+        }
+
+        Ok(Some(id))
+    }
+
+    /// Attachments are either downloaded next to the export or still on Discord's CDN
+    async fn attachment_data(&mut self, base: &str, url: &str) -> anyhow::Result<Vec<u8>> {
+        if url.starts_with("https://") || url.starts_with("http://") {
+            let response = self.http.get(url).send().await?.error_for_status()?;
+            return Ok(response.bytes().await?.to_vec());
+        }
+
+        let path = if base.is_empty() {
+            url.to_string()
+        } else {
+            format!("{}/{}", base, url)
+        };
+        // Exports written with percent-encoded media paths
+        match self.archive.read(&path) {
+            Ok(data) => Ok(data),
+            Err(e) => match urlencoding::decode(&path) {
+                Ok(decoded) if decoded != path => self.archive.read(&decoded),
+                _ => Err(e),
+            },
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! Removes attachments that were uploaded but never sent, and stored files no attachment
+//! refers to any more
+
+use crate::state::AppState;
+use std::time::Duration;
+
+/// How often to look for leftovers. Listing every stored file isn't cheap.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How long an upload may wait to be sent, and how old a stored file must be before
+/// it's considered unreferenced
+const ORPHAN_AGE: Duration = Duration::from_secs(60 * 60);
+
+pub async fn run(state: AppState) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match state.attachment_service.purge_unsent(ORPHAN_AGE).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Deleted {} attachments that were never sent", count),
+            Err(e) => tracing::warn!("Failed to delete unsent attachments: {}", e),
+        }
+
+        match state.attachment_service.purge_unreferenced_files(ORPHAN_AGE).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Deleted {} unreferenced stored files", count),
+            Err(e) => tracing::warn!("Failed to delete unreferenced stored files: {}", e),
+        }
+    }
+}
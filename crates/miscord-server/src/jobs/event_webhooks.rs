@@ -0,0 +1,250 @@
+//! Queues community events for outgoing webhooks and delivers them with retries
+
+use crate::models::{WebhookDelivery, WEBHOOK_EVENT_TYPES};
+use crate::safe_http;
+use crate::state::AppState;
+use crate::ws::connections::{BroadcastEvent, BroadcastScope};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// How often to look for deliveries that are due
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Deliveries sent concurrently per batch
+const BATCH_SIZE: i64 = 20;
+
+/// Attempts before a delivery is given up
+const MAX_ATTEMPTS: i32 = 6;
+
+/// Delay before the first retry; each later retry waits twice as long
+const BASE_RETRY_DELAY_SECS: i64 = 30;
+
+/// Must stay below the claim lease so a slow endpoint isn't sent the event twice
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn run(state: AppState) {
+    let events = state.connections.listen_to_broadcasts().await;
+    tokio::spawn(enqueue_events(state.clone(), events));
+
+    // Webhook URLs are user supplied, so they may only reach public addresses
+    let client = match safe_http::builder(REQUEST_TIMEOUT)
+        .user_agent("MiscordWebhooks/1.0")
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::error!("Failed to create webhook HTTP client: {}", e);
+            return;
+        }
+    };
+
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        // Keep sending until a batch comes back short so a backlog drains in one pass
+        loop {
+            let deliveries = match state.event_webhook_service.claim_due(BATCH_SIZE).await {
+                Ok(deliveries) => deliveries,
+                Err(e) => {
+                    tracing::warn!("Failed to claim webhook deliveries: {}", e);
+                    break;
+                }
+            };
+
+            let count = deliveries.len();
+            futures_util::future::join_all(
+                deliveries
+                    .into_iter()
+                    .map(|delivery| deliver(&state, &client, delivery)),
+            )
+            .await;
+
+            if (count as i64) < BATCH_SIZE {
+                break;
+            }
+        }
+    }
+}
+
+/// Turn broadcasts that webhooks can subscribe to into queued deliveries
+async fn enqueue_events(state: AppState, mut events: mpsc::UnboundedReceiver<BroadcastEvent>) {
+    // Channels never move between communities, so their lookups can be kept
+    let mut channel_communities: HashMap<Uuid, Option<Uuid>> = HashMap::new();
+
+    while let Some(event) = events.recv().await {
+        let data = match serde_json::to_value(&event.message) {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::warn!("Failed to serialize webhook event: {}", e);
+                continue;
+            }
+        };
+        let Some(name) = data
+            .get("type")
+            .and_then(|t| t.as_str())
+            .and_then(|t| WEBHOOK_EVENT_TYPES.iter().find(|name| **name == t))
+        else {
+            continue;
+        };
+
+        let community_id = match event.scope {
+            BroadcastScope::Community(id) => Some(id),
+            BroadcastScope::Channel(channel_id) => match channel_communities.get(&channel_id) {
+                Some(community_id) => *community_id,
+                None => match state.channel_service.get_by_id(channel_id).await {
+                    Ok(channel) => {
+                        channel_communities.insert(channel_id, channel.community_id);
+                        channel.community_id
+                    }
+                    Err(e) => {
+                        tracing::debug!("Skipping {} event for channel {}: {}", name, channel_id, e);
+                        None
+                    }
+                },
+            },
+        };
+        // DMs and group DMs have no community and no webhooks
+        let Some(community_id) = community_id else {
+            continue;
+        };
+
+        let payload = serde_json::json!({
+            "event": name,
+            "community_id": community_id,
+            "created_at": Utc::now(),
+            "data": data,
+        });
+        if let Err(e) = state
+            .event_webhook_service
+            .enqueue(community_id, name, &payload)
+            .await
+        {
+            tracing::warn!("Failed to queue {} event for webhooks: {}", name, e);
+        }
+    }
+}
+
+/// Hex HMAC-SHA256 of `{timestamp}.{body}`, so receivers can reject replayed deliveries
+fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+fn retry_delay(attempts: i32) -> chrono::Duration {
+    chrono::Duration::seconds(BASE_RETRY_DELAY_SECS << (attempts - 1).clamp(0, 16))
+}
+
+async fn deliver(state: &AppState, client: &reqwest::Client, delivery: WebhookDelivery) {
+    let id = delivery.id;
+    let service = &state.event_webhook_service;
+
+    let webhook = match service.get_by_id(delivery.webhook_id).await {
+        Ok(webhook) => webhook,
+        Err(e) => {
+            tracing::warn!("Failed to load webhook for delivery {}: {}", id, e);
+            return;
+        }
+    };
+    if !webhook.enabled {
+        if let Err(e) = service
+            .mark_attempt_failed(id, None, "Webhook is disabled", None)
+            .await
+        {
+            tracing::warn!("Failed to update webhook delivery {}: {}", id, e);
+        }
+        return;
+    }
+
+    let body = match serde_json::to_vec(&delivery.payload.0) {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::warn!("Failed to serialize webhook delivery {}: {}", id, e);
+            return;
+        }
+    };
+    let timestamp = Utc::now().timestamp();
+    let signature = sign(&webhook.secret, timestamp, &body);
+
+    let result = client
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-Miscord-Event", &delivery.event)
+        .header("X-Miscord-Delivery", id.to_string())
+        .header("X-Miscord-Timestamp", timestamp.to_string())
+        .header("X-Miscord-Signature", format!("sha256={}", signature))
+        .body(body)
+        .send()
+        .await;
+
+    let outcome = match result {
+        Ok(response) if response.status().is_success() => Ok(response.status().as_u16() as i32),
+        Ok(response) => Err((
+            Some(response.status().as_u16() as i32),
+            format!("Endpoint responded with {}", response.status()),
+        )),
+        Err(e) => Err((None, e.to_string())),
+    };
+
+    let result = match outcome {
+        Ok(status) => service.mark_succeeded(id, status).await,
+        Err((status, error)) => {
+            let attempts = delivery.attempts + 1;
+            let retry_at = (attempts < MAX_ATTEMPTS).then(|| Utc::now() + retry_delay(attempts));
+            tracing::debug!("Webhook delivery {} attempt {} failed: {}", id, attempts, error);
+            service.mark_attempt_failed(id, status, &error, retry_at).await
+        }
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to update webhook delivery {}: {}", id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures() {
+        let cases = [
+            (
+                "secret",
+                1_700_000_000,
+                r#"{"event":"message_created"}"#,
+                "4fbf414139098588cc31e819ccebf960699d147368b51572e7657c1332d9c962",
+            ),
+            ("", 0, "", "b849d5a581847b281957065739df36df2463d1977ea8d6e1e4e6cf33fadc68c3"),
+        ];
+
+        for (secret, timestamp, body, expected) in cases {
+            assert_eq!(sign(secret, timestamp, body.as_bytes()), expected, "{secret:?} {timestamp}");
+        }
+    }
+
+    #[test]
+    fn retry_delays() {
+        let cases = [
+            (0, 30),
+            (1, 30),
+            (2, 60),
+            (3, 120),
+            (MAX_ATTEMPTS - 1, 30 << (MAX_ATTEMPTS - 2)),
+            (100, 30 << 16),
+        ];
+
+        for (attempts, expected) in cases {
+            assert_eq!(retry_delay(attempts), chrono::Duration::seconds(expected), "{attempts}");
+        }
+    }
+}
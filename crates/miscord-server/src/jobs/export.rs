@@ -0,0 +1,326 @@
+//! Builds queued channel and thread exports, and deletes them once they expire
+
+use crate::error::{AppError, Result};
+use crate::export::{
+    self, ExportDocument, ExportFile, ExportedAttachment, ExportedAuthor, ExportedChannel, ExportedMessage,
+    ExportedReaction,
+};
+use crate::models::{Export, MessageAttachment, User};
+use crate::state::AppState;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How often to look for queued exports
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often to look for expired exports
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How long a finished export can be downloaded for
+const EXPORT_LIFETIME: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Messages loaded per query; progress is reported after each page
+const PAGE_SIZE: i64 = 500;
+
+/// Tell the requesting user how their export is doing
+async fn notify(state: &AppState, export: Export) {
+    let user_id = export.requested_by_id;
+    state
+        .connections
+        .send_to_user(
+            user_id,
+            &miscord_protocol::ServerMessage::ExportUpdated {
+                export: export::to_export_data(export),
+            },
+        )
+        .await;
+}
+
+pub async fn run(state: AppState) {
+    match state.export_service.requeue_interrupted().await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("Requeued {} interrupted exports", count),
+        Err(e) => tracing::warn!("Failed to requeue interrupted exports: {}", e),
+    }
+
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        loop {
+            let export = match state.export_service.claim_next().await {
+                Ok(Some(export)) => export,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::warn!("Failed to claim export: {}", e);
+                    break;
+                }
+            };
+
+            let id = export.id;
+            notify(&state, export.clone()).await;
+
+            let result = match build(&state, &export).await {
+                Ok(()) => state.export_service.complete(id).await,
+                Err(e) => {
+                    tracing::warn!("Export {} failed: {:?}", id, e);
+                    state.export_service.fail(id, &e.to_string()).await
+                }
+            };
+
+            match result {
+                Ok(export) => notify(&state, export).await,
+                Err(e) => tracing::warn!("Failed to update export {}: {}", id, e),
+            }
+        }
+    }
+}
+
+/// Delete exports, and their archives, once they've been downloadable for long enough
+pub async fn run_cleanup(state: AppState) {
+    let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match state.export_service.purge_expired(EXPORT_LIFETIME).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Deleted {} expired exports", count),
+            Err(e) => tracing::warn!("Failed to delete expired exports: {}", e),
+        }
+    }
+}
+
+/// Look up users that haven't been seen yet in this export
+async fn load_users(state: &AppState, users: &mut HashMap<Uuid, User>, ids: &[Uuid]) -> Result<()> {
+    let mut missing: Vec<Uuid> = ids.iter().copied().filter(|id| !users.contains_key(id)).collect();
+    missing.sort();
+    missing.dedup();
+
+    if !missing.is_empty() {
+        for user in state.user_service.get_by_ids(&missing).await? {
+            users.insert(user.id, user);
+        }
+    }
+
+    Ok(())
+}
+
+fn author(users: &HashMap<Uuid, User>, id: Uuid) -> ExportedAuthor {
+    match users.get(&id) {
+        Some(user) => ExportedAuthor {
+            id,
+            username: user.username.clone(),
+            display_name: user.display_name.clone(),
+        },
+        None => ExportedAuthor {
+            id,
+            username: "unknown".to_string(),
+            display_name: "Unknown".to_string(),
+        },
+    }
+}
+
+fn display_name(users: &HashMap<Uuid, User>, id: Uuid) -> String {
+    users
+        .get(&id)
+        .map(|u| u.display_name.clone())
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+/// Copy a file out of remote storage so the synchronous zip writer can read it.
+/// Files missing from storage stay missing; the zip writer skips them.
+async fn stage_file(state: &AppState, dir: &Path, attachment: &MessageAttachment) -> Result<PathBuf> {
+    let path = dir.join(attachment.id.to_string());
+    match state
+        .attachment_service
+        .read_file(attachment.file_id, &attachment.filename)
+        .await
+    {
+        Ok(data) => {
+            tokio::fs::create_dir_all(dir).await.map_err(|e| {
+                AppError::Internal(anyhow::anyhow!("Failed to create staging directory: {}", e))
+            })?;
+            tokio::fs::write(&path, data).await.map_err(|e| {
+                AppError::Internal(anyhow::anyhow!("Failed to stage export file: {}", e))
+            })?;
+        }
+        Err(AppError::NotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+    Ok(path)
+}
+
+/// Collect the export's messages page by page and write the zip
+async fn build(state: &AppState, export: &Export) -> Result<()> {
+    state.export_service.ensure_work_dir().await?;
+
+    let channel = state.channel_service.get_by_id(export.channel_id).await?;
+    let community = match channel.community_id {
+        Some(community_id) => {
+            sqlx::query_scalar!("SELECT name FROM communities WHERE id = $1", community_id)
+                .fetch_optional(&state.db)
+                .await?
+        }
+        None => None,
+    };
+
+    let total = state
+        .export_service
+        .count_messages(export.channel_id, export.thread_parent_id)
+        .await? as i32;
+    let progress = state.export_service.set_progress(export.id, 0, total).await?;
+    notify(state, progress).await;
+
+    let mut users: HashMap<Uuid, User> = HashMap::new();
+    let mut messages: Vec<(Option<Uuid>, ExportedMessage)> = Vec::new();
+    let mut files = Vec::new();
+    let staging_dir = state.export_service.work_path(export.id).with_extension("files");
+    let mut cursor = None;
+
+    loop {
+        let page = state
+            .export_service
+            .list_messages(export.channel_id, export.thread_parent_id, cursor, PAGE_SIZE)
+            .await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        cursor = Some((last.created_at, last.id));
+
+        let ids: Vec<Uuid> = page.iter().map(|m| m.id).collect();
+        let reactions = state
+            .message_service
+            .get_reactions_for_messages(&ids, export.requested_by_id)
+            .await?;
+
+        let mut attachments: HashMap<Uuid, Vec<ExportedAttachment>> = HashMap::new();
+        for attachment in state.attachment_service.get_by_message_ids(&ids).await? {
+            let Some(message_id) = attachment.message_id else {
+                continue;
+            };
+            let path = export::attachment_zip_path(attachment.id, &attachment.filename);
+            let disk_path = match state
+                .attachment_service
+                .local_file_path(attachment.file_id, &attachment.filename)
+            {
+                Some(disk_path) => disk_path,
+                None => stage_file(state, &staging_dir, &attachment).await?,
+            };
+            files.push(ExportFile {
+                zip_path: path.clone(),
+                disk_path,
+            });
+            attachments.entry(message_id).or_default().push(ExportedAttachment {
+                id: attachment.id,
+                filename: attachment.filename,
+                content_type: attachment.content_type,
+                size_bytes: attachment.size_bytes,
+                path,
+            });
+        }
+
+        let user_ids: Vec<Uuid> = page
+            .iter()
+            .flat_map(|m| [Some(m.author_id), m.pinned_by_id])
+            .flatten()
+            .chain(reactions.values().flatten().flat_map(|(_, ids, _)| ids.iter().copied()))
+            .collect();
+        load_users(state, &mut users, &user_ids).await?;
+
+        let page_len = page.len();
+        for message in page {
+            let message_reactions = reactions
+                .get(&message.id)
+                .map(|r| {
+                    r.iter()
+                        .map(|(emoji, user_ids, _)| ExportedReaction {
+                            emoji: emoji.clone(),
+                            users: user_ids.iter().map(|id| display_name(&users, *id)).collect(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            messages.push((
+                message.thread_parent_id,
+                ExportedMessage {
+                    id: message.id,
+                    author: author(&users, message.author_id),
+                    content: message.content,
+                    created_at: message.created_at,
+                    edited_at: message.edited_at,
+                    reply_to_id: message.reply_to_id,
+                    pinned_at: message.pinned_at,
+                    pinned_by: message.pinned_by_id.map(|id| display_name(&users, id)),
+                    reactions: message_reactions,
+                    attachments: attachments.remove(&message.id).unwrap_or_default(),
+                    replies: Vec::new(),
+                },
+            ));
+        }
+
+        let exported = messages.len() as i32;
+        let progress = state
+            .export_service
+            .set_progress(export.id, exported, total.max(exported))
+            .await?;
+        notify(state, progress).await;
+
+        if (page_len as i64) < PAGE_SIZE {
+            break;
+        }
+    }
+
+    // Nest thread replies under their parents; replies always come after the parent
+    let message_count = messages.len();
+    let mut replies: HashMap<Uuid, Vec<ExportedMessage>> = HashMap::new();
+    let mut top_level = Vec::new();
+    for (parent_id, message) in messages {
+        match parent_id {
+            Some(parent_id) => replies.entry(parent_id).or_default().push(message),
+            None => top_level.push(message),
+        }
+    }
+    for message in &mut top_level {
+        if let Some(thread) = replies.remove(&message.id) {
+            message.replies = thread;
+        }
+    }
+    // Replies whose parent is missing are kept rather than dropped
+    top_level.extend(replies.into_values().flatten());
+    top_level.sort_by_key(|m| m.created_at);
+
+    let document = ExportDocument {
+        exported_at: chrono::Utc::now(),
+        community,
+        channel: ExportedChannel {
+            id: channel.id,
+            name: channel.name,
+            topic: channel.topic,
+        },
+        thread_parent_id: export.thread_parent_id,
+        message_count,
+        messages: top_level,
+    };
+
+    let dest = state.export_service.work_path(export.id);
+    let format = export.format;
+    let zip_path = dest.clone();
+    let result = tokio::task::spawn_blocking(move || export::write_zip(&zip_path, &document, format, &files))
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Export task panicked: {}", e)));
+
+    // Files staged from remote storage are no longer needed
+    if tokio::fs::try_exists(&staging_dir).await.unwrap_or(false) {
+        if let Err(e) = tokio::fs::remove_dir_all(&staging_dir).await {
+            tracing::warn!("Failed to remove export staging directory {:?}: {}", staging_dir, e);
+        }
+    }
+
+    result??;
+    state.export_service.store_archive(export.id, &dest).await
+}
@@ -0,0 +1,40 @@
+//! Drops short-lived rows and caches once they are no longer needed: expired
+//! interactions, old webhook deliveries, stale link previews, abandoned uploads and
+//! the media proxy's disk cache
+
+use crate::state::AppState;
+use std::time::Duration;
+
+/// How often to clean up
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+pub async fn run(state: AppState) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        // Interactions are only kept while their application may still respond
+        if let Err(e) = state.interaction_service.purge_expired().await {
+            tracing::warn!("Failed to purge expired interactions: {}", e);
+        }
+
+        if let Err(e) = state.event_webhook_service.purge_old_deliveries().await {
+            tracing::warn!("Failed to purge old webhook deliveries: {}", e);
+        }
+
+        if let Err(e) = state.link_preview_service.purge_expired().await {
+            tracing::warn!("Failed to purge expired link previews: {}", e);
+        }
+
+        match state.upload_service.purge_expired().await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Discarded {} abandoned uploads", count),
+            Err(e) => tracing::warn!("Failed to discard abandoned uploads: {}", e),
+        }
+
+        if let Err(e) = state.media_proxy_service.purge().await {
+            tracing::warn!("Failed to purge media cache: {}", e);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! Background jobs that run for the lifetime of the server
+
+mod attachment_gc;
+mod event_webhooks;
+mod export;
+mod housekeeping;
+mod retention;
+mod thread_archive;
+
+use crate::state::AppState;
+
+/// Start all background jobs
+pub fn spawn_all(state: AppState) {
+    tokio::spawn(thread_archive::run(state.clone()));
+    tokio::spawn(retention::run(state.clone()));
+    tokio::spawn(housekeeping::run(state.clone()));
+    tokio::spawn(event_webhooks::run(state.clone()));
+    tokio::spawn(attachment_gc::run(state.clone()));
+    tokio::spawn(export::run_cleanup(state.clone()));
+    tokio::spawn(export::run(state));
+}
@@ -0,0 +1,61 @@
+//! Purges messages older than their channel's retention period or disappearing timer
+
+use crate::state::AppState;
+use std::time::Duration;
+
+/// How often to look for expired messages
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Maximum number of messages deleted per transaction
+const BATCH_SIZE: i64 = 500;
+
+pub async fn run(state: AppState) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        // Keep purging until a batch comes back short so a backlog drains in one pass
+        loop {
+            let purged = match state.message_service.purge_expired(BATCH_SIZE).await {
+                Ok(purged) => purged,
+                Err(e) => {
+                    tracing::warn!("Failed to purge expired messages: {}", e);
+                    break;
+                }
+            };
+
+            let count = purged.messages.len();
+            if count > 0 {
+                tracing::info!("Purged {} expired messages", count);
+            }
+
+            for attachment in &purged.orphaned_files {
+                if let Err(e) = state
+                    .attachment_service
+                    .remove_file(attachment.file_id, &attachment.filename)
+                    .await
+                {
+                    tracing::warn!("Failed to remove file {}: {}", attachment.file_id, e);
+                }
+            }
+
+            for (message_id, channel_id) in purged.messages {
+                state
+                    .connections
+                    .broadcast_to_channel(
+                        channel_id,
+                        &miscord_protocol::ServerMessage::MessageDeleted {
+                            message_id,
+                            channel_id,
+                        },
+                    )
+                    .await;
+            }
+
+            if (count as i64) < BATCH_SIZE {
+                break;
+            }
+        }
+    }
+}
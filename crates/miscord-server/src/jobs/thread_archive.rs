@@ -0,0 +1,44 @@
+//! Archives threads that have had no replies for a while
+
+use crate::state::AppState;
+use std::time::Duration;
+
+/// How often to look for inactive threads
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub async fn run(state: AppState) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let archived = match state
+            .thread_service
+            .archive_inactive(state.config.thread_auto_archive_days)
+            .await
+        {
+            Ok(archived) => archived,
+            Err(e) => {
+                tracing::warn!("Failed to archive inactive threads: {}", e);
+                continue;
+            }
+        };
+
+        if !archived.is_empty() {
+            tracing::info!("Archived {} inactive threads", archived.len());
+        }
+
+        for thread in archived {
+            state
+                .connections
+                .broadcast_to_channel(
+                    thread.channel_id,
+                    &miscord_protocol::ServerMessage::ThreadArchived {
+                        parent_message_id: thread.parent_message_id,
+                        channel_id: thread.channel_id,
+                    },
+                )
+                .await;
+        }
+    }
+}
@@ -6,10 +6,17 @@ pub mod api;
 pub mod auth;
 pub mod db;
 pub mod error;
+pub mod export;
+pub mod file_type;
+pub mod import;
+pub mod jobs;
+pub mod media_info;
 pub mod models;
+pub mod safe_http;
 pub mod services;
 pub mod sfu;
 pub mod state;
+pub mod storage;
 pub mod webrtc;
 pub mod ws;
 
@@ -31,7 +38,37 @@ pub async fn create_app(config: state::Config) -> Result<(axum::Router, sqlx::Pg
         );
     }
 
-    let app_state = state::AppState::new(config, db_pool.clone());
+    let app_state = state::AppState::new(config, db_pool.clone())?;
+    jobs::spawn_all(app_state.clone());
     let router = api::create_router(app_state);
     Ok((router, db_pool))
 }
+
+/// Copy attachment files from one storage backend to another without starting the server
+pub async fn run_storage_migration(
+    config: state::Config,
+    from: storage::StorageKind,
+    to: storage::StorageKind,
+    delete_source: bool,
+) -> Result<storage::MigrationSummary> {
+    let db_pool = db::init_pool(&config.database_url).await?;
+    db::run_migrations(&db_pool).await?;
+
+    let open = |kind| storage::open(kind, config.upload_dir.clone(), config.s3.as_ref());
+    let (from, to) = (open(from)?, open(to)?);
+    let summary = storage::migrate(&db_pool, from.as_ref(), to.as_ref(), delete_source).await?;
+    Ok(summary)
+}
+
+/// Import a DiscordChatExporter export into a community without starting the server
+pub async fn run_discord_import(
+    config: state::Config,
+    community_id: uuid::Uuid,
+    path: &std::path::Path,
+) -> Result<import::ImportSummary> {
+    let db_pool = db::init_pool(&config.database_url).await?;
+    db::run_migrations(&db_pool).await?;
+
+    let app_state = state::AppState::new(config, db_pool)?;
+    import::import_discord(&app_state, community_id, path).await
+}
@@ -1,6 +1,9 @@
 use anyhow::Result;
 use miscord_server::state;
+use miscord_server::storage::StorageKind;
+use std::path::Path;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use uuid::Uuid;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -16,10 +19,15 @@ async fn main() -> Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    tracing::info!("Starting Miscord server...");
-
     // Load configuration
     let config = state::Config::load()?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(command) = args.first() {
+        return run_command(config, command, &args[1..]).await;
+    }
+
+    tracing::info!("Starting Miscord server...");
     let bind_address = config.bind_address.clone();
 
     // Create application
@@ -33,3 +41,63 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Maintenance commands that run instead of the server
+async fn run_command(config: state::Config, command: &str, args: &[String]) -> Result<()> {
+    match (command, args) {
+        ("import-discord", [community_id, path]) => {
+            let Ok(community_id) = community_id.parse::<Uuid>() else {
+                usage();
+            };
+            let summary =
+                miscord_server::run_discord_import(config, community_id, Path::new(path)).await?;
+            tracing::info!(
+                "Imported {} channels, {} messages, {} reactions and {} attachments ({} skipped); {} unclaimed placeholder users",
+                summary.channels,
+                summary.messages,
+                summary.reactions,
+                summary.attachments,
+                summary.skipped,
+                summary.placeholders
+            );
+            Ok(())
+        }
+        ("migrate-storage", [from, to, rest @ ..]) => {
+            let (Some(from), Some(to)) = (StorageKind::parse(from), StorageKind::parse(to)) else {
+                usage();
+            };
+            let delete_source = match rest {
+                [] => false,
+                [flag] if flag == "--delete-source" => true,
+                _ => usage(),
+            };
+            if from == to {
+                usage();
+            }
+            let summary =
+                miscord_server::run_storage_migration(config, from, to, delete_source).await?;
+            tracing::info!(
+                "Copied {} files, {} already present, {} missing, {} failed",
+                summary.copied,
+                summary.skipped,
+                summary.missing,
+                summary.failed
+            );
+            Ok(())
+        }
+        _ => usage(),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: miscord-server [import-discord <community-id> <export-path>]");
+    eprintln!("       miscord-server [migrate-storage <local|s3> <local|s3> [--delete-source]]");
+    eprintln!();
+    eprintln!(
+        "  import-discord   Import a DiscordChatExporter JSON export (directory, .json or .zip)"
+    );
+    eprintln!(
+        "  migrate-storage  Copy attachment files between storage backends, optionally deleting the originals"
+    );
+    std::process::exit(2);
+}
@@ -0,0 +1,120 @@
+//! Encoder for BlurHash (https://blurha.sh), a short string clients turn into a blurred
+//! placeholder while the real image loads
+
+use image::RgbaImage;
+use std::f64::consts::PI;
+
+const BASE83: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+fn encode83(value: u32, length: u32, out: &mut String) {
+    for i in 1..=length {
+        let digit = (value / 83u32.pow(length - i)) % 83;
+        out.push(BASE83[digit as usize] as char);
+    }
+}
+
+fn srgb_to_linear(value: u8) -> f64 {
+    let v = value as f64 / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f64) -> u32 {
+    let v = value.clamp(0.0, 1.0);
+    if v <= 0.0031308 {
+        (v * 12.92 * 255.0 + 0.5) as u32
+    } else {
+        ((1.055 * v.powf(1.0 / 2.4) - 0.055) * 255.0 + 0.5) as u32
+    }
+}
+
+fn sign_pow(value: f64, exp: f64) -> f64 {
+    value.abs().powf(exp).copysign(value)
+}
+
+/// Encode an image with `x_components` by `y_components` (each 1-9) cosine components.
+/// The image only needs to be a few dozen pixels across; detail beyond that is lost anyway.
+pub fn encode(image: &RgbaImage, x_components: u32, y_components: u32) -> String {
+    let (width, height) = image.dimensions();
+
+    let mut factors = Vec::with_capacity((x_components * y_components) as usize);
+    for j in 0..y_components {
+        for i in 0..x_components {
+            let normalisation = if i == 0 && j == 0 { 1.0 } else { 2.0 };
+            let mut factor = [0.0; 3];
+            for (x, y, pixel) in image.enumerate_pixels() {
+                let basis = normalisation
+                    * (PI * i as f64 * x as f64 / width as f64).cos()
+                    * (PI * j as f64 * y as f64 / height as f64).cos();
+                for (channel, value) in factor.iter_mut().enumerate() {
+                    *value += basis * srgb_to_linear(pixel[channel]);
+                }
+            }
+            let scale = 1.0 / (width * height) as f64;
+            factors.push(factor.map(|value| value * scale));
+        }
+    }
+
+    let mut hash = String::new();
+    encode83((x_components - 1) + (y_components - 1) * 9, 1, &mut hash);
+
+    let (dc, ac) = factors.split_first().expect("at least one component");
+    let maximum_value = if ac.is_empty() {
+        encode83(0, 1, &mut hash);
+        1.0
+    } else {
+        let actual_max = ac
+            .iter()
+            .flat_map(|factor| factor.iter())
+            .fold(0.0f64, |max, value| max.max(value.abs()));
+        let quantised_max = (actual_max * 166.0 - 0.5).floor().clamp(0.0, 82.0) as u32;
+        encode83(quantised_max, 1, &mut hash);
+        (quantised_max + 1) as f64 / 166.0
+    };
+
+    let dc_value = (linear_to_srgb(dc[0]) << 16) + (linear_to_srgb(dc[1]) << 8) + linear_to_srgb(dc[2]);
+    encode83(dc_value, 4, &mut hash);
+
+    for factor in ac {
+        let quantised = factor.map(|value| {
+            (sign_pow(value / maximum_value, 0.5) * 9.0 + 9.5).floor().clamp(0.0, 18.0) as u32
+        });
+        encode83(quantised[0] * 19 * 19 + quantised[1] * 19 + quantised[2], 2, &mut hash);
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn encodes() {
+        let solid = |width, height, color: [u8; 3]| {
+            RgbaImage::from_pixel(width, height, Rgba([color[0], color[1], color[2], 255]))
+        };
+        let gradient = RgbaImage::from_fn(32, 32, |x, y| {
+            Rgba([(x * 255 / 31) as u8, (y * 255 / 31) as u8, 128, 255])
+        });
+        let halves = RgbaImage::from_fn(32, 32, |x, _| {
+            if x < 16 { Rgba([255, 0, 0, 255]) } else { Rgba([0, 0, 255, 255]) }
+        });
+
+        let cases = [
+            ("white pixel", solid(1, 1, [255, 255, 255]), 1, 1, "00TSUA"),
+            ("solid color", solid(4, 4, [30, 120, 200]), 1, 1, "003e?K"),
+            ("white", solid(32, 32, [255, 255, 255]), 4, 3, "L9TSUA~qfQ~q~qoffQoffQfQfQfQ"),
+            ("gradient", gradient, 4, 3, "L$Het82swxX8l}WDjte;gJfjfQfj"),
+            ("halves", halves, 3, 4, "T~LjfL|TsRo3n~jsfQfQfQo3n~js"),
+        ];
+
+        for (case, image, x_components, y_components, expected) in cases {
+            assert_eq!(encode(&image, x_components, y_components), expected, "{case}");
+        }
+    }
+}
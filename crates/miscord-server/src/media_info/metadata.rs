@@ -0,0 +1,229 @@
+//! Removing EXIF, XMP and other embedded metadata, which can give away where and when a
+//! photo was taken, from JPEG, PNG and WebP files without re-encoding them
+
+/// JPEG segments holding EXIF and XMP (APP1) or IPTC (APP13)
+const JPEG_METADATA_MARKERS: &[u8] = &[0xE1, 0xED];
+
+/// PNG chunks holding EXIF, text and timestamps
+const PNG_METADATA_CHUNKS: &[&[u8; 4]] = &[b"eXIf", b"tEXt", b"zTXt", b"iTXt", b"tIME"];
+
+/// WebP chunks holding EXIF and XMP
+const WEBP_METADATA_CHUNKS: &[&[u8; 4]] = &[b"EXIF", b"XMP "];
+
+/// EXIF tag saying which way up a photo is
+const ORIENTATION_TAG: u16 = 0x0112;
+
+/// A copy of the file without its metadata. None when the file has none, or isn't a
+/// JPEG, PNG or WebP file this can read.
+pub fn strip_metadata(data: &[u8]) -> Option<Vec<u8>> {
+    if data.starts_with(&[0xFF, 0xD8]) {
+        strip_jpeg(data)
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        strip_png(data)
+    } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        strip_webp(data)
+    } else {
+        None
+    }
+}
+
+fn read_u16_be(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32_be(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn read_u32_le(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn strip_jpeg(data: &[u8]) -> Option<Vec<u8>> {
+    let mut output = data[..2].to_vec();
+    let mut pos = 2;
+    let mut stripped = false;
+    let mut orientation = None;
+    // Orientation goes back right after the JFIF header, when there is one
+    let mut insert_at = 2;
+
+    loop {
+        if *data.get(pos)? != 0xFF {
+            return None;
+        }
+        let marker = *data.get(pos + 1)?;
+        match marker {
+            // Padding before a marker
+            0xFF => {
+                pos += 1;
+                continue;
+            }
+            // Start of the image data, or the end of an image without any: the rest is kept as-is
+            0xDA | 0xD9 => {
+                output.extend_from_slice(&data[pos..]);
+                break;
+            }
+            // Markers without a length
+            0x01 | 0xD0..=0xD7 => {
+                output.extend_from_slice(&data[pos..pos + 2]);
+                pos += 2;
+                continue;
+            }
+            _ => {}
+        }
+
+        let length = read_u16_be(data, pos + 2)? as usize;
+        let end = pos + 2 + length;
+        if length < 2 || end > data.len() {
+            return None;
+        }
+        let segment = &data[pos..end];
+
+        if JPEG_METADATA_MARKERS.contains(&marker) {
+            if marker == 0xE1 && segment[4..].starts_with(b"Exif\0\0") {
+                orientation = exif_orientation(&segment[10..]);
+            }
+            stripped = true;
+        } else {
+            output.extend_from_slice(segment);
+            if marker == 0xE0 && insert_at == pos {
+                insert_at = output.len();
+            }
+        }
+        pos = end;
+    }
+
+    if !stripped {
+        return None;
+    }
+
+    // Dropping the orientation would turn most phone photos on their side
+    if let Some(orientation) = orientation.filter(|&o| o != 1) {
+        output.splice(insert_at..insert_at, orientation_segment(orientation));
+    }
+
+    Some(output)
+}
+
+/// The orientation from an EXIF block's first directory
+fn exif_orientation(tiff: &[u8]) -> Option<u16> {
+    let big_endian = match tiff.get(..2)? {
+        b"MM" => true,
+        b"II" => false,
+        _ => return None,
+    };
+    let read_u16 = |offset: usize| -> Option<u16> {
+        let bytes = tiff.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if big_endian { u16::from_be_bytes(bytes) } else { u16::from_le_bytes(bytes) })
+    };
+    let read_u32 = |offset: usize| -> Option<u32> {
+        let bytes = tiff.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) })
+    };
+
+    let directory = read_u32(4)? as usize;
+    let entries = read_u16(directory)? as usize;
+    (0..entries)
+        .map(|i| directory + 2 + i * 12)
+        .find(|&entry| read_u16(entry) == Some(ORIENTATION_TAG))
+        .and_then(|entry| read_u16(entry + 8))
+        .filter(|orientation| (1..=8).contains(orientation))
+}
+
+/// An APP1 segment with an EXIF block holding nothing but the orientation
+fn orientation_segment(orientation: u16) -> Vec<u8> {
+    let mut segment = vec![0xFF, 0xE1, 0, 0];
+    segment.extend_from_slice(b"Exif\0\0");
+    // Big-endian TIFF header, with the directory straight after it
+    segment.extend_from_slice(b"MM\0\x2A");
+    segment.extend_from_slice(&8u32.to_be_bytes());
+    segment.extend_from_slice(&1u16.to_be_bytes());
+    segment.extend_from_slice(&ORIENTATION_TAG.to_be_bytes());
+    // One SHORT, stored in the first half of the value field
+    segment.extend_from_slice(&3u16.to_be_bytes());
+    segment.extend_from_slice(&1u32.to_be_bytes());
+    segment.extend_from_slice(&orientation.to_be_bytes());
+    segment.extend_from_slice(&[0, 0]);
+    // No further directories
+    segment.extend_from_slice(&0u32.to_be_bytes());
+
+    let length = (segment.len() - 2) as u16;
+    segment[2..4].copy_from_slice(&length.to_be_bytes());
+    segment
+}
+
+fn strip_png(data: &[u8]) -> Option<Vec<u8>> {
+    let mut output = data[..8].to_vec();
+    let mut pos = 8;
+    let mut stripped = false;
+
+    loop {
+        let length = read_u32_be(data, pos)? as usize;
+        let chunk_type = data.get(pos + 4..pos + 8)?;
+        // Length, type, data and CRC
+        let end = pos.checked_add(12 + length)?;
+        if end > data.len() {
+            return None;
+        }
+
+        if PNG_METADATA_CHUNKS.iter().any(|t| t.as_slice() == chunk_type) {
+            stripped = true;
+        } else {
+            output.extend_from_slice(&data[pos..end]);
+        }
+        pos = end;
+
+        // Anything after the end of the image is dropped too
+        if chunk_type == b"IEND" {
+            stripped |= end < data.len();
+            break;
+        }
+    }
+
+    stripped.then_some(output)
+}
+
+fn strip_webp(data: &[u8]) -> Option<Vec<u8>> {
+    let riff_end = (read_u32_le(data, 4)? as usize).checked_add(8)?.min(data.len());
+    let mut output = data[..12].to_vec();
+    let mut pos = 12;
+    let mut stripped = false;
+    let mut extended_header = None;
+
+    while pos + 8 <= riff_end {
+        let chunk_type = &data[pos..pos + 4];
+        let length = read_u32_le(data, pos + 4)? as usize;
+        // Chunks are padded to an even length
+        let end = pos.checked_add(8 + length + length % 2)?.min(riff_end);
+        if pos + 8 + length > riff_end {
+            return None;
+        }
+
+        if WEBP_METADATA_CHUNKS.iter().any(|t| t.as_slice() == chunk_type) {
+            stripped = true;
+        } else {
+            if chunk_type == b"VP8X" {
+                extended_header = Some(output.len());
+            }
+            output.extend_from_slice(&data[pos..end]);
+        }
+        pos = end;
+    }
+
+    if !stripped {
+        return None;
+    }
+
+    // The extended header flags which metadata chunks the file has
+    if let Some(header) = extended_header {
+        const EXIF_FLAG: u8 = 0x08;
+        const XMP_FLAG: u8 = 0x04;
+        if let Some(flags) = output.get_mut(header + 8) {
+            *flags &= !(EXIF_FLAG | XMP_FLAG);
+        }
+    }
+
+    let riff_size = (output.len() - 8) as u32;
+    output[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Some(output)
+}
@@ -0,0 +1,232 @@
+//! Dimensions, durations, previews and metadata of uploaded files. Images are read with `image`;
+//! videos and audio need `ffprobe` and `ffmpeg` on the PATH and are skipped without them.
+
+mod blurhash;
+mod metadata;
+
+pub use metadata::strip_metadata;
+
+use anyhow::Context;
+use image::imageops::FilterType;
+use image::io::{Limits, Reader as ImageReader};
+use image::{DynamicImage, GenericImageView, ImageOutputFormat};
+use std::io::Cursor;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Thumbnails fit within this many pixels on each side
+pub const THUMBNAIL_SIZE: u32 = 400;
+
+/// Images with more pixels than this on a side aren't decoded
+const MAX_SOURCE_DIMENSION: u32 = 16384;
+
+/// Most memory decoding one image may take
+const MAX_DECODE_ALLOC: u64 = 512 * 1024 * 1024;
+
+/// Size the image is shrunk to before computing its blurhash
+const BLURHASH_SOURCE_SIZE: u32 = 32;
+
+/// How long ffprobe or ffmpeg may take on one file
+const TOOL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What was learned about an uploaded file. Files that aren't media, or couldn't be
+/// read, simply have less of it.
+#[derive(Debug, Default)]
+pub struct MediaInfo {
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub duration_secs: Option<f64>,
+    pub blurhash: Option<String>,
+    /// Encoded thumbnail and its content type
+    pub thumbnail: Option<(Vec<u8>, &'static str)>,
+}
+
+/// Read an image's size and make its blurhash and, when it's larger than a thumbnail, its
+/// thumbnail. This decodes the whole image, so run it off the async runtime.
+pub fn analyze_image(data: &[u8]) -> anyhow::Result<MediaInfo> {
+    let format = image::guess_format(data).context("Unrecognized image format")?;
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_DIMENSION);
+    limits.max_image_height = Some(MAX_SOURCE_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODE_ALLOC);
+    let mut reader = ImageReader::with_format(Cursor::new(data), format);
+    reader.limits(limits);
+    let img = reader.decode().context("Failed to decode image")?;
+
+    describe_image(&img, false)
+}
+
+fn describe_image(img: &DynamicImage, always_thumbnail: bool) -> anyhow::Result<MediaInfo> {
+    let (width, height) = img.dimensions();
+
+    let small = img.resize_exact(BLURHASH_SOURCE_SIZE, BLURHASH_SOURCE_SIZE, FilterType::Triangle);
+    let (x_components, y_components) = if width >= height { (4, 3) } else { (3, 4) };
+    let blurhash = blurhash::encode(&small.to_rgba8(), x_components, y_components);
+
+    let thumbnail = if always_thumbnail || width > THUMBNAIL_SIZE || height > THUMBNAIL_SIZE {
+        let thumb = img.resize(THUMBNAIL_SIZE, THUMBNAIL_SIZE, FilterType::Triangle);
+
+        // JPEG is much smaller for photos, but only PNG keeps transparency
+        let (thumb, output_format, content_type) = if img.color().has_alpha() {
+            (thumb, ImageOutputFormat::Png, "image/png")
+        } else {
+            (DynamicImage::ImageRgb8(thumb.to_rgb8()), ImageOutputFormat::Jpeg(80), "image/jpeg")
+        };
+        let mut output = Vec::new();
+        thumb
+            .write_to(&mut Cursor::new(&mut output), output_format)
+            .context("Failed to encode thumbnail")?;
+        Some((output, content_type))
+    } else {
+        None
+    };
+
+    Ok(MediaInfo {
+        width: Some(width as i32),
+        height: Some(height as i32),
+        duration_secs: None,
+        blurhash: Some(blurhash),
+        thumbnail,
+    })
+}
+
+/// Run a command to completion and return what it printed, or None if it failed
+async fn run_tool(command: &mut Command) -> Option<Vec<u8>> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+
+    match tokio::time::timeout(TOOL_TIMEOUT, command.output()).await {
+        Ok(Ok(output)) if output.status.success() => Some(output.stdout),
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => {
+            tracing::debug!("Couldn't run {:?}: {}", command.as_std().get_program(), e);
+            None
+        }
+        Err(_) => {
+            tracing::warn!("{:?} timed out", command.as_std().get_program());
+            None
+        }
+    }
+}
+
+/// Read the duration of a video or audio file and, for video, the size and first frame
+pub async fn analyze_media_file(path: &Path, is_video: bool) -> MediaInfo {
+    let mut info = MediaInfo::default();
+
+    let probe = run_tool(
+        Command::new("ffprobe")
+            .args(["-v", "error", "-select_streams", "v:0"])
+            .args(["-show_entries", "stream=width,height:format=duration", "-of", "json"])
+            .arg(path),
+    )
+    .await
+    .and_then(|output| serde_json::from_slice::<serde_json::Value>(&output).ok());
+
+    if let Some(probe) = &probe {
+        info.duration_secs = probe["format"]["duration"]
+            .as_str()
+            .and_then(|s| s.parse().ok())
+            .filter(|duration: &f64| duration.is_finite());
+        if is_video {
+            info.width = probe["streams"][0]["width"].as_i64().map(|w| w as i32);
+            info.height = probe["streams"][0]["height"].as_i64().map(|h| h as i32);
+        }
+    }
+
+    if !is_video {
+        return info;
+    }
+
+    let frame = run_tool(
+        Command::new("ffmpeg")
+            .args(["-v", "error", "-i"])
+            .arg(path)
+            .args(["-frames:v", "1", "-f", "image2pipe", "-vcodec", "png", "-"]),
+    )
+    .await;
+
+    if let Some(frame) = frame {
+        let described = tokio::task::spawn_blocking(move || {
+            let img = image::load_from_memory(&frame).context("Failed to decode video frame")?;
+            describe_image(&img, true)
+        })
+        .await;
+
+        match described {
+            Ok(Ok(frame_info)) => {
+                info.width = info.width.or(frame_info.width);
+                info.height = info.height.or(frame_info.height);
+                info.blurhash = frame_info.blurhash;
+                info.thumbnail = frame_info.thumbnail;
+            }
+            Ok(Err(e)) => tracing::warn!("Failed to make video thumbnail: {:#}", e),
+            Err(e) => tracing::warn!("Video thumbnail task failed: {}", e),
+        }
+    }
+
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    fn encode_png(img: DynamicImage) -> Vec<u8> {
+        let mut output = Vec::new();
+        img.write_to(&mut Cursor::new(&mut output), ImageOutputFormat::Png)
+            .unwrap();
+        output
+    }
+
+    fn opaque(width: u32, height: u32) -> Vec<u8> {
+        encode_png(DynamicImage::ImageRgb8(RgbImage::from_pixel(width, height, Rgb([30, 120, 200]))))
+    }
+
+    fn transparent(width: u32, height: u32) -> Vec<u8> {
+        let img = RgbaImage::from_pixel(width, height, Rgba([30, 120, 200, 100]));
+        encode_png(DynamicImage::ImageRgba8(img))
+    }
+
+    #[test]
+    fn analyzes_images() {
+        // (case, image, expected thumbnail size and type)
+        let cases = [
+            ("small", opaque(100, 50), None),
+            ("thumbnail size", opaque(400, 400), None),
+            ("wide", opaque(800, 200), Some((400, 100, "image/jpeg"))),
+            ("tall", opaque(300, 900), Some((133, 400, "image/jpeg"))),
+            ("transparent", transparent(1000, 500), Some((400, 200, "image/png"))),
+        ];
+
+        for (case, data, thumbnail) in cases {
+            let (width, height) = image::load_from_memory(&data).unwrap().dimensions();
+
+            let info = analyze_image(&data).unwrap();
+            assert_eq!(info.width, Some(width as i32), "{case}");
+            assert_eq!(info.height, Some(height as i32), "{case}");
+            assert_eq!(info.duration_secs, None, "{case}");
+            // 4 by 3 components: size flag, maximum, DC and 11 AC values
+            assert_eq!(info.blurhash.map(|hash| hash.len()), Some(28), "{case}");
+
+            let thumbnail_info = info.thumbnail.map(|(data, content_type)| {
+                let (width, height) = image::load_from_memory(&data).unwrap().dimensions();
+                (width, height, content_type)
+            });
+            assert_eq!(thumbnail_info, thumbnail, "{case}");
+        }
+    }
+
+    #[test]
+    fn rejects_non_images() {
+        for data in [&b""[..], b"not an image", b"\x89PNG\r\n\x1a\n truncated"] {
+            assert!(analyze_image(data).is_err(), "{data:?}");
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A channel following an announcement channel, with the source's names for display
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ChannelFollow {
+    pub id: Uuid,
+    pub source_channel_id: Uuid,
+    pub source_channel_name: String,
+    pub source_community_id: Uuid,
+    pub source_community_name: String,
+    pub target_channel_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateChannelFollow {
+    /// Channel the announcements are copied into
+    pub target_channel_id: Uuid,
+}
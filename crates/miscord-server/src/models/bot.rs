@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A bot token; the secret itself is never stored
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BotToken {
+    pub id: Uuid,
+    pub bot_id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// A newly created token, the only time the secret is returned
+#[derive(Debug, Serialize)]
+pub struct CreatedBotToken {
+    #[serde(flatten)]
+    pub token: BotToken,
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBot {
+    pub username: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBotToken {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddCommunityBot {
+    pub bot_id: Uuid,
+    /// `CommunityRole` permission bits granted to the bot
+    pub permissions: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCommunityBot {
+    pub permissions: i64,
+}
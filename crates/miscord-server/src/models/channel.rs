@@ -11,6 +11,8 @@ pub struct Channel {
     pub topic: Option<String>,
     pub channel_type: ChannelType,
     pub position: i32,
+    pub parent_id: Option<Uuid>, // Category this channel is nested under
+    pub retention_seconds: Option<i32>, // Messages older than this are purged
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -23,6 +25,9 @@ pub enum ChannelType {
     Voice,
     DirectMessage,
     GroupDm,
+    Category,
+    Forum,
+    Announcement,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -66,11 +71,23 @@ pub struct VoiceState {
     pub joined_at: DateTime<Utc>,
 }
 
+/// How far a user has read in a channel
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ChannelReadState {
+    pub user_id: Uuid,
+    pub channel_id: Uuid,
+    pub last_read_message_id: Option<Uuid>,
+    pub last_read_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateChannel {
     pub name: String,
     pub topic: Option<String>,
     pub channel_type: ChannelType,
+    /// Category to create the channel in
+    #[serde(default)]
+    pub parent_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -79,3 +96,23 @@ pub struct UpdateChannel {
     pub topic: Option<String>,
 }
 
+/// New position (and category) for a channel in a bulk reorder
+#[derive(Debug, Deserialize)]
+pub struct ChannelPosition {
+    pub id: Uuid,
+    pub position: i32,
+    pub parent_id: Option<Uuid>,
+}
+
+/// Retention period (or DM disappearing timer); None keeps history forever
+#[derive(Debug, Deserialize)]
+pub struct SetChannelRetention {
+    pub retention_seconds: Option<i32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct MarkChannelRead {
+    /// Last message the user has seen; defaults to the newest message in the channel.
+    /// A thread reply stands for its thread's parent.
+    pub message_id: Option<Uuid>,
+}
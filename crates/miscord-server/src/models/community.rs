@@ -35,6 +35,15 @@ pub struct CommunityRole {
     pub created_at: DateTime<Utc>,
 }
 
+/// Permission bits of a role. Roles currently only restrict what bots may do;
+/// people are limited by community ownership alone.
+impl CommunityRole {
+    pub const SEND_MESSAGES: i64 = 1 << 0;
+    pub const ADD_REACTIONS: i64 = 1 << 1;
+    pub const MANAGE_PINS: i64 = 1 << 2;
+    pub const ALL_PERMISSIONS: i64 = Self::SEND_MESSAGES | Self::ADD_REACTIONS | Self::MANAGE_PINS;
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateCommunity {
     pub name: String,
@@ -60,3 +69,20 @@ pub struct CommunityInvite {
     pub expires_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
+
+/// Custom emoji uploaded to a community, referenced as `<:name:id>`
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CommunityEmoji {
+    pub id: Uuid,
+    pub community_id: Uuid,
+    pub name: String,
+    pub attachment_id: Uuid,
+    pub url: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RenameEmoji {
+    pub name: String,
+}
@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Events an outgoing webhook can subscribe to, named like the `ServerMessage`
+/// variants they are sent as
+pub const WEBHOOK_EVENT_TYPES: &[&str] = &[
+    "message_created",
+    "message_updated",
+    "message_deleted",
+    "message_pinned",
+    "message_unpinned",
+    "reaction_added",
+    "reaction_removed",
+    "member_joined",
+    "member_left",
+    "voice_user_joined",
+    "voice_user_left",
+    "channel_created",
+    "channel_updated",
+    "channel_deleted",
+    "community_updated",
+];
+
+/// An outgoing webhook that receives a community's events
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EventWebhook {
+    pub id: Uuid,
+    pub community_id: Uuid,
+    pub url: String,
+    pub events: Vec<String>,
+    pub secret: String,
+    pub enabled: bool,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "webhook_delivery_status", rename_all = "snake_case")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+/// One event queued for, or sent to, an outgoing webhook
+#[derive(Debug, Clone, FromRow)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: String,
+    pub payload: Json<serde_json::Value>,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateEventWebhook {
+    pub url: String,
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateEventWebhook {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+}
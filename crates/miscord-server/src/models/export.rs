@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A channel or thread export and how far the background job has got
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Export {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub thread_parent_id: Option<Uuid>, // Set when only one thread is exported
+    pub requested_by_id: Uuid,
+    pub format: ExportFormat,
+    pub status: ExportStatus,
+    pub exported_messages: i32,
+    pub total_messages: i32,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "export_format", rename_all = "snake_case")]
+pub enum ExportFormat {
+    Json,
+    Html,
+    Markdown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "export_status", rename_all = "snake_case")]
+pub enum ExportStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateExport {
+    pub format: ExportFormat,
+    /// Export only this thread instead of the whole channel
+    #[serde(default)]
+    pub thread_parent_id: Option<Uuid>,
+}
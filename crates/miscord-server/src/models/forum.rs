@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Tag that can be applied to posts in a forum channel
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ForumTag {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub name: String,
+    pub position: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A forum post: the parent message of a thread with a title and tags
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ForumPost {
+    pub message_id: Uuid,
+    pub channel_id: Uuid,
+    pub title: String,
+    pub author_id: Uuid,
+    pub author_name: String,
+    pub content: String,
+    pub tag_ids: Vec<Uuid>,
+    pub reply_count: i32,
+    pub last_reply_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateForumTag {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateForumPost {
+    pub title: String,
+    pub content: String,
+    #[serde(default)]
+    pub tag_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateForumPostTags {
+    pub tag_ids: Vec<Uuid>,
+}
@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// An imported message author that no real account has claimed yet
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PlaceholderUser {
+    pub user_id: Uuid,
+    pub community_id: Uuid,
+    pub external_id: String,
+    pub display_name: String,
+    pub message_count: i64,
+    /// Members who have asked to claim the placeholder's history
+    pub claim_requested_by: Vec<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClaimPlaceholderUser {
+    /// Account that takes over the placeholder's messages and reactions. It must have
+    /// asked to claim the placeholder.
+    pub user_id: Uuid,
+}
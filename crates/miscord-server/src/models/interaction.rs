@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use miscord_protocol::{ButtonData, CommandOptionData, EmbedData, InteractionOptionData};
+use serde::Deserialize;
+use sqlx::types::Json;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A slash command, with the name of the application that registered it
+#[derive(Debug, Clone, FromRow)]
+pub struct SlashCommand {
+    pub id: Uuid,
+    pub community_id: Uuid,
+    pub application_id: Uuid,
+    pub application_name: String,
+    pub name: String,
+    pub description: String,
+    pub options: Json<Vec<CommandOptionData>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A command invocation or button click waiting for its application to respond
+#[derive(Debug, Clone, FromRow)]
+pub struct Interaction {
+    pub id: Uuid,
+    pub application_id: Uuid,
+    pub user_id: Uuid,
+    pub channel_id: Uuid,
+    /// Set for button clicks
+    pub message_id: Option<Uuid>,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Register a command, or replace the caller's command of the same name
+#[derive(Debug, Deserialize)]
+pub struct CreateSlashCommand {
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub options: Vec<CommandOptionData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InvokeCommand {
+    pub command_id: Uuid,
+    #[serde(default)]
+    pub options: Vec<InteractionOptionData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClickButton {
+    pub custom_id: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InteractionResponseKind {
+    /// Reply with a new message
+    #[default]
+    ChannelMessage,
+    /// Edit the message whose button was clicked
+    UpdateMessage,
+}
+
+/// How an application answers an interaction. When updating a message,
+/// fields left out keep their current value.
+#[derive(Debug, Deserialize)]
+pub struct InteractionResponse {
+    #[serde(default, rename = "type")]
+    pub kind: InteractionResponseKind,
+    pub content: Option<String>,
+    /// Only show the reply to the user who invoked the interaction
+    #[serde(default)]
+    pub ephemeral: bool,
+    pub embeds: Option<Vec<EmbedData>>,
+    pub components: Option<Vec<ButtonData>>,
+}
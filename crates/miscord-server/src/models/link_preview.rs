@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+
+/// OpenGraph metadata response
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OpenGraphData {
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub site_name: Option<String>,
+    /// For video embeds: "youtube", "vimeo", etc.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_type: Option<String>,
+    /// Channel/author name for videos
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_name: Option<String>,
+}
+
+/// A cached preview; `data` is None when the URL couldn't be previewed
+#[derive(Debug, Clone)]
+pub struct LinkPreview {
+    pub url: String,
+    pub data: Option<Json<OpenGraphData>>,
+    pub fetched_at: DateTime<Utc>,
+}
@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use miscord_protocol::EmbedData;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
@@ -16,6 +17,10 @@ pub struct Message {
     pub last_reply_at: Option<DateTime<Utc>>,
     pub pinned_at: Option<DateTime<Utc>>,
     pub pinned_by_id: Option<Uuid>,
+    pub forwarded_from_message_id: Option<Uuid>,
+    pub forwarded_from_channel_id: Option<Uuid>,
+    pub forwarded_from_author_id: Option<Uuid>,
+    pub forwarded_from_created_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -23,11 +28,49 @@ pub struct Message {
 pub struct MessageAttachment {
     pub id: Uuid,
     pub message_id: Option<Uuid>,
+    /// ID of the stored file; differs from `id` when the attachment was forwarded or reused
+    pub file_id: Uuid,
     pub filename: String,
     pub content_type: String,
     pub size_bytes: i64,
     pub url: String,
     pub created_at: DateTime<Utc>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub duration_secs: Option<f64>,
+    pub blurhash: Option<String>,
+    /// Whether a thumbnail is stored with the file
+    pub has_thumbnail: bool,
+}
+
+/// What a newly uploaded file belongs to
+#[derive(Debug, Clone, Copy)]
+pub struct AttachmentOwner {
+    /// None until the message it was uploaded for is sent
+    pub message_id: Option<Uuid>,
+    /// Whose storage quota the file counts against
+    pub uploader_id: Uuid,
+    /// Which community's storage quota it counts against; None in DMs
+    pub community_id: Option<Uuid>,
+}
+
+impl From<MessageAttachment> for miscord_protocol::AttachmentData {
+    fn from(attachment: MessageAttachment) -> Self {
+        Self {
+            thumbnail_url: attachment
+                .has_thumbnail
+                .then(|| format!("/api/files/{}/thumbnail", attachment.id)),
+            id: attachment.id,
+            filename: attachment.filename,
+            content_type: attachment.content_type,
+            size_bytes: attachment.size_bytes,
+            url: attachment.url,
+            width: attachment.width,
+            height: attachment.height,
+            duration_secs: attachment.duration_secs,
+            blurhash: attachment.blurhash,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -45,6 +88,9 @@ pub struct CreateMessage {
     pub reply_to_id: Option<Uuid>,
     #[serde(default)]
     pub attachment_ids: Vec<Uuid>,
+    /// Only bots may send embeds
+    #[serde(default)]
+    pub embeds: Vec<EmbedData>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -52,6 +98,12 @@ pub struct UpdateMessage {
     pub content: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ForwardMessage {
+    /// Channel or DM to forward the message into
+    pub channel_id: Uuid,
+}
+
 /// Message with author information included
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageWithAuthor {
@@ -85,3 +137,19 @@ pub struct ReactionCount {
     pub user_ids: Vec<Uuid>,
     pub reacted_by_me: bool,
 }
+
+/// A thread as shown in thread lists, with the viewing user's follow and read state
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ThreadSummary {
+    pub parent_message_id: Uuid,
+    pub channel_id: Uuid,
+    pub channel_name: String,
+    pub author_id: Uuid,
+    pub author_name: String,
+    pub content: String,
+    pub reply_count: i32,
+    pub last_reply_at: Option<DateTime<Utc>>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub following: bool,
+    pub unread_count: i64,
+}
@@ -1,9 +1,29 @@
+pub mod announcement;
+pub mod bot;
 pub mod channel;
 pub mod community;
+pub mod event_webhook;
+pub mod export;
+pub mod forum;
+pub mod import;
+pub mod interaction;
+pub mod link_preview;
 pub mod message;
+pub mod upload;
 pub mod user;
+pub mod webhook;
 
+pub use announcement::*;
+pub use bot::*;
 pub use channel::*;
 pub use community::*;
+pub use event_webhook::*;
+pub use export::*;
+pub use forum::*;
+pub use import::*;
+pub use interaction::*;
+pub use link_preview::*;
 pub use message::*;
+pub use upload::*;
 pub use user::*;
+pub use webhook::*;
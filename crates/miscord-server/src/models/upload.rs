@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A file being uploaded in chunks. It becomes an attachment with the same ID once complete.
+#[derive(Debug, Clone, FromRow)]
+pub struct UploadSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub channel_id: Uuid,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub chunk_size: i64,
+    pub received_bytes: i64,
+    pub storage_upload_id: String,
+    pub part_tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateUpload {
+    pub channel_id: Uuid,
+    pub filename: String,
+    pub size_bytes: i64,
+}
+
+/// Ask to attach a file the server already has, instead of uploading it again
+#[derive(Debug, Deserialize)]
+pub struct ReuseUpload {
+    pub channel_id: Uuid,
+    pub filename: String,
+    /// Hex SHA-256 of the file's contents
+    pub sha256: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AppendChunkQuery {
+    /// Where the chunk starts in the file; must be the number of bytes received so far
+    pub offset: i64,
+}
+
+/// How large a community's attachments may be
+#[derive(Debug, Serialize)]
+pub struct UploadLimit {
+    /// The limit that applies to members
+    pub max_upload_bytes: i64,
+    /// The community's own setting, when it has one
+    pub community_max_upload_bytes: Option<i64>,
+    /// The highest limit the server allows a community to set
+    pub server_max_upload_bytes: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetUploadLimit {
+    /// None goes back to the server default
+    pub max_upload_bytes: Option<i64>,
+}
@@ -14,6 +14,7 @@ pub struct User {
     pub avatar_url: Option<String>,
     pub status: UserStatus,
     pub custom_status: Option<String>,
+    pub bot: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -38,6 +39,7 @@ pub struct PublicUser {
     pub avatar_url: Option<String>,
     pub status: UserStatus,
     pub custom_status: Option<String>,
+    pub bot: bool,
 }
 
 impl From<User> for PublicUser {
@@ -49,6 +51,7 @@ impl From<User> for PublicUser {
             avatar_url: user.avatar_url,
             status: user.status,
             custom_status: user.custom_status,
+            bot: user.bot,
         }
     }
 }
@@ -67,3 +70,15 @@ pub struct UpdateUser {
     pub avatar_url: Option<String>,
     pub custom_status: Option<String>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PrivacySettings {
+    /// Whether other DM participants can see how far this user has read. Receipts are
+    /// reciprocal, so turning this off also hides other participants' receipts.
+    pub read_receipts_enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePrivacySettings {
+    pub read_receipts_enabled: Option<bool>,
+}
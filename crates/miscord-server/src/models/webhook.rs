@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// An incoming webhook that posts into a channel as its own user
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub avatar_url: Option<String>,
+    pub token: String,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Name and avatar a webhook message was posted with
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WebhookMessage {
+    pub message_id: Uuid,
+    /// None once the webhook has been deleted
+    pub webhook_id: Option<Uuid>,
+    pub username: String,
+    pub avatar_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhook {
+    pub name: String,
+    pub avatar_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhook {
+    pub name: Option<String>,
+    /// An empty string removes the avatar
+    pub avatar_url: Option<String>,
+}
+
+/// Body of a webhook call, compatible with the common subset of Discord's format
+#[derive(Debug, Default, Deserialize)]
+pub struct ExecuteWebhook {
+    #[serde(default)]
+    pub content: String,
+    /// Overrides the webhook's name for this message
+    pub username: Option<String>,
+    /// Overrides the webhook's avatar for this message
+    pub avatar_url: Option<String>,
+    #[serde(default)]
+    pub embeds: Vec<WebhookEmbed>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookEmbed {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub color: Option<u32>,
+    #[serde(default)]
+    pub fields: Vec<WebhookEmbedField>,
+    pub thumbnail: Option<WebhookEmbedMedia>,
+    pub image: Option<WebhookEmbedMedia>,
+    pub footer: Option<WebhookEmbedFooter>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookEmbedField {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub inline: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookEmbedMedia {
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookEmbedFooter {
+    pub text: String,
+}
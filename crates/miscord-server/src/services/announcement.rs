@@ -0,0 +1,161 @@
+use crate::error::{AppError, Result};
+use crate::models::ChannelFollow;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct AnnouncementService {
+    db: PgPool,
+}
+
+impl AnnouncementService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    // Follows
+
+    /// Copy future announcements of `source_channel_id` into `target_channel_id`
+    pub async fn follow(
+        &self,
+        source_channel_id: Uuid,
+        target_channel_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<ChannelFollow> {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO channel_follows (id, source_channel_id, target_channel_id, created_by_id, created_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            ON CONFLICT (source_channel_id, target_channel_id) DO NOTHING
+            RETURNING id
+            "#,
+            Uuid::new_v4(),
+            source_channel_id,
+            target_channel_id,
+            user_id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::Conflict("Channel already follows this announcement channel".to_string()))?;
+
+        self.get_follow(id).await
+    }
+
+    pub async fn unfollow(&self, source_channel_id: Uuid, target_channel_id: Uuid) -> Result<()> {
+        let result = sqlx::query!(
+            "DELETE FROM channel_follows WHERE source_channel_id = $1 AND target_channel_id = $2",
+            source_channel_id,
+            target_channel_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Follow not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_follow(&self, id: Uuid) -> Result<ChannelFollow> {
+        let follow = sqlx::query_as!(
+            ChannelFollow,
+            r#"
+            SELECT f.id, f.source_channel_id, c.name as source_channel_name,
+                   cm.id as source_community_id, cm.name as source_community_name,
+                   f.target_channel_id, f.created_at
+            FROM channel_follows f
+            INNER JOIN channels c ON c.id = f.source_channel_id
+            INNER JOIN communities cm ON cm.id = c.community_id
+            WHERE f.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Follow not found".to_string()))?;
+
+        Ok(follow)
+    }
+
+    /// Announcement channels a channel follows
+    pub async fn list_follows(&self, target_channel_id: Uuid) -> Result<Vec<ChannelFollow>> {
+        let follows = sqlx::query_as!(
+            ChannelFollow,
+            r#"
+            SELECT f.id, f.source_channel_id, c.name as source_channel_name,
+                   cm.id as source_community_id, cm.name as source_community_name,
+                   f.target_channel_id, f.created_at
+            FROM channel_follows f
+            INNER JOIN channels c ON c.id = f.source_channel_id
+            INNER JOIN communities cm ON cm.id = c.community_id
+            WHERE f.target_channel_id = $1
+            ORDER BY cm.name, c.name
+            "#,
+            target_channel_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(follows)
+    }
+
+    /// Channels that receive copies of an announcement channel's posts
+    pub async fn get_follower_channel_ids(&self, source_channel_id: Uuid) -> Result<Vec<Uuid>> {
+        let ids = sqlx::query_scalar!(
+            "SELECT target_channel_id FROM channel_follows WHERE source_channel_id = $1",
+            source_channel_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(ids)
+    }
+
+    // Publishing
+
+    /// Record that a message was published. Fails if it already was.
+    pub async fn mark_published(&self, message_id: Uuid, user_id: Uuid) -> Result<DateTime<Utc>> {
+        let published_at = sqlx::query_scalar!(
+            r#"
+            INSERT INTO message_publications (message_id, published_by_id, published_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (message_id) DO NOTHING
+            RETURNING published_at
+            "#,
+            message_id,
+            user_id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::Conflict("Message was already published".to_string()))?;
+
+        Ok(published_at)
+    }
+
+    pub async fn is_crosspost(&self, message_id: Uuid) -> Result<bool> {
+        let is_crosspost = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM message_crossposts WHERE message_id = $1)",
+            message_id
+        )
+        .fetch_one(&self.db)
+        .await?
+        .unwrap_or(false);
+
+        Ok(is_crosspost)
+    }
+
+    /// When each of the given messages was published, for those that were
+    pub async fn get_published_at(&self, message_ids: &[Uuid]) -> Result<HashMap<Uuid, DateTime<Utc>>> {
+        let rows = sqlx::query!(
+            "SELECT message_id, published_at FROM message_publications WHERE message_id = ANY($1)",
+            message_ids
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| (r.message_id, r.published_at)).collect())
+    }
+}
@@ -1,109 +1,429 @@
 use crate::error::{AppError, Result};
-use crate::models::MessageAttachment;
+use crate::file_type::{self, FileType};
+use crate::media_info::{self, MediaInfo};
+use crate::models::{AttachmentOwner, MessageAttachment};
+use crate::storage::{
+    attachment_key, key_file_id, thumbnail_key, ByteStream, DownloadHeaders, StorageBackend,
+};
+use chrono::Utc;
+use futures_util::StreamExt;
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
+use std::borrow::Cow;
+use std::collections::HashSet;
 use std::path::PathBuf;
-use tokio::fs;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
 /// Maximum file size: 25 MB
 const MAX_FILE_SIZE: usize = 25 * 1024 * 1024;
 
-/// Allowed file extensions
-const ALLOWED_EXTENSIONS: &[&str] = &[
-    // Images
-    "jpg", "jpeg", "png", "gif", "webp", "svg", "ico", "bmp",
-    // Documents
-    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "odp",
-    "txt", "rtf", "csv", "md",
-    // Archives
-    "zip", "tar", "gz", "7z", "rar",
-    // Audio
-    "mp3", "wav", "ogg", "flac", "m4a", "aac",
-    // Video
-    "mp4", "webm", "mov", "avi", "mkv",
-    // Code
-    "js", "ts", "py", "rs", "go", "java", "c", "cpp", "h", "hpp", "cs",
-    "html", "css", "json", "xml", "yaml", "yml", "toml", "sql", "sh",
-];
+/// Larger images are stored as uploaded, without their metadata being stripped or a
+/// thumbnail or dimensions, rather than decoded: 50 MB
+const MAX_ANALYZED_IMAGE_SIZE: i64 = 50 * 1024 * 1024;
+
+/// Images whose metadata can be stripped
+const STRIPPABLE_CONTENT_TYPES: &[&str] = &["image/jpeg", "image/png", "image/webp"];
+
+/// Most attachments with the same contents looked through for one stored under the
+/// right extension
+const MAX_HASH_MATCHES: i64 = 50;
+
+/// Hex SHA-256 of a file's contents, which identical uploads share
+pub fn content_hash(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Longest filename accepted, in characters
+const MAX_FILENAME_LENGTH: usize = 255;
+
+/// Check a filename can be shown and sent back in a Content-Disposition header:
+/// 1-255 characters, without control characters, quotes or backslashes
+pub fn validate_filename(filename: &str) -> Result<()> {
+    let length = filename.chars().count();
+    if length == 0 || length > MAX_FILENAME_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "Filename must be 1-{} characters",
+            MAX_FILENAME_LENGTH
+        )));
+    }
+    if filename.chars().any(|c| c.is_control() || c == '"' || c == '\\') {
+        return Err(AppError::BadRequest(
+            "Filename must not contain control characters, quotes or backslashes".to_string(),
+        ));
+    }
+    Ok(())
+}
 
 #[derive(Clone)]
 pub struct AttachmentService {
     db: PgPool,
-    upload_dir: PathBuf,
+    storage: Arc<dyn StorageBackend>,
     base_url: String,
+    /// Where remote files are downloaded to while ffmpeg reads them
+    scratch_dir: PathBuf,
+    /// Remove EXIF and other metadata from images
+    strip_metadata: bool,
 }
 
 impl AttachmentService {
-    pub fn new(db: PgPool, upload_dir: PathBuf, base_url: String) -> Self {
-        Self { db, upload_dir, base_url }
+    pub fn new(
+        db: PgPool,
+        storage: Arc<dyn StorageBackend>,
+        base_url: String,
+        scratch_dir: PathBuf,
+        strip_metadata: bool,
+    ) -> Self {
+        Self {
+            db,
+            storage,
+            base_url,
+            scratch_dir,
+            strip_metadata,
+        }
     }
 
-    /// Ensure the upload directory exists
-    pub async fn ensure_upload_dir(&self) -> Result<()> {
-        fs::create_dir_all(&self.upload_dir).await.map_err(|e| {
-            AppError::Internal(anyhow::anyhow!("Failed to create upload directory: {}", e))
-        })?;
-        Ok(())
+    /// Ensure the storage backend can take uploads (creates the upload directory when local)
+    pub async fn ensure_storage_ready(&self) -> Result<()> {
+        self.storage.ensure_ready().await
     }
 
-    /// Validate file before upload
-    pub fn validate_file(&self, filename: &str, size: usize) -> Result<()> {
+    /// Validate file before upload, returning the content type it's stored as
+    pub fn validate_file(&self, filename: &str, data: &[u8]) -> Result<&'static str> {
+        validate_filename(filename)?;
         // Check file size
-        if size > MAX_FILE_SIZE {
+        if data.len() > MAX_FILE_SIZE {
             return Err(AppError::BadRequest(format!(
                 "File too large. Maximum size is {} MB",
                 MAX_FILE_SIZE / 1024 / 1024
             )));
         }
 
-        // Check extension
-        let extension = filename
-            .rsplit('.')
-            .next()
-            .map(|s| s.to_lowercase())
-            .unwrap_or_default();
+        self.check_contents(filename, data)
+    }
 
-        if !ALLOWED_EXTENSIONS.contains(&extension.as_str()) {
+    fn file_type(&self, filename: &str) -> Result<&'static FileType> {
+        file_type::for_filename(filename).ok_or_else(|| {
+            let extension = filename
+                .rsplit('.')
+                .next()
+                .map(|s| s.to_lowercase())
+                .unwrap_or_default();
+            AppError::BadRequest(format!("File type '{}' is not allowed", extension))
+        })
+    }
+
+    /// Check the file's extension is one attachments may have, returning the content type
+    /// files with it are stored as. The content type clients send isn't trusted.
+    pub fn validate_extension(&self, filename: &str) -> Result<&'static str> {
+        validate_filename(filename)?;
+        self.file_type(filename).map(|file_type| file_type.content_type)
+    }
+
+    /// Check the start of a file really is what its extension says, so that, say, an HTML
+    /// page can't be passed off as a PNG. Returns the content type it's stored as.
+    pub fn check_contents(&self, filename: &str, data: &[u8]) -> Result<&'static str> {
+        let file_type = self.file_type(filename)?;
+        if !file_type.matches(data) {
             return Err(AppError::BadRequest(format!(
-                "File type '{}' is not allowed",
-                extension
+                "The contents of {} don't match its file type",
+                filename
             )));
         }
 
-        Ok(())
+        Ok(file_type.content_type)
+    }
+
+    /// The file without its EXIF and other metadata, when it's an image that has some
+    fn without_metadata<'a>(&self, content_type: &str, data: &'a [u8]) -> Cow<'a, [u8]> {
+        if !self.strip_metadata || !STRIPPABLE_CONTENT_TYPES.contains(&content_type) {
+            return Cow::Borrowed(data);
+        }
+
+        match media_info::strip_metadata(data) {
+            Some(stripped) => Cow::Owned(stripped),
+            None => Cow::Borrowed(data),
+        }
+    }
+
+    /// Strip the metadata from an image already in storage, as `save_file` does for files
+    /// it's given whole. Returns the file's size afterwards.
+    pub async fn strip_stored_metadata(
+        &self,
+        file_id: Uuid,
+        filename: &str,
+        content_type: &str,
+        size_bytes: i64,
+    ) -> Result<i64> {
+        if !self.strip_metadata
+            || !STRIPPABLE_CONTENT_TYPES.contains(&content_type)
+            || size_bytes > MAX_ANALYZED_IMAGE_SIZE
+        {
+            return Ok(size_bytes);
+        }
+
+        let data = self.read_file(file_id, filename).await?;
+        match self.without_metadata(content_type, &data) {
+            Cow::Owned(stripped) => {
+                self.storage
+                    .put(&attachment_key(file_id, filename), &stripped, content_type)
+                    .await?;
+                Ok(stripped.len() as i64)
+            }
+            Cow::Borrowed(_) => Ok(size_bytes),
+        }
     }
 
     /// Save a file and create database record
-    /// message_id can be None for attachments uploaded before the message is created
+    /// The owner's message_id can be None for attachments uploaded before the message is created
     pub async fn save_file(
         &self,
-        message_id: Option<Uuid>,
+        owner: AttachmentOwner,
         filename: &str,
-        content_type: &str,
         data: &[u8],
     ) -> Result<MessageAttachment> {
-        self.validate_file(filename, data.len())?;
+        let content_type = self.validate_file(filename, data)?;
+
+        // Hashed as uploaded, since that's what clients hash too
+        let sha256 = content_hash(data);
+        if let Some(attachment) = self.reuse_identical(&sha256, owner, filename, content_type).await? {
+            return Ok(attachment);
+        }
+
+        let data = self.without_metadata(content_type, data);
 
         let id = Uuid::new_v4();
-        let extension = filename
-            .rsplit('.')
-            .next()
-            .map(|s| format!(".{}", s.to_lowercase()))
-            .unwrap_or_default();
 
         // Store with UUID-based filename to avoid conflicts
-        let storage_filename = format!("{}{}", id, extension);
-        let file_path = self.upload_dir.join(&storage_filename);
+        self.storage
+            .put(&attachment_key(id, filename), &data, content_type)
+            .await?;
+
+        let info = self.analyze(id, filename, content_type, data.len() as i64, Some(&*data)).await;
+        self.create_record(id, owner, filename, content_type, data.len() as i64, &sha256, &info)
+            .await
+    }
 
-        // Write file to disk
-        let mut file = fs::File::create(&file_path).await.map_err(|e| {
-            AppError::Internal(anyhow::anyhow!("Failed to create file: {}", e))
-        })?;
-        file.write_all(data).await.map_err(|e| {
-            AppError::Internal(anyhow::anyhow!("Failed to write file: {}", e))
-        })?;
+    /// Hash a file already in storage, reading it a piece at a time
+    pub async fn hash_stored_file(&self, file_id: Uuid, filename: &str) -> Result<String> {
+        let mut stream = self.open_file(file_id, filename).await?;
+        let mut hasher = Sha256::new();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to read stored file: {}", e)))?;
+            hasher.update(&chunk);
+        }
 
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    /// An attachment whose file has these contents and is stored under the same extension
+    /// `filename` would be. With `visible_to`, only files that user uploaded or that were
+    /// uploaded to its community are considered, so that knowing a file's hash isn't enough
+    /// to get a copy of it.
+    pub async fn find_by_hash(
+        &self,
+        sha256: &str,
+        filename: &str,
+        visible_to: Option<&AttachmentOwner>,
+    ) -> Result<Option<MessageAttachment>> {
+        let candidates = sqlx::query_as!(
+            MessageAttachment,
+            r#"
+            SELECT id, message_id as "message_id: _", file_id, filename, content_type, size_bytes, url, created_at,
+                   width, height, duration_secs, blurhash, has_thumbnail
+            FROM message_attachments
+            WHERE sha256 = $1
+              AND ($2::UUID IS NULL OR uploader_id = $2 OR community_id = $3)
+            ORDER BY created_at ASC
+            LIMIT $4
+            "#,
+            sha256,
+            visible_to.map(|owner| owner.uploader_id),
+            visible_to.and_then(|owner| owner.community_id),
+            MAX_HASH_MATCHES
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(candidates.into_iter().find(|candidate| {
+            attachment_key(candidate.file_id, &candidate.filename) == attachment_key(candidate.file_id, filename)
+        }))
+    }
+
+    /// Create an attachment that shares `existing`'s stored file. None when `existing` has
+    /// been deleted meanwhile. The source row is locked while it's copied, so a concurrent
+    /// delete waits for the new row and then sees the file is still referenced.
+    pub async fn reuse_file(
+        &self,
+        existing: &MessageAttachment,
+        owner: AttachmentOwner,
+        filename: &str,
+        content_type: &str,
+    ) -> Result<Option<MessageAttachment>> {
+        let id = Uuid::new_v4();
+        let url = format!("/api/files/{}", id);
+
+        let attachment = sqlx::query_as!(
+            MessageAttachment,
+            r#"
+            INSERT INTO message_attachments
+                (id, message_id, file_id, filename, content_type, size_bytes, url,
+                 width, height, duration_secs, blurhash, has_thumbnail, uploader_id, community_id, sha256)
+            SELECT $1, $2, file_id, $3, $4, size_bytes, $5,
+                   width, height, duration_secs, blurhash, has_thumbnail, $6, $7, sha256
+            FROM message_attachments
+            WHERE id = $8
+            FOR KEY SHARE
+            RETURNING id, message_id as "message_id: _", file_id, filename, content_type, size_bytes, url, created_at,
+                      width, height, duration_secs, blurhash, has_thumbnail
+            "#,
+            id,
+            owner.message_id,
+            filename,
+            content_type,
+            url,
+            owner.uploader_id,
+            owner.community_id,
+            existing.id
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(attachment)
+    }
+
+    /// Attach an identical file already in storage instead of storing another copy, when
+    /// there is one. Whoever uploads the bytes evidently has the file, so any match will do.
+    pub async fn reuse_identical(
+        &self,
+        sha256: &str,
+        owner: AttachmentOwner,
+        filename: &str,
+        content_type: &str,
+    ) -> Result<Option<MessageAttachment>> {
+        match self.find_by_hash(sha256, filename, None).await? {
+            Some(existing) => self.reuse_file(&existing, owner, filename, content_type).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Work out an uploaded file's dimensions, duration and blurhash, and store its thumbnail.
+    /// Pass the contents when they're at hand; otherwise they're read back from storage.
+    /// Problems are logged rather than failing the upload, leaving the details empty.
+    pub async fn analyze(
+        &self,
+        file_id: Uuid,
+        filename: &str,
+        content_type: &str,
+        size_bytes: i64,
+        data: Option<&[u8]>,
+    ) -> MediaInfo {
+        let result = if content_type.starts_with("image/") && content_type != "image/svg+xml" {
+            self.analyze_image(file_id, filename, size_bytes, data).await
+        } else if content_type.starts_with("video/") || content_type.starts_with("audio/") {
+            self.analyze_media_file(file_id, filename, content_type.starts_with("video/"), data)
+                .await
+        } else {
+            return MediaInfo::default();
+        };
+
+        let mut info = match result {
+            Ok(info) => info,
+            Err(e) => {
+                tracing::warn!("Couldn't analyze {} ({}): {}", filename, file_id, e);
+                return MediaInfo::default();
+            }
+        };
+
+        if let Some((thumbnail, thumbnail_type)) = &info.thumbnail {
+            if let Err(e) = self.storage.put(&thumbnail_key(file_id), thumbnail, thumbnail_type).await {
+                tracing::warn!("Failed to store thumbnail of {}: {}", file_id, e);
+                info.thumbnail = None;
+            }
+        }
+
+        info
+    }
+
+    async fn analyze_image(
+        &self,
+        file_id: Uuid,
+        filename: &str,
+        size_bytes: i64,
+        data: Option<&[u8]>,
+    ) -> Result<MediaInfo> {
+        if size_bytes > MAX_ANALYZED_IMAGE_SIZE {
+            return Ok(MediaInfo::default());
+        }
+
+        let data = match data {
+            Some(data) => data.to_vec(),
+            None => self.read_file(file_id, filename).await?,
+        };
+
+        tokio::task::spawn_blocking(move || media_info::analyze_image(&data))
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Image analysis task failed: {}", e)))?
+            .map_err(AppError::Internal)
+    }
+
+    /// ffmpeg needs a file, so use the stored one when it's on disk and a scratch copy otherwise
+    async fn analyze_media_file(
+        &self,
+        file_id: Uuid,
+        filename: &str,
+        is_video: bool,
+        data: Option<&[u8]>,
+    ) -> Result<MediaInfo> {
+        if let Some(path) = self.local_file_path(file_id, filename) {
+            return Ok(media_info::analyze_media_file(&path, is_video).await);
+        }
+
+        let write_error =
+            |e: std::io::Error| AppError::Internal(anyhow::anyhow!("Failed to write scratch file: {}", e));
+        tokio::fs::create_dir_all(&self.scratch_dir).await.map_err(write_error)?;
+        let path = self.scratch_dir.join(attachment_key(file_id, filename));
+
+        let copied: Result<()> = async {
+            let mut file = tokio::fs::File::create(&path).await.map_err(write_error)?;
+            match data {
+                Some(data) => file.write_all(data).await.map_err(write_error)?,
+                None => {
+                    let mut stream = self.open_file(file_id, filename).await?;
+                    while let Some(chunk) = stream.next().await {
+                        let chunk = chunk.map_err(|e| {
+                            AppError::Internal(anyhow::anyhow!("Failed to read stored file: {}", e))
+                        })?;
+                        file.write_all(&chunk).await.map_err(write_error)?;
+                    }
+                }
+            }
+            file.flush().await.map_err(write_error)
+        }
+        .await;
+
+        let info = match copied {
+            Ok(()) => Ok(media_info::analyze_media_file(&path, is_video).await),
+            Err(e) => Err(e),
+        };
+        let _ = tokio::fs::remove_file(&path).await;
+        info
+    }
+
+    /// Create the database record for a file already in storage under `id`
+    pub async fn create_record(
+        &self,
+        id: Uuid,
+        owner: AttachmentOwner,
+        filename: &str,
+        content_type: &str,
+        size_bytes: i64,
+        sha256: &str,
+        info: &MediaInfo,
+    ) -> Result<MessageAttachment> {
         // Create URL for the file
         let url = format!("/api/files/{}", id);
 
@@ -112,16 +432,27 @@ impl AttachmentService {
         let attachment = sqlx::query_as!(
             MessageAttachment,
             r#"
-            INSERT INTO message_attachments (id, message_id, filename, content_type, size_bytes, url)
-            VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING id, message_id as "message_id: _", filename, content_type, size_bytes, url, created_at
+            INSERT INTO message_attachments
+                (id, message_id, file_id, filename, content_type, size_bytes, url,
+                 width, height, duration_secs, blurhash, has_thumbnail, uploader_id, community_id, sha256)
+            VALUES ($1, $2, $1, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            RETURNING id, message_id as "message_id: _", file_id, filename, content_type, size_bytes, url, created_at,
+                      width, height, duration_secs, blurhash, has_thumbnail
             "#,
             id,
-            message_id,
+            owner.message_id,
             filename,
             content_type,
-            data.len() as i64,
-            url
+            size_bytes,
+            url,
+            info.width,
+            info.height,
+            info.duration_secs,
+            info.blurhash,
+            info.thumbnail.is_some(),
+            owner.uploader_id,
+            owner.community_id,
+            sha256
         )
         .fetch_one(&self.db)
         .await?;
@@ -129,23 +460,81 @@ impl AttachmentService {
         Ok(attachment)
     }
 
-    /// Link attachments to a message (update message_id)
-    pub async fn link_to_message(&self, attachment_ids: &[Uuid], message_id: Uuid) -> Result<()> {
-        if attachment_ids.is_empty() {
+    /// Check every attachment is one of `owner`'s own uploads to its community that hasn't
+    /// been sent yet. Emoji images never belong to a message either, but aren't uploads
+    /// anyone may send.
+    pub async fn check_linkable(&self, attachment_ids: &[Uuid], owner: &AttachmentOwner) -> Result<()> {
+        let ids: HashSet<Uuid> = attachment_ids.iter().copied().collect();
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<Uuid> = ids.into_iter().collect();
+
+        let linkable = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM message_attachments a
+            WHERE a.id = ANY($1)
+              AND a.message_id IS NULL
+              AND a.uploader_id = $2
+              AND a.community_id IS NOT DISTINCT FROM $3
+              AND NOT EXISTS (SELECT 1 FROM community_emojis e WHERE e.attachment_id = a.id)
+            "#,
+            &ids,
+            owner.uploader_id,
+            owner.community_id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        if linkable != ids.len() as i64 {
+            return Err(AppError::BadRequest(
+                "Attachments must be your own unsent uploads to this channel's community".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Link the owner's unsent uploads to its message. Like `check_linkable`, nothing is
+    /// linked unless every attachment may be.
+    pub async fn link_to_message(&self, attachment_ids: &[Uuid], owner: &AttachmentOwner) -> Result<()> {
+        let ids: HashSet<Uuid> = attachment_ids.iter().copied().collect();
+        if ids.is_empty() {
             return Ok(());
         }
+        let ids: Vec<Uuid> = ids.into_iter().collect();
+        let message_id = owner
+            .message_id
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Linking attachments needs a message")))?;
 
-        sqlx::query!(
+        let mut tx = self.db.begin().await?;
+        let linked = sqlx::query!(
             r#"
-            UPDATE message_attachments
+            UPDATE message_attachments a
             SET message_id = $1
-            WHERE id = ANY($2)
+            WHERE a.id = ANY($2)
+              AND a.message_id IS NULL
+              AND a.uploader_id = $3
+              AND a.community_id IS NOT DISTINCT FROM $4
+              AND NOT EXISTS (SELECT 1 FROM community_emojis e WHERE e.attachment_id = a.id)
             "#,
             message_id,
-            attachment_ids
+            &ids,
+            owner.uploader_id,
+            owner.community_id
         )
-        .execute(&self.db)
-        .await?;
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if linked != ids.len() as u64 {
+            tx.rollback().await?;
+            return Err(AppError::BadRequest(
+                "Attachments must be your own unsent uploads to this channel's community".to_string(),
+            ));
+        }
+        tx.commit().await?;
 
         Ok(())
     }
@@ -155,7 +544,8 @@ impl AttachmentService {
         let attachment = sqlx::query_as!(
             MessageAttachment,
             r#"
-            SELECT id, message_id as "message_id: _", filename, content_type, size_bytes, url, created_at
+            SELECT id, message_id as "message_id: _", file_id, filename, content_type, size_bytes, url, created_at,
+                   width, height, duration_secs, blurhash, has_thumbnail
             FROM message_attachments WHERE id = $1
             "#,
             id
@@ -172,7 +562,8 @@ impl AttachmentService {
         let attachments = sqlx::query_as!(
             MessageAttachment,
             r#"
-            SELECT id, message_id as "message_id: _", filename, content_type, size_bytes, url, created_at
+            SELECT id, message_id as "message_id: _", file_id, filename, content_type, size_bytes, url, created_at,
+                   width, height, duration_secs, blurhash, has_thumbnail
             FROM message_attachments WHERE message_id = $1
             ORDER BY created_at ASC
             "#,
@@ -193,7 +584,8 @@ impl AttachmentService {
         let attachments = sqlx::query_as!(
             MessageAttachment,
             r#"
-            SELECT id, message_id as "message_id: _", filename, content_type, size_bytes, url, created_at
+            SELECT id, message_id as "message_id: _", file_id, filename, content_type, size_bytes, url, created_at,
+                   width, height, duration_secs, blurhash, has_thumbnail
             FROM message_attachments WHERE message_id = ANY($1)
             ORDER BY created_at ASC
             "#,
@@ -205,42 +597,182 @@ impl AttachmentService {
         Ok(attachments)
     }
 
-    /// Get the file path on disk for an attachment
-    pub fn get_file_path(&self, id: Uuid, filename: &str) -> PathBuf {
-        let extension = filename
-            .rsplit('.')
-            .next()
-            .map(|s| format!(".{}", s.to_lowercase()))
-            .unwrap_or_default();
+    /// Path of an attachment's stored file, when it's kept on this server's disk
+    pub fn local_file_path(&self, file_id: Uuid, filename: &str) -> Option<PathBuf> {
+        self.storage.local_path(&attachment_key(file_id, filename))
+    }
 
-        self.upload_dir.join(format!("{}{}", id, extension))
+    /// Read file contents from storage
+    pub async fn read_file(&self, file_id: Uuid, filename: &str) -> Result<Vec<u8>> {
+        self.storage.get(&attachment_key(file_id, filename)).await
     }
 
-    /// Read file contents from disk
-    pub async fn read_file(&self, id: Uuid, filename: &str) -> Result<Vec<u8>> {
-        let path = self.get_file_path(id, filename);
-        fs::read(&path).await.map_err(|e| {
-            AppError::Internal(anyhow::anyhow!("Failed to read file: {}", e))
-        })
+    /// Stream file contents from storage
+    pub async fn open_file(&self, file_id: Uuid, filename: &str) -> Result<ByteStream> {
+        self.storage.get_stream(&attachment_key(file_id, filename)).await
+    }
+
+    /// A link clients can download the file from directly, when the storage backend offers one
+    pub fn download_url(&self, attachment: &MessageAttachment, content_disposition: &str) -> Option<String> {
+        self.storage.presigned_url(
+            &attachment_key(attachment.file_id, &attachment.filename),
+            &DownloadHeaders {
+                content_type: &attachment.content_type,
+                content_disposition,
+            },
+        )
+    }
+
+    /// Read a file's thumbnail from storage
+    pub async fn read_thumbnail(&self, file_id: Uuid) -> Result<Vec<u8>> {
+        self.storage.get(&thumbnail_key(file_id)).await
+    }
+
+    /// Remove a stored file, and its thumbnail, once no attachment references it
+    pub async fn remove_file(&self, file_id: Uuid, filename: &str) -> Result<()> {
+        self.storage.delete(&attachment_key(file_id, filename)).await?;
+        self.storage.delete(&thumbnail_key(file_id)).await
     }
 
     /// Delete an attachment (file and database record)
+    /// The file is kept in storage while forwarded copies or identical uploads still reference it.
     pub async fn delete(&self, id: Uuid) -> Result<()> {
-        let attachment = self.get_by_id(id).await?;
+        // Delete from database first, so an identical upload can't start sharing the file
+        // after it's been found unused
+        let attachment = sqlx::query!(
+            "DELETE FROM message_attachments WHERE id = $1 RETURNING file_id, filename",
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Attachment not found".to_string()))?;
 
-        // Delete file from disk
-        let path = self.get_file_path(id, &attachment.filename);
-        if path.exists() {
-            fs::remove_file(&path).await.map_err(|e| {
-                AppError::Internal(anyhow::anyhow!("Failed to delete file: {}", e))
-            })?;
-        }
+        let shared = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM message_attachments WHERE file_id = $1)",
+            attachment.file_id
+        )
+        .fetch_one(&self.db)
+        .await?
+        .unwrap_or(false);
 
-        // Delete from database
-        sqlx::query!("DELETE FROM message_attachments WHERE id = $1", id)
-            .execute(&self.db)
-            .await?;
+        // Delete file from storage. Should this fail, the storage sweep removes it later.
+        if !shared {
+            self.remove_file(attachment.file_id, &attachment.filename).await?;
+        }
 
         Ok(())
     }
+
+    /// Delete attachments uploaded more than `max_age` ago that were never sent in a
+    /// message, and their files. Custom emoji images never belong to a message, so they're
+    /// kept. Returns how many were deleted.
+    pub async fn purge_unsent(&self, max_age: Duration) -> Result<usize> {
+        let purged = sqlx::query!(
+            r#"
+            DELETE FROM message_attachments a
+            WHERE a.message_id IS NULL
+              AND a.created_at < NOW() - make_interval(secs => $1)
+              AND NOT EXISTS (SELECT 1 FROM community_emojis e WHERE e.attachment_id = a.id)
+            RETURNING a.file_id, a.filename
+            "#,
+            max_age.as_secs_f64()
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        for attachment in &purged {
+            let shared = sqlx::query_scalar!(
+                "SELECT EXISTS(SELECT 1 FROM message_attachments WHERE file_id = $1)",
+                attachment.file_id
+            )
+            .fetch_one(&self.db)
+            .await?
+            .unwrap_or(false);
+
+            if !shared {
+                if let Err(e) = self.remove_file(attachment.file_id, &attachment.filename).await {
+                    tracing::warn!("Failed to remove file {}: {}", attachment.file_id, e);
+                }
+            }
+        }
+
+        Ok(purged.len())
+    }
+
+    /// Delete stored files and thumbnails no attachment refers to, such as those of messages
+    /// deleted along with their channel. Files newer than `min_age` are left alone, since
+    /// their attachment may not be recorded yet. Returns how many were deleted.
+    pub async fn purge_unreferenced_files(&self, min_age: Duration) -> Result<usize> {
+        const BATCH_SIZE: usize = 1000;
+
+        let cutoff = Utc::now() - chrono::Duration::seconds(min_age.as_secs() as i64);
+        let files: Vec<(String, Uuid)> = self
+            .storage
+            .list()
+            .await?
+            .into_iter()
+            .filter(|file| file.modified < cutoff)
+            .filter_map(|file| key_file_id(&file.key).map(|id| (file.key, id)))
+            .collect();
+
+        let mut removed = 0;
+        for batch in files.chunks(BATCH_SIZE) {
+            let ids: Vec<Uuid> = batch.iter().map(|(_, id)| *id).collect();
+
+            // Files being uploaded in chunks are stored under their upload's ID
+            let referenced: HashSet<Uuid> = sqlx::query_scalar!(
+                r#"
+                SELECT file_id AS "id!" FROM message_attachments WHERE file_id = ANY($1)
+                UNION
+                SELECT id AS "id!" FROM upload_sessions WHERE id = ANY($1)
+                "#,
+                &ids
+            )
+            .fetch_all(&self.db)
+            .await?
+            .into_iter()
+            .collect();
+
+            for (key, id) in batch {
+                if referenced.contains(id) {
+                    continue;
+                }
+                match self.storage.delete(key).await {
+                    Ok(()) => removed += 1,
+                    Err(e) => tracing::warn!("Failed to remove unreferenced file {}: {}", key, e),
+                }
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filenames() {
+        let cases = [
+            ("cat.png", true),
+            ("my report (final).pdf", true),
+            ("r\u{e9}sum\u{e9}.txt", true),
+            ("\u{1f431}.gif", true),
+            ("it's.txt", true),
+            ("", false),
+            ("a\"b.txt", false),
+            ("a\\b.txt", false),
+            ("a\r\nContent-Type: text/html.txt", false),
+            ("a\0b.txt", false),
+            ("tab\there.txt", false),
+            ("del\u{7f}.txt", false),
+        ];
+        for (filename, valid) in cases {
+            assert_eq!(validate_filename(filename).is_ok(), valid, "{filename:?}");
+        }
+
+        assert!(validate_filename(&"\u{e9}".repeat(MAX_FILENAME_LENGTH)).is_ok());
+        assert!(validate_filename(&"a".repeat(MAX_FILENAME_LENGTH + 1)).is_err());
+    }
 }
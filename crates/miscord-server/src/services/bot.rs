@@ -0,0 +1,295 @@
+use crate::error::{AppError, Result};
+use crate::models::{BotToken, CommunityRole, User, UserStatus};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Bot tokens start with this, which tells them apart from login JWTs
+pub const BOT_TOKEN_PREFIX: &str = "mcb_";
+
+const TOKEN_SECRET_LENGTH: usize = 48;
+
+fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+#[derive(Clone)]
+pub struct BotService {
+    db: PgPool,
+}
+
+impl BotService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Create a bot account managed by `owner_id`
+    pub async fn create(&self, owner_id: Uuid, username: &str, display_name: &str) -> Result<User> {
+        let existing = sqlx::query_scalar!("SELECT EXISTS(SELECT 1 FROM users WHERE username = $1)", username)
+            .fetch_one(&self.db)
+            .await?;
+
+        if existing.unwrap_or(false) {
+            return Err(AppError::Conflict("Username already exists".to_string()));
+        }
+
+        let id = Uuid::new_v4();
+
+        // An empty password hash can never be verified; bots sign in with tokens
+        let bot = sqlx::query_as!(
+            User,
+            r#"
+            INSERT INTO users (id, username, display_name, email, password_hash, status, bot, bot_owner_id,
+                               created_at, updated_at)
+            VALUES ($1, $2, $3, $4, '', $5, TRUE, $6, NOW(), NOW())
+            RETURNING id, username, display_name, email, password_hash, avatar_url,
+                      status as "status: UserStatus", custom_status, bot, created_at, updated_at
+            "#,
+            id,
+            username,
+            display_name,
+            format!("{}@bot.invalid", id),
+            UserStatus::Offline as UserStatus,
+            owner_id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(bot)
+    }
+
+    pub async fn list_by_owner(&self, owner_id: Uuid) -> Result<Vec<User>> {
+        let bots = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, display_name, email, password_hash, avatar_url,
+                   status as "status: UserStatus", custom_status, bot, created_at, updated_at
+            FROM users WHERE bot_owner_id = $1
+            ORDER BY username
+            "#,
+            owner_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(bots)
+    }
+
+    /// A bot, if `owner_id` manages it
+    pub async fn get_owned(&self, bot_id: Uuid, owner_id: Uuid) -> Result<User> {
+        sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, display_name, email, password_hash, avatar_url,
+                   status as "status: UserStatus", custom_status, bot, created_at, updated_at
+            FROM users WHERE id = $1 AND bot_owner_id = $2
+            "#,
+            bot_id,
+            owner_id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Bot not found".to_string()))
+    }
+
+    /// Delete a bot account together with its tokens, memberships and messages
+    pub async fn delete(&self, bot_id: Uuid) -> Result<()> {
+        sqlx::query!("DELETE FROM users WHERE id = $1 AND bot", bot_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Create a token; the returned secret cannot be retrieved again
+    pub async fn create_token(&self, bot_id: Uuid, name: &str) -> Result<(BotToken, String)> {
+        let secret: String = rand::thread_rng()
+            .sample_iter(&rand::distributions::Alphanumeric)
+            .take(TOKEN_SECRET_LENGTH)
+            .map(char::from)
+            .collect();
+        let token = format!("{}{}", BOT_TOKEN_PREFIX, secret);
+
+        let created = sqlx::query_as!(
+            BotToken,
+            r#"
+            INSERT INTO bot_tokens (id, bot_id, name, token_hash, created_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            RETURNING id, bot_id, name, created_at, last_used_at
+            "#,
+            Uuid::new_v4(),
+            bot_id,
+            name,
+            hash_token(&token)
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok((created, token))
+    }
+
+    pub async fn list_tokens(&self, bot_id: Uuid) -> Result<Vec<BotToken>> {
+        let tokens = sqlx::query_as!(
+            BotToken,
+            r#"
+            SELECT id, bot_id, name, created_at, last_used_at
+            FROM bot_tokens WHERE bot_id = $1
+            ORDER BY created_at
+            "#,
+            bot_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(tokens)
+    }
+
+    pub async fn revoke_token(&self, bot_id: Uuid, token_id: Uuid) -> Result<()> {
+        let result = sqlx::query!(
+            "DELETE FROM bot_tokens WHERE id = $1 AND bot_id = $2",
+            token_id,
+            bot_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Token not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// The bot a token belongs to, as (user ID, username)
+    pub async fn authenticate(&self, token: &str) -> Result<(Uuid, String)> {
+        let bot = sqlx::query!(
+            r#"
+            UPDATE bot_tokens t SET last_used_at = NOW()
+            FROM users u
+            WHERE t.token_hash = $1 AND u.id = t.bot_id
+            RETURNING u.id, u.username
+            "#,
+            hash_token(token)
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or(AppError::Unauthorized)?;
+
+        Ok((bot.id, bot.username))
+    }
+
+    /// Make a bot a member of a community, with its own role holding its permissions
+    pub async fn add_to_community(&self, community_id: Uuid, bot: &User, permissions: i64) -> Result<()> {
+        let member_id = Uuid::new_v4();
+        let role_id = Uuid::new_v4();
+        let role_name: String = bot.display_name.chars().take(64).collect();
+        let mut tx = self.db.begin().await?;
+
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO community_members (id, community_id, user_id, joined_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (community_id, user_id) DO NOTHING
+            "#,
+            member_id,
+            community_id,
+            bot.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if inserted.rows_affected() == 0 {
+            return Err(AppError::Conflict("The bot is already a member".to_string()));
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO community_roles (id, community_id, name, permissions, position, created_at)
+            VALUES ($1, $2, $3, $4, 0, NOW())
+            "#,
+            role_id,
+            community_id,
+            role_name,
+            permissions & CommunityRole::ALL_PERMISSIONS
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO member_roles (member_id, role_id) VALUES ($1, $2)",
+            member_id,
+            role_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Change the permissions of a bot's role in a community
+    pub async fn set_permissions(&self, community_id: Uuid, bot_id: Uuid, permissions: i64) -> Result<()> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE community_roles SET permissions = $3
+            WHERE id IN (
+                SELECT mr.role_id FROM member_roles mr
+                INNER JOIN community_members m ON m.id = mr.member_id
+                WHERE m.community_id = $1 AND m.user_id = $2
+            )
+            "#,
+            community_id,
+            bot_id,
+            permissions & CommunityRole::ALL_PERMISSIONS
+        )
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Bot is not a member of this community".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Permission bits of all roles a member has in a community
+    pub async fn get_permissions(&self, community_id: Uuid, user_id: Uuid) -> Result<i64> {
+        let permissions = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(BIT_OR(r.permissions), 0) as "permissions!"
+            FROM member_roles mr
+            INNER JOIN community_members m ON m.id = mr.member_id
+            INNER JOIN community_roles r ON r.id = mr.role_id
+            WHERE m.community_id = $1 AND m.user_id = $2
+            "#,
+            community_id,
+            user_id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(permissions)
+    }
+
+    /// Remove the roles created for a bot when it leaves a community
+    pub async fn remove_roles(&self, community_id: Uuid, bot_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+            DELETE FROM community_roles
+            WHERE id IN (
+                SELECT mr.role_id FROM member_roles mr
+                INNER JOIN community_members m ON m.id = mr.member_id
+                WHERE m.community_id = $1 AND m.user_id = $2
+            )
+            "#,
+            community_id,
+            bot_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,7 +1,10 @@
 use crate::error::{AppError, Result};
-use crate::models::{Channel, ChannelType, CreateChannel, UpdateChannel, VoiceState};
+use crate::models::{
+    Channel, ChannelPosition, ChannelReadState, ChannelType, CreateChannel, UpdateChannel, VoiceState,
+};
 use chrono::{DateTime, Utc};
 use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 #[derive(Clone)]
@@ -15,6 +18,13 @@ impl ChannelService {
     }
 
     pub async fn create(&self, community_id: Uuid, input: CreateChannel) -> Result<Channel> {
+        if let Some(parent_id) = input.parent_id {
+            if input.channel_type == ChannelType::Category {
+                return Err(AppError::BadRequest("Categories cannot be nested".to_string()));
+            }
+            self.require_category(community_id, parent_id).await?;
+        }
+
         // Get the next position
         let max_position: Option<i32> = sqlx::query_scalar!(
             "SELECT MAX(position) FROM channels WHERE community_id = $1",
@@ -28,17 +38,18 @@ impl ChannelService {
         let channel = sqlx::query_as!(
             Channel,
             r#"
-            INSERT INTO channels (id, community_id, name, topic, channel_type, position, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, NOW(), NOW())
+            INSERT INTO channels (id, community_id, name, topic, channel_type, position, parent_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW(), NOW())
             RETURNING id, community_id, name, topic, channel_type as "channel_type: ChannelType",
-                      position, created_at, updated_at
+                      position, parent_id, retention_seconds, created_at, updated_at
             "#,
             Uuid::new_v4(),
             community_id,
             input.name,
             input.topic,
             input.channel_type as ChannelType,
-            position
+            position,
+            input.parent_id
         )
         .fetch_one(&self.db)
         .await?;
@@ -51,7 +62,7 @@ impl ChannelService {
             Channel,
             r#"
             SELECT id, community_id, name, topic, channel_type as "channel_type: ChannelType",
-                   position, created_at, updated_at
+                   position, parent_id, retention_seconds, created_at, updated_at
             FROM channels WHERE id = $1
             "#,
             id
@@ -63,12 +74,80 @@ impl ChannelService {
         Ok(channel)
     }
 
+    /// Check whether a user can read and post in a channel
+    /// (community member for community channels, participant for DMs and group DMs)
+    pub async fn user_has_access(&self, channel_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let has_access = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM channels c
+                WHERE c.id = $1 AND (
+                    (c.community_id IS NOT NULL AND EXISTS (
+                        SELECT 1 FROM community_members cm
+                        WHERE cm.community_id = c.community_id AND cm.user_id = $2
+                    ))
+                    OR EXISTS (
+                        SELECT 1 FROM direct_message_channels dm
+                        WHERE dm.channel_id = c.id AND (dm.user1_id = $2 OR dm.user2_id = $2)
+                    )
+                    OR EXISTS (
+                        SELECT 1 FROM group_dm_channels g
+                        JOIN group_dm_members gm ON gm.group_dm_id = g.id
+                        WHERE g.channel_id = c.id AND gm.user_id = $2
+                    )
+                )
+            )
+            "#,
+            channel_id,
+            user_id
+        )
+        .fetch_one(&self.db)
+        .await?
+        .unwrap_or(false);
+
+        Ok(has_access)
+    }
+
+    /// Which of the given channels the user can access, as `user_has_access` decides
+    pub async fn accessible_channel_ids(&self, channel_ids: &[Uuid], user_id: Uuid) -> Result<HashSet<Uuid>> {
+        if channel_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let ids = sqlx::query_scalar!(
+            r#"
+            SELECT c.id FROM channels c
+            WHERE c.id = ANY($1) AND (
+                (c.community_id IS NOT NULL AND EXISTS (
+                    SELECT 1 FROM community_members cm
+                    WHERE cm.community_id = c.community_id AND cm.user_id = $2
+                ))
+                OR EXISTS (
+                    SELECT 1 FROM direct_message_channels dm
+                    WHERE dm.channel_id = c.id AND (dm.user1_id = $2 OR dm.user2_id = $2)
+                )
+                OR EXISTS (
+                    SELECT 1 FROM group_dm_channels g
+                    JOIN group_dm_members gm ON gm.group_dm_id = g.id
+                    WHERE g.channel_id = c.id AND gm.user_id = $2
+                )
+            )
+            "#,
+            channel_ids,
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(ids.into_iter().collect())
+    }
+
     pub async fn list_by_community(&self, community_id: Uuid) -> Result<Vec<Channel>> {
         let channels = sqlx::query_as!(
             Channel,
             r#"
             SELECT id, community_id, name, topic, channel_type as "channel_type: ChannelType",
-                   position, created_at, updated_at
+                   position, parent_id, retention_seconds, created_at, updated_at
             FROM channels WHERE community_id = $1
             ORDER BY position
             "#,
@@ -80,6 +159,56 @@ impl ChannelService {
         Ok(channels)
     }
 
+    /// Check that a channel is a category in the given community
+    async fn require_category(&self, community_id: Uuid, category_id: Uuid) -> Result<()> {
+        let is_category = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM channels
+                WHERE id = $1 AND community_id = $2 AND channel_type = 'category'
+            )
+            "#,
+            category_id,
+            community_id
+        )
+        .fetch_one(&self.db)
+        .await?
+        .unwrap_or(false);
+
+        if !is_category {
+            return Err(AppError::BadRequest(
+                "Parent must be a category in the same community".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Atomically update the positions and categories of a community's channels.
+    /// Channels not listed keep their current position and category.
+    pub async fn reorder(&self, community_id: Uuid, positions: &[ChannelPosition]) -> Result<Vec<Channel>> {
+        let channels = self.list_by_community(community_id).await?;
+        let types: HashMap<Uuid, ChannelType> =
+            channels.into_iter().map(|c| (c.id, c.channel_type)).collect();
+
+        validate_positions(&types, positions)?;
+
+        let mut tx = self.db.begin().await?;
+        for entry in positions {
+            sqlx::query!(
+                "UPDATE channels SET position = $2, parent_id = $3, updated_at = NOW() WHERE id = $1",
+                entry.id,
+                entry.position,
+                entry.parent_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        self.list_by_community(community_id).await
+    }
+
     pub async fn update(&self, id: Uuid, input: UpdateChannel) -> Result<Channel> {
         let channel = sqlx::query_as!(
             Channel,
@@ -90,7 +219,7 @@ impl ChannelService {
                 updated_at = NOW()
             WHERE id = $1
             RETURNING id, community_id, name, topic, channel_type as "channel_type: ChannelType",
-                      position, created_at, updated_at
+                      position, parent_id, retention_seconds, created_at, updated_at
             "#,
             id,
             input.name,
@@ -103,6 +232,27 @@ impl ChannelService {
         Ok(channel)
     }
 
+    /// Set how long messages in a channel are kept (None keeps them forever)
+    pub async fn set_retention(&self, id: Uuid, retention_seconds: Option<i32>) -> Result<Channel> {
+        let channel = sqlx::query_as!(
+            Channel,
+            r#"
+            UPDATE channels
+            SET retention_seconds = $2, updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, community_id, name, topic, channel_type as "channel_type: ChannelType",
+                      position, parent_id, retention_seconds, created_at, updated_at
+            "#,
+            id,
+            retention_seconds
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Channel not found".to_string()))?;
+
+        Ok(channel)
+    }
+
     pub async fn delete(&self, id: Uuid) -> Result<()> {
         let result = sqlx::query!("DELETE FROM channels WHERE id = $1", id)
             .execute(&self.db)
@@ -215,7 +365,7 @@ impl ChannelService {
             Channel,
             r#"
             SELECT c.id, c.community_id, c.name, c.topic, c.channel_type as "channel_type: ChannelType",
-                   c.position, c.created_at, c.updated_at
+                   c.position, c.parent_id, c.retention_seconds, c.created_at, c.updated_at
             FROM channels c
             INNER JOIN direct_message_channels dm ON c.id = dm.channel_id
             WHERE (dm.user1_id = $1 AND dm.user2_id = $2) OR (dm.user1_id = $2 AND dm.user2_id = $1)
@@ -238,7 +388,7 @@ impl ChannelService {
             INSERT INTO channels (id, community_id, name, topic, channel_type, position, created_at, updated_at)
             VALUES ($1, NULL, 'Direct Message', NULL, $2, 0, NOW(), NOW())
             RETURNING id, community_id, name, topic, channel_type as "channel_type: ChannelType",
-                      position, created_at, updated_at
+                      position, parent_id, retention_seconds, created_at, updated_at
             "#,
             channel_id,
             ChannelType::DirectMessage as ChannelType
@@ -267,7 +417,7 @@ impl ChannelService {
             Channel,
             r#"
             SELECT c.id, c.community_id, c.name, c.topic, c.channel_type as "channel_type: ChannelType",
-                   c.position, c.created_at, c.updated_at
+                   c.position, c.parent_id, c.retention_seconds, c.created_at, c.updated_at
             FROM channels c
             INNER JOIN direct_message_channels dm ON c.id = dm.channel_id
             WHERE dm.user1_id = $1 OR dm.user2_id = $1
@@ -283,22 +433,136 @@ impl ChannelService {
 
     // Channel read state operations
 
-    /// Mark a channel as read for a user (upsert last_read_at to now)
-    pub async fn mark_channel_read(&self, channel_id: Uuid, user_id: Uuid) -> Result<()> {
+    /// Mark a channel as read up to a message (the newest message when `message_id` is None).
+    /// The read position never moves backwards; returns the resulting read state.
+    pub async fn mark_channel_read(
+        &self,
+        channel_id: Uuid,
+        user_id: Uuid,
+        message_id: Option<Uuid>,
+    ) -> Result<ChannelReadState> {
+        let target = if let Some(message_id) = message_id {
+            // Thread replies aren't part of the channel history, so reading one counts as
+            // reading up to its thread's parent
+            let message = sqlx::query!(
+                r#"
+                SELECT COALESCE(p.id, m.id) AS "id!", COALESCE(p.created_at, m.created_at) AS "created_at!"
+                FROM messages m
+                LEFT JOIN messages p ON p.id = m.thread_parent_id
+                WHERE m.id = $1 AND m.channel_id = $2
+                "#,
+                message_id,
+                channel_id
+            )
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
+            Some((message.id, message.created_at))
+        } else {
+            sqlx::query!(
+                r#"
+                SELECT id, created_at FROM messages
+                WHERE channel_id = $1 AND thread_parent_id IS NULL
+                ORDER BY created_at DESC
+                LIMIT 1
+                "#,
+                channel_id
+            )
+            .fetch_optional(&self.db)
+            .await?
+            .map(|m| (m.id, m.created_at))
+        };
+
+        let (last_read_message_id, last_read_at) = match target {
+            Some((id, created_at)) => (Some(id), created_at),
+            None => (None, Utc::now()),
+        };
+
         sqlx::query!(
             r#"
-            INSERT INTO channel_read_states (user_id, channel_id, last_read_at)
-            VALUES ($1, $2, NOW())
+            INSERT INTO channel_read_states (user_id, channel_id, last_read_message_id, last_read_at)
+            VALUES ($1, $2, $3, $4)
             ON CONFLICT (user_id, channel_id)
-            DO UPDATE SET last_read_at = NOW()
+            DO UPDATE SET last_read_message_id = EXCLUDED.last_read_message_id,
+                          last_read_at = EXCLUDED.last_read_at
+            WHERE channel_read_states.last_read_at <= EXCLUDED.last_read_at
             "#,
             user_id,
-            channel_id
+            channel_id,
+            last_read_message_id,
+            last_read_at
         )
         .execute(&self.db)
         .await?;
 
-        Ok(())
+        let read_state = sqlx::query_as!(
+            ChannelReadState,
+            r#"
+            SELECT user_id, channel_id, last_read_message_id, last_read_at
+            FROM channel_read_states
+            WHERE user_id = $1 AND channel_id = $2
+            "#,
+            user_id,
+            channel_id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(read_state)
+    }
+
+    /// Get the users taking part in a DM or group DM channel
+    pub async fn get_dm_participants(&self, channel_id: Uuid) -> Result<Vec<Uuid>> {
+        let participants = sqlx::query_scalar!(
+            r#"
+            SELECT user1_id as "user_id!" FROM direct_message_channels WHERE channel_id = $1
+            UNION
+            SELECT user2_id as "user_id!" FROM direct_message_channels WHERE channel_id = $1
+            UNION
+            SELECT gm.user_id as "user_id!" FROM group_dm_members gm
+            INNER JOIN group_dm_channels g ON g.id = gm.group_dm_id
+            WHERE g.channel_id = $1
+            "#,
+            channel_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(participants)
+    }
+
+    /// Get the DM or group DM participants who share read receipts, and so may see others'
+    pub async fn get_read_receipt_participants(&self, channel_id: Uuid) -> Result<Vec<Uuid>> {
+        let participants = self.get_dm_participants(channel_id).await?;
+
+        let sharing = sqlx::query_scalar!(
+            "SELECT id FROM users WHERE id = ANY($1) AND read_receipts_enabled",
+            &participants
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(sharing)
+    }
+
+    /// Get read states of users in a channel who share read receipts
+    pub async fn get_read_receipts(&self, channel_id: Uuid) -> Result<Vec<ChannelReadState>> {
+        let receipts = sqlx::query_as!(
+            ChannelReadState,
+            r#"
+            SELECT crs.user_id, crs.channel_id, crs.last_read_message_id, crs.last_read_at
+            FROM channel_read_states crs
+            INNER JOIN users u ON u.id = crs.user_id
+            WHERE crs.channel_id = $1
+              AND crs.last_read_message_id IS NOT NULL
+              AND u.read_receipts_enabled
+            "#,
+            channel_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(receipts)
     }
 
     /// Get the last read timestamp for a user in a channel
@@ -383,3 +647,71 @@ impl ChannelService {
         Ok(counts)
     }
 }
+
+/// Check a reorder request against the community's channels (ID -> type): each channel
+/// listed once, all in the community, and only non-category channels placed in categories
+fn validate_positions(types: &HashMap<Uuid, ChannelType>, positions: &[ChannelPosition]) -> Result<()> {
+    let mut seen = HashSet::new();
+    for entry in positions {
+        if !seen.insert(entry.id) {
+            return Err(AppError::BadRequest(format!("Channel {} is listed twice", entry.id)));
+        }
+        let Some(channel_type) = types.get(&entry.id) else {
+            return Err(AppError::BadRequest(format!(
+                "Channel {} is not in this community",
+                entry.id
+            )));
+        };
+        if let Some(parent_id) = entry.parent_id {
+            if *channel_type == ChannelType::Category {
+                return Err(AppError::BadRequest("Categories cannot be nested".to_string()));
+            }
+            if types.get(&parent_id) != Some(&ChannelType::Category) {
+                return Err(AppError::BadRequest(
+                    "Parent must be a category in the same community".to_string(),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: Uuid, parent_id: Option<Uuid>) -> ChannelPosition {
+        ChannelPosition { id, position: 0, parent_id }
+    }
+
+    #[test]
+    fn reorder_positions() {
+        let text = Uuid::new_v4();
+        let voice = Uuid::new_v4();
+        let category = Uuid::new_v4();
+        let other_category = Uuid::new_v4();
+        let elsewhere = Uuid::new_v4();
+        let types = HashMap::from([
+            (text, ChannelType::Text),
+            (voice, ChannelType::Voice),
+            (category, ChannelType::Category),
+            (other_category, ChannelType::Category),
+        ]);
+
+        let cases = [
+            ("empty", vec![], true),
+            ("top level", vec![entry(text, None), entry(category, None)], true),
+            ("into a category", vec![entry(text, Some(category)), entry(voice, Some(category))], true),
+            ("listed twice", vec![entry(text, None), entry(text, Some(category))], false),
+            ("other community", vec![entry(elsewhere, None)], false),
+            ("nested category", vec![entry(other_category, Some(category))], false),
+            ("parent is not a category", vec![entry(voice, Some(text))], false),
+            ("parent in other community", vec![entry(text, Some(elsewhere))], false),
+        ];
+
+        for (case, positions, valid) in cases {
+            assert_eq!(validate_positions(&types, &positions).is_ok(), valid, "{case}");
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use crate::error::{AppError, Result};
+use crate::models::CommunityEmoji;
+use image::imageops::FilterType;
+use image::io::{Limits, Reader as ImageReader};
+use image::{GenericImageView, ImageFormat, ImageOutputFormat};
+use sqlx::PgPool;
+use std::io::Cursor;
+use uuid::Uuid;
+
+/// Maximum size of an uploaded emoji image: 512 KB
+pub const MAX_EMOJI_UPLOAD_SIZE: usize = 512 * 1024;
+
+/// Emoji images are scaled down to fit within this many pixels
+const EMOJI_SIZE: u32 = 128;
+
+/// Images with more pixels than this on a side are refused before decoding
+const MAX_SOURCE_DIMENSION: u32 = 4096;
+
+/// Most memory decoding an emoji image may take: 64 MB
+const MAX_DECODE_ALLOC: u64 = 64 * 1024 * 1024;
+
+/// Maximum number of custom emoji per community
+const MAX_EMOJIS_PER_COMMUNITY: i64 = 100;
+
+/// Validate an emoji name (2-32 characters, letters, digits and underscores)
+pub fn validate_emoji_name(name: &str) -> Result<()> {
+    if name.len() < 2 || name.len() > 32 {
+        return Err(AppError::BadRequest(
+            "Emoji name must be between 2 and 32 characters".to_string(),
+        ));
+    }
+
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(AppError::BadRequest(
+            "Emoji name may only contain letters, digits and underscores".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Whether a reaction key refers to a custom emoji: `<:name:id>`, or the
+/// older `:name:` form that no longer identifies a single emoji
+pub fn is_custom_emoji_key(emoji: &str) -> bool {
+    (emoji.starts_with("<:") && emoji.ends_with('>'))
+        || (emoji.len() > 2 && emoji.starts_with(':') && emoji.ends_with(':'))
+}
+
+/// Extract the name and id from a `<:name:id>` custom emoji key, if the key is one
+pub fn parse_custom_emoji(emoji: &str) -> Option<(&str, Uuid)> {
+    let (name, id) = miscord_protocol::parse_custom_emoji_key(emoji)?;
+    validate_emoji_name(name).ok().map(|_| (name, id))
+}
+
+/// Decode an uploaded image and scale it down to emoji size.
+/// Returns the encoded bytes and file extension.
+/// Small GIFs are kept as-is so animation is preserved; everything else is re-encoded as PNG.
+pub fn process_emoji_image(data: &[u8]) -> Result<(Vec<u8>, &'static str)> {
+    if data.len() > MAX_EMOJI_UPLOAD_SIZE {
+        return Err(AppError::BadRequest(format!(
+            "Emoji image too large. Maximum size is {} KB",
+            MAX_EMOJI_UPLOAD_SIZE / 1024
+        )));
+    }
+
+    let format = image::guess_format(data)
+        .map_err(|_| AppError::BadRequest("Unsupported emoji image format".to_string()))?;
+
+    // A small file can decode to a huge image, so its size is checked first
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_DIMENSION);
+    limits.max_image_height = Some(MAX_SOURCE_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODE_ALLOC);
+    let mut reader = ImageReader::with_format(Cursor::new(data), format);
+    reader.limits(limits);
+    let img = reader
+        .decode()
+        .map_err(|e| AppError::BadRequest(format!("Failed to decode emoji image: {}", e)))?;
+
+    let (width, height) = img.dimensions();
+    if format == ImageFormat::Gif && width <= EMOJI_SIZE && height <= EMOJI_SIZE {
+        return Ok((data.to_vec(), "gif"));
+    }
+
+    let img = if width > EMOJI_SIZE || height > EMOJI_SIZE {
+        img.resize(EMOJI_SIZE, EMOJI_SIZE, FilterType::Lanczos3)
+    } else {
+        img
+    };
+
+    let mut output = Vec::new();
+    img.write_to(&mut Cursor::new(&mut output), ImageOutputFormat::Png)
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to encode emoji image: {}", e)))?;
+
+    Ok((output, "png"))
+}
+
+#[derive(Clone)]
+pub struct EmojiService {
+    db: PgPool,
+}
+
+impl EmojiService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Check that a community can take another emoji with this name
+    pub async fn check_can_create(&self, community_id: Uuid, name: &str) -> Result<()> {
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM community_emojis WHERE community_id = $1",
+            community_id
+        )
+        .fetch_one(&self.db)
+        .await?
+        .unwrap_or(0);
+
+        if count >= MAX_EMOJIS_PER_COMMUNITY {
+            return Err(AppError::BadRequest(format!(
+                "Communities can have at most {} custom emoji",
+                MAX_EMOJIS_PER_COMMUNITY
+            )));
+        }
+
+        self.check_name_available(community_id, name).await
+    }
+
+    /// Check that no other emoji in the community uses this name
+    async fn check_name_available(&self, community_id: Uuid, name: &str) -> Result<()> {
+        let exists = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM community_emojis WHERE community_id = $1 AND name = $2)",
+            community_id,
+            name
+        )
+        .fetch_one(&self.db)
+        .await?
+        .unwrap_or(false);
+
+        if exists {
+            return Err(AppError::Conflict(format!(
+                "An emoji named :{}: already exists",
+                name
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub async fn create(
+        &self,
+        community_id: Uuid,
+        name: &str,
+        attachment_id: Uuid,
+        created_by: Uuid,
+    ) -> Result<CommunityEmoji> {
+        let id = Uuid::new_v4();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO community_emojis (id, community_id, name, attachment_id, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, NOW())
+            "#,
+            id,
+            community_id,
+            name,
+            attachment_id,
+            created_by
+        )
+        .execute(&self.db)
+        .await?;
+
+        self.get_by_id(id).await
+    }
+
+    pub async fn get_by_id(&self, id: Uuid) -> Result<CommunityEmoji> {
+        let emoji = sqlx::query_as!(
+            CommunityEmoji,
+            r#"
+            SELECT e.id, e.community_id, e.name, e.attachment_id, a.url as "url!",
+                   e.created_by, e.created_at
+            FROM community_emojis e
+            INNER JOIN message_attachments a ON a.id = e.attachment_id
+            WHERE e.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Emoji not found".to_string()))?;
+
+        Ok(emoji)
+    }
+
+    pub async fn list_by_community(&self, community_id: Uuid) -> Result<Vec<CommunityEmoji>> {
+        let emojis = sqlx::query_as!(
+            CommunityEmoji,
+            r#"
+            SELECT e.id, e.community_id, e.name, e.attachment_id, a.url as "url!",
+                   e.created_by, e.created_at
+            FROM community_emojis e
+            INNER JOIN message_attachments a ON a.id = e.attachment_id
+            WHERE e.community_id = $1
+            ORDER BY e.name ASC
+            "#,
+            community_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(emojis)
+    }
+
+    pub async fn rename(&self, id: Uuid, name: &str) -> Result<CommunityEmoji> {
+        let emoji = self.get_by_id(id).await?;
+
+        if emoji.name == name {
+            return Ok(emoji);
+        }
+
+        self.check_name_available(emoji.community_id, name).await?;
+
+        sqlx::query!(
+            "UPDATE community_emojis SET name = $2 WHERE id = $1",
+            id,
+            name
+        )
+        .execute(&self.db)
+        .await?;
+
+        self.get_by_id(id).await
+    }
+
+    /// Check whether an emoji with this id and name exists in one of the user's communities
+    pub async fn is_available_to_user(&self, user_id: Uuid, id: Uuid, name: &str) -> Result<bool> {
+        let exists = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM community_emojis e
+                INNER JOIN community_members m ON m.community_id = e.community_id
+                WHERE m.user_id = $1 AND e.id = $2 AND e.name = $3
+            )
+            "#,
+            user_id,
+            id,
+            name
+        )
+        .fetch_one(&self.db)
+        .await?
+        .unwrap_or(false);
+
+        Ok(exists)
+    }
+
+    /// Check whether an attachment is the image of a custom emoji
+    pub async fn is_emoji_attachment(&self, attachment_id: Uuid) -> Result<bool> {
+        let exists = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM community_emojis WHERE attachment_id = $1)",
+            attachment_id
+        )
+        .fetch_one(&self.db)
+        .await?
+        .unwrap_or(false);
+
+        Ok(exists)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, RgbaImage};
+
+    fn encode(img: &DynamicImage, format: ImageOutputFormat) -> Vec<u8> {
+        let mut data = Vec::new();
+        img.write_to(&mut Cursor::new(&mut data), format).unwrap();
+        data
+    }
+
+    #[test]
+    fn emoji_names() {
+        for name in ["ok", "party_parrot", "abc123", &"a".repeat(32)] {
+            assert!(validate_emoji_name(name).is_ok(), "{} should be valid", name);
+        }
+        for name in ["a", "", &"a".repeat(33), "has space", "dash-ed", "émoji", ":colon:"] {
+            assert!(validate_emoji_name(name).is_err(), "{} should be invalid", name);
+        }
+    }
+
+    #[test]
+    fn custom_emoji_keys() {
+        let id = Uuid::new_v4();
+        let key = format!("<:party_parrot:{}>", id);
+        assert_eq!(parse_custom_emoji(&key), Some(("party_parrot", id)));
+        assert!(is_custom_emoji_key(&key));
+
+        assert_eq!(parse_custom_emoji(&format!("<:a:{}>", id)), None);
+        assert_eq!(parse_custom_emoji(&format!("<:dash-ed:{}>", id)), None);
+        assert_eq!(parse_custom_emoji(":party_parrot:"), None);
+        assert!(is_custom_emoji_key(":party_parrot:"));
+
+        for emoji in ["👍", "party_parrot", ":", "::"] {
+            assert_eq!(parse_custom_emoji(emoji), None, "{}", emoji);
+            assert!(!is_custom_emoji_key(emoji), "{}", emoji);
+        }
+    }
+
+    #[test]
+    fn large_images_are_scaled_down_to_png() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(512, 256));
+        let (output, extension) = process_emoji_image(&encode(&img, ImageOutputFormat::Png)).unwrap();
+
+        assert_eq!(extension, "png");
+        let scaled = image::load_from_memory(&output).unwrap();
+        assert_eq!(scaled.dimensions(), (EMOJI_SIZE, EMOJI_SIZE / 2));
+    }
+
+    #[test]
+    fn small_images_keep_their_size() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(32, 32));
+        let (output, extension) =
+            process_emoji_image(&encode(&img, ImageOutputFormat::Jpeg(90))).unwrap();
+
+        assert_eq!(extension, "png");
+        assert_eq!(image::load_from_memory(&output).unwrap().dimensions(), (32, 32));
+    }
+
+    #[test]
+    fn small_gifs_are_kept_as_is() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(64, 64));
+        let data = encode(&img, ImageOutputFormat::Gif);
+        let (output, extension) = process_emoji_image(&data).unwrap();
+
+        assert_eq!(extension, "gif");
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    fn oversized_images_are_refused_before_decoding() {
+        // Compresses to almost nothing, but is wider than any emoji source may be
+        let img = DynamicImage::ImageLuma8(image::GrayImage::new(MAX_SOURCE_DIMENSION + 1, 1));
+        let data = encode(&img, ImageOutputFormat::Png);
+        assert!(data.len() < MAX_EMOJI_UPLOAD_SIZE);
+
+        assert!(process_emoji_image(&data).is_err());
+    }
+
+    #[test]
+    fn non_images_are_refused() {
+        assert!(process_emoji_image(b"not an image").is_err());
+        assert!(process_emoji_image(&vec![0; MAX_EMOJI_UPLOAD_SIZE + 1]).is_err());
+    }
+}
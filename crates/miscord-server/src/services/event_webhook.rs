@@ -0,0 +1,271 @@
+use crate::error::{AppError, Result};
+use crate::models::{EventWebhook, WebhookDelivery, WebhookDeliveryStatus};
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const SECRET_LENGTH: usize = 64;
+
+/// How long a claimed delivery is held before another attempt may pick it up, in seconds
+const CLAIM_LEASE_SECS: f64 = 60.0;
+
+/// Finished deliveries are kept in the log this long
+const DELIVERY_LOG_DAYS: i32 = 7;
+
+fn generate_secret() -> String {
+    rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(SECRET_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+#[derive(Clone)]
+pub struct EventWebhookService {
+    db: PgPool,
+}
+
+impl EventWebhookService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(
+        &self,
+        community_id: Uuid,
+        url: &str,
+        events: &[String],
+        created_by_id: Uuid,
+    ) -> Result<EventWebhook> {
+        let webhook = sqlx::query_as!(
+            EventWebhook,
+            r#"
+            INSERT INTO event_webhooks (id, community_id, url, events, secret, enabled, created_by_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, TRUE, $6, NOW())
+            RETURNING id, community_id, url, events, secret, enabled, created_by_id, created_at
+            "#,
+            Uuid::new_v4(),
+            community_id,
+            url,
+            events,
+            generate_secret(),
+            created_by_id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(webhook)
+    }
+
+    pub async fn get_by_id(&self, id: Uuid) -> Result<EventWebhook> {
+        sqlx::query_as!(
+            EventWebhook,
+            r#"
+            SELECT id, community_id, url, events, secret, enabled, created_by_id, created_at
+            FROM event_webhooks WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook not found".to_string()))
+    }
+
+    pub async fn list_by_community(&self, community_id: Uuid) -> Result<Vec<EventWebhook>> {
+        let webhooks = sqlx::query_as!(
+            EventWebhook,
+            r#"
+            SELECT id, community_id, url, events, secret, enabled, created_by_id, created_at
+            FROM event_webhooks WHERE community_id = $1
+            ORDER BY created_at
+            "#,
+            community_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(webhooks)
+    }
+
+    pub async fn update(
+        &self,
+        id: Uuid,
+        url: Option<&str>,
+        events: Option<&[String]>,
+        enabled: Option<bool>,
+    ) -> Result<EventWebhook> {
+        sqlx::query_as!(
+            EventWebhook,
+            r#"
+            UPDATE event_webhooks
+            SET url = COALESCE($2, url),
+                events = COALESCE($3, events),
+                enabled = COALESCE($4, enabled)
+            WHERE id = $1
+            RETURNING id, community_id, url, events, secret, enabled, created_by_id, created_at
+            "#,
+            id,
+            url,
+            events,
+            enabled
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook not found".to_string()))
+    }
+
+    /// Replace a webhook's signing secret
+    pub async fn regenerate_secret(&self, id: Uuid) -> Result<EventWebhook> {
+        sqlx::query_as!(
+            EventWebhook,
+            r#"
+            UPDATE event_webhooks SET secret = $2
+            WHERE id = $1
+            RETURNING id, community_id, url, events, secret, enabled, created_by_id, created_at
+            "#,
+            id,
+            generate_secret()
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook not found".to_string()))
+    }
+
+    pub async fn delete(&self, id: Uuid) -> Result<()> {
+        sqlx::query!("DELETE FROM event_webhooks WHERE id = $1", id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Queue an event for every enabled webhook of the community that subscribes to it
+    pub async fn enqueue(&self, community_id: Uuid, event: &str, payload: &serde_json::Value) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO webhook_deliveries (id, webhook_id, event, payload, status, next_attempt_at, created_at)
+            SELECT gen_random_uuid(), id, $2::text, $3, 'pending', NOW(), NOW()
+            FROM event_webhooks
+            WHERE community_id = $1 AND enabled AND $2::text = ANY(events)
+            "#,
+            community_id,
+            event,
+            Json(payload) as _
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Take deliveries whose next attempt is due. They're held for a lease period so a
+    /// delivery interrupted by a restart is retried rather than lost.
+    pub async fn claim_due(&self, limit: i64) -> Result<Vec<WebhookDelivery>> {
+        let deliveries = sqlx::query_as!(
+            WebhookDelivery,
+            r#"
+            UPDATE webhook_deliveries SET next_attempt_at = NOW() + make_interval(secs => $2)
+            WHERE id IN (
+                SELECT id FROM webhook_deliveries
+                WHERE status = 'pending' AND next_attempt_at <= NOW()
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, webhook_id, event, payload as "payload: Json<serde_json::Value>",
+                      status as "status: WebhookDeliveryStatus", attempts, next_attempt_at,
+                      response_status, error, created_at, completed_at
+            "#,
+            limit,
+            CLAIM_LEASE_SECS
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    pub async fn mark_succeeded(&self, id: Uuid, response_status: i32) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'succeeded', attempts = attempts + 1, response_status = $2,
+                error = NULL, completed_at = NOW()
+            WHERE id = $1
+            "#,
+            id,
+            response_status
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a failed attempt; without a retry time the delivery is given up
+    pub async fn mark_attempt_failed(
+        &self,
+        id: Uuid,
+        response_status: Option<i32>,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE webhook_deliveries
+            SET attempts = attempts + 1, response_status = $2, error = $3,
+                status = CASE WHEN $4::timestamptz IS NULL THEN 'failed' ELSE 'pending' END::webhook_delivery_status,
+                next_attempt_at = COALESCE($4, next_attempt_at),
+                completed_at = CASE WHEN $4::timestamptz IS NULL THEN NOW() END
+            WHERE id = $1
+            "#,
+            id,
+            response_status,
+            error,
+            retry_at
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// A webhook's most recent deliveries, newest first
+    pub async fn list_deliveries(&self, webhook_id: Uuid, limit: i64) -> Result<Vec<WebhookDelivery>> {
+        let deliveries = sqlx::query_as!(
+            WebhookDelivery,
+            r#"
+            SELECT id, webhook_id, event, payload as "payload: Json<serde_json::Value>",
+                   status as "status: WebhookDeliveryStatus", attempts, next_attempt_at,
+                   response_status, error, created_at, completed_at
+            FROM webhook_deliveries
+            WHERE webhook_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+            webhook_id,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    /// Drop finished deliveries that have aged out of the log
+    pub async fn purge_old_deliveries(&self) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM webhook_deliveries
+            WHERE status <> 'pending' AND created_at < NOW() - make_interval(days => $1)
+            "#,
+            DELIVERY_LOG_DAYS
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
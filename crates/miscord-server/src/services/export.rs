@@ -0,0 +1,315 @@
+use crate::error::{AppError, Result};
+use crate::models::{Export, ExportFormat, ExportStatus, Message};
+use crate::storage::{ByteStream, DownloadHeaders, StorageBackend};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::fs;
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+
+/// Finished archives are moved into storage in parts of this size. S3 needs every part
+/// but the last to be at least 5 MB.
+const UPLOAD_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Storage key of an export's archive. Exports have their own prefix, so the sweep for
+/// unreferenced attachment files never sees them.
+fn archive_key(id: Uuid) -> String {
+    format!("exports/{}.zip", id)
+}
+
+#[derive(Clone)]
+pub struct ExportService {
+    db: PgPool,
+    storage: Arc<dyn StorageBackend>,
+    /// Where archives are built before they're stored
+    work_dir: PathBuf,
+}
+
+impl ExportService {
+    pub fn new(db: PgPool, storage: Arc<dyn StorageBackend>, work_dir: PathBuf) -> Self {
+        Self { db, storage, work_dir }
+    }
+
+    /// Ensure the directory archives are built in exists
+    pub async fn ensure_work_dir(&self) -> Result<()> {
+        fs::create_dir_all(&self.work_dir).await.map_err(|e| {
+            AppError::Internal(anyhow::anyhow!("Failed to create export directory: {}", e))
+        })?;
+        Ok(())
+    }
+
+    /// Path an export's zip file is built at
+    pub fn work_path(&self, id: Uuid) -> PathBuf {
+        self.work_dir.join(format!("{}.zip", id))
+    }
+
+    /// Move a built archive into storage. It's uploaded in parts, so it never has to fit
+    /// in memory.
+    pub async fn store_archive(&self, id: Uuid, path: &Path) -> Result<()> {
+        let read_error =
+            |e: std::io::Error| AppError::Internal(anyhow::anyhow!("Failed to read export: {}", e));
+        let key = archive_key(id);
+        let mut file = fs::File::open(path).await.map_err(read_error)?;
+
+        let upload_id = self.storage.create_multipart(&key, "application/zip").await?;
+        let result = async {
+            let mut part_tags = Vec::new();
+            loop {
+                let mut part = Vec::new();
+                (&mut file)
+                    .take(UPLOAD_PART_SIZE)
+                    .read_to_end(&mut part)
+                    .await
+                    .map_err(read_error)?;
+                if part.is_empty() {
+                    break;
+                }
+                let part_number = part_tags.len() as i32 + 1;
+                part_tags.push(self.storage.upload_part(&key, &upload_id, part_number, &part).await?);
+            }
+            self.storage.complete_multipart(&key, &upload_id, &part_tags).await
+        }
+        .await;
+
+        if result.is_err() {
+            if let Err(e) = self.storage.abort_multipart(&key, &upload_id).await {
+                tracing::warn!("Failed to abort export upload {}: {}", id, e);
+            }
+        }
+
+        // A failed export isn't retried, so the built archive goes either way
+        if let Err(e) = fs::remove_file(path).await {
+            tracing::warn!("Failed to remove built export {:?}: {}", path, e);
+        }
+        result
+    }
+
+    /// Read a finished export's archive from storage
+    pub async fn open_archive(&self, id: Uuid) -> Result<ByteStream> {
+        self.storage.get_stream(&archive_key(id)).await
+    }
+
+    /// A link the archive can be downloaded from directly, when the storage backend offers one
+    pub fn download_url(&self, id: Uuid, content_disposition: &str) -> Option<String> {
+        self.storage.presigned_url(
+            &archive_key(id),
+            &DownloadHeaders {
+                content_type: "application/zip",
+                content_disposition,
+            },
+        )
+    }
+
+    /// Delete exports that finished more than `max_age` ago, and their archives.
+    /// Returns how many were deleted.
+    pub async fn purge_expired(&self, max_age: Duration) -> Result<usize> {
+        let ids = sqlx::query_scalar!(
+            "DELETE FROM exports WHERE completed_at < NOW() - make_interval(secs => $1::float8) RETURNING id",
+            max_age.as_secs_f64()
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        // Failed exports have no archive; deleting a missing one is not an error
+        for id in &ids {
+            if let Err(e) = self.storage.delete(&archive_key(*id)).await {
+                tracing::warn!("Failed to delete export archive {}: {}", id, e);
+            }
+        }
+
+        Ok(ids.len())
+    }
+
+    /// Queue an export for the background job
+    pub async fn create(
+        &self,
+        channel_id: Uuid,
+        thread_parent_id: Option<Uuid>,
+        user_id: Uuid,
+        format: ExportFormat,
+    ) -> Result<Export> {
+        let export = sqlx::query_as!(
+            Export,
+            r#"
+            INSERT INTO exports (id, channel_id, thread_parent_id, requested_by_id, format, status, created_at)
+            VALUES ($1, $2, $3, $4, $5, 'pending', NOW())
+            RETURNING id, channel_id, thread_parent_id, requested_by_id,
+                      format as "format: ExportFormat", status as "status: ExportStatus",
+                      exported_messages, total_messages, error, created_at, completed_at
+            "#,
+            Uuid::new_v4(),
+            channel_id,
+            thread_parent_id,
+            user_id,
+            format as ExportFormat
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(export)
+    }
+
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Export> {
+        let export = sqlx::query_as!(
+            Export,
+            r#"
+            SELECT id, channel_id, thread_parent_id, requested_by_id,
+                   format as "format: ExportFormat", status as "status: ExportStatus",
+                   exported_messages, total_messages, error, created_at, completed_at
+            FROM exports WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Export not found".to_string()))?;
+
+        Ok(export)
+    }
+
+    /// Mark the oldest pending export as running and return it
+    pub async fn claim_next(&self) -> Result<Option<Export>> {
+        let export = sqlx::query_as!(
+            Export,
+            r#"
+            UPDATE exports SET status = 'running'
+            WHERE id = (
+                SELECT id FROM exports WHERE status = 'pending'
+                ORDER BY created_at ASC
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, channel_id, thread_parent_id, requested_by_id,
+                      format as "format: ExportFormat", status as "status: ExportStatus",
+                      exported_messages, total_messages, error, created_at, completed_at
+            "#
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(export)
+    }
+
+    /// Put exports that were running when the server stopped back in the queue
+    pub async fn requeue_interrupted(&self) -> Result<u64> {
+        let result = sqlx::query!(
+            "UPDATE exports SET status = 'pending', exported_messages = 0 WHERE status = 'running'"
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn set_progress(&self, id: Uuid, exported_messages: i32, total_messages: i32) -> Result<Export> {
+        let export = sqlx::query_as!(
+            Export,
+            r#"
+            UPDATE exports SET exported_messages = $2, total_messages = $3
+            WHERE id = $1
+            RETURNING id, channel_id, thread_parent_id, requested_by_id,
+                      format as "format: ExportFormat", status as "status: ExportStatus",
+                      exported_messages, total_messages, error, created_at, completed_at
+            "#,
+            id,
+            exported_messages,
+            total_messages
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(export)
+    }
+
+    pub async fn complete(&self, id: Uuid) -> Result<Export> {
+        let export = sqlx::query_as!(
+            Export,
+            r#"
+            UPDATE exports SET status = 'completed', completed_at = NOW()
+            WHERE id = $1
+            RETURNING id, channel_id, thread_parent_id, requested_by_id,
+                      format as "format: ExportFormat", status as "status: ExportStatus",
+                      exported_messages, total_messages, error, created_at, completed_at
+            "#,
+            id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(export)
+    }
+
+    pub async fn fail(&self, id: Uuid, error: &str) -> Result<Export> {
+        let export = sqlx::query_as!(
+            Export,
+            r#"
+            UPDATE exports SET status = 'failed', error = $2, completed_at = NOW()
+            WHERE id = $1
+            RETURNING id, channel_id, thread_parent_id, requested_by_id,
+                      format as "format: ExportFormat", status as "status: ExportStatus",
+                      exported_messages, total_messages, error, created_at, completed_at
+            "#,
+            id,
+            error
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(export)
+    }
+
+    /// Number of messages an export will contain
+    pub async fn count_messages(&self, channel_id: Uuid, thread_parent_id: Option<Uuid>) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) FROM messages
+            WHERE channel_id = $1
+              AND ($2::uuid IS NULL OR id = $2 OR thread_parent_id = $2)
+            "#,
+            channel_id,
+            thread_parent_id
+        )
+        .fetch_one(&self.db)
+        .await?
+        .unwrap_or(0);
+
+        Ok(count)
+    }
+
+    /// Next page of an export's messages, oldest first, including thread replies.
+    /// `after` is the creation time and ID of the last message of the previous page.
+    pub async fn list_messages(
+        &self,
+        channel_id: Uuid,
+        thread_parent_id: Option<Uuid>,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<Message>> {
+        let (after_created_at, after_id) = after.unzip();
+
+        let messages = sqlx::query_as!(
+            Message,
+            r#"
+            SELECT id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, forwarded_from_message_id, forwarded_from_channel_id, forwarded_from_author_id, forwarded_from_created_at, created_at
+            FROM messages
+            WHERE channel_id = $1
+              AND ($2::uuid IS NULL OR id = $2 OR thread_parent_id = $2)
+              AND ($3::timestamptz IS NULL OR (created_at, id) > ($3, $4))
+            ORDER BY created_at ASC, id ASC
+            LIMIT $5
+            "#,
+            channel_id,
+            thread_parent_id,
+            after_created_at,
+            after_id,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(messages)
+    }
+}
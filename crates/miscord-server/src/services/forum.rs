@@ -0,0 +1,287 @@
+use crate::error::{AppError, Result};
+use crate::models::{ForumPost, ForumTag};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Maximum number of tags per forum channel
+const MAX_TAGS_PER_FORUM: i64 = 20;
+/// Maximum number of tags on a single post
+const MAX_TAGS_PER_POST: usize = 5;
+
+#[derive(Clone)]
+pub struct ForumService {
+    db: PgPool,
+}
+
+impl ForumService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    // Tags
+
+    pub async fn list_tags(&self, channel_id: Uuid) -> Result<Vec<ForumTag>> {
+        let tags = sqlx::query_as!(
+            ForumTag,
+            r#"
+            SELECT id, channel_id, name, position, created_at
+            FROM forum_tags WHERE channel_id = $1
+            ORDER BY position, name
+            "#,
+            channel_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(tags)
+    }
+
+    pub async fn get_tag(&self, id: Uuid) -> Result<ForumTag> {
+        let tag = sqlx::query_as!(
+            ForumTag,
+            "SELECT id, channel_id, name, position, created_at FROM forum_tags WHERE id = $1",
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Tag not found".to_string()))?;
+
+        Ok(tag)
+    }
+
+    pub async fn create_tag(&self, channel_id: Uuid, name: &str) -> Result<ForumTag> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > 32 {
+            return Err(AppError::BadRequest(
+                "Tag names must be 1-32 characters".to_string(),
+            ));
+        }
+
+        let stats = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "count!",
+                   COALESCE(BOOL_OR(LOWER(name) = LOWER($2)), false) as "exists!"
+            FROM forum_tags WHERE channel_id = $1
+            "#,
+            channel_id,
+            name
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        if stats.exists {
+            return Err(AppError::Conflict(format!("Tag '{}' already exists", name)));
+        }
+        if stats.count >= MAX_TAGS_PER_FORUM {
+            return Err(AppError::BadRequest(format!(
+                "A forum can have at most {} tags",
+                MAX_TAGS_PER_FORUM
+            )));
+        }
+
+        let tag = sqlx::query_as!(
+            ForumTag,
+            r#"
+            INSERT INTO forum_tags (id, channel_id, name, position, created_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            RETURNING id, channel_id, name, position, created_at
+            "#,
+            Uuid::new_v4(),
+            channel_id,
+            name,
+            stats.count as i32
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(tag)
+    }
+
+    /// Delete a tag; it is removed from every post that had it
+    pub async fn delete_tag(&self, id: Uuid) -> Result<()> {
+        sqlx::query!("DELETE FROM forum_tags WHERE id = $1", id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Check that tags exist in the forum and aren't too many
+    async fn validate_tags(&self, channel_id: Uuid, tag_ids: &[Uuid]) -> Result<()> {
+        if tag_ids.len() > MAX_TAGS_PER_POST {
+            return Err(AppError::BadRequest(format!(
+                "A post can have at most {} tags",
+                MAX_TAGS_PER_POST
+            )));
+        }
+
+        let known = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM forum_tags WHERE channel_id = $1 AND id = ANY($2)"#,
+            channel_id,
+            tag_ids
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        if known != tag_ids.len() as i64 {
+            return Err(AppError::BadRequest(
+                "Unknown tag for this forum".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Posts
+
+    /// Create a post: the thread's parent message, its title and tags
+    pub async fn create_post(
+        &self,
+        channel_id: Uuid,
+        author_id: Uuid,
+        title: &str,
+        content: &str,
+        tag_ids: &[Uuid],
+    ) -> Result<ForumPost> {
+        let title = title.trim();
+        if title.is_empty() || title.chars().count() > 100 {
+            return Err(AppError::BadRequest(
+                "Post titles must be 1-100 characters".to_string(),
+            ));
+        }
+
+        let mut tag_ids = tag_ids.to_vec();
+        tag_ids.sort();
+        tag_ids.dedup();
+        self.validate_tags(channel_id, &tag_ids).await?;
+
+        let message_id = Uuid::new_v4();
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO messages (id, channel_id, author_id, content, reply_to_id, thread_parent_id, reply_count, created_at)
+            VALUES ($1, $2, $3, $4, NULL, NULL, 0, NOW())
+            "#,
+            message_id,
+            channel_id,
+            author_id,
+            content
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO forum_posts (message_id, channel_id, title) VALUES ($1, $2, $3)",
+            message_id,
+            channel_id,
+            title
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO forum_post_tags (message_id, tag_id) SELECT $1, UNNEST($2::uuid[])",
+            message_id,
+            &tag_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!("UPDATE channels SET updated_at = NOW() WHERE id = $1", channel_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        self.get_post(message_id).await
+    }
+
+    pub async fn get_post(&self, message_id: Uuid) -> Result<ForumPost> {
+        let post = sqlx::query_as!(
+            ForumPost,
+            r#"
+            SELECT p.message_id, p.channel_id, p.title, m.author_id, u.display_name as author_name,
+                   m.content, m.reply_count, m.last_reply_at, m.created_at,
+                   COALESCE(ARRAY_AGG(pt.tag_id) FILTER (WHERE pt.tag_id IS NOT NULL), '{}') as "tag_ids!"
+            FROM forum_posts p
+            INNER JOIN messages m ON m.id = p.message_id
+            INNER JOIN users u ON u.id = m.author_id
+            LEFT JOIN forum_post_tags pt ON pt.message_id = p.message_id
+            WHERE p.message_id = $1
+            GROUP BY p.message_id, m.id, u.id
+            "#,
+            message_id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Post not found".to_string()))?;
+
+        Ok(post)
+    }
+
+    /// List posts in a forum, most recently active first.
+    /// With `tag_ids`, only posts that have any of the tags are listed.
+    pub async fn list_posts(
+        &self,
+        channel_id: Uuid,
+        tag_ids: &[Uuid],
+        limit: i64,
+    ) -> Result<Vec<ForumPost>> {
+        let posts = sqlx::query_as!(
+            ForumPost,
+            r#"
+            SELECT p.message_id, p.channel_id, p.title, m.author_id, u.display_name as author_name,
+                   m.content, m.reply_count, m.last_reply_at, m.created_at,
+                   COALESCE(ARRAY_AGG(pt.tag_id) FILTER (WHERE pt.tag_id IS NOT NULL), '{}') as "tag_ids!"
+            FROM forum_posts p
+            INNER JOIN messages m ON m.id = p.message_id
+            INNER JOIN users u ON u.id = m.author_id
+            LEFT JOIN forum_post_tags pt ON pt.message_id = p.message_id
+            WHERE p.channel_id = $1
+              AND (cardinality($2::uuid[]) = 0 OR EXISTS (
+                  SELECT 1 FROM forum_post_tags f
+                  WHERE f.message_id = p.message_id AND f.tag_id = ANY($2)
+              ))
+            GROUP BY p.message_id, m.id, u.id
+            ORDER BY COALESCE(m.last_reply_at, m.created_at) DESC
+            LIMIT $3
+            "#,
+            channel_id,
+            tag_ids,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(posts)
+    }
+
+    /// Replace the tags of a post
+    pub async fn set_post_tags(&self, message_id: Uuid, tag_ids: &[Uuid]) -> Result<ForumPost> {
+        let post = self.get_post(message_id).await?;
+
+        let mut tag_ids = tag_ids.to_vec();
+        tag_ids.sort();
+        tag_ids.dedup();
+        self.validate_tags(post.channel_id, &tag_ids).await?;
+
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query!("DELETE FROM forum_post_tags WHERE message_id = $1", message_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!(
+            "INSERT INTO forum_post_tags (message_id, tag_id) SELECT $1, UNNEST($2::uuid[])",
+            message_id,
+            &tag_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.get_post(message_id).await
+    }
+}
@@ -0,0 +1,362 @@
+use crate::error::{AppError, Result};
+use crate::models::{PlaceholderUser, UserStatus};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Kinds of Discord objects tracked in `imported_records`
+pub const RECORD_CHANNEL: &str = "channel";
+pub const RECORD_MESSAGE: &str = "message";
+
+/// A message to insert with its original timestamps
+pub struct ImportedMessage {
+    pub channel_id: Uuid,
+    pub author_id: Uuid,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub reply_to_id: Option<Uuid>,
+    pub thread_parent_id: Option<Uuid>,
+    pub pinned: bool,
+}
+
+#[derive(Clone)]
+pub struct ImportService {
+    db: PgPool,
+}
+
+impl ImportService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Get the placeholder user standing in for a Discord author in a community,
+    /// creating it on first sight
+    pub async fn get_or_create_placeholder(
+        &self,
+        community_id: Uuid,
+        external_id: &str,
+        display_name: &str,
+    ) -> Result<Uuid> {
+        let existing = sqlx::query_scalar!(
+            "SELECT user_id FROM placeholder_users WHERE community_id = $1 AND external_id = $2",
+            community_id,
+            external_id
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        if let Some(user_id) = existing {
+            return Ok(user_id);
+        }
+
+        let user_id = Uuid::new_v4();
+        let username = format!("imported-{}", &user_id.simple().to_string()[..12]);
+        let display_name: String = display_name.chars().take(64).collect();
+        let mut tx = self.db.begin().await?;
+
+        // An empty password hash can never be verified, so placeholders cannot log in
+        sqlx::query!(
+            r#"
+            INSERT INTO users (id, username, display_name, email, password_hash, status, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, '', $5, NOW(), NOW())
+            "#,
+            user_id,
+            username,
+            display_name,
+            format!("{}@placeholder.invalid", user_id),
+            UserStatus::Offline as UserStatus,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO placeholder_users (user_id, community_id, external_id, created_at)
+            VALUES ($1, $2, $3, NOW())
+            "#,
+            user_id,
+            community_id,
+            external_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(user_id)
+    }
+
+    /// Whether a user is an imported placeholder
+    pub async fn is_placeholder(&self, user_id: Uuid) -> Result<bool> {
+        let is_placeholder = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM placeholder_users WHERE user_id = $1)",
+            user_id
+        )
+        .fetch_one(&self.db)
+        .await?
+        .unwrap_or(false);
+
+        Ok(is_placeholder)
+    }
+
+    /// Local ID of a previously imported channel or message, if it still exists
+    pub async fn find_record(&self, community_id: Uuid, kind: &str, external_id: &str) -> Result<Option<Uuid>> {
+        let local_id = sqlx::query_scalar!(
+            r#"
+            SELECT r.local_id FROM imported_records r
+            WHERE r.community_id = $1 AND r.kind = $2 AND r.external_id = $3
+              AND (EXISTS (SELECT 1 FROM channels WHERE id = r.local_id)
+                   OR EXISTS (SELECT 1 FROM messages WHERE id = r.local_id))
+            "#,
+            community_id,
+            kind,
+            external_id
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(local_id)
+    }
+
+    pub async fn record(&self, community_id: Uuid, kind: &str, external_id: &str, local_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO imported_records (community_id, kind, external_id, local_id)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (community_id, kind, external_id) DO UPDATE SET local_id = $4
+            "#,
+            community_id,
+            kind,
+            external_id,
+            local_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Insert a message keeping its original timestamps.
+    /// Pinned messages are recorded as pinned when they were sent, by nobody in particular.
+    pub async fn insert_message(&self, message: ImportedMessage) -> Result<Uuid> {
+        let id = Uuid::new_v4();
+        let pinned_at = message.pinned.then_some(message.created_at);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO messages (id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id,
+                                  reply_count, pinned_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, 0, $8, $9)
+            "#,
+            id,
+            message.channel_id,
+            message.author_id,
+            message.content,
+            message.edited_at,
+            message.reply_to_id,
+            message.thread_parent_id,
+            pinned_at,
+            message.created_at
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Turn an imported message into the post of a forum channel
+    pub async fn insert_forum_post(&self, message_id: Uuid, channel_id: Uuid, title: &str) -> Result<()> {
+        let title: String = title.chars().take(100).collect();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO forum_posts (message_id, channel_id, title)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (message_id) DO NOTHING
+            "#,
+            message_id,
+            channel_id,
+            title
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Recount a thread's replies after importing them
+    pub async fn refresh_thread(&self, parent_message_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE messages SET
+                reply_count = (SELECT COUNT(*) FROM messages WHERE thread_parent_id = $1)::int,
+                last_reply_at = (SELECT MAX(created_at) FROM messages WHERE thread_parent_id = $1)
+            WHERE id = $1
+            "#,
+            parent_message_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Placeholder users of a community that haven't been claimed
+    pub async fn list_placeholders(&self, community_id: Uuid) -> Result<Vec<PlaceholderUser>> {
+        let placeholders = sqlx::query_as!(
+            PlaceholderUser,
+            r#"
+            SELECT p.user_id, p.community_id, p.external_id, u.display_name,
+                   (SELECT COUNT(*) FROM messages m WHERE m.author_id = p.user_id) as "message_count!",
+                   ARRAY(
+                       SELECT r.user_id FROM placeholder_claim_requests r
+                       WHERE r.placeholder_id = p.user_id
+                       ORDER BY r.created_at
+                   ) as "claim_requested_by!",
+                   p.created_at
+            FROM placeholder_users p
+            INNER JOIN users u ON u.id = p.user_id
+            WHERE p.community_id = $1
+            ORDER BY u.display_name
+            "#,
+            community_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(placeholders)
+    }
+
+    /// Record that a member wants a placeholder's history; the owner still has to approve
+    pub async fn request_claim(&self, community_id: Uuid, placeholder_id: Uuid, user_id: Uuid) -> Result<()> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO placeholder_claim_requests (placeholder_id, user_id, created_at)
+            SELECT user_id, $3, NOW() FROM placeholder_users
+            WHERE user_id = $1 AND community_id = $2
+            ON CONFLICT (placeholder_id, user_id) DO NOTHING
+            "#,
+            placeholder_id,
+            community_id,
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() == 0 && !self.has_claim_request(placeholder_id, user_id).await? {
+            return Err(AppError::NotFound("Placeholder user not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Withdraw a member's request to claim a placeholder
+    pub async fn cancel_claim_request(&self, placeholder_id: Uuid, user_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM placeholder_claim_requests WHERE placeholder_id = $1 AND user_id = $2",
+            placeholder_id,
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn has_claim_request(&self, placeholder_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let requested = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM placeholder_claim_requests WHERE placeholder_id = $1 AND user_id = $2)",
+            placeholder_id,
+            user_id
+        )
+        .fetch_one(&self.db)
+        .await?
+        .unwrap_or(false);
+
+        Ok(requested)
+    }
+
+    /// Hand a placeholder's messages, pins, reactions and uploads over to the real account
+    /// that asked for them, then remove the placeholder
+    pub async fn claim(&self, community_id: Uuid, placeholder_id: Uuid, user_id: Uuid) -> Result<()> {
+        let exists = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM placeholder_users WHERE user_id = $1 AND community_id = $2)",
+            placeholder_id,
+            community_id
+        )
+        .fetch_one(&self.db)
+        .await?
+        .unwrap_or(false);
+
+        if !exists {
+            return Err(AppError::NotFound("Placeholder user not found".to_string()));
+        }
+
+        let mut tx = self.db.begin().await?;
+
+        // Both sides must agree: the member asked, and the owner is approving now
+        let requested = sqlx::query_scalar!(
+            r#"
+            DELETE FROM placeholder_claim_requests
+            WHERE placeholder_id = $1 AND user_id = $2
+            RETURNING user_id
+            "#,
+            placeholder_id,
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if requested.is_none() {
+            return Err(AppError::BadRequest(
+                "The account hasn't asked to claim this placeholder".to_string(),
+            ));
+        }
+
+        sqlx::query!("UPDATE messages SET author_id = $2 WHERE author_id = $1", placeholder_id, user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!("UPDATE messages SET pinned_by_id = $2 WHERE pinned_by_id = $1", placeholder_id, user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            "UPDATE messages SET forwarded_from_author_id = $2 WHERE forwarded_from_author_id = $1",
+            placeholder_id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE message_attachments SET uploader_id = $2 WHERE uploader_id = $1",
+            placeholder_id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // The account may already have reacted with the same emoji
+        sqlx::query!(
+            r#"
+            INSERT INTO message_reactions (id, message_id, user_id, emoji, created_at)
+            SELECT gen_random_uuid(), message_id, $2, emoji, created_at
+            FROM message_reactions WHERE user_id = $1
+            ON CONFLICT (message_id, user_id, emoji) DO NOTHING
+            "#,
+            placeholder_id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Removes the placeholder row and its remaining reactions
+        sqlx::query!("DELETE FROM users WHERE id = $1", placeholder_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
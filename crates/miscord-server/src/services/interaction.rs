@@ -0,0 +1,289 @@
+use crate::error::{AppError, Result};
+use crate::models::{CreateMessage, CreateSlashCommand, Interaction, Message, SlashCommand, UpdateMessage};
+use crate::services::message::MessageService;
+use miscord_protocol::{ButtonData, CommandOptionData, EmbedData};
+use sqlx::types::Json;
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// How long an application has to respond to an interaction, in seconds
+pub const RESPONSE_WINDOW_SECS: i64 = 15 * 60;
+
+#[derive(Clone)]
+pub struct InteractionService {
+    db: PgPool,
+}
+
+impl InteractionService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Commands registered in a community, by name
+    pub async fn list_commands(&self, community_id: Uuid) -> Result<Vec<SlashCommand>> {
+        let commands = sqlx::query_as!(
+            SlashCommand,
+            r#"
+            SELECT c.id, c.community_id, c.application_id, u.display_name as application_name,
+                   c.name, c.description, c.options as "options: Json<Vec<CommandOptionData>>",
+                   c.created_at, c.updated_at
+            FROM slash_commands c
+            INNER JOIN users u ON u.id = c.application_id
+            WHERE c.community_id = $1
+            ORDER BY c.name
+            "#,
+            community_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(commands)
+    }
+
+    pub async fn get_command(&self, id: Uuid) -> Result<SlashCommand> {
+        sqlx::query_as!(
+            SlashCommand,
+            r#"
+            SELECT c.id, c.community_id, c.application_id, u.display_name as application_name,
+                   c.name, c.description, c.options as "options: Json<Vec<CommandOptionData>>",
+                   c.created_at, c.updated_at
+            FROM slash_commands c
+            INNER JOIN users u ON u.id = c.application_id
+            WHERE c.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Command not found".to_string()))
+    }
+
+    /// Register a command, replacing the application's own command of the same name
+    pub async fn upsert_command(
+        &self,
+        community_id: Uuid,
+        application_id: Uuid,
+        input: CreateSlashCommand,
+    ) -> Result<SlashCommand> {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO slash_commands (id, community_id, application_id, name, description, options, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, NOW(), NOW())
+            ON CONFLICT (community_id, name) DO UPDATE
+                SET description = EXCLUDED.description, options = EXCLUDED.options, updated_at = NOW()
+                WHERE slash_commands.application_id = EXCLUDED.application_id
+            RETURNING id
+            "#,
+            Uuid::new_v4(),
+            community_id,
+            application_id,
+            input.name,
+            input.description,
+            Json(input.options) as _
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| {
+            AppError::Conflict("Another application already registered this command".to_string())
+        })?;
+
+        self.get_command(id).await
+    }
+
+    pub async fn delete_command(&self, id: Uuid) -> Result<()> {
+        sqlx::query!("DELETE FROM slash_commands WHERE id = $1", id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove the commands an application registered in a community it left
+    pub async fn delete_application_commands(&self, community_id: Uuid, application_id: Uuid) -> Result<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM slash_commands WHERE community_id = $1 AND application_id = $2",
+            community_id,
+            application_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn create_interaction(
+        &self,
+        application_id: Uuid,
+        user_id: Uuid,
+        channel_id: Uuid,
+        message_id: Option<Uuid>,
+    ) -> Result<Interaction> {
+        let interaction = sqlx::query_as!(
+            Interaction,
+            r#"
+            INSERT INTO interactions (id, application_id, user_id, channel_id, message_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, NOW())
+            RETURNING id, application_id, user_id, channel_id, message_id, responded_at, created_at
+            "#,
+            Uuid::new_v4(),
+            application_id,
+            user_id,
+            channel_id,
+            message_id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(interaction)
+    }
+
+    pub async fn get_interaction(&self, id: Uuid) -> Result<Interaction> {
+        sqlx::query_as!(
+            Interaction,
+            r#"
+            SELECT id, application_id, user_id, channel_id, message_id, responded_at, created_at
+            FROM interactions WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Interaction not found".to_string()))
+    }
+
+    /// Claim the single response an interaction allows, while it hasn't expired
+    pub async fn mark_responded(&self, id: Uuid) -> Result<()> {
+        let mut conn = self.db.acquire().await?;
+        Self::claim_response(&mut conn, id).await
+    }
+
+    /// `mark_responded` on a connection the caller holds
+    async fn claim_response(conn: &mut PgConnection, id: Uuid) -> Result<()> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE interactions SET responded_at = NOW()
+            WHERE id = $1 AND responded_at IS NULL
+              AND created_at > NOW() - make_interval(secs => $2)
+            "#,
+            id,
+            RESPONSE_WINDOW_SECS as f64
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::Conflict(
+                "Interaction was already responded to or has expired".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Respond with a new message. The response is only claimed if the message, its embeds
+    /// and its buttons are all stored, so a failure leaves the interaction open for a retry.
+    pub async fn respond_with_message(
+        &self,
+        id: Uuid,
+        channel_id: Uuid,
+        author_id: Uuid,
+        input: CreateMessage,
+        components: &[ButtonData],
+    ) -> Result<Message> {
+        let mut tx = self.db.begin().await?;
+        Self::claim_response(&mut tx, id).await?;
+        let message = MessageService::insert(&mut tx, channel_id, author_id, &input).await?;
+        if !components.is_empty() {
+            Self::write_components(&mut tx, message.id, components).await?;
+        }
+        tx.commit().await?;
+
+        Ok(message)
+    }
+
+    /// Respond by editing the message whose button was clicked. Only the parts given are
+    /// replaced, together with claiming the response.
+    pub async fn respond_with_update(
+        &self,
+        id: Uuid,
+        message_id: Uuid,
+        author_id: Uuid,
+        content: Option<UpdateMessage>,
+        embeds: Option<&[EmbedData]>,
+        components: Option<&[ButtonData]>,
+    ) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        Self::claim_response(&mut tx, id).await?;
+        if let Some(content) = &content {
+            MessageService::write_update(&mut tx, message_id, author_id, content).await?;
+        }
+        if let Some(embeds) = embeds {
+            MessageService::write_embeds(&mut tx, message_id, embeds).await?;
+        }
+        if let Some(components) = components {
+            Self::write_components(&mut tx, message_id, components).await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Forget interactions whose response window has long passed
+    pub async fn purge_expired(&self) -> Result<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM interactions WHERE created_at < NOW() - INTERVAL '1 day'"
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Replace the buttons on a message; an empty list removes them
+    pub async fn set_components(&self, message_id: Uuid, components: &[ButtonData]) -> Result<()> {
+        let mut conn = self.db.acquire().await?;
+        Self::write_components(&mut conn, message_id, components).await
+    }
+
+    /// `set_components` on a connection the caller holds
+    async fn write_components(conn: &mut PgConnection, message_id: Uuid, components: &[ButtonData]) -> Result<()> {
+        if components.is_empty() {
+            sqlx::query!("DELETE FROM message_components WHERE message_id = $1", message_id)
+                .execute(&mut *conn)
+                .await?;
+        } else {
+            sqlx::query!(
+                r#"
+                INSERT INTO message_components (message_id, components)
+                VALUES ($1, $2)
+                ON CONFLICT (message_id) DO UPDATE SET components = EXCLUDED.components
+                "#,
+                message_id,
+                Json(components) as _
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Buttons of several messages, keyed by message ID
+    pub async fn get_components(&self, message_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<ButtonData>>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT message_id, components as "components: Json<Vec<ButtonData>>"
+            FROM message_components WHERE message_id = ANY($1)
+            "#,
+            message_ids
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.message_id, row.components.0))
+            .collect())
+    }
+}
@@ -0,0 +1,115 @@
+use crate::error::Result;
+use crate::models::{LinkPreview, OpenGraphData};
+use sqlx::types::Json;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// How long a fetched preview is served from the cache, in seconds
+const PREVIEW_TTL_SECS: f64 = 24.0 * 60.0 * 60.0;
+
+/// How long a URL that couldn't be previewed is left alone, in seconds
+const FAILURE_TTL_SECS: f64 = 60.0 * 60.0;
+
+#[derive(Clone)]
+pub struct LinkPreviewService {
+    db: PgPool,
+    /// URLs being fetched right now, so concurrent requests for one URL share a fetch
+    fetching: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl LinkPreviewService {
+    pub fn new(db: PgPool) -> Self {
+        Self {
+            db,
+            fetching: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// The cached preview of a URL, unless it has expired
+    pub async fn get_fresh(&self, url: &str) -> Result<Option<LinkPreview>> {
+        let preview = sqlx::query_as!(
+            LinkPreview,
+            r#"
+            SELECT url, data as "data: Json<OpenGraphData>", fetched_at
+            FROM link_previews
+            WHERE url = $1
+              AND fetched_at > NOW() - make_interval(secs => CASE WHEN data IS NULL THEN $3::float8 ELSE $2::float8 END)
+            "#,
+            url,
+            PREVIEW_TTL_SECS,
+            FAILURE_TTL_SECS
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(preview)
+    }
+
+    /// Cache the result of fetching a URL; None records that it couldn't be previewed
+    pub async fn store(&self, url: &str, data: Option<&OpenGraphData>) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO link_previews (url, data, fetched_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (url) DO UPDATE SET data = EXCLUDED.data, fetched_at = EXCLUDED.fetched_at
+            "#,
+            url,
+            data.map(Json) as _
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Wait for the lock held while a URL is fetched. Whoever gets it after the first
+    /// fetcher should find the result in the cache. The URL's entry is forgotten when
+    /// the guard is dropped, including when the request is cancelled mid-fetch.
+    pub async fn lock_fetch<'a>(&'a self, url: &'a str) -> FetchGuard<'a> {
+        let lock = {
+            let mut fetching = self.fetching.lock().unwrap();
+            fetching.entry(url.to_string()).or_default().clone()
+        };
+        let guard = lock.clone().lock_owned().await;
+        FetchGuard {
+            fetching: &self.fetching,
+            url,
+            lock,
+            _guard: guard,
+        }
+    }
+
+    /// Drop previews that can no longer be served
+    pub async fn purge_expired(&self) -> Result<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM link_previews WHERE fetched_at < NOW() - make_interval(secs => $1)",
+            PREVIEW_TTL_SECS.max(FAILURE_TTL_SECS)
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Held while fetching a URL; see [`LinkPreviewService::lock_fetch`]
+pub struct FetchGuard<'a> {
+    fetching: &'a Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+    url: &'a str,
+    lock: Arc<tokio::sync::Mutex<()>>,
+    _guard: tokio::sync::OwnedMutexGuard<()>,
+}
+
+impl Drop for FetchGuard<'_> {
+    fn drop(&mut self) {
+        let Ok(mut fetching) = self.fetching.lock() else {
+            return;
+        };
+        // Requests that were already waiting still hold this lock; a newer entry
+        // belongs to a later fetch
+        if fetching.get(self.url).is_some_and(|lock| Arc::ptr_eq(lock, &self.lock)) {
+            fetching.remove(self.url);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use crate::error::{AppError, Result};
+use image::imageops::FilterType;
+use image::io::{Limits, Reader as ImageReader};
+use image::{ImageFormat, ImageOutputFormat};
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tokio::fs;
+use uuid::Uuid;
+
+/// Largest external image the proxy will download: 10 MB
+pub const MAX_PROXY_IMAGE_SIZE: usize = 10 * 1024 * 1024;
+
+/// Images with more pixels than this on a side are refused before decoding
+const MAX_SOURCE_DIMENSION: u32 = 8192;
+
+/// Most memory decoding a proxied image may take: 64 MB
+const MAX_DECODE_ALLOC: u64 = 64 * 1024 * 1024;
+
+/// Cached images are served for this long before being fetched again
+const CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// The cache is trimmed back to this size, oldest images first: 1 GB
+const MAX_CACHE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Content type for the image formats the proxy passes on
+fn content_type(format: ImageFormat) -> Option<&'static str> {
+    match format {
+        ImageFormat::Png => Some("image/png"),
+        ImageFormat::Jpeg => Some("image/jpeg"),
+        ImageFormat::Gif => Some("image/gif"),
+        ImageFormat::WebP => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// A reader for a downloaded image that refuses ones too large to decode safely
+fn limited_reader(data: &[u8], format: ImageFormat) -> ImageReader<Cursor<&[u8]>> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_DIMENSION);
+    limits.max_image_height = Some(MAX_SOURCE_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODE_ALLOC);
+    let mut reader = ImageReader::with_format(Cursor::new(data), format);
+    reader.limits(limits);
+    reader
+}
+
+/// Validate a downloaded image by its magic bytes and scale it down to fit within `size` pixels.
+/// Returns the bytes to serve and their content type.
+/// Images that already fit are served unchanged, without decoding more than their header,
+/// so GIFs keep their animation; larger ones are re-encoded as JPEG, or PNG when they may
+/// have transparency.
+pub fn process_proxied_image(data: &[u8], size: u32) -> Result<(Vec<u8>, &'static str)> {
+    let format = image::guess_format(data)
+        .ok()
+        .filter(|format| content_type(*format).is_some())
+        .ok_or_else(|| AppError::BadRequest("URL is not a supported image".to_string()))?;
+
+    let (width, height) = limited_reader(data, format)
+        .into_dimensions()
+        .map_err(|e| AppError::BadRequest(format!("Failed to read image: {}", e)))?;
+    if width <= size && height <= size {
+        return Ok((data.to_vec(), content_type(format).unwrap_or("image/png")));
+    }
+
+    let img = limited_reader(data, format)
+        .decode()
+        .map_err(|e| AppError::BadRequest(format!("Failed to decode image: {}", e)))?;
+
+    let img = img.resize(size, size, FilterType::Triangle);
+
+    let (output_format, content_type) = if format == ImageFormat::Jpeg {
+        (ImageOutputFormat::Jpeg(85), "image/jpeg")
+    } else {
+        (ImageOutputFormat::Png, "image/png")
+    };
+    let mut output = Vec::new();
+    img.write_to(&mut Cursor::new(&mut output), output_format)
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to encode image: {}", e)))?;
+
+    Ok((output, content_type))
+}
+
+/// Disk cache of images fetched through the media proxy
+#[derive(Clone)]
+pub struct MediaProxyService {
+    cache_dir: PathBuf,
+}
+
+impl MediaProxyService {
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self { cache_dir }
+    }
+
+    /// Cache file for a URL at a display size
+    fn cache_path(&self, url: &str, size: u32) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(url.as_bytes());
+        hasher.update(size.to_le_bytes());
+        self.cache_dir.join(format!("{:x}", hasher.finalize()))
+    }
+
+    /// A cached image and its content type, unless it has expired
+    pub async fn get_cached(&self, url: &str, size: u32) -> Option<(Vec<u8>, &'static str)> {
+        let path = self.cache_path(url, size);
+        let modified = fs::metadata(&path).await.ok()?.modified().ok()?;
+        if modified.elapsed().map_or(true, |age| age > CACHE_TTL) {
+            return None;
+        }
+
+        let data = fs::read(&path).await.ok()?;
+        // Cached files only ever hold images the proxy validated
+        let content_type = image::guess_format(&data).ok().and_then(content_type)?;
+        Some((data, content_type))
+    }
+
+    pub async fn store(&self, url: &str, size: u32, data: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.cache_dir).await.map_err(|e| {
+            AppError::Internal(anyhow::anyhow!("Failed to create media cache directory: {}", e))
+        })?;
+
+        // Write then rename so a concurrent read never sees a partial file
+        let path = self.cache_path(url, size);
+        let tmp_path = path.with_extension(format!("{}.tmp", Uuid::new_v4()));
+        fs::write(&tmp_path, data)
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to cache image: {}", e)))?;
+        fs::rename(&tmp_path, &path)
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to cache image: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Delete cached images that have expired, then the oldest ones while the cache is
+    /// larger than its cap
+    pub async fn purge(&self) -> Result<u64> {
+        let mut entries = match fs::read_dir(&self.cache_dir).await {
+            Ok(entries) => entries,
+            // Nothing has been cached yet
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => {
+                return Err(AppError::Internal(anyhow::anyhow!(
+                    "Failed to read media cache directory: {}",
+                    e
+                )))
+            }
+        };
+
+        let cutoff = SystemTime::now() - CACHE_TTL;
+        let mut purged = 0;
+        let mut kept = Vec::new();
+        let mut total_bytes = 0;
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let Ok(metadata) = entry.metadata().await else {
+                continue;
+            };
+            let Ok(modified) = metadata.modified() else {
+                continue;
+            };
+            if modified < cutoff {
+                if fs::remove_file(entry.path()).await.is_ok() {
+                    purged += 1;
+                }
+            } else {
+                total_bytes += metadata.len();
+                kept.push((modified, metadata.len(), entry.path()));
+            }
+        }
+
+        kept.sort_by_key(|(modified, _, _)| *modified);
+        for (_, len, path) in kept {
+            if total_bytes <= MAX_CACHE_BYTES {
+                break;
+            }
+            if fs::remove_file(&path).await.is_ok() {
+                total_bytes -= len;
+                purged += 1;
+            }
+        }
+
+        Ok(purged)
+    }
+}
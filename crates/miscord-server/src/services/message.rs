@@ -1,8 +1,19 @@
 use crate::error::{AppError, Result};
 use crate::models::{CreateMessage, Message, MessageAttachment, UpdateMessage};
-use sqlx::PgPool;
+use miscord_protocol::EmbedData;
+use sqlx::types::Json;
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashSet;
 use uuid::Uuid;
 
+/// Messages removed by a retention purge
+pub struct PurgedMessages {
+    /// Deleted messages and the channels they were in
+    pub messages: Vec<(Uuid, Uuid)>,
+    /// Attachments whose stored file is no longer referenced by any message
+    pub orphaned_files: Vec<MessageAttachment>,
+}
+
 #[derive(Clone)]
 pub struct MessageService {
     db: PgPool,
@@ -14,12 +25,27 @@ impl MessageService {
     }
 
     pub async fn create(&self, channel_id: Uuid, author_id: Uuid, input: CreateMessage) -> Result<Message> {
+        let mut tx = self.db.begin().await?;
+        let message = Self::insert(&mut tx, channel_id, author_id, &input).await?;
+        tx.commit().await?;
+
+        Ok(message)
+    }
+
+    /// Create a message and its embeds on a connection the caller holds, so other services
+    /// can create one in the same transaction as their own rows
+    pub async fn insert(
+        conn: &mut PgConnection,
+        channel_id: Uuid,
+        author_id: Uuid,
+        input: &CreateMessage,
+    ) -> Result<Message> {
         let message = sqlx::query_as!(
             Message,
             r#"
             INSERT INTO messages (id, channel_id, author_id, content, reply_to_id, thread_parent_id, reply_count, created_at)
             VALUES ($1, $2, $3, $4, $5, NULL, 0, NOW())
-            RETURNING id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, created_at
+            RETURNING id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, forwarded_from_message_id, forwarded_from_channel_id, forwarded_from_author_id, forwarded_from_created_at, created_at
             "#,
             Uuid::new_v4(),
             channel_id,
@@ -27,22 +53,149 @@ impl MessageService {
             input.content,
             input.reply_to_id
         )
-        .fetch_one(&self.db)
+        .fetch_one(&mut *conn)
         .await?;
 
+        if !input.embeds.is_empty() {
+            Self::write_embeds(conn, message.id, &input.embeds).await?;
+        }
+
         // Update channel's updated_at timestamp
         sqlx::query!("UPDATE channels SET updated_at = NOW() WHERE id = $1", channel_id)
-            .execute(&self.db)
+            .execute(&mut *conn)
             .await?;
 
         Ok(message)
     }
 
+    /// Create a copy of a message in another channel with a reference to the original,
+    /// along with its attachments and embeds. Forwarding a forwarded message keeps pointing
+    /// at the first original. `crosspost` marks the copy as a published announcement.
+    pub async fn forward(
+        &self,
+        original: &Message,
+        channel_id: Uuid,
+        author_id: Uuid,
+        crosspost: bool,
+        attachments: &[MessageAttachment],
+        embeds: &[EmbedData],
+    ) -> Result<(Message, Vec<MessageAttachment>)> {
+        let (source_message_id, source_channel_id, source_author_id, source_created_at) =
+            if let Some(created_at) = original.forwarded_from_created_at {
+                (
+                    original.forwarded_from_message_id,
+                    original.forwarded_from_channel_id,
+                    original.forwarded_from_author_id,
+                    created_at,
+                )
+            } else {
+                (
+                    Some(original.id),
+                    Some(original.channel_id),
+                    Some(original.author_id),
+                    original.created_at,
+                )
+            };
+
+        // The copy appears whole or not at all
+        let mut tx = self.db.begin().await?;
+
+        let message = sqlx::query_as!(
+            Message,
+            r#"
+            INSERT INTO messages (id, channel_id, author_id, content, reply_to_id, thread_parent_id, reply_count,
+                                  forwarded_from_message_id, forwarded_from_channel_id, forwarded_from_author_id,
+                                  forwarded_from_created_at, created_at)
+            VALUES ($1, $2, $3, $4, NULL, NULL, 0, $5, $6, $7, $8, NOW())
+            RETURNING id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, forwarded_from_message_id, forwarded_from_channel_id, forwarded_from_author_id, forwarded_from_created_at, created_at
+            "#,
+            Uuid::new_v4(),
+            channel_id,
+            author_id,
+            original.content,
+            source_message_id,
+            source_channel_id,
+            source_author_id,
+            source_created_at
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if crosspost {
+            sqlx::query!(
+                "INSERT INTO message_crossposts (message_id, source_message_id) VALUES ($1, $2)",
+                message.id,
+                original.id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // Attachments are shared with the copy rather than duplicated on disk: each new
+        // row gets its own ID and URL but points at the original file. The copy counts
+        // against the quotas of whoever forwarded it and the community it landed in.
+        // The original row is locked until commit, so deleting it waits and then sees
+        // the copy still using the file.
+        let mut shared = Vec::with_capacity(attachments.len());
+        for original in attachments {
+            let id = Uuid::new_v4();
+            let url = format!("/api/files/{}", id);
+
+            let attachment = sqlx::query_as!(
+                MessageAttachment,
+                r#"
+                INSERT INTO message_attachments
+                    (id, message_id, file_id, filename, content_type, size_bytes, url,
+                     width, height, duration_secs, blurhash, has_thumbnail, sha256,
+                     uploader_id, community_id)
+                SELECT $1, $2, file_id, filename, content_type, size_bytes, $3,
+                       width, height, duration_secs, blurhash, has_thumbnail, sha256,
+                       $4, (SELECT community_id FROM channels WHERE id = $5)
+                FROM message_attachments
+                WHERE id = $6
+                FOR KEY SHARE
+                RETURNING id, message_id as "message_id: _", file_id, filename, content_type, size_bytes, url, created_at,
+                          width, height, duration_secs, blurhash, has_thumbnail
+                "#,
+                id,
+                message.id,
+                url,
+                author_id,
+                channel_id,
+                original.id
+            )
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Attachment not found".to_string()))?;
+
+            shared.push(attachment);
+        }
+
+        if !embeds.is_empty() {
+            sqlx::query!(
+                "INSERT INTO message_embeds (message_id, embeds) VALUES ($1, $2)",
+                message.id,
+                Json(embeds) as _
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // Update channel's updated_at timestamp
+        sqlx::query!("UPDATE channels SET updated_at = NOW() WHERE id = $1", channel_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok((message, shared))
+    }
+
     pub async fn get_by_id(&self, id: Uuid) -> Result<Message> {
         let message = sqlx::query_as!(
             Message,
             r#"
-            SELECT id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, created_at
+            SELECT id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, forwarded_from_message_id, forwarded_from_channel_id, forwarded_from_author_id, forwarded_from_created_at, created_at
             FROM messages WHERE id = $1
             "#,
             id
@@ -61,20 +214,21 @@ impl MessageService {
         limit: i64,
     ) -> Result<Vec<Message>> {
         let messages = if let Some(before_id) = before {
-            // Get the created_at of the "before" message
+            // Get the created_at of the "before" message; messages sharing it are told apart by ID
             let before_msg = self.get_by_id(before_id).await?;
 
             sqlx::query_as!(
                 Message,
                 r#"
-                SELECT id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, created_at
+                SELECT id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, forwarded_from_message_id, forwarded_from_channel_id, forwarded_from_author_id, forwarded_from_created_at, created_at
                 FROM messages
-                WHERE channel_id = $1 AND created_at < $2 AND thread_parent_id IS NULL
-                ORDER BY created_at DESC
-                LIMIT $3
+                WHERE channel_id = $1 AND (created_at, id) < ($2, $3) AND thread_parent_id IS NULL
+                ORDER BY created_at DESC, id DESC
+                LIMIT $4
                 "#,
                 channel_id,
                 before_msg.created_at,
+                before_msg.id,
                 limit
             )
             .fetch_all(&self.db)
@@ -83,10 +237,10 @@ impl MessageService {
             sqlx::query_as!(
                 Message,
                 r#"
-                SELECT id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, created_at
+                SELECT id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, forwarded_from_message_id, forwarded_from_channel_id, forwarded_from_author_id, forwarded_from_created_at, created_at
                 FROM messages
                 WHERE channel_id = $1 AND thread_parent_id IS NULL
-                ORDER BY created_at DESC
+                ORDER BY created_at DESC, id DESC
                 LIMIT $2
                 "#,
                 channel_id,
@@ -99,20 +253,83 @@ impl MessageService {
         Ok(messages)
     }
 
+    /// List messages newer than `after`, newest first (same order as `list_by_channel`)
+    pub async fn list_after(&self, channel_id: Uuid, after: Uuid, limit: i64) -> Result<Vec<Message>> {
+        let after_msg = self.get_by_id(after).await?;
+
+        let mut messages = sqlx::query_as!(
+            Message,
+            r#"
+            SELECT id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, forwarded_from_message_id, forwarded_from_channel_id, forwarded_from_author_id, forwarded_from_created_at, created_at
+            FROM messages
+            WHERE channel_id = $1 AND (created_at, id) > ($2, $3) AND thread_parent_id IS NULL
+            ORDER BY created_at ASC, id ASC
+            LIMIT $4
+            "#,
+            channel_id,
+            after_msg.created_at,
+            after_msg.id,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        // Fetched oldest first so the page starts right after the cursor
+        messages.reverse();
+
+        Ok(messages)
+    }
+
+    /// List a window of messages centered on `around`, newest first.
+    /// Thread replies are not part of the channel history, so their parent is used as the center.
+    pub async fn list_around(&self, channel_id: Uuid, around: Uuid, limit: i64) -> Result<Vec<Message>> {
+        let mut target = self.get_by_id(around).await?;
+        if let Some(parent_id) = target.thread_parent_id {
+            target = self.get_by_id(parent_id).await?;
+        }
+
+        if target.channel_id != channel_id {
+            return Err(AppError::NotFound("Message not found".to_string()));
+        }
+
+        let before_limit = limit / 2;
+        let after_limit = (limit - before_limit - 1).max(0);
+
+        let newer = self.list_after(channel_id, target.id, after_limit).await?;
+        let older = self.list_by_channel(channel_id, Some(target.id), before_limit).await?;
+
+        let mut messages = newer;
+        messages.push(target);
+        messages.extend(older);
+
+        Ok(messages)
+    }
+
     pub async fn update(&self, id: Uuid, author_id: Uuid, input: UpdateMessage) -> Result<Message> {
+        let mut conn = self.db.acquire().await?;
+        Self::write_update(&mut conn, id, author_id, &input).await
+    }
+
+    /// `update` on a connection the caller holds
+    pub async fn write_update(
+        conn: &mut PgConnection,
+        id: Uuid,
+        author_id: Uuid,
+        input: &UpdateMessage,
+    ) -> Result<Message> {
         let message = sqlx::query_as!(
             Message,
             r#"
             UPDATE messages
             SET content = $3, edited_at = NOW()
             WHERE id = $1 AND author_id = $2
-            RETURNING id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, created_at
+            RETURNING id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, forwarded_from_message_id, forwarded_from_channel_id, forwarded_from_author_id, forwarded_from_created_at, created_at
             "#,
             id,
             author_id,
             input.content
         )
-        .fetch_optional(&self.db)
+        .fetch_optional(&mut *conn)
         .await?
         .ok_or_else(|| AppError::NotFound("Message not found or not owned by user".to_string()))?;
 
@@ -165,30 +382,35 @@ impl MessageService {
         &self,
         parent_message_id: Uuid,
         author_id: Uuid,
-        content: String,
-        reply_to_id: Option<Uuid>,
+        input: &CreateMessage,
     ) -> Result<Message> {
         // Get parent message to verify it exists and get channel_id
         let parent = self.get_by_id(parent_message_id).await?;
 
+        let mut tx = self.db.begin().await?;
+
         // Create the reply with thread_parent_id set
         let message = sqlx::query_as!(
             Message,
             r#"
             INSERT INTO messages (id, channel_id, author_id, content, reply_to_id, thread_parent_id, reply_count, created_at)
             VALUES ($1, $2, $3, $4, $5, $6, 0, NOW())
-            RETURNING id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, created_at
+            RETURNING id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, forwarded_from_message_id, forwarded_from_channel_id, forwarded_from_author_id, forwarded_from_created_at, created_at
             "#,
             Uuid::new_v4(),
             parent.channel_id,
             author_id,
-            content,
-            reply_to_id,
+            input.content,
+            input.reply_to_id,
             parent_message_id
         )
-        .fetch_one(&self.db)
+        .fetch_one(&mut *tx)
         .await?;
 
+        if !input.embeds.is_empty() {
+            Self::write_embeds(&mut tx, message.id, &input.embeds).await?;
+        }
+
         // Update parent's reply_count and last_reply_at
         sqlx::query!(
             r#"
@@ -198,9 +420,11 @@ impl MessageService {
             "#,
             parent_message_id
         )
-        .execute(&self.db)
+        .execute(&mut *tx)
         .await?;
 
+        tx.commit().await?;
+
         Ok(message)
     }
 
@@ -213,7 +437,7 @@ impl MessageService {
         let messages = sqlx::query_as!(
             Message,
             r#"
-            SELECT id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, created_at
+            SELECT id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, forwarded_from_message_id, forwarded_from_channel_id, forwarded_from_author_id, forwarded_from_created_at, created_at
             FROM messages
             WHERE thread_parent_id = $1
             ORDER BY created_at ASC
@@ -263,7 +487,8 @@ impl MessageService {
         let attachments = sqlx::query_as!(
             MessageAttachment,
             r#"
-            SELECT id, message_id, filename, content_type, size_bytes, url, created_at
+            SELECT id, message_id, file_id, filename, content_type, size_bytes, url, created_at,
+                   width, height, duration_secs, blurhash, has_thumbnail
             FROM message_attachments WHERE message_id = $1
             "#,
             message_id
@@ -330,7 +555,8 @@ impl MessageService {
                 Message,
                 r#"
                 SELECT m.id, m.channel_id, m.author_id, m.content, m.edited_at, m.reply_to_id,
-                       m.thread_parent_id, m.reply_count, m.last_reply_at, m.pinned_at, m.pinned_by_id, m.created_at
+                       m.thread_parent_id, m.reply_count, m.last_reply_at, m.pinned_at, m.pinned_by_id,
+                       m.forwarded_from_message_id, m.forwarded_from_channel_id, m.forwarded_from_author_id, m.forwarded_from_created_at, m.created_at
                 FROM messages m
                 JOIN channels c ON m.channel_id = c.id
                 JOIN community_members cm ON c.community_id = cm.community_id
@@ -356,7 +582,8 @@ impl MessageService {
                 Message,
                 r#"
                 SELECT m.id, m.channel_id, m.author_id, m.content, m.edited_at, m.reply_to_id,
-                       m.thread_parent_id, m.reply_count, m.last_reply_at, m.pinned_at, m.pinned_by_id, m.created_at
+                       m.thread_parent_id, m.reply_count, m.last_reply_at, m.pinned_at, m.pinned_by_id,
+                       m.forwarded_from_message_id, m.forwarded_from_channel_id, m.forwarded_from_author_id, m.forwarded_from_created_at, m.created_at
                 FROM messages m
                 JOIN channels c ON m.channel_id = c.id
                 WHERE m.content ILIKE $1
@@ -436,6 +663,48 @@ impl MessageService {
         Ok(result)
     }
 
+    /// Replace the embeds of a message on a connection the caller holds; an empty list
+    /// removes them
+    pub async fn write_embeds(conn: &mut PgConnection, message_id: Uuid, embeds: &[EmbedData]) -> Result<()> {
+        if embeds.is_empty() {
+            sqlx::query!("DELETE FROM message_embeds WHERE message_id = $1", message_id)
+                .execute(&mut *conn)
+                .await?;
+        } else {
+            sqlx::query!(
+                r#"
+                INSERT INTO message_embeds (message_id, embeds)
+                VALUES ($1, $2)
+                ON CONFLICT (message_id) DO UPDATE SET embeds = EXCLUDED.embeds
+                "#,
+                message_id,
+                Json(embeds) as _
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Embeds of several messages, keyed by message ID
+    pub async fn get_embeds(&self, message_ids: &[Uuid]) -> Result<std::collections::HashMap<Uuid, Vec<EmbedData>>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT message_id, embeds as "embeds: Json<Vec<EmbedData>>"
+            FROM message_embeds WHERE message_id = ANY($1)
+            "#,
+            message_ids
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.message_id, row.embeds.0))
+            .collect())
+    }
+
     /// Pin a message
     pub async fn pin_message(&self, message_id: Uuid, user_id: Uuid) -> Result<Message> {
         let message = sqlx::query_as!(
@@ -444,7 +713,7 @@ impl MessageService {
             UPDATE messages
             SET pinned_at = NOW(), pinned_by_id = $2
             WHERE id = $1
-            RETURNING id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, created_at
+            RETURNING id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, forwarded_from_message_id, forwarded_from_channel_id, forwarded_from_author_id, forwarded_from_created_at, created_at
             "#,
             message_id,
             user_id
@@ -464,7 +733,7 @@ impl MessageService {
             UPDATE messages
             SET pinned_at = NULL, pinned_by_id = NULL
             WHERE id = $1
-            RETURNING id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, created_at
+            RETURNING id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, forwarded_from_message_id, forwarded_from_channel_id, forwarded_from_author_id, forwarded_from_created_at, created_at
             "#,
             message_id
         )
@@ -480,7 +749,7 @@ impl MessageService {
         let messages = sqlx::query_as!(
             Message,
             r#"
-            SELECT id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, created_at
+            SELECT id, channel_id, author_id, content, edited_at, reply_to_id, thread_parent_id, reply_count, last_reply_at, pinned_at, pinned_by_id, forwarded_from_message_id, forwarded_from_channel_id, forwarded_from_author_id, forwarded_from_created_at, created_at
             FROM messages
             WHERE channel_id = $1 AND pinned_at IS NOT NULL
             ORDER BY pinned_at DESC
@@ -494,4 +763,78 @@ impl MessageService {
 
         Ok(messages)
     }
+
+    /// Delete up to `limit` messages that are older than their channel's retention period,
+    /// oldest first. Thread replies are removed along with their parent.
+    pub async fn purge_expired(&self, limit: i64) -> Result<PurgedMessages> {
+        let mut tx = self.db.begin().await?;
+
+        let expired = sqlx::query!(
+            r#"
+            SELECT m.id, m.channel_id
+            FROM messages m
+            INNER JOIN channels c ON c.id = m.channel_id
+            WHERE c.retention_seconds IS NOT NULL
+              AND m.created_at < NOW() - c.retention_seconds * INTERVAL '1 second'
+            ORDER BY m.created_at ASC
+            LIMIT $1
+            FOR UPDATE OF m SKIP LOCKED
+            "#,
+            limit
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if expired.is_empty() {
+            return Ok(PurgedMessages {
+                messages: vec![],
+                orphaned_files: vec![],
+            });
+        }
+
+        let ids: Vec<Uuid> = expired.iter().map(|row| row.id).collect();
+
+        // Attachments on the expired messages and on replies that go with them
+        let attachments = sqlx::query_as!(
+            MessageAttachment,
+            r#"
+            SELECT id, message_id as "message_id: _", file_id, filename, content_type, size_bytes, url, created_at,
+                   width, height, duration_secs, blurhash, has_thumbnail
+            FROM message_attachments
+            WHERE message_id = ANY($1)
+               OR message_id IN (SELECT id FROM messages WHERE thread_parent_id = ANY($1))
+            "#,
+            &ids
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM messages WHERE id = ANY($1)", &ids)
+            .execute(&mut *tx)
+            .await?;
+
+        // Forwarded and cross-posted copies may still point at the same files
+        let file_ids: Vec<Uuid> = attachments.iter().map(|a| a.file_id).collect();
+        let still_used: HashSet<Uuid> = sqlx::query_scalar!(
+            "SELECT DISTINCT file_id FROM message_attachments WHERE file_id = ANY($1)",
+            &file_ids
+        )
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .collect();
+
+        tx.commit().await?;
+
+        let mut seen = HashSet::new();
+        let orphaned_files = attachments
+            .into_iter()
+            .filter(|a| !still_used.contains(&a.file_id) && seen.insert(a.file_id))
+            .collect();
+
+        Ok(PurgedMessages {
+            messages: expired.into_iter().map(|row| (row.id, row.channel_id)).collect(),
+            orphaned_files,
+        })
+    }
 }
@@ -1,4 +1,18 @@
+pub mod announcement;
 pub mod attachment;
+pub mod bot;
 pub mod channel;
+pub mod emoji;
+pub mod event_webhook;
+pub mod export;
+pub mod forum;
+pub mod import;
+pub mod interaction;
+pub mod link_preview;
+pub mod media_proxy;
 pub mod message;
+pub mod quota;
+pub mod thread;
+pub mod upload;
 pub mod user;
+pub mod webhook;
@@ -0,0 +1,175 @@
+use crate::error::{AppError, Result};
+use crate::models::{AttachmentOwner, MessageAttachment};
+use miscord_protocol::StorageUsageData;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Storage quotas. A stored file counts once against every user and community holding an
+/// attachment of it: whoever uploaded, forwarded or reused it, and the communities it was
+/// posted in. It stays counted until the last of their attachments is gone, whichever was
+/// first. Uploads still in progress count at their full size.
+#[derive(Clone)]
+pub struct QuotaService {
+    db: PgPool,
+    /// Quota of users without their own; None for no limit
+    default_user_quota: Option<i64>,
+    /// Quota of communities without their own; None for no limit
+    default_community_quota: Option<i64>,
+}
+
+impl QuotaService {
+    pub fn new(db: PgPool, default_user_quota: Option<i64>, default_community_quota: Option<i64>) -> Self {
+        Self {
+            db,
+            default_user_quota,
+            default_community_quota,
+        }
+    }
+
+    pub async fn user_usage(&self, user_id: Uuid) -> Result<StorageUsageData> {
+        let row = sqlx::query!(
+            r#"
+            SELECT u.storage_quota_bytes,
+                   (SELECT COALESCE(SUM(size_bytes), 0) FROM (
+                        SELECT MAX(size_bytes) AS size_bytes FROM message_attachments
+                        WHERE uploader_id = u.id
+                        GROUP BY file_id
+                    ) files)::BIGINT AS "stored!",
+                   (SELECT COALESCE(SUM(size_bytes), 0) FROM upload_sessions
+                    WHERE user_id = u.id)::BIGINT AS "uploading!"
+            FROM users u
+            WHERE u.id = $1
+            "#,
+            user_id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        Ok(StorageUsageData {
+            used_bytes: row.stored + row.uploading,
+            quota_bytes: row.storage_quota_bytes.or(self.default_user_quota),
+        })
+    }
+
+    pub async fn community_usage(&self, community_id: Uuid) -> Result<StorageUsageData> {
+        let row = sqlx::query!(
+            r#"
+            SELECT c.storage_quota_bytes,
+                   (SELECT COALESCE(SUM(size_bytes), 0) FROM (
+                        SELECT MAX(size_bytes) AS size_bytes FROM message_attachments
+                        WHERE community_id = c.id
+                        GROUP BY file_id
+                    ) files)::BIGINT AS "stored!",
+                   (SELECT COALESCE(SUM(s.size_bytes), 0) FROM upload_sessions s
+                    JOIN channels ch ON ch.id = s.channel_id
+                    WHERE ch.community_id = c.id)::BIGINT AS "uploading!"
+            FROM communities c
+            WHERE c.id = $1
+            "#,
+            community_id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Community not found".to_string()))?;
+
+        Ok(StorageUsageData {
+            used_bytes: row.stored + row.uploading,
+            quota_bytes: row.storage_quota_bytes.or(self.default_community_quota),
+        })
+    }
+
+    /// Check `size` more bytes fit in the quotas of the file's uploader and community
+    pub async fn check(&self, owner: &AttachmentOwner, size: i64) -> Result<()> {
+        let usage = self.user_usage(owner.uploader_id).await?;
+        if let Some(quota) = exceeded_quota(&usage, size) {
+            return Err(AppError::BadRequest(format!(
+                "Not enough storage left: you've used {} MB of your {} MB",
+                usage.used_bytes / 1024 / 1024,
+                quota / 1024 / 1024
+            )));
+        }
+
+        if let Some(community_id) = owner.community_id {
+            self.check_community(community_id, size).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Check `size` more bytes fit in a community's quota
+    pub async fn check_community(&self, community_id: Uuid, size: i64) -> Result<()> {
+        let usage = self.community_usage(community_id).await?;
+        if let Some(quota) = exceeded_quota(&usage, size) {
+            return Err(AppError::BadRequest(format!(
+                "Not enough storage left: this community has used {} MB of its {} MB",
+                usage.used_bytes / 1024 / 1024,
+                quota / 1024 / 1024
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Bytes a set of attachments adds to a quota, counting each stored file once
+pub fn distinct_file_size(attachments: &[MessageAttachment]) -> i64 {
+    let mut seen = HashSet::new();
+    attachments
+        .iter()
+        .filter(|attachment| seen.insert(attachment.file_id))
+        .map(|attachment| attachment.size_bytes)
+        .sum()
+}
+
+/// The quota, when adding `size` bytes would go over it
+fn exceeded_quota(usage: &StorageUsageData, size: i64) -> Option<i64> {
+    usage.quota_bytes.filter(|&quota| usage.used_bytes + size > quota)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn attachment(file_id: Uuid, size_bytes: i64) -> MessageAttachment {
+        let id = Uuid::new_v4();
+        MessageAttachment {
+            id,
+            message_id: None,
+            file_id,
+            filename: "file.bin".to_string(),
+            content_type: "application/octet-stream".to_string(),
+            size_bytes,
+            url: format!("/api/files/{}", id),
+            created_at: Utc::now(),
+            width: None,
+            height: None,
+            duration_secs: None,
+            blurhash: None,
+            has_thumbnail: false,
+        }
+    }
+
+    #[test]
+    fn shared_files_are_counted_once() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        assert_eq!(distinct_file_size(&[]), 0);
+        assert_eq!(distinct_file_size(&[attachment(a, 100)]), 100);
+        assert_eq!(distinct_file_size(&[attachment(a, 100), attachment(b, 50)]), 150);
+        assert_eq!(
+            distinct_file_size(&[attachment(a, 100), attachment(b, 50), attachment(a, 100)]),
+            150
+        );
+    }
+
+    #[test]
+    fn quotas_are_exceeded_past_their_limit() {
+        let usage = |used_bytes, quota_bytes| StorageUsageData { used_bytes, quota_bytes };
+        assert_eq!(exceeded_quota(&usage(1000, None), i64::MAX / 2), None);
+        assert_eq!(exceeded_quota(&usage(900, Some(1000)), 100), None);
+        assert_eq!(exceeded_quota(&usage(900, Some(1000)), 101), Some(1000));
+        assert_eq!(exceeded_quota(&usage(1200, Some(1000)), 0), Some(1000));
+    }
+}
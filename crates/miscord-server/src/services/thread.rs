@@ -0,0 +1,224 @@
+use crate::error::Result;
+use crate::models::ThreadSummary;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// An archived thread and the channel it belongs to
+pub struct ArchivedThread {
+    pub parent_message_id: Uuid,
+    pub channel_id: Uuid,
+}
+
+#[derive(Clone)]
+pub struct ThreadService {
+    db: PgPool,
+}
+
+impl ThreadService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// List threads in a channel, most recently active first.
+    /// Returns active threads, or archived ones when `archived` is true.
+    pub async fn list_by_channel(
+        &self,
+        channel_id: Uuid,
+        user_id: Uuid,
+        archived: bool,
+        limit: i64,
+    ) -> Result<Vec<ThreadSummary>> {
+        let threads = sqlx::query_as!(
+            ThreadSummary,
+            r#"
+            SELECT m.id as parent_message_id, m.channel_id, c.name as channel_name,
+                   m.author_id, u.display_name as author_name, m.content,
+                   m.reply_count, m.last_reply_at, m.thread_archived_at as archived_at,
+                   COALESCE(tm.following, FALSE) as "following!",
+                   CASE WHEN tm.user_id IS NULL THEN 0 ELSE (
+                       SELECT COUNT(*) FROM messages r
+                       WHERE r.thread_parent_id = m.id
+                         AND r.author_id != $2
+                         AND r.created_at > tm.last_read_at
+                   ) END as "unread_count!"
+            FROM messages m
+            INNER JOIN channels c ON c.id = m.channel_id
+            INNER JOIN users u ON u.id = m.author_id
+            LEFT JOIN thread_members tm ON tm.parent_message_id = m.id AND tm.user_id = $2
+            WHERE m.channel_id = $1
+              AND m.reply_count > 0
+              AND (m.thread_archived_at IS NOT NULL) = $3
+            ORDER BY m.last_reply_at DESC NULLS LAST
+            LIMIT $4
+            "#,
+            channel_id,
+            user_id,
+            archived,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(threads)
+    }
+
+    /// List unarchived threads a user follows across all channels
+    pub async fn list_followed(&self, user_id: Uuid, limit: i64) -> Result<Vec<ThreadSummary>> {
+        let threads = sqlx::query_as!(
+            ThreadSummary,
+            r#"
+            SELECT m.id as parent_message_id, m.channel_id, c.name as channel_name,
+                   m.author_id, u.display_name as author_name, m.content,
+                   m.reply_count, m.last_reply_at, m.thread_archived_at as archived_at,
+                   tm.following,
+                   (
+                       SELECT COUNT(*) FROM messages r
+                       WHERE r.thread_parent_id = m.id
+                         AND r.author_id != $1
+                         AND r.created_at > tm.last_read_at
+                   ) as "unread_count!"
+            FROM thread_members tm
+            INNER JOIN messages m ON m.id = tm.parent_message_id
+            INNER JOIN channels c ON c.id = m.channel_id
+            INNER JOIN users u ON u.id = m.author_id
+            WHERE tm.user_id = $1
+              AND tm.following
+              AND m.thread_archived_at IS NULL
+            ORDER BY m.last_reply_at DESC NULLS LAST
+            LIMIT $2
+            "#,
+            user_id,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(threads)
+    }
+
+    /// Follow or unfollow a thread. Unfollowing keeps the read state.
+    pub async fn set_following(
+        &self,
+        parent_message_id: Uuid,
+        user_id: Uuid,
+        following: bool,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO thread_members (parent_message_id, user_id, following, last_read_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (parent_message_id, user_id)
+            DO UPDATE SET following = EXCLUDED.following
+            "#,
+            parent_message_id,
+            user_id,
+            following
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mark all replies in a thread as read for a user
+    pub async fn mark_read(&self, parent_message_id: Uuid, user_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO thread_members (parent_message_id, user_id, following, last_read_at)
+            VALUES ($1, $2, FALSE, NOW())
+            ON CONFLICT (parent_message_id, user_id)
+            DO UPDATE SET last_read_at = NOW()
+            "#,
+            parent_message_id,
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a new reply: unarchive the thread and have the replier and the
+    /// thread starter follow it (unless they unfollowed it before)
+    pub async fn on_reply(
+        &self,
+        parent_message_id: Uuid,
+        parent_author_id: Uuid,
+        reply_author_id: Uuid,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE messages SET thread_archived_at = NULL WHERE id = $1",
+            parent_message_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        // The replier has read everything up to their own reply
+        sqlx::query!(
+            r#"
+            INSERT INTO thread_members (parent_message_id, user_id, following, last_read_at)
+            VALUES ($1, $2, TRUE, NOW())
+            ON CONFLICT (parent_message_id, user_id)
+            DO UPDATE SET last_read_at = NOW()
+            "#,
+            parent_message_id,
+            reply_author_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        if parent_author_id != reply_author_id {
+            // Every reply is unread for the thread starter until they open the thread
+            sqlx::query!(
+                r#"
+                INSERT INTO thread_members (parent_message_id, user_id, following, last_read_at)
+                SELECT $1, $2, TRUE, created_at FROM messages WHERE id = $1
+                ON CONFLICT (parent_message_id, user_id) DO NOTHING
+                "#,
+                parent_message_id,
+                parent_author_id
+            )
+            .execute(&self.db)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get users following a thread
+    pub async fn get_followers(&self, parent_message_id: Uuid) -> Result<Vec<Uuid>> {
+        let followers = sqlx::query_scalar!(
+            "SELECT user_id FROM thread_members WHERE parent_message_id = $1 AND following",
+            parent_message_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(followers)
+    }
+
+    /// Archive threads with no replies for `inactive_days` days
+    pub async fn archive_inactive(&self, inactive_days: i32) -> Result<Vec<ArchivedThread>> {
+        let archived = sqlx::query!(
+            r#"
+            UPDATE messages
+            SET thread_archived_at = NOW()
+            WHERE reply_count > 0
+              AND thread_archived_at IS NULL
+              AND COALESCE(last_reply_at, created_at) < NOW() - make_interval(days => $1)
+            RETURNING id, channel_id
+            "#,
+            inactive_days
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(archived
+            .into_iter()
+            .map(|row| ArchivedThread {
+                parent_message_id: row.id,
+                channel_id: row.channel_id,
+            })
+            .collect())
+    }
+}
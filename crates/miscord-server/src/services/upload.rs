@@ -0,0 +1,255 @@
+use crate::error::{AppError, Result};
+use crate::models::{Channel, UploadSession};
+use crate::storage::{attachment_key, StorageBackend};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Size of each chunk of a resumable upload. S3 needs every part but the last to be at least 5 MB.
+pub const UPLOAD_CHUNK_SIZE: i64 = 8 * 1024 * 1024;
+
+/// How long an upload with no new chunks is kept before it's discarded, in seconds
+const SESSION_TTL_SECS: f64 = 24.0 * 60.0 * 60.0;
+
+#[derive(Clone)]
+pub struct UploadService {
+    db: PgPool,
+    storage: Arc<dyn StorageBackend>,
+    /// Limit for DMs and communities that haven't set their own
+    default_limit: i64,
+    /// Highest limit a community may set
+    max_limit: i64,
+}
+
+impl UploadService {
+    pub fn new(db: PgPool, storage: Arc<dyn StorageBackend>, default_limit: i64, max_limit: i64) -> Self {
+        Self {
+            db,
+            storage,
+            default_limit,
+            max_limit,
+        }
+    }
+
+    pub fn max_limit(&self) -> i64 {
+        self.max_limit
+    }
+
+    /// A community's own upload limit, if it has set one
+    pub async fn community_limit(&self, community_id: Uuid) -> Result<Option<i64>> {
+        let limit = sqlx::query_scalar!(
+            "SELECT max_upload_bytes FROM communities WHERE id = $1",
+            community_id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Community not found".to_string()))?;
+
+        Ok(limit)
+    }
+
+    /// The upload limit members of a community have, capped at the server maximum
+    pub async fn effective_community_limit(&self, community_id: Uuid) -> Result<i64> {
+        let limit = self.community_limit(community_id).await?;
+        Ok(limit.unwrap_or(self.default_limit).min(self.max_limit))
+    }
+
+    /// Largest file that may be attached in a channel
+    pub async fn limit_for_channel(&self, channel: &Channel) -> Result<i64> {
+        match channel.community_id {
+            Some(community_id) => self.effective_community_limit(community_id).await,
+            None => Ok(self.default_limit.min(self.max_limit)),
+        }
+    }
+
+    pub async fn set_community_limit(&self, community_id: Uuid, limit: Option<i64>) -> Result<()> {
+        if let Some(limit) = limit {
+            if !(1..=self.max_limit).contains(&limit) {
+                return Err(AppError::BadRequest(format!(
+                    "Upload limit must be between 1 byte and {} MB",
+                    self.max_limit / 1024 / 1024
+                )));
+            }
+        }
+
+        sqlx::query!(
+            "UPDATE communities SET max_upload_bytes = $2, updated_at = NOW() WHERE id = $1",
+            community_id,
+            limit
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Start an upload. The file will be stored under the session's ID.
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        channel_id: Uuid,
+        filename: &str,
+        content_type: &str,
+        size_bytes: i64,
+    ) -> Result<UploadSession> {
+        let id = Uuid::new_v4();
+        let storage_upload_id = self
+            .storage
+            .create_multipart(&attachment_key(id, filename), content_type)
+            .await?;
+
+        let session = sqlx::query_as!(
+            UploadSession,
+            r#"
+            INSERT INTO upload_sessions (id, user_id, channel_id, filename, content_type, size_bytes, chunk_size, storage_upload_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, user_id, channel_id, filename, content_type, size_bytes, chunk_size,
+                      received_bytes, storage_upload_id, part_tags, created_at, updated_at
+            "#,
+            id,
+            user_id,
+            channel_id,
+            filename,
+            content_type,
+            size_bytes,
+            UPLOAD_CHUNK_SIZE,
+            storage_upload_id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(session)
+    }
+
+    /// A user's upload; other people's uploads are reported as not found
+    pub async fn get(&self, id: Uuid, user_id: Uuid) -> Result<UploadSession> {
+        sqlx::query_as!(
+            UploadSession,
+            r#"
+            SELECT id, user_id, channel_id, filename, content_type, size_bytes, chunk_size,
+                   received_bytes, storage_upload_id, part_tags, created_at, updated_at
+            FROM upload_sessions WHERE id = $1 AND user_id = $2
+            "#,
+            id,
+            user_id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Upload not found".to_string()))
+    }
+
+    /// Store the chunk starting at `offset`, which must be where the last chunk ended.
+    /// Every chunk is `chunk_size` long except the one that finishes the file.
+    pub async fn append(&self, session: &UploadSession, offset: i64, data: &[u8]) -> Result<UploadSession> {
+        if offset != session.received_bytes {
+            return Err(AppError::Conflict(format!(
+                "Upload is at offset {}, not {}",
+                session.received_bytes, offset
+            )));
+        }
+
+        let len = data.len() as i64;
+        let end = offset + len;
+        if len == 0 || end > session.size_bytes {
+            return Err(AppError::BadRequest("Chunk doesn't fit in the file".to_string()));
+        }
+        if len != session.chunk_size && end != session.size_bytes {
+            return Err(AppError::BadRequest(format!(
+                "Chunks must be {} bytes except the last",
+                session.chunk_size
+            )));
+        }
+
+        let part_number = (offset / session.chunk_size) as i32 + 1;
+        let tag = self
+            .storage
+            .upload_part(
+                &attachment_key(session.id, &session.filename),
+                &session.storage_upload_id,
+                part_number,
+                data,
+            )
+            .await?;
+
+        // Only advance from the offset checked above, in case the same chunk was sent twice at once
+        sqlx::query_as!(
+            UploadSession,
+            r#"
+            UPDATE upload_sessions
+            SET received_bytes = $3, part_tags = array_append(part_tags, $4), updated_at = NOW()
+            WHERE id = $1 AND received_bytes = $2
+            RETURNING id, user_id, channel_id, filename, content_type, size_bytes, chunk_size,
+                      received_bytes, storage_upload_id, part_tags, created_at, updated_at
+            "#,
+            session.id,
+            offset,
+            end,
+            tag
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::Conflict("Chunk was already received".to_string()))
+    }
+
+    /// Assemble a fully received upload into its file in storage and forget the session
+    pub async fn complete(&self, session: &UploadSession) -> Result<()> {
+        if session.received_bytes != session.size_bytes {
+            return Err(AppError::BadRequest(format!(
+                "Upload is incomplete: {} of {} bytes received",
+                session.received_bytes, session.size_bytes
+            )));
+        }
+
+        self.storage
+            .complete_multipart(
+                &attachment_key(session.id, &session.filename),
+                &session.storage_upload_id,
+                &session.part_tags,
+            )
+            .await?;
+
+        sqlx::query!("DELETE FROM upload_sessions WHERE id = $1", session.id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Abandon an upload and discard what was received
+    pub async fn cancel(&self, session: &UploadSession) -> Result<()> {
+        self.storage
+            .abort_multipart(
+                &attachment_key(session.id, &session.filename),
+                &session.storage_upload_id,
+            )
+            .await?;
+
+        sqlx::query!("DELETE FROM upload_sessions WHERE id = $1", session.id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Discard uploads that haven't received a chunk in a day
+    pub async fn purge_expired(&self) -> Result<usize> {
+        let expired = sqlx::query_as!(
+            UploadSession,
+            r#"
+            SELECT id, user_id, channel_id, filename, content_type, size_bytes, chunk_size,
+                   received_bytes, storage_upload_id, part_tags, created_at, updated_at
+            FROM upload_sessions
+            WHERE updated_at < NOW() - make_interval(secs => $1)
+            "#,
+            SESSION_TTL_SECS
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        for session in &expired {
+            self.cancel(session).await?;
+        }
+
+        Ok(expired.len())
+    }
+}
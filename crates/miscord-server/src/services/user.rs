@@ -1,5 +1,7 @@
 use crate::error::{AppError, Result};
-use crate::models::{CreateUser, PublicUser, UpdateUser, User, UserStatus};
+use crate::models::{
+    CreateUser, PrivacySettings, PublicUser, UpdatePrivacySettings, UpdateUser, User, UserStatus,
+};
 use argon2::{
     password_hash::{rand_core::OsRng, SaltString},
     Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
@@ -46,7 +48,7 @@ impl UserService {
             INSERT INTO users (id, username, display_name, email, password_hash, status, created_at, updated_at)
             VALUES ($1, $2, $3, $4, $5, $6, NOW(), NOW())
             RETURNING id, username, display_name, email, password_hash, avatar_url,
-                      status as "status: UserStatus", custom_status, created_at, updated_at
+                      status as "status: UserStatus", custom_status, bot, created_at, updated_at
             "#,
             Uuid::new_v4(),
             input.username,
@@ -66,7 +68,7 @@ impl UserService {
             User,
             r#"
             SELECT id, username, display_name, email, password_hash, avatar_url,
-                   status as "status: UserStatus", custom_status, created_at, updated_at
+                   status as "status: UserStatus", custom_status, bot, created_at, updated_at
             FROM users WHERE id = $1
             "#,
             id
@@ -78,12 +80,29 @@ impl UserService {
         Ok(user)
     }
 
+    /// Get several users in one query; unknown IDs are skipped
+    pub async fn get_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>> {
+        let users = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, display_name, email, password_hash, avatar_url,
+                   status as "status: UserStatus", custom_status, bot, created_at, updated_at
+            FROM users WHERE id = ANY($1)
+            "#,
+            ids
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(users)
+    }
+
     pub async fn get_by_username(&self, username: &str) -> Result<User> {
         let user = sqlx::query_as!(
             User,
             r#"
             SELECT id, username, display_name, email, password_hash, avatar_url,
-                   status as "status: UserStatus", custom_status, created_at, updated_at
+                   status as "status: UserStatus", custom_status, bot, created_at, updated_at
             FROM users WHERE username = $1
             "#,
             username
@@ -98,6 +117,11 @@ impl UserService {
     pub async fn verify_credentials(&self, username: &str, password: &str) -> Result<User> {
         let user = self.get_by_username(username).await?;
 
+        // Placeholder, webhook and bot users have no password and cannot log in
+        if user.password_hash.is_empty() {
+            return Err(AppError::Unauthorized);
+        }
+
         let parsed_hash = PasswordHash::new(&user.password_hash)
             .map_err(|e| AppError::Internal(anyhow::anyhow!("Invalid password hash: {}", e)))?;
 
@@ -119,7 +143,7 @@ impl UserService {
                 updated_at = NOW()
             WHERE id = $1
             RETURNING id, username, display_name, email, password_hash, avatar_url,
-                      status as "status: UserStatus", custom_status, created_at, updated_at
+                      status as "status: UserStatus", custom_status, bot, created_at, updated_at
             "#,
             id,
             input.display_name,
@@ -145,12 +169,49 @@ impl UserService {
         Ok(())
     }
 
+    pub async fn get_privacy_settings(&self, id: Uuid) -> Result<PrivacySettings> {
+        let settings = sqlx::query_as!(
+            PrivacySettings,
+            "SELECT read_receipts_enabled FROM users WHERE id = $1",
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        Ok(settings)
+    }
+
+    pub async fn update_privacy_settings(
+        &self,
+        id: Uuid,
+        input: UpdatePrivacySettings,
+    ) -> Result<PrivacySettings> {
+        let settings = sqlx::query_as!(
+            PrivacySettings,
+            r#"
+            UPDATE users
+            SET read_receipts_enabled = COALESCE($2, read_receipts_enabled),
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING read_receipts_enabled
+            "#,
+            id,
+            input.read_receipts_enabled
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        Ok(settings)
+    }
+
     pub async fn get_friends(&self, user_id: Uuid) -> Result<Vec<PublicUser>> {
         let friends = sqlx::query_as!(
             PublicUser,
             r#"
             SELECT u.id, u.username, u.display_name, u.avatar_url,
-                   u.status as "status: UserStatus", u.custom_status
+                   u.status as "status: UserStatus", u.custom_status, u.bot
             FROM users u
             INNER JOIN friendships f ON (f.user1_id = u.id OR f.user2_id = u.id)
             WHERE (f.user1_id = $1 OR f.user2_id = $1) AND u.id != $1
@@ -0,0 +1,263 @@
+use crate::error::{AppError, Result};
+use crate::models::{CreateMessage, Message, UserStatus, Webhook, WebhookMessage};
+use crate::services::message::MessageService;
+use rand::Rng;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const TOKEN_LENGTH: usize = 64;
+
+fn generate_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+#[derive(Clone)]
+pub struct WebhookService {
+    db: PgPool,
+}
+
+impl WebhookService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Create a webhook together with the user it posts as
+    pub async fn create(
+        &self,
+        channel_id: Uuid,
+        name: &str,
+        avatar_url: Option<&str>,
+        created_by_id: Uuid,
+    ) -> Result<Webhook> {
+        let id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let mut tx = self.db.begin().await?;
+
+        // An empty password hash can never be verified, so webhook users cannot log in
+        sqlx::query!(
+            r#"
+            INSERT INTO users (id, username, display_name, email, password_hash, avatar_url, status, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, '', $5, $6, NOW(), NOW())
+            "#,
+            user_id,
+            format!("webhook-{}", &user_id.simple().to_string()[..12]),
+            name,
+            format!("{}@webhook.invalid", user_id),
+            avatar_url,
+            UserStatus::Offline as UserStatus,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let webhook = sqlx::query_as!(
+            Webhook,
+            r#"
+            INSERT INTO webhooks (id, channel_id, user_id, name, avatar_url, token, created_by_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+            RETURNING id, channel_id, user_id, name, avatar_url, token, created_by_id, created_at
+            "#,
+            id,
+            channel_id,
+            user_id,
+            name,
+            avatar_url,
+            generate_token(),
+            created_by_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(webhook)
+    }
+
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Webhook> {
+        sqlx::query_as!(
+            Webhook,
+            "SELECT id, channel_id, user_id, name, avatar_url, token, created_by_id, created_at FROM webhooks WHERE id = $1",
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook not found".to_string()))
+    }
+
+    /// Look up a webhook by its URL; a wrong token is reported as not found
+    pub async fn get_by_token(&self, id: Uuid, token: &str) -> Result<Webhook> {
+        let webhook = self.get_by_id(id).await?;
+
+        // Compare without short-circuiting so the token can't be guessed by timing
+        let matches = webhook.token.len() == token.len()
+            && webhook
+                .token
+                .bytes()
+                .zip(token.bytes())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0;
+        if !matches {
+            return Err(AppError::NotFound("Webhook not found".to_string()));
+        }
+
+        Ok(webhook)
+    }
+
+    pub async fn list_by_channel(&self, channel_id: Uuid) -> Result<Vec<Webhook>> {
+        let webhooks = sqlx::query_as!(
+            Webhook,
+            r#"
+            SELECT id, channel_id, user_id, name, avatar_url, token, created_by_id, created_at
+            FROM webhooks WHERE channel_id = $1
+            ORDER BY created_at
+            "#,
+            channel_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(webhooks)
+    }
+
+    /// Rename a webhook or change its avatar; its user is kept in sync
+    pub async fn update(&self, id: Uuid, name: Option<&str>, avatar_url: Option<&str>) -> Result<Webhook> {
+        let mut tx = self.db.begin().await?;
+
+        let webhook = sqlx::query_as!(
+            Webhook,
+            r#"
+            UPDATE webhooks
+            SET name = COALESCE($2, name),
+                avatar_url = CASE WHEN $3::text IS NULL THEN avatar_url ELSE NULLIF($3, '') END
+            WHERE id = $1
+            RETURNING id, channel_id, user_id, name, avatar_url, token, created_by_id, created_at
+            "#,
+            id,
+            name,
+            avatar_url
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook not found".to_string()))?;
+
+        sqlx::query!(
+            "UPDATE users SET display_name = $2, avatar_url = $3, updated_at = NOW() WHERE id = $1",
+            webhook.user_id,
+            webhook.name,
+            webhook.avatar_url
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(webhook)
+    }
+
+    /// Replace the token, invalidating the old URL
+    pub async fn regenerate_token(&self, id: Uuid) -> Result<Webhook> {
+        sqlx::query_as!(
+            Webhook,
+            r#"
+            UPDATE webhooks SET token = $2 WHERE id = $1
+            RETURNING id, channel_id, user_id, name, avatar_url, token, created_by_id, created_at
+            "#,
+            id,
+            generate_token()
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook not found".to_string()))
+    }
+
+    /// Delete a webhook. Its messages stay, still attributed to the webhook's user.
+    pub async fn delete(&self, id: Uuid) -> Result<()> {
+        let result = sqlx::query!("DELETE FROM webhooks WHERE id = $1", id)
+            .execute(&self.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Webhook not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Post a message as a webhook. The message, its embeds, the name and avatar it was
+    /// posted under and its files (already stored as unsent uploads) appear together or
+    /// not at all.
+    pub async fn post_message(
+        &self,
+        webhook: &Webhook,
+        username: &str,
+        avatar_url: Option<&str>,
+        input: CreateMessage,
+    ) -> Result<(Message, WebhookMessage)> {
+        let mut tx = self.db.begin().await?;
+
+        let message = MessageService::insert(&mut tx, webhook.channel_id, webhook.user_id, &input).await?;
+
+        let webhook_message = sqlx::query_as!(
+            WebhookMessage,
+            r#"
+            INSERT INTO webhook_messages (message_id, webhook_id, username, avatar_url)
+            VALUES ($1, $2, $3, $4)
+            RETURNING message_id, webhook_id, username, avatar_url
+            "#,
+            message.id,
+            webhook.id,
+            username,
+            avatar_url
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if !input.attachment_ids.is_empty() {
+            let linked = sqlx::query!(
+                r#"
+                UPDATE message_attachments SET message_id = $1
+                WHERE id = ANY($2) AND message_id IS NULL AND uploader_id = $3
+                "#,
+                message.id,
+                &input.attachment_ids,
+                webhook.user_id
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+            // Unsent uploads are purged after a while, so one may have gone meanwhile
+            if linked != input.attachment_ids.len() as u64 {
+                return Err(AppError::Conflict("Uploaded files are no longer available".to_string()));
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok((message, webhook_message))
+    }
+
+    /// Webhook names and avatars for the messages that were posted through one
+    pub async fn get_messages(&self, message_ids: &[Uuid]) -> Result<HashMap<Uuid, WebhookMessage>> {
+        if message_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let messages = sqlx::query_as!(
+            WebhookMessage,
+            r#"
+            SELECT message_id, webhook_id, username, avatar_url
+            FROM webhook_messages WHERE message_id = ANY($1)
+            "#,
+            message_ids
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(messages.into_iter().map(|m| (m.message_id, m)).collect())
+    }
+}
@@ -1,8 +1,13 @@
 use crate::services::{
-    attachment::AttachmentService, channel::ChannelService, message::MessageService,
-    user::UserService,
+    announcement::AnnouncementService, attachment::AttachmentService, bot::BotService,
+    channel::ChannelService, emoji::EmojiService, event_webhook::EventWebhookService,
+    export::ExportService, forum::ForumService, import::ImportService,
+    interaction::InteractionService, link_preview::LinkPreviewService,
+    media_proxy::MediaProxyService, message::MessageService, quota::QuotaService,
+    thread::ThreadService, upload::UploadService, user::UserService, webhook::WebhookService,
 };
 use crate::sfu::SfuSessionManager;
+use crate::storage::{self, S3Config, StorageKind};
 use crate::ws::connections::ConnectionManager;
 use sqlx::PgPool;
 use std::path::PathBuf;
@@ -18,6 +23,24 @@ pub struct Config {
     pub upload_dir: PathBuf,
     pub base_url: String,
     pub tenor_api_key: Option<String>,
+    /// Threads with no replies for this many days are archived
+    pub thread_auto_archive_days: i32,
+    /// Where attachment files are stored
+    pub storage_backend: StorageKind,
+    /// Bucket settings, when S3_BUCKET is set
+    pub s3: Option<S3Config>,
+    /// Largest attachment, in bytes, for DMs and communities that haven't set their own
+    pub upload_limit_bytes: i64,
+    /// Highest upload limit a community may set, in bytes
+    pub max_upload_limit_bytes: i64,
+    /// Remove EXIF and other metadata from uploaded JPEG, PNG and WebP images
+    pub strip_image_metadata: bool,
+    /// Attachment storage each user may use, in bytes, unless they have their own quota.
+    /// None for no limit.
+    pub user_storage_quota_bytes: Option<i64>,
+    /// Attachment storage each community may use, in bytes, unless it has its own quota.
+    /// None for no limit.
+    pub community_storage_quota_bytes: Option<i64>,
 }
 
 #[derive(Clone)]
@@ -57,6 +80,42 @@ impl Config {
             tracing::info!("TENOR_API_KEY not set, GIF search will be disabled");
         }
 
+        let thread_auto_archive_days = std::env::var("THREAD_AUTO_ARCHIVE_DAYS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(7);
+
+        let storage_backend = match std::env::var("STORAGE_BACKEND") {
+            Ok(name) => StorageKind::parse(&name)
+                .ok_or_else(|| anyhow::anyhow!("Unknown STORAGE_BACKEND {}; expected local or s3", name))?,
+            Err(_) => StorageKind::Local,
+        };
+        let s3 = S3Config::from_env()?;
+        if storage_backend == StorageKind::S3 && s3.is_none() {
+            anyhow::bail!("STORAGE_BACKEND=s3 requires S3_BUCKET to be set");
+        }
+
+        let upload_limit_mb: i64 = std::env::var("UPLOAD_LIMIT_MB")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(100);
+        let max_upload_limit_mb: i64 = std::env::var("MAX_UPLOAD_LIMIT_MB")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(2048);
+
+        let strip_image_metadata = std::env::var("STRIP_IMAGE_METADATA")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(true);
+
+        let user_storage_quota_mb: Option<i64> = std::env::var("USER_STORAGE_QUOTA_MB")
+            .ok()
+            .and_then(|s| s.parse().ok());
+        let community_storage_quota_mb: Option<i64> = std::env::var("COMMUNITY_STORAGE_QUOTA_MB")
+            .ok()
+            .and_then(|s| s.parse().ok());
+
         Ok(Config {
             bind_address,
             database_url,
@@ -66,6 +125,14 @@ impl Config {
             upload_dir,
             base_url,
             tenor_api_key,
+            thread_auto_archive_days,
+            storage_backend,
+            s3,
+            upload_limit_bytes: upload_limit_mb * 1024 * 1024,
+            max_upload_limit_bytes: max_upload_limit_mb * 1024 * 1024,
+            strip_image_metadata,
+            user_storage_quota_bytes: user_storage_quota_mb.map(|mb| mb * 1024 * 1024),
+            community_storage_quota_bytes: community_storage_quota_mb.map(|mb| mb * 1024 * 1024),
         })
     }
 }
@@ -79,20 +146,64 @@ pub struct AppState {
     pub channel_service: ChannelService,
     pub message_service: MessageService,
     pub attachment_service: AttachmentService,
+    pub upload_service: UploadService,
+    pub quota_service: QuotaService,
+    pub emoji_service: EmojiService,
+    pub thread_service: ThreadService,
+    pub forum_service: ForumService,
+    pub announcement_service: AnnouncementService,
+    pub export_service: ExportService,
+    pub import_service: ImportService,
+    pub webhook_service: WebhookService,
+    pub bot_service: BotService,
+    pub interaction_service: InteractionService,
+    pub event_webhook_service: EventWebhookService,
+    pub link_preview_service: LinkPreviewService,
+    pub media_proxy_service: MediaProxyService,
     pub sfu: Arc<SfuSessionManager>,
 }
 
 impl AppState {
-    pub fn new(config: Config, db: PgPool) -> Self {
+    pub fn new(config: Config, db: PgPool) -> anyhow::Result<Self> {
         let connections = Arc::new(ConnectionManager::new());
         let user_service = UserService::new(db.clone());
         let channel_service = ChannelService::new(db.clone());
         let message_service = MessageService::new(db.clone());
+        let storage = storage::open(config.storage_backend, config.upload_dir.clone(), config.s3.as_ref())?;
         let attachment_service = AttachmentService::new(
             db.clone(),
-            config.upload_dir.clone(),
+            storage.clone(),
             config.base_url.clone(),
+            config.upload_dir.join("tmp"),
+            config.strip_image_metadata,
+        );
+        let upload_service = UploadService::new(
+            db.clone(),
+            storage,
+            config.upload_limit_bytes,
+            config.max_upload_limit_bytes,
+        );
+        let quota_service = QuotaService::new(
+            db.clone(),
+            config.user_storage_quota_bytes,
+            config.community_storage_quota_bytes,
         );
+        let emoji_service = EmojiService::new(db.clone());
+        let thread_service = ThreadService::new(db.clone());
+        let forum_service = ForumService::new(db.clone());
+        let announcement_service = AnnouncementService::new(db.clone());
+        let export_service = ExportService::new(
+            db.clone(),
+            storage.clone(),
+            config.upload_dir.join("tmp").join("exports"),
+        );
+        let import_service = ImportService::new(db.clone());
+        let webhook_service = WebhookService::new(db.clone());
+        let bot_service = BotService::new(db.clone());
+        let interaction_service = InteractionService::new(db.clone());
+        let event_webhook_service = EventWebhookService::new(db.clone());
+        let link_preview_service = LinkPreviewService::new(db.clone());
+        let media_proxy_service = MediaProxyService::new(config.upload_dir.join("media-cache"));
 
         // Create SFU session manager with ICE servers from config
         let turn_servers: Vec<(String, String, String)> = config
@@ -104,7 +215,7 @@ impl AppState {
         let sfu = SfuSessionManager::new(config.stun_servers.clone(), turn_servers)
             .expect("Failed to create SFU session manager");
 
-        Self {
+        Ok(Self {
             config,
             db,
             connections,
@@ -112,7 +223,21 @@ impl AppState {
             channel_service,
             message_service,
             attachment_service,
+            upload_service,
+            quota_service,
+            emoji_service,
+            thread_service,
+            forum_service,
+            announcement_service,
+            export_service,
+            import_service,
+            webhook_service,
+            bot_service,
+            interaction_service,
+            event_webhook_service,
+            link_preview_service,
+            media_proxy_service,
             sfu: Arc::new(sfu),
-        }
+        })
     }
 }
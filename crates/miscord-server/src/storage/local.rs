@@ -0,0 +1,177 @@
+use super::{ByteStream, DownloadHeaders, StorageBackend, StoredFile};
+use crate::error::{AppError, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use tokio::fs;
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+/// Files kept in a directory on this server
+pub struct LocalStorage {
+    dir: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(key)
+    }
+
+    /// Directory holding the parts of an unfinished upload
+    fn parts_dir(&self, upload_id: &str) -> Result<PathBuf> {
+        // Upload IDs are ours, but they end up in a path so make sure of it
+        let upload_id = Uuid::parse_str(upload_id)
+            .map_err(|_| AppError::BadRequest("Invalid upload ID".to_string()))?;
+        Ok(self.dir.join(".uploads").join(upload_id.to_string()))
+    }
+}
+
+/// Create the directory a file goes in, for keys with a prefix such as `exports/`
+async fn create_parent(path: &std::path::Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await.map_err(write_error)?;
+    }
+    Ok(())
+}
+
+fn write_error(e: std::io::Error) -> AppError {
+    AppError::Internal(anyhow::anyhow!("Failed to write file: {}", e))
+}
+
+fn read_error(e: std::io::Error) -> AppError {
+    if e.kind() == ErrorKind::NotFound {
+        AppError::NotFound("File not found".to_string())
+    } else {
+        AppError::Internal(anyhow::anyhow!("Failed to read file: {}", e))
+    }
+}
+
+#[async_trait]
+impl StorageBackend for LocalStorage {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn ensure_ready(&self) -> Result<()> {
+        fs::create_dir_all(&self.dir).await.map_err(|e| {
+            AppError::Internal(anyhow::anyhow!("Failed to create upload directory: {}", e))
+        })?;
+        Ok(())
+    }
+
+    async fn put(&self, key: &str, data: &[u8], _content_type: &str) -> Result<()> {
+        let path = self.path(key);
+        create_parent(&path).await?;
+        fs::write(path, data).await.map_err(write_error)
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        fs::read(self.path(key)).await.map_err(read_error)
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<ByteStream> {
+        let file = fs::File::open(self.path(key)).await.map_err(read_error)?;
+        Ok(ReaderStream::new(file).boxed())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        fs::try_exists(self.path(key))
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to check file: {}", e)))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match fs::remove_file(self.path(key)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::Internal(anyhow::anyhow!("Failed to delete file: {}", e))),
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<StoredFile>> {
+        let list_error = |e: std::io::Error| AppError::Internal(anyhow::anyhow!("Failed to list files: {}", e));
+
+        let mut entries = match fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            // Nothing has been uploaded yet
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(list_error(e)),
+        };
+
+        let mut files = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(list_error)? {
+            let metadata = entry.metadata().await.map_err(list_error)?;
+            if !metadata.is_file() {
+                continue;
+            }
+            let Ok(key) = entry.file_name().into_string() else {
+                continue;
+            };
+            let modified = metadata.modified().map_err(list_error)?;
+            files.push(StoredFile {
+                key,
+                modified: DateTime::<Utc>::from(modified),
+            });
+        }
+
+        Ok(files)
+    }
+
+    async fn create_multipart(&self, _key: &str, _content_type: &str) -> Result<String> {
+        let upload_id = Uuid::new_v4().to_string();
+        fs::create_dir_all(self.parts_dir(&upload_id)?)
+            .await
+            .map_err(write_error)?;
+        Ok(upload_id)
+    }
+
+    async fn upload_part(&self, _key: &str, upload_id: &str, part_number: i32, data: &[u8]) -> Result<String> {
+        let path = self.parts_dir(upload_id)?.join(part_number.to_string());
+        fs::write(path, data).await.map_err(write_error)?;
+        // Parts are found by number, so there's nothing to remember about them
+        Ok(String::new())
+    }
+
+    async fn complete_multipart(&self, key: &str, upload_id: &str, part_tags: &[String]) -> Result<()> {
+        let parts_dir = self.parts_dir(upload_id)?;
+
+        // Join the parts next to them, then move the whole file into place at once
+        let joined_path = parts_dir.join("joined");
+        let mut joined = fs::File::create(&joined_path).await.map_err(write_error)?;
+        for part_number in 1..=part_tags.len() {
+            let mut part = fs::File::open(parts_dir.join(part_number.to_string()))
+                .await
+                .map_err(read_error)?;
+            tokio::io::copy(&mut part, &mut joined).await.map_err(write_error)?;
+        }
+        joined.sync_all().await.map_err(write_error)?;
+        drop(joined);
+
+        let path = self.path(key);
+        create_parent(&path).await?;
+        fs::rename(&joined_path, path).await.map_err(write_error)?;
+        self.abort_multipart(key, upload_id).await
+    }
+
+    async fn abort_multipart(&self, _key: &str, upload_id: &str) -> Result<()> {
+        match fs::remove_dir_all(self.parts_dir(upload_id)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::Internal(anyhow::anyhow!("Failed to delete upload parts: {}", e))),
+        }
+    }
+
+    fn presigned_url(&self, _key: &str, _headers: &DownloadHeaders<'_>) -> Option<String> {
+        None
+    }
+
+    fn local_path(&self, key: &str) -> Option<PathBuf> {
+        Some(self.path(key))
+    }
+}
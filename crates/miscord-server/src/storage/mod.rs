@@ -0,0 +1,220 @@
+//! Where attachment files are kept: the local upload directory, or an S3-compatible
+//! bucket so several server instances can share them
+
+mod local;
+mod s3;
+
+pub use local::LocalStorage;
+pub use s3::{S3Config, S3Storage};
+
+use crate::error::{AppError, Result};
+use async_trait::async_trait;
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
+use sqlx::PgPool;
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Contents of a stored file, read in chunks
+pub type ByteStream = BoxStream<'static, std::io::Result<Bytes>>;
+
+/// Which backend files are stored in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageKind {
+    Local,
+    S3,
+}
+
+impl StorageKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "local" => Some(Self::Local),
+            "s3" => Some(Self::S3),
+            _ => None,
+        }
+    }
+}
+
+/// A file found by listing a backend
+#[derive(Debug, Clone)]
+pub struct StoredFile {
+    pub key: String,
+    pub modified: DateTime<Utc>,
+}
+
+/// Headers a direct download is served with
+pub struct DownloadHeaders<'a> {
+    pub content_type: &'a str,
+    pub content_disposition: &'a str,
+}
+
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+
+    /// Prepare for the first write, such as creating directories
+    async fn ensure_ready(&self) -> Result<()>;
+
+    async fn put(&self, key: &str, data: &[u8], content_type: &str) -> Result<()>;
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>>;
+
+    async fn get_stream(&self, key: &str) -> Result<ByteStream>;
+
+    async fn exists(&self, key: &str) -> Result<bool>;
+
+    /// Delete a file; one that's already gone is not an error
+    async fn delete(&self, key: &str) -> Result<()>;
+
+    /// Every file at the top level, where attachments and thumbnails are kept. Anything
+    /// under a directory or prefix, such as unfinished uploads, is left out.
+    async fn list(&self) -> Result<Vec<StoredFile>>;
+
+    /// Start writing a file in parts, returning the backend's ID for the upload
+    async fn create_multipart(&self, key: &str, content_type: &str) -> Result<String>;
+
+    /// Store part `part_number`, counting from 1. Uploading a part again replaces it.
+    /// Returns the tag `complete_multipart` needs for the part.
+    async fn upload_part(&self, key: &str, upload_id: &str, part_number: i32, data: &[u8]) -> Result<String>;
+
+    /// Join the parts, in order, into the file at `key`
+    async fn complete_multipart(&self, key: &str, upload_id: &str, part_tags: &[String]) -> Result<()>;
+
+    /// Discard an unfinished upload's parts; one that's already gone is not an error
+    async fn abort_multipart(&self, key: &str, upload_id: &str) -> Result<()>;
+
+    /// A time-limited URL clients can download the file from directly, when the backend has one
+    fn presigned_url(&self, key: &str, headers: &DownloadHeaders<'_>) -> Option<String>;
+
+    /// The file's path when it's on this server's disk
+    fn local_path(&self, key: &str) -> Option<PathBuf>;
+}
+
+/// Storage key of an attachment's file: its file ID and lowercased extension
+pub fn attachment_key(file_id: Uuid, filename: &str) -> String {
+    let extension = filename
+        .rsplit('.')
+        .next()
+        .map(|s| format!(".{}", s.to_lowercase()))
+        .unwrap_or_default();
+
+    format!("{}{}", file_id, extension)
+}
+
+/// Storage key of the thumbnail kept alongside an image or video file
+pub fn thumbnail_key(file_id: Uuid) -> String {
+    format!("{}.thumb", file_id)
+}
+
+/// The file ID a storage key belongs to, when it's an attachment's file or thumbnail
+pub fn key_file_id(key: &str) -> Option<Uuid> {
+    key.split('.').next().and_then(|id| Uuid::parse_str(id).ok())
+}
+
+/// Create a backend; S3 needs its bucket settings
+pub fn open(kind: StorageKind, upload_dir: PathBuf, s3: Option<&S3Config>) -> Result<Arc<dyn StorageBackend>> {
+    match (kind, s3) {
+        (StorageKind::S3, Some(s3)) => Ok(Arc::new(S3Storage::new(s3.clone()))),
+        (StorageKind::S3, None) => Err(AppError::Internal(anyhow::anyhow!(
+            "S3 storage selected without S3 configuration; set S3_BUCKET"
+        ))),
+        (StorageKind::Local, _) => Ok(Arc::new(LocalStorage::new(upload_dir))),
+    }
+}
+
+/// Outcome of moving files between backends
+#[derive(Debug, Default)]
+pub struct MigrationSummary {
+    pub copied: usize,
+    /// Already present in the destination
+    pub skipped: usize,
+    pub missing: usize,
+    pub failed: usize,
+}
+
+/// Copy every attachment file from one backend to another, optionally deleting the
+/// originals. Files already in the destination are left alone, so an interrupted
+/// migration can simply be run again.
+pub async fn migrate(
+    db: &PgPool,
+    from: &dyn StorageBackend,
+    to: &dyn StorageBackend,
+    delete_source: bool,
+) -> Result<MigrationSummary> {
+    to.ensure_ready().await?;
+
+    // Forwarded copies share a file, so each file is listed once
+    let files = sqlx::query!(
+        r#"
+        SELECT DISTINCT ON (file_id) file_id, filename, content_type, has_thumbnail
+        FROM message_attachments
+        ORDER BY file_id
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut summary = MigrationSummary::default();
+    for file in files {
+        let mut keys = vec![(attachment_key(file.file_id, &file.filename), file.content_type.as_str())];
+        if file.has_thumbnail {
+            // Its content type only matters for direct downloads, which thumbnails don't use
+            keys.push((thumbnail_key(file.file_id), "application/octet-stream"));
+        }
+
+        for (key, content_type) in keys {
+            migrate_file(from, to, &key, content_type, delete_source, &mut summary).await?;
+        }
+
+        let done = summary.copied + summary.skipped;
+        if done % 100 == 0 {
+            tracing::info!("Migrated {} files", done);
+        }
+    }
+
+    Ok(summary)
+}
+
+async fn migrate_file(
+    from: &dyn StorageBackend,
+    to: &dyn StorageBackend,
+    key: &str,
+    content_type: &str,
+    delete_source: bool,
+    summary: &mut MigrationSummary,
+) -> Result<()> {
+    if to.exists(key).await? {
+        summary.skipped += 1;
+    } else {
+        let data = match from.get(key).await {
+            Ok(data) => data,
+            Err(AppError::NotFound(_)) => {
+                tracing::warn!("File {} is missing from {} storage", key, from.name());
+                summary.missing += 1;
+                return Ok(());
+            }
+            Err(e) => {
+                tracing::warn!("Failed to read {} from {} storage: {}", key, from.name(), e);
+                summary.failed += 1;
+                return Ok(());
+            }
+        };
+        if let Err(e) = to.put(key, &data, content_type).await {
+            tracing::warn!("Failed to write {} to {} storage: {}", key, to.name(), e);
+            summary.failed += 1;
+            return Ok(());
+        }
+        summary.copied += 1;
+    }
+
+    if delete_source {
+        if let Err(e) = from.delete(key).await {
+            tracing::warn!("Failed to delete {} from {} storage: {}", key, from.name(), e);
+        }
+    }
+
+    Ok(())
+}
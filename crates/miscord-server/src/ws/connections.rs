@@ -8,6 +8,21 @@ pub struct ConnectionInfo {
     pub user_id: Uuid,
     pub subscribed_channels: HashSet<Uuid>,
     pub subscribed_threads: HashSet<Uuid>,
+    pub communities: HashSet<Uuid>,
+}
+
+/// Where a broadcast was sent
+#[derive(Debug, Clone, Copy)]
+pub enum BroadcastScope {
+    Channel(Uuid),
+    Community(Uuid),
+}
+
+/// A copy of a channel or community broadcast, for listeners other than WebSocket clients
+#[derive(Debug, Clone)]
+pub struct BroadcastEvent {
+    pub scope: BroadcastScope,
+    pub message: ServerMessage,
 }
 
 pub struct ConnectionManager {
@@ -21,6 +36,10 @@ pub struct ConnectionManager {
     channel_subscribers: RwLock<HashMap<Uuid, HashSet<Uuid>>>,
     /// Map from thread parent message ID to connection IDs subscribed to that thread
     thread_subscribers: RwLock<HashMap<Uuid, HashSet<Uuid>>>,
+    /// Map from community ID to connection IDs of its members
+    community_connections: RwLock<HashMap<Uuid, HashSet<Uuid>>>,
+    /// Receives a copy of every channel and community broadcast (outgoing webhooks)
+    broadcast_listener: RwLock<Option<mpsc::UnboundedSender<BroadcastEvent>>>,
 }
 
 impl ConnectionManager {
@@ -31,6 +50,24 @@ impl ConnectionManager {
             user_connections: RwLock::new(HashMap::new()),
             channel_subscribers: RwLock::new(HashMap::new()),
             thread_subscribers: RwLock::new(HashMap::new()),
+            community_connections: RwLock::new(HashMap::new()),
+            broadcast_listener: RwLock::new(None),
+        }
+    }
+
+    /// Receive a copy of every channel and community broadcast, replacing any earlier listener
+    pub async fn listen_to_broadcasts(&self) -> mpsc::UnboundedReceiver<BroadcastEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        *self.broadcast_listener.write().await = Some(sender);
+        receiver
+    }
+
+    async fn notify_broadcast_listener(&self, scope: BroadcastScope, message: &ServerMessage) {
+        if let Some(listener) = self.broadcast_listener.read().await.as_ref() {
+            let _ = listener.send(BroadcastEvent {
+                scope,
+                message: message.clone(),
+            });
         }
     }
 
@@ -48,6 +85,7 @@ impl ConnectionManager {
                 user_id,
                 subscribed_channels: HashSet::new(),
                 subscribed_threads: HashSet::new(),
+                communities: HashSet::new(),
             },
         );
 
@@ -89,6 +127,13 @@ impl ConnectionManager {
                 }
             }
 
+            // Remove from community fan-out
+            for community_id in &info.communities {
+                if let Some(conns) = self.community_connections.write().await.get_mut(community_id) {
+                    conns.remove(&connection_id);
+                }
+            }
+
             tracing::debug!(
                 "User {} disconnected (connection ID {})",
                 info.user_id,
@@ -124,6 +169,71 @@ impl ConnectionManager {
         }
     }
 
+    /// Register a connection to receive events for a community it is a member of
+    pub async fn add_connection_to_community(&self, connection_id: Uuid, community_id: Uuid) {
+        if let Some(info) = self.connection_info.write().await.get_mut(&connection_id) {
+            info.communities.insert(community_id);
+        }
+
+        self.community_connections
+            .write()
+            .await
+            .entry(community_id)
+            .or_default()
+            .insert(connection_id);
+    }
+
+    /// Register all of a user's connections for a community they joined or created
+    pub async fn add_user_to_community(&self, user_id: Uuid, community_id: Uuid) {
+        let conn_ids: Vec<Uuid> = self
+            .user_connections
+            .read()
+            .await
+            .get(&user_id)
+            .map(|conns| conns.iter().copied().collect())
+            .unwrap_or_default();
+
+        for conn_id in conn_ids {
+            self.add_connection_to_community(conn_id, community_id).await;
+        }
+    }
+
+    /// Stop sending a community's events to a user who left it
+    pub async fn remove_user_from_community(&self, user_id: Uuid, community_id: Uuid) {
+        let conn_ids: Vec<Uuid> = self
+            .user_connections
+            .read()
+            .await
+            .get(&user_id)
+            .map(|conns| conns.iter().copied().collect())
+            .unwrap_or_default();
+
+        let mut connection_info = self.connection_info.write().await;
+        let mut community_connections = self.community_connections.write().await;
+        for conn_id in conn_ids {
+            if let Some(info) = connection_info.get_mut(&conn_id) {
+                info.communities.remove(&community_id);
+            }
+            if let Some(conns) = community_connections.get_mut(&community_id) {
+                conns.remove(&conn_id);
+            }
+        }
+    }
+
+    /// Forget a deleted community
+    pub async fn remove_community(&self, community_id: Uuid) {
+        let Some(conn_ids) = self.community_connections.write().await.remove(&community_id) else {
+            return;
+        };
+
+        let mut connection_info = self.connection_info.write().await;
+        for conn_id in conn_ids {
+            if let Some(info) = connection_info.get_mut(&conn_id) {
+                info.communities.remove(&community_id);
+            }
+        }
+    }
+
     pub async fn subscribe_to_thread(&self, connection_id: Uuid, thread_id: Uuid) {
         if let Some(info) = self.connection_info.write().await.get_mut(&connection_id) {
             info.subscribed_threads.insert(thread_id);
@@ -186,6 +296,9 @@ impl ConnectionManager {
             }
         };
 
+        self.notify_broadcast_listener(BroadcastScope::Channel(channel_id), message)
+            .await;
+
         let subscribers = self.channel_subscribers.read().await;
         let senders = self.senders.read().await;
 
@@ -203,6 +316,36 @@ impl ConnectionManager {
         }
     }
 
+    /// Send a message to every connected member of a community
+    pub async fn broadcast_to_community(&self, community_id: Uuid, message: &ServerMessage) {
+        let json = match serde_json::to_string(message) {
+            Ok(j) => j,
+            Err(e) => {
+                tracing::error!("Failed to serialize message: {}", e);
+                return;
+            }
+        };
+
+        self.notify_broadcast_listener(BroadcastScope::Community(community_id), message)
+            .await;
+
+        let community_connections = self.community_connections.read().await;
+        let senders = self.senders.read().await;
+
+        if let Some(conns) = community_connections.get(&community_id) {
+            tracing::debug!("Broadcasting to {} connections of community {}", conns.len(), community_id);
+            for conn_id in conns {
+                if let Some(sender) = senders.get(conn_id) {
+                    if let Err(e) = sender.send(json.clone()) {
+                        tracing::error!("Failed to send message to {}: {}", conn_id, e);
+                    }
+                }
+            }
+        } else {
+            tracing::debug!("No connections for community {}", community_id);
+        }
+    }
+
     pub async fn send_to_user(&self, user_id: Uuid, message: &ServerMessage) {
         let json = match serde_json::to_string(message) {
             Ok(j) => j,
@@ -1,4 +1,4 @@
-use crate::auth::verify_token;
+use crate::auth::authenticate;
 use crate::sfu::TrackRouter;
 use crate::state::AppState;
 use axum::{
@@ -56,8 +56,8 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
 
     let (user_id, _username) = match auth {
         ClientMessage::Authenticate { token } => {
-            match verify_token(&token, &state.config.jwt_secret) {
-                Ok(claims) => (claims.sub, claims.username),
+            match authenticate(&state, &token).await {
+                Ok(auth) => (auth.user_id, auth.username),
                 Err(_) => {
                     let _ = sender
                         .send(Message::Text(
@@ -105,6 +105,27 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
     // Register connection with the connection manager
     state.connections.add_connection(connection_id, user_id, tx).await;
 
+    // Receive channel and member events for the user's communities
+    match sqlx::query_scalar!(
+        "SELECT community_id FROM community_members WHERE user_id = $1",
+        user_id
+    )
+    .fetch_all(&state.db)
+    .await
+    {
+        Ok(community_ids) => {
+            for community_id in community_ids {
+                state
+                    .connections
+                    .add_connection_to_community(connection_id, community_id)
+                    .await;
+            }
+        }
+        Err(e) => {
+            tracing::error!("Failed to load communities for user {}: {}", user_id, e);
+        }
+    }
+
     // Update user status to online
     if let Err(e) = state
         .user_service
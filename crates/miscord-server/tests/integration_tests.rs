@@ -38,6 +38,17 @@ impl TestServer {
             bind_address: "127.0.0.1:0".to_string(),
             stun_servers: vec![],
             turn_servers: vec![],
+            upload_dir: std::env::temp_dir().join(format!("miscord-test-{}", uuid::Uuid::new_v4())),
+            base_url: "http://127.0.0.1".to_string(),
+            tenor_api_key: None,
+            thread_auto_archive_days: 7,
+            storage_backend: miscord_server::storage::StorageKind::Local,
+            s3: None,
+            upload_limit_bytes: 100 * 1024 * 1024,
+            max_upload_limit_bytes: 2048 * 1024 * 1024,
+            strip_image_metadata: true,
+            user_storage_quota_bytes: None,
+            community_storage_quota_bytes: None,
         };
 
         let (router, db_pool) = miscord_server::create_app(config).await?;
@@ -171,6 +182,92 @@ async fn get_channels(
     Ok(channels)
 }
 
+/// The first text channel of a community
+async fn first_text_channel(
+    client: &Client,
+    http_url: &str,
+    token: &str,
+    community_id: uuid::Uuid,
+) -> anyhow::Result<uuid::Uuid> {
+    let channels = get_channels(client, http_url, token, community_id).await?;
+    channels
+        .iter()
+        .find(|c| c["channel_type"] == "text")
+        .and_then(|c| c["id"].as_str())
+        .and_then(|s| uuid::Uuid::parse_str(s).ok())
+        .ok_or_else(|| anyhow::anyhow!("No text channel found"))
+}
+
+/// Upload a file in one chunk and return the attachment it became
+async fn upload_file(
+    client: &Client,
+    http_url: &str,
+    token: &str,
+    channel_id: uuid::Uuid,
+    filename: &str,
+    contents: &[u8],
+) -> anyhow::Result<serde_json::Value> {
+    let session: serde_json::Value = client
+        .post(format!("{}/api/uploads", http_url))
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&json!({
+            "channel_id": channel_id,
+            "filename": filename,
+            "size_bytes": contents.len()
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let session_id = session["id"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("No upload id in response"))?;
+
+    client
+        .put(format!("{}/api/uploads/{}/chunks?offset=0", http_url, session_id))
+        .header("Authorization", format!("Bearer {}", token))
+        .body(contents.to_vec())
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let attachment = client
+        .post(format!("{}/api/uploads/{}/complete", http_url, session_id))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(attachment)
+}
+
+/// Ask to attach a file the server already has, by its hash
+async fn reuse_upload(
+    client: &Client,
+    http_url: &str,
+    token: &str,
+    channel_id: uuid::Uuid,
+    filename: &str,
+    contents: &[u8],
+) -> anyhow::Result<serde_json::Value> {
+    let attachment = client
+        .post(format!("{}/api/uploads/existing", http_url))
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&json!({
+            "channel_id": channel_id,
+            "filename": filename,
+            "sha256": miscord_server::services::attachment::content_hash(contents)
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(attachment)
+}
+
 /// Connect to WebSocket and authenticate
 async fn connect_websocket(
     ws_url: &str,
@@ -487,3 +584,128 @@ async fn test_send_message_via_api() {
     assert!(!messages.is_empty());
     assert!(messages.iter().any(|m| m["content"] == message_content));
 }
+
+#[tokio::test]
+async fn test_identical_uploads_share_a_file() {
+    let server = start_test_server().await;
+    let client = Client::new();
+    let http_url = server.http_url();
+    let username = format!("testuser_{}", uuid::Uuid::new_v4().to_string().split('-').next().unwrap());
+
+    let (token, _user_id) = create_test_user(&client, &http_url, &username)
+        .await
+        .expect("Failed to create test user");
+    let community_id = create_community(&client, &http_url, &token, "Test Community")
+        .await
+        .expect("Failed to create community");
+    let channel_id = first_text_channel(&client, &http_url, &token, community_id)
+        .await
+        .expect("Failed to get channel");
+
+    let contents = format!("Shared notes {}", uuid::Uuid::new_v4()).into_bytes();
+    let first = upload_file(&client, &http_url, &token, channel_id, "notes.txt", &contents)
+        .await
+        .expect("First upload failed");
+    let second = upload_file(&client, &http_url, &token, channel_id, "copy.txt", &contents)
+        .await
+        .expect("Second upload failed");
+    assert_ne!(first["id"], second["id"]);
+    assert_eq!(second["filename"], "copy.txt");
+
+    // The shared file is only counted once
+    let usage: serde_json::Value = client
+        .get(format!("{}/api/users/me/storage", http_url))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(usage["used_bytes"], contents.len());
+
+    // Deleting one attachment leaves the file for the other
+    let response = client
+        .delete(format!("{}/api/attachments/{}", http_url, first["id"].as_str().unwrap()))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let response = client
+        .get(format!("{}{}", http_url, second["url"].as_str().unwrap()))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.bytes().await.unwrap().as_ref(), contents.as_slice());
+}
+
+#[tokio::test]
+async fn test_reuse_upload_only_offers_visible_files() {
+    let server = start_test_server().await;
+    let client = Client::new();
+    let http_url = server.http_url();
+    let alice_username = format!("alice_{}", uuid::Uuid::new_v4().to_string().split('-').next().unwrap());
+    let bob_username = format!("bob_{}", uuid::Uuid::new_v4().to_string().split('-').next().unwrap());
+
+    let (alice_token, _alice_id) = create_test_user(&client, &http_url, &alice_username)
+        .await
+        .expect("Failed to create Alice");
+    let (bob_token, _bob_id) = create_test_user(&client, &http_url, &bob_username)
+        .await
+        .expect("Failed to create Bob");
+
+    let alice_community = create_community(&client, &http_url, &alice_token, "Alice's Community")
+        .await
+        .expect("Failed to create community");
+    let alice_channel = first_text_channel(&client, &http_url, &alice_token, alice_community)
+        .await
+        .expect("Failed to get channel");
+    let bob_community = create_community(&client, &http_url, &bob_token, "Bob's Community")
+        .await
+        .expect("Failed to create community");
+    let bob_channel = first_text_channel(&client, &http_url, &bob_token, bob_community)
+        .await
+        .expect("Failed to get channel");
+
+    let contents = format!("Private notes {}", uuid::Uuid::new_v4()).into_bytes();
+    let original = upload_file(&client, &http_url, &alice_token, alice_channel, "notes.txt", &contents)
+        .await
+        .expect("Upload failed");
+
+    // Knowing the hash isn't enough to get someone else's file
+    let reused = reuse_upload(&client, &http_url, &bob_token, bob_channel, "notes.txt", &contents)
+        .await
+        .expect("Reuse request failed");
+    assert!(reused.is_null(), "Bob got Alice's file: {}", reused);
+
+    // The uploader can reuse their own file, under a new name with the same extension
+    let reused = reuse_upload(&client, &http_url, &alice_token, alice_channel, "AGAIN.TXT", &contents)
+        .await
+        .expect("Reuse request failed");
+    assert_ne!(reused["id"], original["id"]);
+    assert_eq!(reused["filename"], "AGAIN.TXT");
+    assert_eq!(reused["size_bytes"], contents.len());
+
+    // Files are stored under their extension, so a different one needs a fresh upload
+    let reused = reuse_upload(&client, &http_url, &alice_token, alice_channel, "notes.md", &contents)
+        .await
+        .expect("Reuse request failed");
+    assert!(reused.is_null());
+
+    // Filenames are checked as they are for uploads
+    let response = client
+        .post(format!("{}/api/uploads/existing", http_url))
+        .header("Authorization", format!("Bearer {}", alice_token))
+        .json(&json!({
+            "channel_id": alice_channel,
+            "filename": "bad\"name.txt",
+            "sha256": miscord_server::services::attachment::content_hash(&contents)
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+}